@@ -24,6 +24,7 @@ pub use ta::{TaPool};
 pub use fc::{FcPool};
 pub use one::{OncePool};
 pub use align::{AlignAlloc};
+pub use sort::{sort, sort_by, sort_by_key};
 #[cfg(not(no_libc))] pub use libc::{Libc};
 #[cfg(not(freestanding))] pub use bda::{Bda};
 #[cfg(not(freestanding))] pub use tl::{TlAlc};
@@ -38,6 +39,7 @@ mod align;
 mod ta;
 mod one;
 mod fc;
+mod sort;
 #[cfg(not(no_libc))] mod libc;
 #[cfg(not(freestanding))] mod bda;
 #[cfg(not(freestanding))] mod tl;
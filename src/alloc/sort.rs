@@ -0,0 +1,85 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{mem};
+use core::cmp::{Ord, Ordering};
+use {MemPool, alloc_array, free_array};
+
+/// Sorts a slice with a scratch buffer from a memory pool.
+///
+/// [argument, slice]
+/// The slice to sort.
+///
+/// [argument, pool]
+/// The pool from which the scratch buffer is allocated.
+///
+/// = Remarks
+///
+/// See `sort_by` for details.
+pub fn sort<T, M: ?Sized>(slice: &mut [T], pool: &mut M)
+    where T: Ord,
+          M: MemPool,
+{
+    sort_by(slice, pool, |one, two| one.cmp(two));
+}
+
+/// Sorts a slice with a comparison function and a scratch buffer from a memory pool.
+///
+/// [argument, slice]
+/// The slice to sort.
+///
+/// [argument, pool]
+/// The pool from which the scratch buffer is allocated.
+///
+/// [argument, f]
+/// The comparison function.
+///
+/// = Remarks
+///
+/// The sort is stable. The scratch buffer can hold half as many elements as the slice
+/// and is freed before this function returns. The sort runs in `O(n log n)` time. If
+/// the buffer cannot be allocated, this falls back to the in-place `sort_by` method of
+/// slices, which is slower but never fails.
+pub fn sort_by<T, M: ?Sized, F>(slice: &mut [T], pool: &mut M, f: F)
+    where M: MemPool,
+          F: FnMut(&T, &T) -> Ordering,
+{
+    let num = slice.len() / 2;
+    if num == 0 || mem::size_of::<T>() == 0 {
+        slice.sort_by(f);
+        return;
+    }
+    unsafe {
+        match alloc_array::<T, M>(pool, num) {
+            Ok((buf, cap)) => {
+                slice.sort_by_buf(buf, f);
+                free_array(pool, buf, cap);
+            },
+            _ => slice.sort_by(f),
+        }
+    }
+}
+
+/// Sorts a slice by a key and with a scratch buffer from a memory pool.
+///
+/// [argument, slice]
+/// The slice to sort.
+///
+/// [argument, pool]
+/// The pool from which the scratch buffer is allocated.
+///
+/// [argument, f]
+/// The function that extracts the key of an element.
+///
+/// = Remarks
+///
+/// See `sort_by` for details.
+pub fn sort_by_key<T, M: ?Sized, K, F>(slice: &mut [T], pool: &mut M, mut f: F)
+    where M: MemPool,
+          K: Ord,
+          F: FnMut(&T) -> K,
+{
+    sort_by(slice, pool, |one, two| f(one).cmp(&f(two)));
+}
@@ -0,0 +1,269 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![crate_name = "lrs_buf_writer"]
+#![crate_type = "lib"]
+#![no_std]
+
+extern crate lrs_base as base;
+extern crate lrs_io as io;
+extern crate lrs_alloc as alloc;
+
+use base::prelude::*;
+use core::{slice, ptr, mem};
+use core::ptr::{NoAliasMemPtr};
+use base::{error};
+use alloc::{MemPool};
+use io::{Read, Write, BufWrite};
+
+pub mod std { pub use base::std::*; }
+
+/// The maximum number of slices passed to a single `gather_write` of the wrapped
+/// writer.
+const MAX_SLICES: usize = 16;
+
+/// A buffered writer.
+///
+/// = Remarks
+///
+/// Writes are collected in the buffer until it is full. The buffered bytes are then
+/// written together with the new data in a single `gather_write` call of the wrapped
+/// writer.
+///
+/// The buffer is flushed when the writer is dropped. Errors that occur at this point
+/// are ignored. Use `flush` to handle them.
+pub struct BufWriter<W, Heap = alloc::Heap>
+    where W: Write,
+          Heap: MemPool,
+{
+    data: NoAliasMemPtr<u8>,
+    cap: usize,
+    start: usize,
+    end: usize,
+    write: W,
+    pool: Heap,
+}
+
+impl<W, H = alloc::Heap> BufWriter<W, H>
+    where W: Write,
+          H: MemPool,
+{
+    /// Allocates a new buffered writer.
+    ///
+    /// [argument, write]
+    /// The writer that will be wrapped in the buffered writer.
+    ///
+    /// [argument, size]
+    /// The buffer-size of the buffered writer.
+    ///
+    /// = Remarks
+    ///
+    /// `size` will be increased to the next power of two.
+    pub fn new(write: W, size: usize) -> Result<Self>
+        where H: OutOf,
+    {
+        Self::with_pool(write, size, H::out_of(()))
+    }
+
+    /// Allocates a new buffered writer.
+    ///
+    /// [argument, write]
+    /// The writer that will be wrapped in the buffered writer.
+    ///
+    /// [argument, size]
+    /// The buffer-size of the buffered writer.
+    ///
+    /// [argument, pool]
+    /// The pool from which the buffer will be allocated.
+    ///
+    /// = Remarks
+    ///
+    /// `size` will be increased to the next power of two.
+    pub fn with_pool(write: W, size: usize, mut pool: H) -> Result<Self> {
+        let size = match size.checked_next_power_of_two() {
+            Some(n) => n,
+            _ => return Err(error::NoMemory),
+        };
+        let ptr = unsafe { try!(alloc::alloc_array(&mut pool, size)).0 };
+        let ptr = unsafe { NoAliasMemPtr::new(ptr) };
+        Ok(BufWriter {
+            data: ptr,
+            cap: size,
+            start: 0,
+            end: 0,
+            write: write,
+            pool: pool,
+        })
+    }
+
+    /// Returns the number of currently buffered bytes.
+    pub fn available(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns the total buffer capacity.
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.write
+    }
+
+    /// Writes all buffered bytes to the wrapped writer.
+    ///
+    /// = Remarks
+    ///
+    /// If an error occurs, the bytes that have not been written remain in the buffer.
+    pub fn flush(&mut self) -> Result {
+        while self.start < self.end {
+            let n = try!(self.write.write(unsafe { self.buffered() }));
+            if n == 0 {
+                return Err(error::DeviceFull);
+            }
+            self.start += n;
+        }
+        self.start = 0;
+        self.end = 0;
+        Ok(())
+    }
+
+    /// Returns the buffered bytes.
+    ///
+    /// = Remarks
+    ///
+    /// The lifetime is not bound to `self` so that the slice can be passed to the
+    /// wrapped writer. It must not outlive the next modification of the buffer.
+    unsafe fn buffered<'a>(&self) -> &'a [u8] {
+        slice::from_ptr(self.data.get().add(self.start), self.end - self.start)
+    }
+
+    fn unused(&mut self) -> &mut [u8] {
+        unsafe { slice::from_ptr(self.data.get().add(self.end), self.cap - self.end) }
+    }
+
+    /// Moves the buffered bytes to the start of the buffer.
+    fn compact(&mut self) {
+        if self.start == 0 {
+            return;
+        }
+        let len = self.end - self.start;
+        unsafe {
+            ptr::memmove(self.data.get(), self.data.get().add(self.start), len);
+        }
+        self.start = 0;
+        self.end = len;
+    }
+
+    /// Copies as many bytes as possible from `buf` into the buffer, starting at byte
+    /// `skip`.
+    fn append(&mut self, buf: &[&[u8]], mut skip: usize) -> usize {
+        self.compact();
+        let mut sum = 0;
+        for b in buf {
+            if skip >= b.len() {
+                skip -= b.len();
+                continue;
+            }
+            let n = mem::copy(self.unused(), &b[skip..]);
+            skip = 0;
+            self.end += n;
+            sum += n;
+            if self.end == self.cap {
+                break;
+            }
+        }
+        sum
+    }
+}
+
+impl<W, H> Write for BufWriter<W, H>
+    where W: Write,
+          H: MemPool,
+{
+    fn gather_write(&mut self, buf: &[&[u8]]) -> Result<usize> {
+        let mut total = 0;
+        for b in buf {
+            total += b.len();
+        }
+        if total <= self.cap - self.available() {
+            return Ok(self.append(buf, 0));
+        }
+
+        // The data doesn't fit. Submit the buffered bytes and as many of the new
+        // buffers as possible in one call.
+        let mut slices: [&[u8]; MAX_SLICES] = [&[]; MAX_SLICES];
+        let mut num = 0;
+        let buffered = self.available();
+        if buffered > 0 {
+            slices[0] = unsafe { self.buffered() };
+            num = 1;
+        }
+        for b in buf {
+            if num == MAX_SLICES {
+                break;
+            }
+            slices[num] = *b;
+            num += 1;
+        }
+        let n = try!(self.write.gather_write(&slices[..num]));
+
+        if n < buffered {
+            self.start += n;
+            return Ok(self.append(buf, 0));
+        }
+        self.start = 0;
+        self.end = 0;
+        let written = n - buffered;
+        Ok(written + self.append(buf, written))
+    }
+}
+
+impl<W, H> BufWrite for BufWriter<W, H>
+    where W: Write,
+          H: MemPool,
+{
+    fn read_to_eof<R>(&mut self, mut r: R) -> Result<usize>
+        where R: Read,
+    {
+        let mut len = 0;
+        loop {
+            if self.end == self.cap {
+                try!(self.flush());
+            }
+            let n = try!(r.read(self.unused().as_mut()));
+            if n == 0 {
+                return Ok(len);
+            }
+            self.end += n;
+            len += n;
+        }
+    }
+
+    fn read<R>(&mut self, mut r: R, n: usize) -> Result<usize>
+        where R: Read,
+    {
+        if self.end == self.cap {
+            try!(self.flush());
+        }
+        let n = {
+            let unused = self.unused();
+            let len = if unused.len() < n { unused.len() } else { n };
+            try!(r.read(unused[..len].as_mut()))
+        };
+        self.end += n;
+        Ok(n)
+    }
+}
+
+impl<W, H> Drop for BufWriter<W, H>
+    where W: Write,
+          H: MemPool,
+{
+    fn drop(&mut self) {
+        let _ = self.flush();
+        unsafe { alloc::free_array(&mut self.pool, self.data.get(), self.cap); }
+    }
+}
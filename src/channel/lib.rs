@@ -0,0 +1,449 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![crate_name = "lrs_channel"]
+#![crate_type = "lib"]
+#![no_std]
+
+extern crate lrs_base as base;
+extern crate lrs_io as io;
+extern crate lrs_fmt as fmt;
+extern crate lrs_cty as cty;
+extern crate lrs_atomic as atomic;
+extern crate lrs_lock as lock;
+extern crate lrs_alloc as alloc;
+extern crate lrs_rc as rc;
+extern crate lrs_ringbuf as ringbuf;
+extern crate lrs_time_base as time_base;
+extern crate lrs_syscall as syscall;
+extern crate lrs_fd as fd;
+extern crate lrs_event as event;
+
+use base::prelude::*;
+use core::marker::{Leak};
+use base::{error};
+use io::{Write};
+use fmt::{Debug};
+use cty::{c_int};
+use atomic::{Atomic};
+use lock::{Mutex, MutexGuard};
+use rc::{Arc};
+use ringbuf::{DynRingBuf};
+use time_base::{time_to_timespec, Time, clock};
+use syscall::{futex_wait, futex_wake};
+use fd::{FdContainer};
+use event::{Eventfd};
+use event::flags::{EFD_SEMAPHORE, EFD_DONT_BLOCK, EFD_CLOSE_ON_EXEC};
+
+pub mod std { pub use fmt::std::*; }
+
+/// The error returned when the other half of a channel has been dropped.
+///
+/// = Remarks
+///
+/// This is an alias of `BrokenPipe` and not a distinct error. A function that
+/// returns errors from both a channel and a pipe cannot tell the two apart.
+#[allow(non_upper_case_globals)]
+pub const Disconnected: error::Errno = error::BrokenPipe;
+
+/// A futex threads can sleep on until it's notified.
+struct Signal {
+    seq: Atomic<c_int>,
+    sleeping: Atomic<c_int>,
+}
+
+impl Signal {
+    fn new() -> Signal {
+        Signal {
+            seq: Atomic::new(0),
+            sleeping: Atomic::new(0),
+        }
+    }
+
+    /// Returns the value that has to be passed to `wait`.
+    ///
+    /// = Remarks
+    ///
+    /// This has to be called before the condition the caller wants to wait for is
+    /// checked. Otherwise a notification between the check and the wait can be lost.
+    fn seq(&self) -> c_int {
+        self.seq.load()
+    }
+
+    /// Sleeps until the signal is notified or the deadline has passed.
+    fn wait(&self, seq: c_int, deadline: Option<Time>) -> Result {
+        self.sleeping.add(1);
+        let res = match deadline {
+            Some(then) => self.wait_until(seq, then),
+            None => {
+                futex_wait(&self.seq, seq, None);
+                Ok(())
+            },
+        };
+        self.sleeping.sub(1);
+        res
+    }
+
+    fn wait_until(&self, seq: c_int, then: Time) -> Result {
+        let now = try!(clock::MONO_RAW.get_time());
+        if now >= then {
+            return Err(error::TimedOut);
+        }
+        let spec = time_to_timespec(then - now);
+        match rv!(futex_wait(&self.seq, seq, Some(&spec))) {
+            Err(error::TimedOut) => Err(error::TimedOut),
+            _ => Ok(()),
+        }
+    }
+
+    /// Wakes up to `n` sleeping threads.
+    fn notify(&self, n: usize) {
+        self.seq.add(1);
+        if self.sleeping.load() > 0 {
+            futex_wake(&self.seq, n);
+        }
+    }
+}
+
+struct State<T> {
+    buf: DynRingBuf<T>,
+    // The capacity of a synchronous channel.
+    cap: Option<usize>,
+    senders: usize,
+    receiver: bool,
+    // Contains one token per message in the buffer and one more once all senders have
+    // been dropped.
+    event: Option<Eventfd>,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    // Notified when a message is sent or the last sender is dropped.
+    recv_signal: Signal,
+    // Notified when a message is received or the receiver is dropped.
+    send_signal: Signal,
+}
+
+fn create<T>(buf: DynRingBuf<T>,
+             cap: Option<usize>) -> Result<(Sender<T>, Receiver<T>)>
+    where T: Leak,
+{
+    let state = State {
+        buf: buf,
+        cap: cap,
+        senders: 1,
+        receiver: true,
+        event: None,
+    };
+    let shared: Arc<Shared<T>> = try!(Arc::new()).set(Shared {
+        state: Mutex::new(state),
+        recv_signal: Signal::new(),
+        send_signal: Signal::new(),
+    });
+    let receiver = Receiver { shared: shared.add_ref() };
+    Ok((Sender { shared: shared }, receiver))
+}
+
+/// Creates a new asynchronous channel.
+///
+/// [return_value]
+/// Returns the sending and the receiving half of the channel.
+///
+/// = Remarks
+///
+/// The buffer of the channel grows as required. Sending only fails if the receiver has
+/// been dropped or no memory is available.
+///
+/// = See also
+///
+/// * link:lrs::sync::sync_channel
+pub fn channel<T>() -> Result<(Sender<T>, Receiver<T>)>
+    where T: Leak,
+{
+    create(DynRingBuf::new(), None)
+}
+
+/// Creates a new synchronous channel.
+///
+/// [argument, cap]
+/// The number of messages that can be buffered in the channel.
+///
+/// [return_value]
+/// Returns the sending and the receiving half of the channel.
+///
+/// = Remarks
+///
+/// If the buffer is full, `send` blocks until the receiver has received a message. The
+/// capacity must be at least `1`.
+///
+/// = See also
+///
+/// * link:lrs::sync::channel
+pub fn sync_channel<T>(cap: usize) -> Result<(Sender<T>, Receiver<T>)>
+    where T: Leak,
+{
+    if cap == 0 {
+        return Err(error::InvalidArgument);
+    }
+    create(try!(DynRingBuf::with_capacity(cap)), Some(cap))
+}
+
+/// An error returned when a message could not be sent.
+pub struct SendError<T> {
+    /// The message that could not be sent.
+    pub val: T,
+    /// The reason.
+    ///
+    /// = Remarks
+    ///
+    /// This is `Disconnected` if the receiver has been dropped, `WouldBlock` if the
+    /// buffer of a synchronous channel is full, and `NoMemory` if the buffer of an
+    /// asynchronous channel could not be grown.
+    pub error: error::Errno,
+}
+
+impl<T> Debug for SendError<T> {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        write!(w, "SendError {{ error: {:?} }}", self.error)
+    }
+}
+
+/// The sending half of a channel.
+///
+/// = Remarks
+///
+/// Senders can be cloned to send messages from multiple threads.
+pub struct Sender<T>
+    where T: Leak,
+{
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Sender<T>
+    where T: Leak,
+{
+    fn push<'a>(&'a self, state: &mut MutexGuard<'a, State<T>>,
+                val: T) -> Result<(), SendError<T>> {
+        if !state.receiver {
+            return Err(SendError { val: val, error: Disconnected });
+        }
+        if let Some(cap) = state.cap {
+            if state.buf.len() >= cap {
+                return Err(SendError { val: val, error: error::WouldBlock });
+            }
+        }
+        if let Err(e) = state.buf.reserve(1) {
+            return Err(SendError { val: val, error: e });
+        }
+        state.buf.push_right(val);
+        if let Some(ref event) = state.event {
+            let _ = event.add(1);
+        }
+        self.shared.recv_signal.notify(1);
+        Ok(())
+    }
+
+    /// Tries to send a message without blocking.
+    ///
+    /// [argument, val]
+    /// The message to send.
+    ///
+    /// [return_value]
+    /// Returns the message and the reason if it could not be sent.
+    pub fn try_send(&self, val: T) -> Result<(), SendError<T>> {
+        let mut state = self.shared.state.lock();
+        self.push(&mut state, val)
+    }
+
+    /// Sends a message.
+    ///
+    /// [argument, val]
+    /// The message to send.
+    ///
+    /// [return_value]
+    /// Returns the message and the reason if it could not be sent.
+    ///
+    /// = Remarks
+    ///
+    /// If this is a synchronous channel and the buffer is full, this function blocks
+    /// until there is space in the buffer or the receiver has been dropped.
+    pub fn send(&self, mut val: T) -> Result<(), SendError<T>> {
+        loop {
+            let seq = self.shared.send_signal.seq();
+            val = match self.try_send(val) {
+                Err(SendError { val, error: error::WouldBlock }) => val,
+                res => return res,
+            };
+            let _ = self.shared.send_signal.wait(seq, None);
+        }
+    }
+}
+
+unsafe impl<T> Send for Sender<T> where T: Leak+Send { }
+unsafe impl<T> Sync for Sender<T> where T: Leak+Send { }
+
+impl<T> To for Sender<T>
+    where T: Leak,
+{
+    fn to(&self) -> Sender<T> {
+        self.shared.state.lock().senders += 1;
+        Sender { shared: self.shared.add_ref() }
+    }
+}
+
+impl<T> TryTo for Sender<T>
+    where T: Leak,
+{
+    fn try_to(&self) -> Result<Sender<T>> {
+        Ok(self.to())
+    }
+}
+
+impl<T> Drop for Sender<T>
+    where T: Leak,
+{
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            if let Some(ref event) = state.event {
+                let _ = event.add(1);
+            }
+            self.shared.recv_signal.notify(!0);
+        }
+    }
+}
+
+impl<T> Debug for Sender<T>
+    where T: Leak,
+{
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        w.write_all(b"Sender").ignore_ok()
+    }
+}
+
+/// The receiving half of a channel.
+pub struct Receiver<T>
+    where T: Leak,
+{
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T>
+    where T: Leak,
+{
+    /// Tries to receive a message without blocking.
+    ///
+    /// [return_value]
+    /// Returns the message or an error.
+    ///
+    /// = Remarks
+    ///
+    /// If the channel is empty, `WouldBlock` is returned. If the channel is empty and
+    /// all senders have been dropped, `Disconnected` is returned.
+    pub fn try_recv(&self) -> Result<T> {
+        let mut state = self.shared.state.lock();
+        match state.buf.pop_left() {
+            Some(val) => {
+                if let Some(ref event) = state.event {
+                    let _ = event.get();
+                }
+                self.shared.send_signal.notify(1);
+                Ok(val)
+            },
+            None if state.senders == 0 => Err(Disconnected),
+            None => Err(error::WouldBlock),
+        }
+    }
+
+    fn recv_until(&self, deadline: Option<Time>) -> Result<T> {
+        loop {
+            let seq = self.shared.recv_signal.seq();
+            match self.try_recv() {
+                Err(error::WouldBlock) => { },
+                res => return res,
+            }
+            try!(self.shared.recv_signal.wait(seq, deadline));
+        }
+    }
+
+    /// Receives a message.
+    ///
+    /// [return_value]
+    /// Returns the message or an error.
+    ///
+    /// = Remarks
+    ///
+    /// If the channel is empty, this function blocks until a message has been sent. If
+    /// the channel is empty and all senders have been dropped, `Disconnected` is
+    /// returned.
+    pub fn recv(&self) -> Result<T> {
+        self.recv_until(None)
+    }
+
+    /// Receives a message or times out.
+    ///
+    /// [argument, time]
+    /// An upper bound for the amount of time until this function returns.
+    ///
+    /// [return_value]
+    /// Returns the message or an error.
+    ///
+    /// = Remarks
+    ///
+    /// If no message has been received before the time has expired, `TimedOut` is
+    /// returned. If the channel is empty and all senders have been dropped,
+    /// `Disconnected` is returned.
+    pub fn recv_timeout(&self, time: Time) -> Result<T> {
+        let then = try!(clock::MONO_RAW.get_time()) + time;
+        self.recv_until(Some(then))
+    }
+
+    /// Returns an eventfd that is readable while `try_recv` does not return
+    /// `WouldBlock`.
+    ///
+    /// [return_value]
+    /// Returns the file descriptor of the eventfd.
+    ///
+    /// = Remarks
+    ///
+    /// The eventfd is created on the first call and stays alive as long as the channel.
+    /// It can be registered in an `Epoll` to wait for messages together with other file
+    /// descriptors. It must not be read directly.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::poll::Epoll
+    pub fn eventfd(&self) -> Result<c_int> {
+        let mut state = self.shared.state.lock();
+        if let Some(ref event) = state.event {
+            return Ok(event.borrow());
+        }
+        let tokens = state.buf.len() + (state.senders == 0) as usize;
+        let flags = EFD_SEMAPHORE | EFD_DONT_BLOCK | EFD_CLOSE_ON_EXEC;
+        let event = try!(Eventfd::new_init(tokens as u32, flags));
+        let fd = event.borrow();
+        state.event = Some(event);
+        Ok(fd)
+    }
+}
+
+unsafe impl<T> Send for Receiver<T> where T: Leak+Send { }
+
+impl<T> Drop for Receiver<T>
+    where T: Leak,
+{
+    fn drop(&mut self) {
+        self.shared.state.lock().receiver = false;
+        self.shared.send_signal.notify(!0);
+    }
+}
+
+impl<T> Debug for Receiver<T>
+    where T: Leak,
+{
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        w.write_all(b"Receiver").ignore_ok()
+    }
+}
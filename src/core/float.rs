@@ -16,6 +16,7 @@ use intrinsics::{self};
 macro_rules! float_impls {
     (
         $t:ident;
+        $bits:ident;
         $abs:ident;
         $copysign:ident;
         $sqrt:ident;
@@ -58,6 +59,14 @@ macro_rules! float_impls {
                 self < 0.0
             }
 
+            pub fn to_bits(self) -> $bits {
+                unsafe { intrinsics::transmute(self) }
+            }
+
+            pub fn from_bits(bits: $bits) -> Self {
+                unsafe { intrinsics::transmute(bits) }
+            }
+
             pub fn abs(self) -> Self {
                 unsafe { intrinsics::$abs(self) }
             }
@@ -203,5 +212,5 @@ macro_rules! float_impls {
     }
 }
 
-float_impls!(f32; fabsf32; copysignf32; sqrtf32; powf32; powif32; sinf32; cosf32; expf32; exp2f32; logf32; log10f32; log2f32; floorf32; ceilf32; "f32");
-float_impls!(f64; fabsf64; copysignf64; sqrtf64; powf64; powif64; sinf64; cosf64; expf64; exp2f64; logf64; log10f64; log2f64; floorf64; ceilf64; "f64");
+float_impls!(f32; u32; fabsf32; copysignf32; sqrtf32; powf32; powif32; sinf32; cosf32; expf32; exp2f32; logf32; log10f32; log2f32; floorf32; ceilf32; "f32");
+float_impls!(f64; u64; fabsf64; copysignf64; sqrtf64; powf64; powif64; sinf64; cosf64; expf64; exp2f64; logf64; log10f64; log2f64; floorf64; ceilf64; "f64");
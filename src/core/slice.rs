@@ -10,7 +10,7 @@ use cmp::{self, Ord};
 use option::{Option};
 use option::Option::{None, Some};
 use iter::{Iterator};
-use sort::{sort};
+use sort::{self};
 use marker::{Pod};
 use data::{d8};
 
@@ -131,6 +131,10 @@ impl<T> [T] {
     /// [return_value]
     /// Returns the found index in the first slot or the index at which an element that
     /// satisfies the predicate could be inserted in the second slot.
+    ///
+    /// = Remarks
+    ///
+    /// If multiple elements satisfy the predicate, any one of them can be returned.
     pub fn find_binary<F>(&self, mut f: F) -> (Option<usize>, usize)
         where F: FnMut(&T) -> Ordering,
     {
@@ -218,7 +222,75 @@ impl<T> [T] {
         true
     }
 
+    /// Performs a binary search to find the first element in the slice which satisfies
+    /// a predicate.
+    ///
+    /// [argument, f]
+    /// The predicate. It returns how an element compares to the element that is being
+    /// searched for.
+    ///
+    /// [return_value]
+    /// Returns the found index in the first slot or the index at which an element that
+    /// satisfies the predicate could be inserted in the second slot.
+    ///
+    /// = Remarks
+    ///
+    /// The slice must be sorted with respect to the predicate. Unlike `find_binary`,
+    /// this always returns the first of multiple matching elements and the second slot
+    /// is the index of that element.
+    pub fn binary_search_by<F>(&self, mut f: F) -> (Option<usize>, usize)
+        where F: FnMut(&T) -> Ordering,
+    {
+        let mut left = 0;
+        let mut right = self.len();
+        while left < right {
+            let pos = left + (right - left) / 2;
+            match f(&self[pos]) {
+                Ordering::Less => left = pos + 1,
+                _ => right = pos,
+            }
+        }
+        match left < self.len() && f(&self[left]) == Ordering::Equal {
+            true => (Some(left), left),
+            false => (None, left),
+        }
+    }
+
+    /// Performs a binary search to find the first element in the slice which is equal
+    /// to a value.
+    ///
+    /// [argument, val]
+    /// The value to search for.
+    pub fn binary_search(&self, val: &T) -> (Option<usize>, usize)
+        where T: Ord,
+    {
+        self.binary_search_by(|el| el.cmp(val))
+    }
+
+    /// Performs a binary search to find the first element in the slice whose key is
+    /// equal to a value.
+    ///
+    /// [argument, key]
+    /// The key to search for.
+    ///
+    /// [argument, f]
+    /// The function that extracts the key of an element.
+    ///
+    /// = Remarks
+    ///
+    /// The slice must be sorted by the key.
+    pub fn binary_search_by_key<K, F>(&self, key: &K, mut f: F) -> (Option<usize>, usize)
+        where K: Ord,
+              F: FnMut(&T) -> K,
+    {
+        self.binary_search_by(|el| f(el).cmp(key))
+    }
+
     /// Sorts the slice in-place.
+    ///
+    /// = Remarks
+    ///
+    /// The sort is stable. See `sort_by` for details.
     pub fn sort(&mut self)
         where T: Ord,
     {
@@ -229,11 +301,138 @@ impl<T> [T] {
     ///
     /// [argument, f]
     /// The comparison function.
+    ///
+    /// = Remarks
+    ///
+    /// The sort is stable, i.e., equal elements keep their relative order. It does not
+    /// allocate and runs in `O(n log^2 n)` time in the worst case and in `O(n)` time if
+    /// the slice is already sorted. The `sort_by` function in `lrs::alloc` runs in `O(n
+    /// log n)` time by allocating a scratch buffer.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::alloc::sort_by
     pub fn sort_by<F>(&mut self, mut f: F)
         where F: FnMut(&T, &T) -> Ordering
     {
         if mem::size_of::<T>() != 0 {
-            unsafe { sort(self, &mut f); }
+            unsafe { sort::merge_sort(self, 0 as *mut T, &mut f); }
+        }
+    }
+
+    /// Sorts the slice in-place by a key.
+    ///
+    /// [argument, f]
+    /// The function that extracts the key of an element.
+    ///
+    /// = Remarks
+    ///
+    /// The sort is stable. See `sort_by` for details.
+    pub fn sort_by_key<K, F>(&mut self, mut f: F)
+        where K: Ord,
+              F: FnMut(&T) -> K,
+    {
+        self.sort_by(|one, two| f(one).cmp(&f(two)));
+    }
+
+    /// Sorts the slice in-place with a comparison function and a scratch buffer.
+    ///
+    /// [argument, buf]
+    /// A pointer to uninitialized memory that can hold at least half as many elements
+    /// as the slice.
+    ///
+    /// [argument, f]
+    /// The comparison function.
+    ///
+    /// = Remarks
+    ///
+    /// The sort is stable and runs in `O(n log n)` time. The buffer is only used as
+    /// scratch space and its contents are not dropped. If the buffer is too small, the
+    /// behavior is undefined.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::alloc::sort_by
+    pub unsafe fn sort_by_buf<F>(&mut self, buf: *mut T, mut f: F)
+        where F: FnMut(&T, &T) -> Ordering
+    {
+        if mem::size_of::<T>() != 0 {
+            sort::merge_sort(self, buf, &mut f);
+        }
+    }
+
+    /// Sorts the slice in-place without preserving the order of equal elements.
+    pub fn sort_unstable(&mut self)
+        where T: Ord,
+    {
+        self.sort_unstable_by(|one, two| one.cmp(two));
+    }
+
+    /// Sorts the slice in-place with a comparison function without preserving the order
+    /// of equal elements.
+    ///
+    /// [argument, f]
+    /// The comparison function.
+    ///
+    /// = Remarks
+    ///
+    /// This uses pattern-defeating quicksort. It does not allocate, runs in `O(n log n)`
+    /// time in the worst case, and in `O(n)` time for sorted, reverse-sorted, and
+    /// constant slices.
+    pub fn sort_unstable_by<F>(&mut self, mut f: F)
+        where F: FnMut(&T, &T) -> Ordering
+    {
+        if mem::size_of::<T>() != 0 {
+            unsafe { sort::sort_unstable(self, &mut f); }
+        }
+    }
+
+    /// Sorts the slice in-place by a key without preserving the order of equal elements.
+    ///
+    /// [argument, f]
+    /// The function that extracts the key of an element.
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+        where K: Ord,
+              F: FnMut(&T) -> K,
+    {
+        self.sort_unstable_by(|one, two| f(one).cmp(&f(two)));
+    }
+
+    /// Reorders the slice so that an element is at its sorted position.
+    ///
+    /// [argument, index]
+    /// The index of the element.
+    ///
+    /// = Remarks
+    ///
+    /// See `select_nth_unstable_by` for details.
+    pub fn select_nth_unstable(&mut self, index: usize)
+        where T: Ord,
+    {
+        self.select_nth_unstable_by(index, |one, two| one.cmp(two));
+    }
+
+    /// Reorders the slice with a comparison function so that an element is at its
+    /// sorted position.
+    ///
+    /// [argument, index]
+    /// The index of the element.
+    ///
+    /// [argument, f]
+    /// The comparison function.
+    ///
+    /// = Remarks
+    ///
+    /// Afterwards, the element at `index` is the element that would be there if the
+    /// slice were sorted. No element before it is greater and no element after it is
+    /// less. This runs in `O(n)` time on average. If the index is out of bounds, the
+    /// process is aborted.
+    pub fn select_nth_unstable_by<F>(&mut self, index: usize, mut f: F)
+        where F: FnMut(&T, &T) -> Ordering
+    {
+        assert!(index < self.len());
+        if mem::size_of::<T>() != 0 {
+            unsafe { sort::select_nth(self, index, &mut f); }
         }
     }
 
@@ -2,41 +2,552 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use {mem, slice};
+//! Sorting algorithms.
+//!
+//! = Remarks
+//!
+//! Panics abort the process, so the functions in this module don't have to restore the
+//! slice if the comparison function panics.
+
+use {mem, ptr, cmp};
 use ops::{Ordering, FnMut};
-use ops::Ordering::{Greater};
+use ops::Ordering::{Less};
+use option::{Option};
+use option::Option::{Some, None};
+
+// Slices up to this length are sorted with insertion sort.
+const MAX_INSERTION: usize = 20;
+
+// Slices of at least this length use the pseudomedian of nine as the pivot.
+const MIN_NINTHER: usize = 50;
+
+// If choosing the pivot needs more swaps than this, the slice is probably descending.
+const MAX_PIVOT_SWAPS: usize = 12;
+
+// The maximum number of out-of-order pairs fixed by `partial_insertion_sort`.
+const MAX_FIX_STEPS: usize = 5;
+
+// Slices shorter than this are not fixed by `partial_insertion_sort`.
+const MIN_FIX_LEN: usize = 50;
+
+unsafe fn swap<T>(p: *mut T, a: usize, b: usize) {
+    if a != b {
+        mem::swap(&mut *p.add(a), &mut *p.add(b));
+    }
+}
+
+/// Moves the last element to the left until it is no longer less than its predecessor.
+unsafe fn insert_tail<T, F>(v: &mut [T], f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    if len < 2 || f(&*p.add(len - 1), &*p.add(len - 2)) != Less {
+        return;
+    }
+    let tmp = ptr::read(p.add(len - 1));
+    let mut i = len - 1;
+    while i > 0 && f(&tmp, &*p.add(i - 1)) == Less {
+        ptr::memcpy(p.add(i), p.add(i - 1), 1);
+        i -= 1;
+    }
+    ptr::write(p.add(i), tmp);
+}
 
-pub unsafe fn sort<T, F>(slice: &mut [T], f: &mut F)
+/// Moves the first element to the right until its successor is no longer less than it.
+unsafe fn insert_head<T, F>(v: &mut [T], f: &mut F)
     where F: FnMut(&T, &T) -> Ordering,
 {
-    if slice.len() < 2 {
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    if len < 2 || f(&*p.add(1), &*p) != Less {
         return;
     }
+    let tmp = ptr::read(p);
+    let mut i = 1;
+    while i < len && f(&*p.add(i), &tmp) == Less {
+        ptr::memcpy(p.add(i - 1), p.add(i), 1);
+        i += 1;
+    }
+    ptr::write(p.add(i - 1), tmp);
+}
+
+/// Stable insertion sort.
+unsafe fn insertion_sort<T, F>(v: &mut [T], f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    for i in 2..v.len() + 1 {
+        insert_tail(&mut v[..i], f);
+    }
+}
 
-    let pivot = &mut slice[0] as *mut T;
-    let mut start = pivot.add(1);
-    let mut end = pivot.add(slice.len() - 1);
-    while start <= end {
-        while start <= end && f(&*start, &*pivot) != Greater {
-            start = start.offset(1);
+unsafe fn sift_down<T, F>(p: *mut T, mut node: usize, end: usize, f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * node + 1;
+        if child >= end {
+            return;
         }
-        while start < end && f(&*end, &*pivot) == Greater {
-            end = end.offset(-1);
+        if child + 1 < end && f(&*p.add(child), &*p.add(child + 1)) == Less {
+            child += 1;
         }
-        if start < end {
-            mem::swap(&mut *start, &mut *end);
-            start = start.offset(1);
-            end = end.offset(-1);
-        } else {
+        if f(&*p.add(node), &*p.add(child)) != Less {
+            return;
+        }
+        swap(p, node, child);
+        node = child;
+    }
+}
+
+/// Heapsort, used when quicksort keeps choosing bad pivots.
+unsafe fn heapsort<T, F>(v: &mut [T], f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    let mut i = len / 2;
+    while i > 0 {
+        i -= 1;
+        sift_down(p, i, len, f);
+    }
+    let mut end = len;
+    while end > 1 {
+        end -= 1;
+        swap(p, 0, end);
+        sift_down(p, 0, end, f);
+    }
+}
+
+/// Swaps the elements at `a` and `b` if they are out of order.
+unsafe fn sort2<T, F>(p: *mut T, a: &mut usize, b: &mut usize, swaps: &mut usize,
+                      f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    if f(&*p.add(*b), &*p.add(*a)) == Less {
+        mem::swap(a, b);
+        *swaps += 1;
+    }
+}
+
+/// Orders the indices `a`, `b`, and `c` by the elements they point to.
+unsafe fn sort3<T, F>(p: *mut T, a: &mut usize, b: &mut usize, c: &mut usize,
+                      swaps: &mut usize, f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    sort2(p, a, b, swaps, f);
+    sort2(p, b, c, swaps, f);
+    sort2(p, a, b, swaps, f);
+}
+
+/// Returns the index of the median of the element at `i` and its neighbors.
+unsafe fn median_adjacent<T, F>(p: *mut T, i: usize, swaps: &mut usize,
+                                f: &mut F) -> usize
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let (mut a, mut b, mut c) = (i - 1, i, i + 1);
+    sort3(p, &mut a, &mut b, &mut c, swaps, f);
+    b
+}
+
+/// Chooses a pivot and returns its index and whether the slice is likely sorted.
+unsafe fn choose_pivot<T, F>(v: &mut [T], f: &mut F) -> (usize, bool)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    let mut a = len / 4 * 1;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+    let mut swaps = 0;
+
+    if len >= 8 {
+        if len >= MIN_NINTHER {
+            a = median_adjacent(p, a, &mut swaps, f);
+            b = median_adjacent(p, b, &mut swaps, f);
+            c = median_adjacent(p, c, &mut swaps, f);
+        }
+        sort3(p, &mut a, &mut b, &mut c, &mut swaps, f);
+    }
+
+    if swaps < MAX_PIVOT_SWAPS {
+        (b, swaps == 0)
+    } else {
+        // The maximum number of swaps has been used. The slice is likely descending.
+        v.reverse();
+        (len - 1 - b, true)
+    }
+}
+
+/// Swaps some elements around to break patterns that cause unbalanced partitions.
+unsafe fn break_patterns<T>(v: &mut [T]) {
+    let len = v.len();
+    if len < 8 {
+        return;
+    }
+    let p = v.as_mut_ptr();
+    let mut seed = len as u32;
+    let mask = len.next_power_of_two() - 1;
+    let pos = len / 4 * 2;
+    for i in 0..3 {
+        // Xorshift
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        let mut other = seed as usize & mask;
+        if other >= len {
+            other -= len;
+        }
+        swap(p, pos - 1 + i, other);
+    }
+}
+
+/// Sorts a slice that has only a few out-of-order elements.
+///
+/// Returns whether the slice has been sorted.
+unsafe fn partial_insertion_sort<T, F>(v: &mut [T], f: &mut F) -> bool
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    let mut i = 1;
+    for _ in 0..MAX_FIX_STEPS {
+        while i < len && f(&*p.add(i), &*p.add(i - 1)) != Less {
+            i += 1;
+        }
+        if i == len {
+            return true;
+        }
+        if len < MIN_FIX_LEN {
+            return false;
+        }
+        swap(p, i - 1, i);
+        insert_tail(&mut v[..i], f);
+        insert_head(&mut v[i..], f);
+    }
+    false
+}
+
+/// Partitions the slice into elements less than the pivot followed by the pivot and
+/// elements not less than the pivot.
+///
+/// Returns the new index of the pivot and whether the slice was already partitioned.
+unsafe fn partition<T, F>(v: &mut [T], pivot: usize, f: &mut F) -> (usize, bool)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    swap(p, 0, pivot);
+
+    let mut l = 1;
+    let mut r = len;
+    let mut was_partitioned = true;
+    loop {
+        while l < r && f(&*p.add(l), &*p) == Less {
+            l += 1;
+        }
+        while l < r && f(&*p.add(r - 1), &*p) != Less {
+            r -= 1;
+        }
+        if l >= r {
             break;
         }
+        was_partitioned = false;
+        r -= 1;
+        swap(p, l, r);
+        l += 1;
     }
-    start = start.offset(-1);
-    if pivot < start {
-        mem::swap(&mut *pivot, &mut *start);
+
+    swap(p, 0, l - 1);
+    (l - 1, was_partitioned)
+}
+
+/// Partitions the slice into elements equal to the pivot followed by elements greater
+/// than the pivot.
+///
+/// Returns the number of elements equal to the pivot. This assumes that no element is
+/// less than the pivot.
+unsafe fn partition_equal<T, F>(v: &mut [T], pivot: usize, f: &mut F) -> usize
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    swap(p, 0, pivot);
+
+    let mut l = 1;
+    let mut r = len;
+    loop {
+        while l < r && f(&*p, &*p.add(l)) != Less {
+            l += 1;
+        }
+        while l < r && f(&*p, &*p.add(r - 1)) == Less {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        swap(p, l, r);
+        l += 1;
     }
-    let start_len = (start as usize - pivot as usize) / mem::size_of::<T>();
+    l
+}
+
+unsafe fn pdqsort_rec<'a, T, F>(mut v: &'a mut [T], f: &mut F, mut pred: Option<&'a T>,
+                               mut limit: usize)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let mut was_balanced = true;
+    let mut was_partitioned = true;
+
+    loop {
+        let len = v.len();
+        if len <= MAX_INSERTION {
+            insertion_sort(v, f);
+            return;
+        }
+        if limit == 0 {
+            heapsort(v, f);
+            return;
+        }
+        if !was_balanced {
+            break_patterns(v);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(v, f);
+        if was_balanced && was_partitioned && likely_sorted {
+            if partial_insertion_sort(v, f) {
+                return;
+            }
+        }
+
+        // If the predecessor of this slice is not less than the pivot, then all elements
+        // equal to the predecessor are already at their final position.
+        if let Some(pred) = pred {
+            if f(pred, &v[pivot]) != Less {
+                let mid = partition_equal(v, pivot, f);
+                let tmp = v;
+                v = &mut tmp[mid..];
+                continue;
+            }
+        }
+
+        let (mid, partitioned) = partition(v, pivot, f);
+        was_balanced = cmp::min(mid, len - mid) >= len / 8;
+        was_partitioned = partitioned;
+
+        // Recurse into the shorter side to limit the stack depth.
+        let tmp = v;
+        let (left, right) = tmp.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+        if left.len() < right.len() {
+            pdqsort_rec(left, f, pred, limit);
+            v = right;
+            pred = Some(pivot);
+        } else {
+            pdqsort_rec(right, f, Some(pivot), limit);
+            v = left;
+        }
+    }
+}
+
+/// Pattern-defeating quicksort.
+///
+/// Not stable. Falls back to heapsort if too many bad pivots are chosen so that the
+/// worst case is `O(n log n)`.
+pub unsafe fn sort_unstable<T, F>(v: &mut [T], f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let limit = usize::bits() - v.len().leading_zeros();
+    pdqsort_rec(v, f, None, limit);
+}
+
+/// Reorders the slice so that the element at `index` is at its sorted position.
+///
+/// Elements before `index` are not greater and elements after `index` are not less than
+/// the element at `index`.
+pub unsafe fn select_nth<'a, T, F>(mut v: &'a mut [T], mut index: usize, f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let mut limit = usize::bits() - v.len().leading_zeros();
+    let mut pred: Option<&'a T> = None;
+    let mut was_balanced = true;
+
+    loop {
+        let len = v.len();
+        if len <= MAX_INSERTION {
+            insertion_sort(v, f);
+            return;
+        }
+        if limit == 0 {
+            heapsort(v, f);
+            return;
+        }
+        if !was_balanced {
+            break_patterns(v);
+            limit -= 1;
+        }
+
+        let (pivot, _) = choose_pivot(v, f);
+
+        if let Some(pred) = pred {
+            if f(pred, &v[pivot]) != Less {
+                let mid = partition_equal(v, pivot, f);
+                if index < mid {
+                    return;
+                }
+                let tmp = v;
+                v = &mut tmp[mid..];
+                index -= mid;
+                continue;
+            }
+        }
+
+        let (mid, _) = partition(v, pivot, f);
+        was_balanced = cmp::min(mid, len - mid) >= len / 8;
+        if mid == index {
+            return;
+        }
+
+        let tmp = v;
+        let (left, right) = tmp.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        if index < mid {
+            v = left;
+        } else {
+            v = right;
+            pred = Some(&pivot[0]);
+            index -= mid + 1;
+        }
+    }
+}
+
+/// Returns the index of the first element that is not less than `x`.
+unsafe fn lower_bound<T, F>(v: &[T], x: &T, f: &mut F) -> usize
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let mut left = 0;
+    let mut right = v.len();
+    while left < right {
+        let mid = left + (right - left) / 2;
+        if f(&v[mid], x) == Less {
+            left = mid + 1;
+        } else {
+            right = mid;
+        }
+    }
+    left
+}
+
+/// Returns the index of the first element that is greater than `x`.
+unsafe fn upper_bound<T, F>(v: &[T], x: &T, f: &mut F) -> usize
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let mut left = 0;
+    let mut right = v.len();
+    while left < right {
+        let mid = left + (right - left) / 2;
+        if f(x, &v[mid]) == Less {
+            right = mid;
+        } else {
+            left = mid + 1;
+        }
+    }
+    left
+}
+
+/// Rotates the slice to the left by `k` elements.
+fn rotate<T>(v: &mut [T], k: usize) {
+    v[..k].reverse();
+    v[k..].reverse();
+    v.reverse();
+}
+
+/// Merges the sorted runs `v[..mid]` and `v[mid..]` without a buffer.
+unsafe fn merge_in_place<T, F>(v: &mut [T], mid: usize, f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    if mid == 0 || mid == len {
+        return;
+    }
+    if len == 2 {
+        if f(&v[1], &v[0]) == Less {
+            swap(v.as_mut_ptr(), 0, 1);
+        }
+        return;
+    }
+
+    let (cut1, cut2);
+    if mid >= len - mid {
+        cut1 = mid / 2;
+        cut2 = mid + lower_bound(&v[mid..], &v[cut1], f);
+    } else {
+        cut2 = mid + (len - mid) / 2;
+        cut1 = upper_bound(&v[..mid], &v[cut2], f);
+    }
+    rotate(&mut v[cut1..cut2], mid - cut1);
 
-    sort(slice::from_ptr(pivot          , start_len                  ), &mut *f);
-    sort(slice::from_ptr(start.offset(1), slice.len() - start_len - 1), &mut *f);
+    let new_mid = cut1 + (cut2 - mid);
+    merge_in_place(&mut v[..new_mid], cut1, f);
+    merge_in_place(&mut v[new_mid..], cut2 - new_mid, f);
+}
+
+/// Merges the sorted runs `v[..mid]` and `v[mid..]` with a buffer that can hold `mid`
+/// elements.
+unsafe fn merge_buf<T, F>(v: &mut [T], mid: usize, buf: *mut T, f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    let p = v.as_mut_ptr();
+    ptr::memcpy(buf, p, mid);
+
+    // The output position is always before the next element of the right run so that
+    // no element is overwritten before it has been moved.
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < mid && j < len {
+        if f(&*p.add(j), &*buf.add(i)) == Less {
+            ptr::memcpy(p.add(k), p.add(j), 1);
+            j += 1;
+        } else {
+            ptr::memcpy(p.add(k), buf.add(i), 1);
+            i += 1;
+        }
+        k += 1;
+    }
+    ptr::memcpy(p.add(k), buf.add(i), mid - i);
+}
+
+/// Stable merge sort.
+///
+/// `buf` is either null or points to uninitialized memory that can hold `v.len() / 2`
+/// elements. Without a buffer, runs are merged in-place in `O(n log n)` time per merge.
+pub unsafe fn merge_sort<T, F>(v: &mut [T], buf: *mut T, f: &mut F)
+    where F: FnMut(&T, &T) -> Ordering,
+{
+    let len = v.len();
+    if len <= MAX_INSERTION {
+        insertion_sort(v, f);
+        return;
+    }
+
+    let mid = len / 2;
+    {
+        let (left, right) = v.split_at_mut(mid);
+        merge_sort(left, buf, f);
+        merge_sort(right, buf, f);
+    }
+
+    // Already in order, e.g., because the input was sorted.
+    if f(&v[mid], &v[mid - 1]) != Less {
+        return;
+    }
+
+    if buf.is_null() {
+        merge_in_place(v, mid, f);
+    } else {
+        merge_buf(v, mid, buf, f);
+    }
 }
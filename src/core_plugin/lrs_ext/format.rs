@@ -353,6 +353,10 @@ impl<'a, 'b> Context<'a, 'b> {
             "?" => "Debug",
             "x" => "LowerHex",
             "X" => "UpperHex",
+            "b" => "Binary",
+            "o" => "Octal",
+            "e" => "LowerExp",
+            "E" => "UpperExp",
             x => {
                 self.ecx.span_err(self.fmtsp, &format!("unknown format trait `{}`", x));
                 "Dummy"
@@ -360,23 +364,134 @@ impl<'a, 'b> Context<'a, 'b> {
         };
 
         // ::fmt::Debug::fmt(arg, writer)
+        // ::fmt::Debug::fmt_spec(arg, &::fmt::Spec { ... }, writer)
 
         let call = {
+            let spec = self.trans_spec(arg);
+            let method = match spec {
+                Some(_) => "fmt_spec",
+                None => "fmt",
+            };
             let path = vec!(self.ecx.ident_of("std"),
                             self.ecx.ident_of("fmt"),
                             self.ecx.ident_of(trait_name),
-                            self.ecx.ident_of("fmt"));
+                            self.ecx.ident_of(method));
 
             let arg_name = &format!("arg{}", self.next_arg - 1);
             let arg = self.ecx.expr_ident(self.fmtsp, self.ecx.ident_of(arg_name));
             let writer = self.ecx.expr_ident(self.fmtsp, self.ecx.ident_of("writer"));
 
-            self.ecx.expr_call_global(self.fmtsp, path, vec!(arg, writer))
+            let args = match spec {
+                Some(spec) => vec!(arg, spec, writer),
+                None => vec!(arg, writer),
+            };
+
+            self.ecx.expr_call_global(self.fmtsp, path, args)
         };
 
         Some(self.trans_common(call))
     }
 
+    /// Translates the format options of an argument or returns `None` if no options
+    /// were specified.
+    fn trans_spec(&mut self, arg: &parse::Argument) -> Option<P<ast::Expr>> {
+        let format = &arg.format;
+        if format.fill.is_none() && format.align == parse::AlignUnknown &&
+                format.flags == 0 && format.width == parse::CountImplied &&
+                format.precision == parse::CountImplied {
+            return None;
+        }
+
+        let sp = self.fmtsp;
+
+        // 'c'
+
+        let fill = self.ecx.expr_lit(sp, ast::LitChar(format.fill.unwrap_or(' ')));
+
+        // Some(::fmt::Align::Left)
+
+        let align = match format.align {
+            parse::AlignLeft => Some("Left"),
+            parse::AlignRight => Some("Right"),
+            parse::AlignCenter => Some("Center"),
+            parse::AlignUnknown => None,
+        };
+        let align = match align {
+            Some(align) => {
+                let path = vec!(self.ecx.ident_of("std"),
+                                self.ecx.ident_of("fmt"),
+                                self.ecx.ident_of("Align"),
+                                self.ecx.ident_of(align));
+                let align = self.ecx.expr_path(self.ecx.path_global(sp, path));
+                self.ecx.expr_some(sp, align)
+            }
+            None => self.ecx.expr_none(sp),
+        };
+
+        // true/false
+
+        let flag = |f: parse::Flag| format.flags & (1 << f as u32) != 0;
+        let plus = self.ecx.expr_bool(sp, flag(parse::FlagSignPlus));
+        let alternate = self.ecx.expr_bool(sp, flag(parse::FlagAlternate));
+        let zero = self.ecx.expr_bool(sp, flag(parse::FlagSignAwareZeroPad));
+
+        // Some(width), Some(precision)
+
+        let width = self.trans_count(format.width, arg);
+        let precision = self.trans_count(format.precision, arg);
+
+        // &::fmt::Spec { fill: fill, align: align, ... }
+
+        let path = vec!(self.ecx.ident_of("std"),
+                        self.ecx.ident_of("fmt"),
+                        self.ecx.ident_of("Spec"));
+        let path = self.ecx.path_global(sp, path);
+
+        let fields = vec!(
+            self.ecx.field_imm(sp, self.ecx.ident_of("fill"), fill),
+            self.ecx.field_imm(sp, self.ecx.ident_of("align"), align),
+            self.ecx.field_imm(sp, self.ecx.ident_of("plus"), plus),
+            self.ecx.field_imm(sp, self.ecx.ident_of("alternate"), alternate),
+            self.ecx.field_imm(sp, self.ecx.ident_of("zero"), zero),
+            self.ecx.field_imm(sp, self.ecx.ident_of("width"), width),
+            self.ecx.field_imm(sp, self.ecx.ident_of("precision"), precision),
+        );
+
+        let spec = self.ecx.expr_struct(sp, path, fields);
+        Some(self.ecx.expr_addr_of(sp, spec))
+    }
+
+    /// Translates a width or precision to an `Option<usize>` expression.
+    fn trans_count(&mut self, count: parse::Count,
+                   arg: &parse::Argument) -> P<ast::Expr> {
+        let i = match count {
+            parse::CountImplied => return self.ecx.expr_none(self.fmtsp),
+            parse::CountIs(n) => {
+                let n = self.ecx.expr_usize(self.fmtsp, n);
+                return self.ecx.expr_some(self.fmtsp, n);
+            }
+            parse::CountIsParam(i) => i,
+            parse::CountIsNextParam => {
+                // The count was consumed before the argument itself.
+                match arg.position {
+                    parse::ArgumentNext => self.next_arg - 2,
+                    _ => self.next_arg - 1,
+                }
+            }
+            parse::CountIsName(..) => {
+                self.ecx.span_err(self.fmtsp, "named counts are not supported");
+                return self.ecx.expr_none(self.fmtsp);
+            }
+        };
+
+        // Some(*argi)
+
+        let arg_name = &format!("arg{}", i);
+        let arg = self.ecx.expr_ident(self.fmtsp, self.ecx.ident_of(arg_name));
+        let arg = self.ecx.expr_deref(self.fmtsp, arg);
+        self.ecx.expr_some(self.fmtsp, arg)
+    }
+
     fn trans_common(&mut self, call: P<ast::Expr>) -> P<ast::Expr> {
 
         // res = call
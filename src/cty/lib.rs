@@ -3057,6 +3057,13 @@ pub struct in6_flowlabel_req {
     pub __flr_pad:   __u32,
 }
 
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct in6_pktinfo {
+    pub ipi6_addr:    in6_addr,
+    pub ipi6_ifindex: c_int,
+}
+
 pub const IPV6_FL_A_GET     : __u8 = 0;
 pub const IPV6_FL_A_PUT     : __u8 = 1;
 pub const IPV6_FL_A_RENEW   : __u8 = 2;
@@ -3252,6 +3259,29 @@ pub const SIOCGHWTSTAMP          : c_int = 0x89b1;
 pub const SIOCDEVPRIVATE         : c_int = 0x89F0;
 pub const SIOCPROTOPRIVATE       : c_int = 0x89E0;
 
+/////////////////////////////////
+// include/uapi/linux/net_tstamp.h
+/////////////////////////////////
+
+pub const SOF_TIMESTAMPING_TX_HARDWARE  : c_uint = 1 << 0;
+pub const SOF_TIMESTAMPING_TX_SOFTWARE  : c_uint = 1 << 1;
+pub const SOF_TIMESTAMPING_RX_HARDWARE  : c_uint = 1 << 2;
+pub const SOF_TIMESTAMPING_RX_SOFTWARE  : c_uint = 1 << 3;
+pub const SOF_TIMESTAMPING_SOFTWARE     : c_uint = 1 << 4;
+pub const SOF_TIMESTAMPING_SYS_HARDWARE : c_uint = 1 << 5;
+pub const SOF_TIMESTAMPING_RAW_HARDWARE : c_uint = 1 << 6;
+pub const SOF_TIMESTAMPING_OPT_ID       : c_uint = 1 << 7;
+pub const SOF_TIMESTAMPING_TX_SCHED     : c_uint = 1 << 8;
+pub const SOF_TIMESTAMPING_TX_ACK       : c_uint = 1 << 9;
+pub const SOF_TIMESTAMPING_OPT_CMSG     : c_uint = 1 << 10;
+pub const SOF_TIMESTAMPING_OPT_TSONLY   : c_uint = 1 << 11;
+
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct scm_timestamping {
+    pub ts: [timespec; 3],
+}
+
 ///////////////////////////
 // include/uapi/linux/tcp.h
 ///////////////////////////
@@ -3284,6 +3314,56 @@ pub const TCP_FASTOPEN             : c_int = 23;
 pub const TCP_TIMESTAMP            : c_int = 24;
 pub const TCP_NOTSENT_LOWAT        : c_int = 25;
 
+pub const TCPI_OPT_TIMESTAMPS : __u8 = 1;
+pub const TCPI_OPT_SACK       : __u8 = 2;
+pub const TCPI_OPT_WSCALE     : __u8 = 4;
+pub const TCPI_OPT_ECN        : __u8 = 8;
+pub const TCPI_OPT_ECN_SEEN   : __u8 = 16;
+pub const TCPI_OPT_SYN_DATA   : __u8 = 32;
+
+#[repr(C)]
+#[derive(Pod)]
+pub struct tcp_info {
+    pub tcpi_state:          __u8,
+    pub tcpi_ca_state:       __u8,
+    pub tcpi_retransmits:    __u8,
+    pub tcpi_probes:         __u8,
+    pub tcpi_backoff:        __u8,
+    pub tcpi_options:        __u8,
+    pub tcpi_wscale:         __u8,
+    pub __tcpi_pad:          __u8,
+
+    pub tcpi_rto:            __u32,
+    pub tcpi_ato:            __u32,
+    pub tcpi_snd_mss:        __u32,
+    pub tcpi_rcv_mss:        __u32,
+
+    pub tcpi_unacked:        __u32,
+    pub tcpi_sacked:         __u32,
+    pub tcpi_lost:           __u32,
+    pub tcpi_retrans:        __u32,
+    pub tcpi_fackets:        __u32,
+
+    pub tcpi_last_data_sent: __u32,
+    pub tcpi_last_ack_sent:  __u32,
+    pub tcpi_last_data_recv: __u32,
+    pub tcpi_last_ack_recv:  __u32,
+
+    pub tcpi_pmtu:           __u32,
+    pub tcpi_rcv_ssthresh:   __u32,
+    pub tcpi_rtt:            __u32,
+    pub tcpi_rttvar:         __u32,
+    pub tcpi_snd_ssthresh:   __u32,
+    pub tcpi_snd_cwnd:       __u32,
+    pub tcpi_advmss:         __u32,
+    pub tcpi_reordering:     __u32,
+
+    pub tcpi_rcv_rtt:        __u32,
+    pub tcpi_rcv_space:      __u32,
+
+    pub tcpi_total_retrans:  __u32,
+}
+
 ///////////////////////////
 // include/uapi/linux/udp.h
 ///////////////////////////
@@ -3480,6 +3560,55 @@ pub unsafe fn NLA_ALIGN(len: c_uint) -> c_uint {
 }
 // #define NLA_HDRLEN		((int) NLA_ALIGN(sizeof(struct nlattr)))
 
+///////////////////////////////////
+// include/uapi/linux/genetlink.h
+///////////////////////////////////
+
+pub const GENL_NAMSIZ : usize = 16;
+
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct genlmsghdr {
+    pub cmd:      __u8,
+    pub version:  __u8,
+    pub reserved: __u16,
+}
+
+pub const GENL_ADMIN_PERM     : c_int = 0x01;
+pub const GENL_CMD_CAP_DO     : c_int = 0x02;
+pub const GENL_CMD_CAP_DUMP   : c_int = 0x04;
+pub const GENL_CMD_CAP_HASPOL : c_int = 0x08;
+
+pub const GENL_ID_CTRL : __u16 = NLMSG_MIN_TYPE;
+
+pub const CTRL_CMD_UNSPEC       : __u8 = 0;
+pub const CTRL_CMD_NEWFAMILY    : __u8 = 1;
+pub const CTRL_CMD_DELFAMILY    : __u8 = 2;
+pub const CTRL_CMD_GETFAMILY    : __u8 = 3;
+pub const CTRL_CMD_NEWOPS       : __u8 = 4;
+pub const CTRL_CMD_DELOPS       : __u8 = 5;
+pub const CTRL_CMD_GETOPS       : __u8 = 6;
+pub const CTRL_CMD_NEWMCAST_GRP : __u8 = 7;
+pub const CTRL_CMD_DELMCAST_GRP : __u8 = 8;
+pub const CTRL_CMD_GETMCAST_GRP : __u8 = 9;
+
+pub const CTRL_ATTR_UNSPEC       : __u16 = 0;
+pub const CTRL_ATTR_FAMILY_ID    : __u16 = 1;
+pub const CTRL_ATTR_FAMILY_NAME  : __u16 = 2;
+pub const CTRL_ATTR_VERSION      : __u16 = 3;
+pub const CTRL_ATTR_HDRSIZE      : __u16 = 4;
+pub const CTRL_ATTR_MAXATTR      : __u16 = 5;
+pub const CTRL_ATTR_OPS          : __u16 = 6;
+pub const CTRL_ATTR_MCAST_GROUPS : __u16 = 7;
+
+pub const CTRL_ATTR_OP_UNSPEC : __u16 = 0;
+pub const CTRL_ATTR_OP_ID     : __u16 = 1;
+pub const CTRL_ATTR_OP_FLAGS  : __u16 = 2;
+
+pub const CTRL_ATTR_MCAST_GRP_UNSPEC : __u16 = 0;
+pub const CTRL_ATTR_MCAST_GRP_NAME   : __u16 = 1;
+pub const CTRL_ATTR_MCAST_GRP_ID     : __u16 = 2;
+
 /////////////////////////////////
 // include/uapi/linux/rtnetlink.h
 /////////////////////////////////
@@ -3806,6 +3935,26 @@ pub struct ifinfomsg {
     pub ifi_change: c_uint,
 }
 
+pub const IFF_UP          : c_uint = 0x1;
+pub const IFF_BROADCAST   : c_uint = 0x2;
+pub const IFF_DEBUG       : c_uint = 0x4;
+pub const IFF_LOOPBACK    : c_uint = 0x8;
+pub const IFF_POINTOPOINT : c_uint = 0x10;
+pub const IFF_NOTRAILERS  : c_uint = 0x20;
+pub const IFF_RUNNING     : c_uint = 0x40;
+pub const IFF_NOARP       : c_uint = 0x80;
+pub const IFF_PROMISC     : c_uint = 0x100;
+pub const IFF_ALLMULTI    : c_uint = 0x200;
+pub const IFF_MASTER      : c_uint = 0x400;
+pub const IFF_SLAVE       : c_uint = 0x800;
+pub const IFF_MULTICAST   : c_uint = 0x1000;
+pub const IFF_PORTSEL     : c_uint = 0x2000;
+pub const IFF_AUTOMEDIA   : c_uint = 0x4000;
+pub const IFF_DYNAMIC     : c_uint = 0x8000;
+pub const IFF_LOWER_UP    : c_uint = 0x10000;
+pub const IFF_DORMANT     : c_uint = 0x20000;
+pub const IFF_ECHO        : c_uint = 0x40000;
+
 #[repr(C)]
 #[derive(Pod, Eq)]
 pub struct prefixmsg {
@@ -4766,6 +4915,14 @@ pub struct ifmap {
     pub port:      c_uchar,
 }
 
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct ifreq {
+    pub ifr_name: [c_char; IFNAMSIZ],
+    // A union whose largest member is ifmap. The first member is used for ints.
+    pub ifr_ifru: ifmap,
+}
+
 ////////////////////////////
 // include/uapi/linux/veth.h
 ////////////////////////////
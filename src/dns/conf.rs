@@ -0,0 +1,137 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{cmp};
+use base::{error};
+use vec::{Vec};
+use parse::{Parse, Parsable};
+use socket::{IpAddr, IpSockAddrBuf};
+use util::{read_file, strip_comment, to_vec, Fields};
+
+/// The maximum number of nameservers that are used.
+pub const MAX_NAMESERVERS: usize = 3;
+
+/// The DNS port.
+pub const PORT: u16 = 53;
+
+/// The resolver configuration.
+///
+/// = Remarks
+///
+/// This corresponds to the contents of `/etc/resolv.conf`. The fields can be modified
+/// after the configuration has been loaded.
+///
+/// = See also
+///
+/// * link:man:resolv.conf(5)
+pub struct Config {
+    /// The nameservers to query in order. If this is empty, the nameserver at
+    /// `127.0.0.1` is queried.
+    pub nameservers: Vec<IpSockAddrBuf>,
+    /// The domains that are appended to names that are not fully qualified.
+    pub search: Vec<Vec<u8>>,
+    /// The number of dots a name needs to be tried as-is before the search list.
+    pub ndots: u8,
+    /// The number of seconds to wait for a reply from a nameserver.
+    pub timeout: u8,
+    /// The number of times every nameserver is tried.
+    pub attempts: u8,
+}
+
+impl Config {
+    /// Creates the default configuration.
+    ///
+    /// = Remarks
+    ///
+    /// The default configuration has no nameservers, an empty search list, `ndots` 1,
+    /// `timeout` 5, and `attempts` 2.
+    pub fn new() -> Config {
+        Config {
+            nameservers: Vec::new(),
+            search: Vec::new(),
+            ndots: 1,
+            timeout: 5,
+            attempts: 2,
+        }
+    }
+
+    /// Loads the configuration from `/etc/resolv.conf`.
+    ///
+    /// = Remarks
+    ///
+    /// If the file does not exist, the default configuration is returned.
+    pub fn load() -> Result<Config> {
+        match read_file(b"/etc/resolv.conf\0") {
+            Ok(data) => Config::parse(&data),
+            Err(error::DoesNotExist) => Ok(Config::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses a configuration in the format of `/etc/resolv.conf`.
+    ///
+    /// [argument, text]
+    /// The contents of the file.
+    ///
+    /// = Remarks
+    ///
+    /// The `nameserver`, `domain`, `search`, and `options` keywords are supported. Of
+    /// the options, `ndots`, `timeout`, and `attempts` are supported. Unknown keywords
+    /// and invalid lines are ignored.
+    pub fn parse(text: &[u8]) -> Result<Config> {
+        let mut conf = Config::new();
+        for line in text.split(|&b| b == b'\n') {
+            let mut fields = Fields::new(strip_comment(line, b"#;"));
+            let keyword = match fields.next() {
+                Some(keyword) => keyword,
+                _ => continue,
+            };
+            if keyword == &b"nameserver"[..] {
+                let addr = match fields.next().map(|a| IpAddr::parse_bytes(a)) {
+                    Some(Ok(addr)) => addr,
+                    _ => continue,
+                };
+                if conf.nameservers.len() < MAX_NAMESERVERS {
+                    try!(conf.nameservers.push(IpSockAddrBuf::new(addr, PORT)));
+                }
+            } else if keyword == &b"domain"[..] {
+                if let Some(domain) = fields.next() {
+                    conf.search.truncate(0);
+                    try!(conf.search.push(try!(to_vec(domain))));
+                }
+            } else if keyword == &b"search"[..] {
+                conf.search.truncate(0);
+                for domain in fields {
+                    try!(conf.search.push(try!(to_vec(domain))));
+                }
+            } else if keyword == &b"options"[..] {
+                for opt in fields {
+                    conf.set_option(opt);
+                }
+            }
+        }
+        Ok(conf)
+    }
+
+    fn set_option(&mut self, opt: &[u8]) {
+        let colon = match opt.find(|&b| b == b':') {
+            Some(colon) => colon,
+            _ => return,
+        };
+        let val: u64 = match opt[colon + 1..].parse() {
+            Ok(val) => val,
+            _ => return,
+        };
+        // The limits are the same as in glibc.
+        let name = &opt[..colon];
+        if name == &b"ndots"[..] {
+            self.ndots = cmp::min(val, 15) as u8;
+        } else if name == &b"timeout"[..] {
+            self.timeout = cmp::max(cmp::min(val, 30), 1) as u8;
+        } else if name == &b"attempts"[..] {
+            self.attempts = cmp::max(cmp::min(val, 5), 1) as u8;
+        }
+    }
+}
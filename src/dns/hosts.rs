@@ -0,0 +1,91 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Lookups in the hosts file.
+//!
+//! = See also
+//!
+//! * link:man:hosts(5)
+
+use base::prelude::*;
+use base::{error};
+use vec::{Vec};
+use parse::{Parsable};
+use socket::{IpAddr};
+use util::{read_file, strip_comment, name_eq, to_vec, Fields};
+
+/// Loads the contents of `/etc/hosts`.
+///
+/// = Remarks
+///
+/// If the file does not exist, an empty vector is returned.
+pub fn load() -> Result<Vec<u8>> {
+    match read_file(b"/etc/hosts\0") {
+        Err(error::DoesNotExist) => Ok(Vec::new()),
+        res => res,
+    }
+}
+
+fn for_each_entry<F>(hosts: &[u8], mut f: F) -> Result
+    where F: FnMut(IpAddr, &[u8]) -> Result,
+{
+    for line in hosts.split(|&b| b == b'\n') {
+        let mut fields = Fields::new(strip_comment(line, b"#"));
+        let addr = match fields.next().map(|a| IpAddr::parse_bytes(a)) {
+            Some(Ok(addr)) => addr,
+            _ => continue,
+        };
+        for name in fields {
+            try!(f(addr, name));
+        }
+    }
+    Ok(())
+}
+
+/// Looks up the addresses of a host name.
+///
+/// [argument, hosts]
+/// The contents of a file in the format of `/etc/hosts`.
+///
+/// [argument, name]
+/// The name to look up.
+///
+/// [argument, res]
+/// The vector to which the addresses will be appended.
+///
+/// = Remarks
+///
+/// Names are compared case-insensitively. The addresses are appended in the order in
+/// which they appear in the file.
+pub fn lookup_name(hosts: &[u8], name: &[u8], res: &mut Vec<IpAddr>) -> Result {
+    for_each_entry(hosts, |addr, n| {
+        if name_eq(n, name) && res.find(|&a| a == addr).is_none() {
+            try!(res.push(addr));
+        }
+        Ok(())
+    })
+}
+
+/// Looks up the host names of an address.
+///
+/// [argument, hosts]
+/// The contents of a file in the format of `/etc/hosts`.
+///
+/// [argument, addr]
+/// The address to look up.
+///
+/// [argument, res]
+/// The vector to which the names will be appended.
+///
+/// = Remarks
+///
+/// The canonical name of each entry is appended before its aliases.
+pub fn lookup_addr(hosts: &[u8], addr: IpAddr, res: &mut Vec<Vec<u8>>) -> Result {
+    for_each_entry(hosts, |a, name| {
+        if a == addr {
+            try!(res.push(try!(to_vec(name))));
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,32 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![crate_name = "lrs_dns"]
+#![crate_type = "lib"]
+#![feature(custom_derive)]
+#![no_std]
+#![allow(non_upper_case_globals)]
+
+extern crate lrs_base       as base;
+extern crate lrs_alloc      as alloc;
+extern crate lrs_vec        as vec;
+extern crate lrs_io         as io;
+extern crate lrs_parse      as parse;
+extern crate lrs_time_base  as time_base;
+extern crate lrs_file       as file;
+extern crate lrs_rand       as rand;
+extern crate lrs_socket     as socket;
+
+mod std { pub use vec::std::*; }
+
+pub use conf::{Config};
+pub use resolver::{Resolver};
+pub use msg::{Message, Header, Question, Record, RecordIter, Srv};
+
+pub mod conf;
+pub mod hosts;
+pub mod msg;
+pub mod resolver;
+
+mod util;
@@ -0,0 +1,458 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! DNS messages.
+//!
+//! = See also
+//!
+//! * link:https://tools.ietf.org/html/rfc1035[RFC 1035]
+
+use base::prelude::*;
+use base::{error};
+use vec::{Vec};
+use socket::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Resource record types.
+pub mod rr_type {
+    /// An Ipv4 address.
+    pub const A: u16 = 1;
+    /// An authoritative name server.
+    pub const Ns: u16 = 2;
+    /// The canonical name of an alias.
+    pub const Cname: u16 = 5;
+    /// The start of a zone of authority.
+    pub const Soa: u16 = 6;
+    /// A domain name pointer.
+    pub const Ptr: u16 = 12;
+    /// A mail exchange.
+    pub const Mx: u16 = 15;
+    /// Text strings.
+    pub const Txt: u16 = 16;
+    /// An Ipv6 address.
+    pub const Aaaa: u16 = 28;
+    /// A service location.
+    pub const Srv: u16 = 33;
+}
+
+/// Resource record classes.
+pub mod rr_class {
+    /// The internet.
+    pub const In: u16 = 1;
+}
+
+/// Response codes.
+pub mod rcode {
+    /// No error.
+    pub const NoError: u8 = 0;
+    /// The server was unable to interpret the query.
+    pub const FormErr: u8 = 1;
+    /// The server was unable to process the query.
+    pub const ServFail: u8 = 2;
+    /// The queried name does not exist.
+    pub const NxDomain: u8 = 3;
+    /// The server does not support the kind of query.
+    pub const NotImp: u8 = 4;
+    /// The server refused to process the query.
+    pub const Refused: u8 = 5;
+}
+
+/// The size of the message header.
+pub const HEADER_SIZE: usize = 12;
+
+/// The maximum size of a message sent over UDP.
+pub const MAX_UDP_SIZE: usize = 512;
+
+/// The maximum length of an encoded domain name.
+pub const MAX_NAME_SIZE: usize = 255;
+
+/// The message is a response.
+pub const FLAG_QR: u16 = 0x8000;
+
+/// The message was truncated.
+pub const FLAG_TC: u16 = 0x0200;
+
+/// Recursion is desired.
+pub const FLAG_RD: u16 = 0x0100;
+
+const MAX_LABEL_SIZE: usize = 63;
+const MAX_JUMPS: usize = 32;
+
+fn be16(data: &[u8], pos: usize) -> Result<u16> {
+    if data.len() < pos + 2 {
+        return Err(error::ProtocolError);
+    }
+    Ok((data[pos] as u16) << 8 | data[pos + 1] as u16)
+}
+
+fn be32(data: &[u8], pos: usize) -> Result<u32> {
+    let hi = try!(be16(data, pos));
+    let lo = try!(be16(data, pos + 2));
+    Ok((hi as u32) << 16 | lo as u32)
+}
+
+fn push16(buf: &mut Vec<u8>, val: u16) -> Result {
+    buf.push_all(&[(val >> 8) as u8, val as u8])
+}
+
+/// Appends a query to a buffer.
+///
+/// [argument, buf]
+/// The buffer to which the query will be appended.
+///
+/// [argument, id]
+/// The identifier of the query.
+///
+/// [argument, name]
+/// The domain name to query, e.g., `example.com`.
+///
+/// [argument, ty]
+/// The record type to query.
+///
+/// = Remarks
+///
+/// The query has the recursion-desired flag set and asks for records of the internet
+/// class. A trailing dot in the name is ignored. If the name contains an empty label,
+/// a label longer than 63 bytes, or is longer than 255 bytes when encoded, an error is
+/// returned.
+pub fn build_query(buf: &mut Vec<u8>, id: u16, name: &[u8], ty: u16) -> Result {
+    let name = match name.len() {
+        0 => name,
+        n if name[n - 1] == b'.' => &name[..n - 1],
+        _ => name,
+    };
+    if name.len() + 2 > MAX_NAME_SIZE {
+        return Err(error::InvalidArgument);
+    }
+    try!(push16(buf, id));
+    try!(push16(buf, FLAG_RD));
+    try!(push16(buf, 1));
+    try!(buf.push_all(&[0; 6]));
+    if name.len() > 0 {
+        for label in name.split(|&b| b == b'.') {
+            if label.len() == 0 || label.len() > MAX_LABEL_SIZE {
+                return Err(error::InvalidArgument);
+            }
+            try!(buf.push(label.len() as u8));
+            try!(buf.push_all(label));
+        }
+    }
+    try!(buf.push(0));
+    try!(push16(buf, ty));
+    push16(buf, rr_class::In)
+}
+
+/// Returns the position after an encoded name.
+fn skip_name(data: &[u8], mut pos: usize) -> Result<usize> {
+    loop {
+        if pos >= data.len() {
+            return Err(error::ProtocolError);
+        }
+        let len = data[pos] as usize;
+        match len & 0xC0 {
+            0x00 if len == 0 => return Ok(pos + 1),
+            0x00 => pos += len + 1,
+            0xC0 => return Ok(pos + 2),
+            _ => return Err(error::ProtocolError),
+        }
+    }
+}
+
+/// Decodes a possibly compressed name.
+///
+/// [argument, data]
+/// The whole message.
+///
+/// [argument, pos]
+/// The position of the name in the message.
+///
+/// [argument, name]
+/// The buffer to which the dot-separated name will be appended.
+///
+/// [return_value]
+/// Returns the position after the encoded name.
+///
+/// = Remarks
+///
+/// The root name is decoded as the empty string.
+pub fn read_name(data: &[u8], mut pos: usize, name: &mut Vec<u8>) -> Result<usize> {
+    let start = name.len();
+    let mut end = None;
+    let mut jumps = 0;
+    loop {
+        if pos >= data.len() {
+            return Err(error::ProtocolError);
+        }
+        let len = data[pos] as usize;
+        match len & 0xC0 {
+            0x00 if len == 0 => {
+                pos += 1;
+                break;
+            },
+            0x00 => {
+                pos += 1;
+                if data.len() < pos + len || name.len() - start + len >= MAX_NAME_SIZE {
+                    return Err(error::ProtocolError);
+                }
+                if name.len() > start {
+                    try!(name.push(b'.'));
+                }
+                try!(name.push_all(&data[pos..pos + len]));
+                pos += len;
+            },
+            0xC0 => {
+                let ptr = try!(be16(data, pos)) & 0x3FFF;
+                jumps += 1;
+                if jumps > MAX_JUMPS {
+                    return Err(error::ProtocolError);
+                }
+                if end.is_none() {
+                    end = Some(pos + 2);
+                }
+                pos = ptr as usize;
+            },
+            _ => return Err(error::ProtocolError),
+        }
+    }
+    Ok(end.unwrap_or(pos))
+}
+
+/// A message header.
+#[derive(Copy, Eq)]
+pub struct Header {
+    /// The identifier of the query.
+    pub id: u16,
+    /// The flags, opcode, and response code.
+    pub flags: u16,
+    /// The number of questions.
+    pub qdcount: u16,
+    /// The number of answer records.
+    pub ancount: u16,
+    /// The number of authority records.
+    pub nscount: u16,
+    /// The number of additional records.
+    pub arcount: u16,
+}
+
+impl Header {
+    /// Returns whether the message is a response.
+    pub fn is_response(&self) -> bool {
+        self.flags & FLAG_QR != 0
+    }
+
+    /// Returns whether the message was truncated.
+    pub fn is_truncated(&self) -> bool {
+        self.flags & FLAG_TC != 0
+    }
+
+    /// Returns the response code.
+    pub fn rcode(&self) -> u8 {
+        (self.flags & 0xF) as u8
+    }
+}
+
+/// A question.
+pub struct Question {
+    /// The queried name.
+    pub name: Vec<u8>,
+    /// The queried record type.
+    pub ty: u16,
+    /// The queried record class.
+    pub class: u16,
+}
+
+/// A resource record.
+pub struct Record<'a> {
+    /// The name the record belongs to.
+    pub name: Vec<u8>,
+    /// The type of the record.
+    pub ty: u16,
+    /// The class of the record.
+    pub class: u16,
+    /// The number of seconds the record may be cached.
+    pub ttl: u32,
+    /// The record data.
+    pub data: &'a [u8],
+    msg: &'a [u8],
+    offset: usize,
+}
+
+/// A service location.
+pub struct Srv {
+    /// The priority of the target. Lower values are preferred.
+    pub priority: u16,
+    /// The relative weight of targets with the same priority.
+    pub weight: u16,
+    /// The port of the service on the target.
+    pub port: u16,
+    /// The domain name of the target.
+    pub target: Vec<u8>,
+}
+
+impl<'a> Record<'a> {
+    /// Returns the address of an `A` or `AAAA` record.
+    ///
+    /// = Remarks
+    ///
+    /// If the record has a different type, an error is returned.
+    pub fn ip(&self) -> Result<IpAddr> {
+        let d = self.data;
+        match (self.ty, d.len()) {
+            (rr_type::A, 4) => Ok(IpAddr::V4(Ipv4Addr(d[0], d[1], d[2], d[3]))),
+            (rr_type::Aaaa, 16) => {
+                let mut s = [0; 8];
+                for i in 0..8 {
+                    s[i] = (d[2 * i] as u16) << 8 | d[2 * i + 1] as u16;
+                }
+                Ok(IpAddr::V6(Ipv6Addr::from_bytes(s)))
+            },
+            (rr_type::A, _) | (rr_type::Aaaa, _) => Err(error::ProtocolError),
+            _ => Err(error::InvalidArgument),
+        }
+    }
+
+    /// Returns the domain name of a `CNAME`, `NS`, or `PTR` record.
+    ///
+    /// = Remarks
+    ///
+    /// If the record has a different type, an error is returned.
+    pub fn domain_name(&self) -> Result<Vec<u8>> {
+        match self.ty {
+            rr_type::Cname | rr_type::Ns | rr_type::Ptr => { },
+            _ => return Err(error::InvalidArgument),
+        }
+        let mut name = Vec::new();
+        try!(read_name(self.msg, self.offset, &mut name));
+        Ok(name)
+    }
+
+    /// Returns the contents of an `SRV` record.
+    ///
+    /// = Remarks
+    ///
+    /// If the record has a different type, an error is returned.
+    pub fn srv(&self) -> Result<Srv> {
+        if self.ty != rr_type::Srv {
+            return Err(error::InvalidArgument);
+        }
+        let mut target = Vec::new();
+        try!(read_name(self.msg, self.offset + 6, &mut target));
+        Ok(Srv {
+            priority: try!(be16(self.data, 0)),
+            weight: try!(be16(self.data, 2)),
+            port: try!(be16(self.data, 4)),
+            target: target,
+        })
+    }
+}
+
+/// A parsed message.
+pub struct Message<'a> {
+    data: &'a [u8],
+    header: Header,
+}
+
+impl<'a> Message<'a> {
+    /// Parses the header of a message.
+    ///
+    /// [argument, data]
+    /// The message.
+    ///
+    /// = Remarks
+    ///
+    /// The rest of the message is parsed lazily.
+    pub fn parse(data: &'a [u8]) -> Result<Message<'a>> {
+        if data.len() < HEADER_SIZE {
+            return Err(error::ProtocolError);
+        }
+        let header = Header {
+            id: try!(be16(data, 0)),
+            flags: try!(be16(data, 2)),
+            qdcount: try!(be16(data, 4)),
+            ancount: try!(be16(data, 6)),
+            nscount: try!(be16(data, 8)),
+            arcount: try!(be16(data, 10)),
+        };
+        Ok(Message { data: data, header: header })
+    }
+
+    /// Returns the header of the message.
+    pub fn header(&self) -> Header {
+        self.header
+    }
+
+    /// Returns the raw message.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the first question of the message.
+    pub fn question(&self) -> Result<Question> {
+        if self.header.qdcount == 0 {
+            return Err(error::ProtocolError);
+        }
+        let mut name = Vec::new();
+        let pos = try!(read_name(self.data, HEADER_SIZE, &mut name));
+        Ok(Question {
+            name: name,
+            ty: try!(be16(self.data, pos)),
+            class: try!(be16(self.data, pos + 2)),
+        })
+    }
+
+    /// Returns an iterator over the answer records of the message.
+    pub fn answers(&self) -> Result<RecordIter<'a>> {
+        let mut pos = HEADER_SIZE;
+        for _ in 0..self.header.qdcount {
+            pos = try!(skip_name(self.data, pos)) + 4;
+        }
+        if pos > self.data.len() {
+            return Err(error::ProtocolError);
+        }
+        Ok(RecordIter { data: self.data, pos: pos, left: self.header.ancount })
+    }
+}
+
+/// An iterator over the records of a message.
+pub struct RecordIter<'a> {
+    data: &'a [u8],
+    pos: usize,
+    left: u16,
+}
+
+impl<'a> RecordIter<'a> {
+    fn read(&mut self) -> Result<Record<'a>> {
+        let mut name = Vec::new();
+        let pos = try!(read_name(self.data, self.pos, &mut name));
+        let len = try!(be16(self.data, pos + 8)) as usize;
+        let offset = pos + 10;
+        if self.data.len() < offset + len {
+            return Err(error::ProtocolError);
+        }
+        self.pos = offset + len;
+        Ok(Record {
+            name: name,
+            ty: try!(be16(self.data, pos)),
+            class: try!(be16(self.data, pos + 2)),
+            ttl: try!(be32(self.data, pos + 4)),
+            data: &self.data[offset..offset + len],
+            msg: self.data,
+            offset: offset,
+        })
+    }
+}
+
+impl<'a> Iterator for RecordIter<'a> {
+    type Item = Result<Record<'a>>;
+    fn next(&mut self) -> Option<Result<Record<'a>>> {
+        if self.left == 0 {
+            return None;
+        }
+        self.left -= 1;
+        let res = self.read();
+        if res.is_err() {
+            self.left = 0;
+        }
+        Some(res)
+    }
+}
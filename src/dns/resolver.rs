@@ -0,0 +1,412 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::cmp::{Ordering};
+use base::{error};
+use vec::{Vec};
+use parse::{Parsable};
+use time_base::{Time};
+use rand::{Rng, GetUrandom};
+use socket::{IpAddr, Ipv4Addr, IpSockAddrBuf};
+use socket::socket::{Socket};
+use socket::flags::{SOCK_CLOSE_ON_EXEC};
+use socket::msg::{MSG_NONE};
+use conf::{Config, PORT};
+use msg::{self, rr_type, rr_class, rcode, Message, Record, Srv, MAX_UDP_SIZE};
+use hosts;
+use util::{to_vec};
+
+/// A stub resolver.
+///
+/// = Remarks
+///
+/// Names are first looked up in the hosts file and then by querying the configured
+/// nameservers. Queries are sent over UDP and retried over TCP if the response was
+/// truncated.
+///
+/// = See also
+///
+/// * link:man:resolver(3)
+pub struct Resolver {
+    conf: Config,
+    hosts: Vec<u8>,
+}
+
+impl Resolver {
+    /// Creates a resolver with the system configuration.
+    ///
+    /// = Remarks
+    ///
+    /// This reads `/etc/resolv.conf` and `/etc/hosts`. Missing files are treated as
+    /// empty.
+    pub fn new() -> Result<Resolver> {
+        let conf = try!(Config::load());
+        let hosts = try!(hosts::load());
+        Ok(Resolver::with_config(conf, hosts))
+    }
+
+    /// Creates a resolver with a custom configuration.
+    ///
+    /// [argument, conf]
+    /// The resolver configuration.
+    ///
+    /// [argument, hosts]
+    /// The contents of a file in the format of `/etc/hosts`.
+    pub fn with_config(conf: Config, hosts: Vec<u8>) -> Resolver {
+        Resolver { conf: conf, hosts: hosts }
+    }
+
+    /// Returns the configuration of the resolver.
+    pub fn config(&self) -> &Config {
+        &self.conf
+    }
+
+    /// Returns a mutable reference to the configuration of the resolver.
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.conf
+    }
+
+    /// Looks up the addresses of a host.
+    ///
+    /// [argument, name]
+    /// The name of the host or a textual Ip address.
+    ///
+    /// = Remarks
+    ///
+    /// If the name is an Ip address, it is returned unchanged. Otherwise, if the name
+    /// appears in the hosts file, those addresses are returned. Otherwise, `A` and
+    /// `AAAA` records are queried and the Ipv4 addresses are returned before the Ipv6
+    /// addresses.
+    ///
+    /// If the name does not exist, `DoesNotExist` is returned. If the name exists but
+    /// has no addresses, `NoDataAvailable` is returned. If no nameserver responded,
+    /// `TimedOut` is returned. If the nameservers failed or refused to answer the query,
+    /// `ConnectionRefused` is returned.
+    pub fn lookup_ip(&self, name: &[u8]) -> Result<Vec<IpAddr>> {
+        let mut res = Vec::new();
+        if let Ok(addr) = IpAddr::parse_bytes(name) {
+            try!(res.push(addr));
+            return Ok(res);
+        }
+        try!(hosts::lookup_name(&self.hosts, name, &mut res));
+        if res.len() > 0 {
+            return Ok(res);
+        }
+        for &ty in &[rr_type::A, rr_type::Aaaa] {
+            match self.query(name, ty, |r| res.push(try!(r.ip()))) {
+                Ok(()) | Err(error::NoDataAvailable) => { },
+                Err(e) => return Err(e),
+            }
+        }
+        match res.len() {
+            0 => Err(error::NoDataAvailable),
+            _ => Ok(res),
+        }
+    }
+
+    /// Looks up the socket addresses of a host.
+    ///
+    /// [argument, name]
+    /// The name of the host or a textual Ip address.
+    ///
+    /// [argument, port]
+    /// The port of the socket addresses.
+    ///
+    /// = Remarks
+    ///
+    /// This is `lookup_ip` with the port attached to every address.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::dns::Resolver::lookup_ip
+    pub fn lookup_host(&self, name: &[u8], port: u16) -> Result<Vec<IpSockAddrBuf>> {
+        let addrs = try!(self.lookup_ip(name));
+        let mut res = try!(Vec::with_capacity(addrs.len()));
+        for &addr in &addrs[..] {
+            try!(res.push(IpSockAddrBuf::new(addr, port)));
+        }
+        Ok(res)
+    }
+
+    /// Looks up the service locations of a name.
+    ///
+    /// [argument, name]
+    /// The name of the service, e.g., `_http._tcp.example.com`.
+    ///
+    /// = Remarks
+    ///
+    /// The locations are sorted by ascending priority and, within the same priority,
+    /// by descending weight.
+    pub fn lookup_srv(&self, name: &[u8]) -> Result<Vec<Srv>> {
+        let mut res = Vec::new();
+        try!(self.query(name, rr_type::Srv, |r| res.push(try!(r.srv()))));
+        res.sort_by(|a, b| match a.priority.cmp(&b.priority) {
+            Ordering::Equal => b.weight.cmp(&a.weight),
+            o => o,
+        });
+        Ok(res)
+    }
+
+    /// Looks up the names of an address.
+    ///
+    /// [argument, addr]
+    /// The address.
+    ///
+    /// = Remarks
+    ///
+    /// If the address appears in the hosts file, those names are returned. Otherwise,
+    /// `PTR` records are queried in the `in-addr.arpa` or `ip6.arpa` domain.
+    pub fn lookup_addr(&self, addr: IpAddr) -> Result<Vec<Vec<u8>>> {
+        let mut res = Vec::new();
+        try!(hosts::lookup_addr(&self.hosts, addr, &mut res));
+        if res.len() > 0 {
+            return Ok(res);
+        }
+        let name = try!(reverse_name(addr));
+        try!(self.query(&name, rr_type::Ptr, |r| res.push(try!(r.domain_name()))));
+        Ok(res)
+    }
+
+    /// Queries records of a name.
+    ///
+    /// [argument, name]
+    /// The name to query.
+    ///
+    /// [argument, ty]
+    /// The record type to query.
+    ///
+    /// [argument, f]
+    /// A function that is called for every answer record of the queried type.
+    ///
+    /// = Remarks
+    ///
+    /// Names with a trailing dot are absolute and queried as-is. Other names are
+    /// combined with the domains in the search list. If the name contains at least
+    /// `ndots` dots, it is tried as-is before the search list, otherwise after it.
+    ///
+    /// If `f` returns an error, the query is aborted and the error is returned.
+    pub fn query<F>(&self, name: &[u8], ty: u16, mut f: F) -> Result
+        where F: FnMut(&Record) -> Result,
+    {
+        if name.len() > 0 && name[name.len() - 1] == b'.' {
+            return self.query_name(name, ty, &mut f);
+        }
+        let mut dots = 0;
+        for &b in name {
+            if b == b'.' {
+                dots += 1;
+            }
+        }
+        let as_is_first = dots >= self.conf.ndots as usize;
+        let mut err = error::DoesNotExist;
+        if as_is_first {
+            match self.query_name(name, ty, &mut f) {
+                Err(e) if is_missing(e) => err = e,
+                res => return res,
+            }
+        }
+        for domain in &self.conf.search[..] {
+            let mut full: Vec<u8> = Vec::new();
+            try!(full.push_all(name));
+            try!(full.push(b'.'));
+            try!(full.push_all(domain));
+            match self.query_name(&full, ty, &mut f) {
+                Err(e) if is_missing(e) => err = e,
+                res => return res,
+            }
+        }
+        if !as_is_first {
+            match self.query_name(name, ty, &mut f) {
+                Err(e) if is_missing(e) => err = e,
+                res => return res,
+            }
+        }
+        Err(err)
+    }
+
+    fn query_name<F>(&self, name: &[u8], ty: u16, f: &mut F) -> Result
+        where F: FnMut(&Record) -> Result,
+    {
+        let id = try!(GetUrandom.gen::<u16>());
+        let mut query = Vec::new();
+        try!(msg::build_query(&mut query, id, name, ty));
+        let resp = try!(self.exchange(&query));
+        let msg = try!(Message::parse(&resp));
+        if msg.header().rcode() == rcode::NxDomain {
+            return Err(error::DoesNotExist);
+        }
+        let mut found = false;
+        for record in try!(msg.answers()) {
+            let record = try!(record);
+            if record.ty == ty && record.class == rr_class::In {
+                try!(f(&record));
+                found = true;
+            }
+        }
+        match found {
+            true => Ok(()),
+            false => Err(error::NoDataAvailable),
+        }
+    }
+
+    /// Sends a query to the nameservers and returns the first usable response.
+    fn exchange(&self, query: &[u8]) -> Result<Vec<u8>> {
+        let default = [IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), PORT)];
+        let servers = match self.conf.nameservers.len() {
+            0 => &default[..],
+            _ => &self.conf.nameservers[..],
+        };
+        let mut err = error::TimedOut;
+        for _ in 0..self.conf.attempts {
+            for server in servers {
+                let mut resp = match self.exchange_udp(server, query) {
+                    Ok(resp) => resp,
+                    Err(e) => { err = e; continue; },
+                };
+                // A response that cannot be parsed is treated like no response at all.
+                let truncated = match Message::parse(&resp) {
+                    Ok(msg) => msg.header().is_truncated(),
+                    Err(e) => { err = e; continue; },
+                };
+                if truncated {
+                    resp = match self.exchange_tcp(server, query) {
+                        Ok(resp) => resp,
+                        Err(e) => { err = e; continue; },
+                    };
+                }
+                let rcode = match Message::parse(&resp) {
+                    Ok(msg) => msg.header().rcode(),
+                    Err(e) => { err = e; continue; },
+                };
+                match rcode {
+                    rcode::NoError | rcode::NxDomain => return Ok(resp),
+                    rcode::ServFail | rcode::Refused => err = error::ConnectionRefused,
+                    _ => err = error::ProtocolError,
+                }
+            }
+        }
+        Err(err)
+    }
+
+    fn socket(&self, server: &IpSockAddrBuf, stream: bool) -> Result<Socket> {
+        let sock = try!(match (server.addr(), stream) {
+            (IpAddr::V4(..), false) => Socket::ipv4_datagram(SOCK_CLOSE_ON_EXEC),
+            (IpAddr::V4(..), true) => Socket::ipv4_stream(SOCK_CLOSE_ON_EXEC),
+            (IpAddr::V6(..), false) => Socket::ipv6_datagram(SOCK_CLOSE_ON_EXEC),
+            (IpAddr::V6(..), true) => Socket::ipv6_stream(SOCK_CLOSE_ON_EXEC),
+        });
+        let timeout = Some(Time::seconds(self.conf.timeout as i64));
+        try!(sock.set_recv_timeout(timeout));
+        try!(sock.set_send_timeout(timeout));
+        try!(sock.connect(server));
+        Ok(sock)
+    }
+
+    fn exchange_udp(&self, server: &IpSockAddrBuf, query: &[u8]) -> Result<Vec<u8>> {
+        let sock = try!(self.socket(server, false));
+        try!(sock.send(query, MSG_NONE));
+        let mut buf = [0; MAX_UDP_SIZE];
+        loop {
+            let len = match sock.recv(&mut buf, MSG_NONE) {
+                Ok(len) => len,
+                Err(error::WouldBlock) => return Err(error::TimedOut),
+                Err(e) => return Err(e),
+            };
+            // Ignore stray packets that do not answer our query.
+            if is_reply(&buf[..len], query) {
+                return to_vec(&buf[..len]);
+            }
+        }
+    }
+
+    fn exchange_tcp(&self, server: &IpSockAddrBuf, query: &[u8]) -> Result<Vec<u8>> {
+        let sock = try!(self.socket(server, true));
+        let mut buf = try!(Vec::with_capacity(query.len() + 2));
+        try!(buf.push_all(&[(query.len() >> 8) as u8, query.len() as u8]));
+        try!(buf.push_all(query));
+        let mut pos = 0;
+        while pos < buf.len() {
+            pos += try!(sock.send(&buf[pos..], MSG_NONE));
+        }
+        let mut len = [0; 2];
+        try!(recv_all(&sock, &mut len));
+        let len = (len[0] as usize) << 8 | len[1] as usize;
+        let mut resp: Vec<u8> = try!(Vec::with_capacity(len));
+        unsafe { resp.set_len(len); }
+        try!(recv_all(&sock, &mut resp));
+        match is_reply(&resp, query) {
+            true => Ok(resp),
+            false => Err(error::ProtocolError),
+        }
+    }
+}
+
+/// Returns whether an error means that the queried records do not exist.
+fn is_missing(e: error::Errno) -> bool {
+    e == error::DoesNotExist || e == error::NoDataAvailable
+}
+
+fn recv_all(sock: &Socket, mut buf: &mut [u8]) -> Result {
+    while buf.len() > 0 {
+        let len = match sock.recv(buf, MSG_NONE) {
+            Ok(0) => return Err(error::ProtocolError),
+            Ok(len) => len,
+            Err(error::WouldBlock) => return Err(error::TimedOut),
+            Err(e) => return Err(e),
+        };
+        buf = &mut {buf}[len..];
+    }
+    Ok(())
+}
+
+/// Checks whether a message is a response to a query.
+fn is_reply(resp: &[u8], query: &[u8]) -> bool {
+    let header = match Message::parse(resp) {
+        Ok(msg) => msg.header(),
+        _ => return false,
+    };
+    if !header.is_response() || &resp[..2] != &query[..2] {
+        return false;
+    }
+    // The question is copied into the response unless the server failed to parse the
+    // query.
+    let q = &query[msg::HEADER_SIZE..];
+    header.qdcount == 0 || (resp.len() >= msg::HEADER_SIZE + q.len() &&
+                            &resp[msg::HEADER_SIZE..msg::HEADER_SIZE + q.len()] == q)
+}
+
+/// Builds the name that is used for reverse lookups of an address.
+fn reverse_name(addr: IpAddr) -> Result<Vec<u8>> {
+    const HEX: &'static [u8; 16] = b"0123456789abcdef";
+    let mut name = Vec::new();
+    match addr {
+        IpAddr::V4(a) => {
+            let bytes = a.to_bytes();
+            for i in 0..4 {
+                let b = bytes[3 - i];
+                if b >= 100 {
+                    try!(name.push(b'0' + b / 100));
+                }
+                if b >= 10 {
+                    try!(name.push(b'0' + b / 10 % 10));
+                }
+                try!(name.push_all(&[b'0' + b % 10, b'.']));
+            }
+            try!(name.push_all(b"in-addr.arpa."));
+        },
+        IpAddr::V6(a) => {
+            let segments = a.to_bytes();
+            for i in 0..8 {
+                let s = segments[7 - i];
+                for j in 0..4 {
+                    let nibble = (s >> (4 * j)) & 0xF;
+                    try!(name.push_all(&[HEX[nibble as usize], b'.']));
+                }
+            }
+            try!(name.push_all(b"ip6.arpa."));
+        },
+    }
+    Ok(name)
+}
@@ -0,0 +1,92 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use vec::{Vec};
+use io::{BufWrite};
+use file::{File};
+
+/// Reads a whole file.
+///
+/// [argument, path]
+/// The null-terminated path of the file.
+pub fn read_file(path: &[u8]) -> Result<Vec<u8>> {
+    let mut file = try!(File::open_read(&path));
+    let mut data: Vec<u8> = Vec::new();
+    try!(data.read_to_eof(&mut file));
+    Ok(data)
+}
+
+/// Returns the part of a line before a comment.
+///
+/// [argument, chars]
+/// The characters that start a comment.
+pub fn strip_comment<'a>(line: &'a [u8], chars: &[u8]) -> &'a [u8] {
+    match line.find(|&b| chars.find(|&c| c == b).is_some()) {
+        Some(pos) => &line[..pos],
+        None => line,
+    }
+}
+
+/// An iterator over the whitespace-separated fields of a line.
+pub struct Fields<'a> {
+    line: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    pub fn new(line: &'a [u8]) -> Fields<'a> {
+        Fields { line: line }
+    }
+}
+
+fn is_space(b: u8) -> bool {
+    b == b' ' || b == b'\t' || b == b'\r'
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = &'a [u8];
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let start = match self.line.find(|&b| !is_space(b)) {
+            Some(start) => start,
+            None => return None,
+        };
+        let line = &self.line[start..];
+        let end = line.find(|&b| is_space(b)).unwrap_or(line.len());
+        self.line = &line[end..];
+        Some(&line[..end])
+    }
+}
+
+/// Compares two domain names ASCII-case-insensitively, ignoring a trailing dot.
+pub fn name_eq(mut a: &[u8], mut b: &[u8]) -> bool {
+    if a.len() > 0 && a[a.len() - 1] == b'.' {
+        a = &a[..a.len() - 1];
+    }
+    if b.len() > 0 && b[b.len() - 1] == b'.' {
+        b = &b[..b.len() - 1];
+    }
+    if a.len() != b.len() {
+        return false;
+    }
+    for i in 0..a.len() {
+        if to_lower(a[i]) != to_lower(b[i]) {
+            return false;
+        }
+    }
+    true
+}
+
+fn to_lower(b: u8) -> u8 {
+    match b {
+        b'A'...b'Z' => b - b'A' + b'a',
+        _ => b,
+    }
+}
+
+/// Copies a byte slice into a new vector.
+pub fn to_vec(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut vec = Vec::new();
+    try!(vec.push_all(bytes));
+    Ok(vec)
+}
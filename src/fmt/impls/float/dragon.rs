@@ -0,0 +1,387 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::cmp::{Ordering};
+use core::cmp::Ordering::{Less, Equal, Greater};
+
+// Digit generation is the Dragon4 algorithm with the boundary handling and scaling of
+// Burger and Dybvig. The value `v` and the half-distances to its neighbors are
+// represented as big integers `r/s`, `m+/s`, and `m-/s`. After scaling by a power of
+// ten, every digit is the integer part of `10 * r / s`. This is slow compared to
+// Grisu or Ryu but it is exact for every input and needs no precomputed tables.
+
+/// The number of 32 bit limbs in a `Big`.
+///
+/// The largest numbers occur for the smallest subnormal `f64`, which is scaled by
+/// `10^324 * 4`, i.e., a little more than 1080 bits.
+const BIG_LIMBS: usize = 40;
+
+/// A fixed-size unsigned big integer.
+#[derive(Copy)]
+struct Big {
+    len: usize,
+    limbs: [u32; BIG_LIMBS],
+}
+
+impl Big {
+    fn from_u64(val: u64) -> Big {
+        let mut big = Big { len: 0, limbs: [0; BIG_LIMBS] };
+        big.limbs[0] = val as u32;
+        big.limbs[1] = (val >> 32) as u32;
+        big.len = if big.limbs[1] != 0 { 2 } else if big.limbs[0] != 0 { 1 } else { 0 };
+        big
+    }
+
+    fn is_zero(&self) -> bool {
+        self.len == 0
+    }
+
+    fn mul_small(&mut self, m: u32) -> &mut Big {
+        let mut carry = 0;
+        for i in 0..self.len {
+            let v = self.limbs[i] as u64 * m as u64 + carry;
+            self.limbs[i] = v as u32;
+            carry = v >> 32;
+        }
+        if carry != 0 {
+            self.limbs[self.len] = carry as u32;
+            self.len += 1;
+        }
+        self
+    }
+
+    fn mul_pow2(&mut self, n: usize) -> &mut Big {
+        if self.len == 0 {
+            return self;
+        }
+        let limbs = n / 32;
+        let bits = n % 32;
+        if bits > 0 {
+            let mut carry = 0;
+            for i in 0..self.len {
+                let v = self.limbs[i];
+                self.limbs[i] = (v << bits) | carry;
+                carry = v >> (32 - bits);
+            }
+            if carry != 0 {
+                self.limbs[self.len] = carry;
+                self.len += 1;
+            }
+        }
+        if limbs > 0 {
+            let mut i = self.len;
+            while i > 0 {
+                i -= 1;
+                self.limbs[i + limbs] = self.limbs[i];
+            }
+            for i in 0..limbs {
+                self.limbs[i] = 0;
+            }
+            self.len += limbs;
+        }
+        self
+    }
+
+    fn mul_pow10(&mut self, mut n: usize) -> &mut Big {
+        while n >= 9 {
+            self.mul_small(1_000_000_000);
+            n -= 9;
+        }
+        let mut m = 1;
+        for _ in 0..n {
+            m *= 10;
+        }
+        self.mul_small(m)
+    }
+
+    fn add(&mut self, other: &Big) -> &mut Big {
+        let len = if self.len > other.len { self.len } else { other.len };
+        let mut carry = 0;
+        for i in 0..len {
+            let v = self.limbs[i] as u64 + other.limbs[i] as u64 + carry;
+            self.limbs[i] = v as u32;
+            carry = v >> 32;
+        }
+        self.len = len;
+        if carry != 0 {
+            self.limbs[len] = carry as u32;
+            self.len += 1;
+        }
+        self
+    }
+
+    /// Subtracts `other`, which must not be larger than `self`.
+    fn sub(&mut self, other: &Big) -> &mut Big {
+        let mut borrow = 0;
+        for i in 0..self.len {
+            let v = (self.limbs[i] as u64).wrapping_sub(other.limbs[i] as u64 + borrow);
+            self.limbs[i] = v as u32;
+            borrow = (v >> 63) & 1;
+        }
+        while self.len > 0 && self.limbs[self.len - 1] == 0 {
+            self.len -= 1;
+        }
+        self
+    }
+
+    fn cmp(&self, other: &Big) -> Ordering {
+        if self.len != other.len {
+            return if self.len < other.len { Less } else { Greater };
+        }
+        let mut i = self.len;
+        while i > 0 {
+            i -= 1;
+            if self.limbs[i] != other.limbs[i] {
+                return if self.limbs[i] < other.limbs[i] { Less } else { Greater };
+            }
+        }
+        Equal
+    }
+
+    /// Replaces `self` by `self % s` and returns `self / s`, which must be less than 10.
+    fn div_digit(&mut self, s: &Big) -> u8 {
+        let mut d = 0;
+        while self.cmp(s) != Less {
+            self.sub(s);
+            d += 1;
+        }
+        d
+    }
+}
+
+/// A finite, non-zero, positive floating point number `mant * 2^exp`.
+pub struct Decoded {
+    pub mant: u64,
+    pub exp: i32,
+    /// Whether the next smaller float is closer than the next larger one.
+    ///
+    /// This is the case if the mantissa is a power of two and the float is normal.
+    pub lower_closer: bool,
+}
+
+/// Where to stop generating digits in `exact`.
+#[derive(Copy)]
+pub enum Limit {
+    /// Generate this many significant digits.
+    Digits(usize),
+    /// Generate all digits up to and including the `10^-n` position.
+    Fraction(usize),
+}
+
+/// Returns a lower bound `k` of `ceil(log10(v))` with `k + 1 >= ceil(log10(v))`.
+fn estimate_k(d: &Decoded) -> i32 {
+    // 2^x <= v < 2^(x+1) and 646456993 / 2^31 is log10(2) rounded down.
+    let x = d.exp + (63 - d.mant.leading_zeros() as i32);
+    ((x as i64 * 646456993) >> 31) as i32 + 1
+}
+
+/// Generates the shortest digits that uniquely identify `d`.
+///
+/// [argument, d]
+/// The number to format.
+///
+/// [argument, buf]
+/// The buffer in which the ASCII digits will be stored.
+///
+/// [return_value]
+/// Returns the number of digits `n` and the exponent `k` such that the value is
+/// `0.buf[..n] * 10^k`.
+///
+/// = Remarks
+///
+/// If the shortest representation is not unique, the closest one is chosen. If two
+/// are equally close, the larger one is chosen.
+pub fn shortest(d: &Decoded, buf: &mut [u8; 17]) -> (usize, i32) {
+    // v = r / s, the lower boundary is (r - m-) / s and the upper boundary is
+    // (r + m+) / s. Everything is multiplied by two so that m+ and m- are integers.
+    let mut r = Big::from_u64(d.mant);
+    let mut s = Big::from_u64(1);
+    let mut mp = Big::from_u64(1);
+    let mut mm = Big::from_u64(1);
+    let shift = if d.lower_closer { 2 } else { 1 };
+    r.mul_pow2(shift);
+    s.mul_pow2(shift);
+    if d.lower_closer {
+        mp.mul_pow2(1);
+    }
+    if d.exp >= 0 {
+        r.mul_pow2(d.exp as usize);
+        mp.mul_pow2(d.exp as usize);
+        mm.mul_pow2(d.exp as usize);
+    } else {
+        s.mul_pow2(-d.exp as usize);
+    }
+
+    // Round-to-even on the parse side means that the boundaries belong to `v` if the
+    // mantissa is even.
+    let inclusive = d.mant & 1 == 0;
+
+    let mut k = estimate_k(d);
+    if k >= 0 {
+        s.mul_pow10(k as usize);
+    } else {
+        r.mul_pow10(-k as usize);
+        mp.mul_pow10(-k as usize);
+        mm.mul_pow10(-k as usize);
+    }
+
+    // Make sure that the upper boundary is less than 1.
+    let mut high = r;
+    high.add(&mp);
+    match high.cmp(&s) {
+        Greater => { s.mul_small(10); k += 1; },
+        Equal if inclusive => { s.mul_small(10); k += 1; },
+        _ => { },
+    }
+
+    let mut n = 0;
+    loop {
+        r.mul_small(10);
+        mp.mul_small(10);
+        mm.mul_small(10);
+        let mut digit = r.div_digit(&s);
+
+        let low = match r.cmp(&mm) {
+            Less => true,
+            Equal => inclusive,
+            Greater => false,
+        };
+        let mut high = r;
+        high.add(&mp);
+        let high = match high.cmp(&s) {
+            Greater => true,
+            Equal => inclusive,
+            Less => false,
+        };
+
+        if low && high {
+            // Both `digit` and `digit + 1` are valid last digits. Choose the closer
+            // one and round ties up.
+            let mut twice = r;
+            twice.mul_pow2(1);
+            if twice.cmp(&s) != Less {
+                digit += 1;
+            }
+        } else if high {
+            digit += 1;
+        }
+
+        buf[n] = b'0' + digit;
+        n += 1;
+        if low || high {
+            break;
+        }
+    }
+
+    (n, k)
+}
+
+/// Generates the correctly rounded digits of `d`.
+///
+/// [argument, d]
+/// The number to format.
+///
+/// [argument, limit]
+/// Where to stop generating digits.
+///
+/// [argument, buf]
+/// The buffer in which the ASCII digits will be stored.
+///
+/// [return_value]
+/// Returns the number of digits `n` and the exponent `k` such that the value rounded
+/// to the requested number of digits is `0.buf[..n] * 10^k`.
+///
+/// = Remarks
+///
+/// Ties are rounded to even. If the buffer is too small to hold all requested digits
+/// or the exact expansion of the number ends before the limit, the missing digits are
+/// zeros. A buffer of 800 bytes can hold every significant digit of an `f64`.
+///
+/// If the value rounds to zero at the requested position, `n` is `0`.
+pub fn exact(d: &Decoded, limit: Limit, buf: &mut [u8]) -> (usize, i32) {
+    let mut r = Big::from_u64(d.mant);
+    let mut s = Big::from_u64(1);
+    if d.exp >= 0 {
+        r.mul_pow2(d.exp as usize);
+    } else {
+        s.mul_pow2(-d.exp as usize);
+    }
+
+    let mut k = estimate_k(d);
+    if k >= 0 {
+        s.mul_pow10(k as usize);
+    } else {
+        r.mul_pow10(-k as usize);
+    }
+    if r.cmp(&s) != Less {
+        s.mul_small(10);
+        k += 1;
+    }
+
+    let digits = match limit {
+        Limit::Digits(n) => n as i64,
+        Limit::Fraction(n) => k as i64 + n as i64,
+    };
+    if digits < 0 {
+        return (0, k);
+    }
+    let digits = digits as usize;
+
+    let mut n = 0;
+    while n < digits && n < buf.len() && !r.is_zero() {
+        r.mul_small(10);
+        buf[n] = b'0' + r.div_digit(&s);
+        n += 1;
+    }
+    for i in n..digits {
+        if i == buf.len() {
+            break;
+        }
+        buf[i] = b'0';
+    }
+    let n = if digits < buf.len() { digits } else { buf.len() };
+
+    // Round the remainder r / s.
+    let mut twice = r;
+    twice.mul_pow2(1);
+    let up = match twice.cmp(&s) {
+        Greater => true,
+        Equal => n > 0 && (buf[n - 1] - b'0') & 1 == 1,
+        Less => false,
+    };
+    if !up {
+        return (n, k);
+    }
+
+    let mut i = n;
+    while i > 0 {
+        i -= 1;
+        if buf[i] < b'9' {
+            buf[i] += 1;
+            return (n, k);
+        }
+        buf[i] = b'0';
+    }
+
+    // All digits were nines or no digits were requested. The value is now
+    // `0.1 * 10^(k+1)`. In the fraction mode this adds a digit in front.
+    k += 1;
+    match limit {
+        Limit::Digits(_) => {
+            if n > 0 {
+                buf[0] = b'1';
+            }
+            (n, k)
+        },
+        Limit::Fraction(_) => {
+            let m = if n < buf.len() { n + 1 } else { n };
+            buf[0] = b'1';
+            for i in 1..m {
+                buf[i] = b'0';
+            }
+            (m, k)
+        },
+    }
+}
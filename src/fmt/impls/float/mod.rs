@@ -0,0 +1,267 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use io::{Write};
+use {Debug, Display, LowerExp, UpperExp, Spec, Align, CharCount};
+use self::dragon::{Decoded, Limit};
+
+mod dragon;
+
+/// The size of the buffer used for exact digits. Every finite `f64` has at most 767
+/// significant digits.
+const EXACT_DIGITS: usize = 800;
+
+/// Values in `[10^MIN_EXP, 10^MAX_EXP)` are printed without an exponent by `Debug` and
+/// `Display`.
+const MIN_EXP: i32 = -4;
+const MAX_EXP: i32 = 16;
+
+enum Class {
+    Nan,
+    Infinite,
+    Zero,
+    Finite(Decoded),
+}
+
+fn decode(bits: u64, mant_bits: u32, exp_bits: u32) -> (bool, Class) {
+    let neg = bits >> (mant_bits + exp_bits) != 0;
+    let exp_mask = (1 << exp_bits) - 1;
+    let biased = ((bits >> mant_bits) & exp_mask) as i32;
+    let frac = bits & ((1 << mant_bits) - 1);
+    // The exponent of the smallest subnormal number.
+    let min_exp = 2 - (1 << (exp_bits - 1)) - mant_bits as i32;
+    let class = if biased == exp_mask as i32 {
+        if frac == 0 { Class::Infinite } else { Class::Nan }
+    } else if biased == 0 {
+        if frac == 0 {
+            Class::Zero
+        } else {
+            Class::Finite(Decoded { mant: frac, exp: min_exp, lower_closer: false })
+        }
+    } else {
+        Class::Finite(Decoded {
+            mant: frac | (1 << mant_bits),
+            exp: min_exp + biased - 1,
+            lower_closer: frac == 0 && biased > 1,
+        })
+    };
+    (neg, class)
+}
+
+fn write_zeros<W: Write>(w: &mut W, mut n: usize) -> Result {
+    const ZEROS: &'static [u8] = b"0000000000000000";
+    while n > 0 {
+        let m = if n > ZEROS.len() { ZEROS.len() } else { n };
+        try!(w.write_all(&ZEROS[..m]));
+        n -= m;
+    }
+    Ok(())
+}
+
+/// Writes the digits `0.digits * 10^k` in positional notation.
+///
+/// [argument, frac]
+/// The minimum number of digits after the decimal point.
+fn write_positional<W: Write>(w: &mut W, digits: &[u8], k: i32, frac: usize) -> Result {
+    if k <= 0 {
+        try!(w.write_all(b"0"));
+    } else {
+        let k = k as usize;
+        if digits.len() >= k {
+            try!(w.write_all(&digits[..k]));
+        } else {
+            try!(w.write_all(digits));
+            try!(write_zeros(w, k - digits.len()));
+        }
+    }
+
+    // The digits after the decimal point.
+    let lead = if k < 0 && digits.len() > 0 { -k as usize } else { 0 };
+    let rest = if k > 0 && digits.len() > k as usize {
+        &digits[k as usize..]
+    } else if k > 0 {
+        &[][..]
+    } else {
+        digits
+    };
+    let len = lead + rest.len();
+    let len = if len > frac { len } else { frac };
+    if len == 0 {
+        return Ok(());
+    }
+    try!(w.write_all(b"."));
+    if lead >= len {
+        return write_zeros(w, len);
+    }
+    try!(write_zeros(w, lead));
+    try!(w.write_all(rest));
+    write_zeros(w, len - lead - rest.len())
+}
+
+/// Writes the digits `0.digits * 10^k` in scientific notation.
+///
+/// [argument, frac]
+/// The minimum number of digits after the decimal point.
+fn write_exp<W: Write>(w: &mut W, digits: &[u8], k: i32, frac: usize,
+                       upper: bool) -> Result {
+    try!(w.write_all(&digits[..1]));
+    let len = if digits.len() - 1 > frac { digits.len() - 1 } else { frac };
+    if len > 0 {
+        try!(w.write_all(b"."));
+        try!(w.write_all(&digits[1..]));
+        try!(write_zeros(w, len - (digits.len() - 1)));
+    }
+    try!(w.write_all(if upper { b"E" } else { b"e" }));
+    Debug::fmt(&(k as i64 - 1), w)
+}
+
+/// The notation in which a float is written.
+#[derive(Copy)]
+enum Mode {
+    /// Positional or scientific notation depending on the magnitude. If the argument is
+    /// true, integral values in positional notation get a trailing `.0`.
+    Shortest(bool),
+    /// Positional notation with exactly this many digits after the decimal point.
+    Fixed(usize),
+    /// Scientific notation with the given number of digits after the decimal point or
+    /// the shortest representation. If the second argument is true, the exponent is
+    /// introduced by `E`.
+    Exp(Option<usize>, bool),
+}
+
+/// Writes the absolute value of a float.
+fn write_abs<W: Write>(w: &mut W, class: &Class, mode: Mode) -> Result {
+    let d = match *class {
+        Class::Nan => return w.write_all(b"NaN").ignore_ok(),
+        Class::Infinite => return w.write_all(b"inf").ignore_ok(),
+        Class::Zero => return match mode {
+            Mode::Shortest(debug) => write_positional(w, &[], 0, debug as usize),
+            Mode::Fixed(prec) => write_positional(w, &[], 0, prec),
+            Mode::Exp(prec, upper) => write_exp(w, b"0", 1, prec.unwrap_or(0), upper),
+        },
+        Class::Finite(ref d) => d,
+    };
+    match mode {
+        Mode::Shortest(debug) => {
+            let mut buf = [0; 17];
+            let (n, k) = dragon::shortest(d, &mut buf);
+            if k - 1 < MIN_EXP || k - 1 >= MAX_EXP {
+                write_exp(w, &buf[..n], k, 0, false)
+            } else {
+                write_positional(w, &buf[..n], k, debug as usize)
+            }
+        },
+        Mode::Fixed(prec) => {
+            let mut buf = [0; EXACT_DIGITS];
+            let (n, k) = dragon::exact(d, Limit::Fraction(prec), &mut buf);
+            write_positional(w, &buf[..n], k, prec)
+        },
+        Mode::Exp(Some(prec), upper) => {
+            let mut buf = [0; EXACT_DIGITS];
+            let (n, k) = dragon::exact(d, Limit::Digits(prec + 1), &mut buf);
+            write_exp(w, &buf[..n], k, prec, upper)
+        },
+        Mode::Exp(None, upper) => {
+            let mut buf = [0; 17];
+            let (n, k) = dragon::shortest(d, &mut buf);
+            write_exp(w, &buf[..n], k, 0, upper)
+        },
+    }
+}
+
+/// Writes a float with its sign and padding.
+fn fmt_float<W: Write>(w: &mut W, spec: &Spec, neg: bool, class: Class,
+                       mode: Mode) -> Result {
+    let sign: &[u8] = if neg { b"-" } else if spec.plus { b"+" } else { b"" };
+    let (sign, finite) = match class {
+        Class::Nan => (&b""[..], false),
+        Class::Infinite => (sign, false),
+        _ => (sign, true),
+    };
+
+    let width = match spec.width {
+        Some(width) => width,
+        None => {
+            try!(w.write_all(sign));
+            return write_abs(w, &class, mode);
+        },
+    };
+
+    let mut count = CharCount(0);
+    try!(write_abs(&mut count, &class, mode));
+    let len = sign.len() + count.0;
+
+    if spec.zero && finite {
+        try!(w.write_all(sign));
+        for _ in len..width {
+            try!(w.write_all(b"0"));
+        }
+        write_abs(w, &class, mode)
+    } else {
+        let post = try!(spec.pad_pre(w, len, Align::Right));
+        try!(w.write_all(sign));
+        try!(write_abs(w, &class, mode));
+        spec.pad_post(w, post)
+    }
+}
+
+macro_rules! impl_float {
+    ($ty:ident, $mant_bits:expr, $exp_bits:expr) => {
+        impl Debug for $ty {
+            fn fmt<W: Write>(&self, w: &mut W) -> Result {
+                Debug::fmt_spec(self, &Spec::new(), w)
+            }
+
+            fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                let (neg, class) = decode(self.to_bits() as u64, $mant_bits, $exp_bits);
+                let mode = match spec.precision {
+                    Some(prec) => Mode::Fixed(prec),
+                    None => Mode::Shortest(true),
+                };
+                fmt_float(w, spec, neg, class, mode)
+            }
+        }
+
+        impl Display for $ty {
+            fn fmt<W: Write>(&self, w: &mut W) -> Result {
+                Display::fmt_spec(self, &Spec::new(), w)
+            }
+
+            fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                let (neg, class) = decode(self.to_bits() as u64, $mant_bits, $exp_bits);
+                let mode = match spec.precision {
+                    Some(prec) => Mode::Fixed(prec),
+                    None => Mode::Shortest(false),
+                };
+                fmt_float(w, spec, neg, class, mode)
+            }
+        }
+
+        impl LowerExp for $ty {
+            fn fmt<W: Write>(&self, w: &mut W) -> Result {
+                LowerExp::fmt_spec(self, &Spec::new(), w)
+            }
+
+            fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                let (neg, class) = decode(self.to_bits() as u64, $mant_bits, $exp_bits);
+                fmt_float(w, spec, neg, class, Mode::Exp(spec.precision, false))
+            }
+        }
+
+        impl UpperExp for $ty {
+            fn fmt<W: Write>(&self, w: &mut W) -> Result {
+                UpperExp::fmt_spec(self, &Spec::new(), w)
+            }
+
+            fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                let (neg, class) = decode(self.to_bits() as u64, $mant_bits, $exp_bits);
+                fmt_float(w, spec, neg, class, Mode::Exp(spec.precision, true))
+            }
+        }
+    }
+}
+
+impl_float!(f32, 23, 8);
+impl_float!(f64, 52, 11);
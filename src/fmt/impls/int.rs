@@ -4,18 +4,26 @@
 
 use base::prelude::*;
 use io::{Write};
-use core::{cmp};
-use {Debug, Display, UpperHex, LowerHex};
-
-impl Debug for i8    { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as i64), w) } }
-impl Debug for i16   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as i64), w) } }
-impl Debug for i32   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as i64), w) } }
-impl Debug for isize { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as i64), w) } }
+use {Debug, Display, UpperHex, LowerHex, Octal, Binary, Spec};
+
+macro_rules! forward {
+    ($from:ident, $to:ident, $as_ty:ty, $($ty:ident)*) => {
+        $(
+            impl $from for $ty {
+                fn fmt<W: Write>(&self, w: &mut W) -> Result {
+                    $to::fmt(&(*self as $as_ty), w)
+                }
+
+                fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                    $to::fmt_spec(&(*self as $as_ty), spec, w)
+                }
+            }
+        )*
+    }
+}
 
-impl Debug for u8    { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as u64), w) } }
-impl Debug for u16   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as u64), w) } }
-impl Debug for u32   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as u64), w) } }
-impl Debug for usize { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(&(*self as u64), w) } }
+forward!(Debug, Debug, i64, i8 i16 i32 isize);
+forward!(Debug, Debug, u64, u8 u16 u32 usize);
 
 const MAX_WIDTH_64: usize = 20; // -9223372036854775808 // 18446744073709551615
 
@@ -35,6 +43,14 @@ impl Debug for i64 {
         };
         w.write_all(buf).ignore_ok()
     }
+
+    fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+        let val = *self;
+        let abs = if val < 0 { 0u64.wrapping_sub(val as u64) } else { val as u64 };
+        let mut buf = [0; MAX_WIDTH_64];
+        let n = format_u64(&mut buf, abs);
+        spec.pad_integral(w, val < 0, b"", &buf[n..])
+    }
 }
 
 impl Debug for u64 {
@@ -43,6 +59,12 @@ impl Debug for u64 {
         let n = format_u64(&mut buf, *self);
         w.write_all(&buf[n..]).ignore_ok()
     }
+
+    fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+        let mut buf = [0; MAX_WIDTH_64];
+        let n = format_u64(&mut buf, *self);
+        spec.pad_integral(w, false, b"", &buf[n..])
+    }
 }
 
 fn format_u64(buf: &mut [u8; MAX_WIDTH_64], mut val: u64) -> usize {
@@ -60,51 +82,60 @@ fn format_u64(buf: &mut [u8; MAX_WIDTH_64], mut val: u64) -> usize {
     i
 }
 
-impl Display for i8    { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for i16   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for i32   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for i64   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for isize { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-
-impl Display for u8    { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for u16   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for u32   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for u64   { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-impl Display for usize { fn fmt<W: Write>(&self, w: &mut W) -> Result { Debug::fmt(self, w) } }
-
-fn hex_fmt<W: Write>(mut val: u64, w: &mut W, base: u8) -> Result {
-    let mut buf = [0; 16];
-    let width = cmp::max(64 - val.leading_zeros() + 3, 4) / 4;
-    for i in 0..width {
-        let rem = (val % 16) as u8;
-        val /= 16;
-        buf[width - i - 1] = match rem {
+forward!(Display, Debug, i64, i8 i16 i32 i64 isize);
+forward!(Display, Debug, u64, u8 u16 u32 u64 usize);
+
+/// Formats a number in a base that is a power of two.
+///
+/// [argument, shift]
+/// The base-2 logarithm of the base.
+///
+/// [argument, alpha]
+/// The digit that represents ten.
+///
+/// [return_value]
+/// Returns the index of the first digit in the buffer.
+fn format_pow2(buf: &mut [u8; 64], mut val: u64, shift: usize, alpha: u8) -> usize {
+    let mask = (1 << shift) - 1;
+    let mut i = buf.len();
+
+    loop {
+        i -= 1;
+        let rem = (val & mask) as u8;
+        buf[i] = match rem {
             0...9 => b'0' + rem,
-            _ => base + rem - 10,
+            _ => alpha + rem - 10,
         };
+        val >>= shift;
+        if val == 0 {
+            break;
+        }
     }
-    try!(w.write_all(&buf[..width]));
-    Ok(())
-}
 
-impl LowerHex for u64 {
-    fn fmt<W: Write>(&self, w: &mut W) -> Result {
-        hex_fmt(*self, w, b'a')
-    }
+    i
 }
 
-impl UpperHex for u64 {
-    fn fmt<W: Write>(&self, w: &mut W) -> Result {
-        hex_fmt(*self, w, b'A')
+macro_rules! pow2 {
+    ($name:ident, $shift:expr, $alpha:expr, $prefix:expr) => {
+        impl $name for u64 {
+            fn fmt<W: Write>(&self, w: &mut W) -> Result {
+                let mut buf = [0; 64];
+                let n = format_pow2(&mut buf, *self, $shift, $alpha);
+                w.write_all(&buf[n..]).ignore_ok()
+            }
+
+            fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                let mut buf = [0; 64];
+                let n = format_pow2(&mut buf, *self, $shift, $alpha);
+                spec.pad_integral(w, false, $prefix, &buf[n..])
+            }
+        }
+
+        forward!($name, $name, u64, u8 u16 u32 usize);
     }
 }
 
-impl LowerHex for u8    { fn fmt<W: Write>(&self, w: &mut W) -> Result { LowerHex::fmt(&(*self as u64), w) } }
-impl LowerHex for u16   { fn fmt<W: Write>(&self, w: &mut W) -> Result { LowerHex::fmt(&(*self as u64), w) } }
-impl LowerHex for u32   { fn fmt<W: Write>(&self, w: &mut W) -> Result { LowerHex::fmt(&(*self as u64), w) } }
-impl LowerHex for usize { fn fmt<W: Write>(&self, w: &mut W) -> Result { LowerHex::fmt(&(*self as u64), w) } }
-
-impl UpperHex for u8    { fn fmt<W: Write>(&self, w: &mut W) -> Result { UpperHex::fmt(&(*self as u64), w) } }
-impl UpperHex for u16   { fn fmt<W: Write>(&self, w: &mut W) -> Result { UpperHex::fmt(&(*self as u64), w) } }
-impl UpperHex for u32   { fn fmt<W: Write>(&self, w: &mut W) -> Result { UpperHex::fmt(&(*self as u64), w) } }
-impl UpperHex for usize { fn fmt<W: Write>(&self, w: &mut W) -> Result { UpperHex::fmt(&(*self as u64), w) } }
+pow2!(LowerHex, 4, b'a', b"0x");
+pow2!(UpperHex, 4, b'A', b"0x");
+pow2!(Octal, 3, b'0', b"0o");
+pow2!(Binary, 1, b'0', b"0b");
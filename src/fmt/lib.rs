@@ -15,7 +15,10 @@ pub use io::{Write};
 
 pub mod std {
     pub use base::std::*;
-    pub mod fmt { pub use {LowerHex, UpperHex, Debug, Display}; }
+    pub mod fmt {
+        pub use {LowerHex, UpperHex, Binary, Octal, LowerExp, UpperExp, Debug, Display};
+        pub use {Spec, Align};
+    }
 }
 
 pub mod impls {
@@ -31,6 +34,204 @@ pub mod impls {
     pub mod range;
 }
 
+/// The alignment of a formatted object within its field.
+#[derive(Copy, Eq)]
+pub enum Align {
+    /// The object is written at the start of the field.
+    Left,
+    /// The object is written at the end of the field.
+    Right,
+    /// The object is written in the middle of the field.
+    ///
+    /// = Remarks
+    ///
+    /// If the padding cannot be split evenly, the additional fill character is
+    /// written after the object.
+    Center,
+}
+
+/// Options that modify how an object is formatted.
+///
+/// = Remarks
+///
+/// These options are created by the `write!` macro from the format specification
+/// after the colon, e.g., `{:>8}`, `{:08x}`, or `{:.3}`.
+#[derive(Copy)]
+pub struct Spec {
+    /// The character used for padding.
+    pub fill: char,
+
+    /// The alignment within the field.
+    ///
+    /// = Remarks
+    ///
+    /// If this is `None`, numbers are aligned to the right and everything else is
+    /// aligned to the left.
+    pub align: Option<Align>,
+
+    /// Whether a `+` is written before non-negative numbers.
+    pub plus: bool,
+
+    /// Whether the alternate form is used.
+    ///
+    /// = Remarks
+    ///
+    /// For binary, octal, and hexadecimal numbers, this writes the `0b`, `0o`, and
+    /// `0x` prefix, respectively.
+    pub alternate: bool,
+
+    /// Whether numbers are padded with zeros after the sign and prefix.
+    ///
+    /// = Remarks
+    ///
+    /// If this is set, `fill` and `align` are ignored for numbers.
+    pub zero: bool,
+
+    /// The minimum number of characters written.
+    pub width: Option<usize>,
+
+    /// The requested precision.
+    ///
+    /// = Remarks
+    ///
+    /// For floating point numbers, this is the number of digits after the decimal
+    /// point. Other types ignore it.
+    pub precision: Option<usize>,
+}
+
+impl Spec {
+    /// Creates a new specification without any options set.
+    pub fn new() -> Spec {
+        Spec {
+            fill: ' ',
+            align: None,
+            plus: false,
+            alternate: false,
+            zero: false,
+            width: None,
+            precision: None,
+        }
+    }
+
+    /// Writes the padding that comes before an object.
+    ///
+    /// [argument, w]
+    /// The writer.
+    ///
+    /// [argument, len]
+    /// The number of characters in the formatted object.
+    ///
+    /// [argument, default]
+    /// The alignment used if none was specified.
+    ///
+    /// [return_value]
+    /// Returns the number of fill characters that have to be written after the object.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::fmt::Spec::pad_post[pad_post]
+    pub fn pad_pre<W: Write>(&self, w: &mut W, len: usize,
+                             default: Align) -> Result<usize> {
+        let width = self.width.unwrap_or(0);
+        if len >= width {
+            return Ok(0);
+        }
+        let pad = width - len;
+        let (pre, post) = match self.align.unwrap_or(default) {
+            Align::Left => (0, pad),
+            Align::Right => (pad, 0),
+            Align::Center => (pad / 2, pad - pad / 2),
+        };
+        try!(self.write_fill(w, pre));
+        Ok(post)
+    }
+
+    /// Writes the padding that comes after an object.
+    ///
+    /// [argument, w]
+    /// The writer.
+    ///
+    /// [argument, n]
+    /// The return value of `pad_pre`.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::fmt::Spec::pad_pre[pad_pre]
+    pub fn pad_post<W: Write>(&self, w: &mut W, n: usize) -> Result {
+        self.write_fill(w, n)
+    }
+
+    /// Writes a number with its sign, prefix, and padding.
+    ///
+    /// [argument, w]
+    /// The writer.
+    ///
+    /// [argument, neg]
+    /// Whether the number is negative.
+    ///
+    /// [argument, prefix]
+    /// The prefix that is written if the alternate form was requested.
+    ///
+    /// [argument, digits]
+    /// The ASCII digits of the absolute value of the number.
+    pub fn pad_integral<W: Write>(&self, w: &mut W, neg: bool, prefix: &[u8],
+                                  digits: &[u8]) -> Result {
+        let sign: &[u8] = if neg {
+            b"-"
+        } else if self.plus {
+            b"+"
+        } else {
+            b""
+        };
+        let prefix: &[u8] = if self.alternate { prefix } else { b"" };
+        let len = sign.len() + prefix.len() + digits.len();
+        if self.zero {
+            try!(w.write_all(sign));
+            try!(w.write_all(prefix));
+            let width = self.width.unwrap_or(0);
+            for _ in len..width {
+                try!(w.write_all(b"0"));
+            }
+            try!(w.write_all(digits));
+            Ok(())
+        } else {
+            let post = try!(self.pad_pre(w, len, Align::Right));
+            try!(w.write_all(sign));
+            try!(w.write_all(prefix));
+            try!(w.write_all(digits));
+            self.pad_post(w, post)
+        }
+    }
+
+    fn write_fill<W: Write>(&self, w: &mut W, n: usize) -> Result {
+        let bytes = self.fill.to_utf8();
+        let bytes = &bytes[..self.fill.len()];
+        for _ in 0..n {
+            try!(w.write_all(bytes));
+        }
+        Ok(())
+    }
+}
+
+/// A writer that counts the characters written to it.
+struct CharCount(usize);
+
+impl Write for CharCount {
+    fn gather_write(&mut self, buf: &[&[u8]]) -> Result<usize> {
+        let mut len = 0;
+        for b in buf {
+            // Count all bytes that are not UTF-8 continuation bytes.
+            for &c in *b {
+                if c & 0xC0 != 0x80 {
+                    self.0 += 1;
+                }
+            }
+            len += b.len();
+        }
+        Ok(len)
+    }
+}
+
 macro_rules! fmt_var {
     ($($(#[$meta:meta])* ty $name:ident)*) => {
         $(
@@ -38,18 +239,49 @@ macro_rules! fmt_var {
             pub trait $name {
                 /// Formats the object into the writer.
                 fn fmt<W: Write>(&self, w: &mut W) -> Result;
+
+                /// Formats the object into the writer with the given options.
+                ///
+                /// [argument, spec]
+                /// The options from the format specification.
+                ///
+                /// [argument, w]
+                /// The writer.
+                ///
+                /// = Remarks
+                ///
+                /// The default implementation pads the output of `fmt` and ignores the
+                /// precision.
+                fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                    if spec.width.is_none() {
+                        return self.fmt(w);
+                    }
+                    let mut count = CharCount(0);
+                    try!(self.fmt(&mut count));
+                    let post = try!(spec.pad_pre(w, count.0, Align::Left));
+                    try!(self.fmt(w));
+                    spec.pad_post(w, post)
+                }
             }
 
             impl<'a, T: $name+?Sized> $name for &'a T {
                 fn fmt<W: Write>(&self, w: &mut W) -> Result {
                     (**self).fmt(w)
                 }
+
+                fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                    (**self).fmt_spec(spec, w)
+                }
             }
 
             impl<'a, T: $name+?Sized> $name for &'a mut T {
                 fn fmt<W: Write>(&self, w: &mut W) -> Result {
                     (**self).fmt(w)
                 }
+
+                fn fmt_spec<W: Write>(&self, spec: &Spec, w: &mut W) -> Result {
+                    (**self).fmt_spec(spec, w)
+                }
             }
         )*
     }
@@ -62,6 +294,18 @@ fmt_var! {
     #[doc = "Objects that can be formatted in a \"upper hex\" form."]
     ty UpperHex
 
+    #[doc = "Objects that can be formatted in a \"binary\" form."]
+    ty Binary
+
+    #[doc = "Objects that can be formatted in a \"octal\" form."]
+    ty Octal
+
+    #[doc = "Objects that can be formatted in a \"lower exponent\" form."]
+    ty LowerExp
+
+    #[doc = "Objects that can be formatted in a \"upper exponent\" form."]
+    ty UpperExp
+
     #[doc = "Objects that can be formatted in a \"debug\" form."]
     ty Debug
 
@@ -94,3 +338,5 @@ macro_rules! impl_slice {
 impl_slice!(Debug, "{:?}");
 impl_slice!(LowerHex, "{:x}");
 impl_slice!(UpperHex, "{:X}");
+impl_slice!(Binary, "{:b}");
+impl_slice!(Octal, "{:o}");
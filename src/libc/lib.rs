@@ -82,6 +82,7 @@ extern {
                           start: unsafe extern fn(*mut u8) -> *mut u8,
                           arg: *mut u8) -> i32;
     pub fn pthread_join(thread: pthread_t, retval: *mut *mut u8) -> i32;
+    pub fn pthread_detach(thread: pthread_t) -> i32;
     pub fn pthread_key_create(key: *mut pthread_key_t, dest: extern fn(*mut u8)) -> i32;
     pub fn pthread_setspecific(key: pthread_key_t, val: *mut u8) -> i32;
     pub fn pthread_getspecific(key: pthread_key_t) -> *mut u8;
@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use atomic::{Atomic};
+use syscall::{futex_wake};
+use cty::{c_int};
+use time_base::{Time, clock};
+use rwlock::{wait};
+
+// The state consists of the number of waiting threads in the lower bits and the
+// generation in the upper bits. Keeping both in one word allows a thread whose wait
+// has timed out to leave the barrier without racing with the thread that releases it.
+
+const COUNT_BITS: usize = 20;
+const COUNT_MASK: c_int = (1 << COUNT_BITS) - 1;
+const GENERATION: c_int = 1 << COUNT_BITS;
+
+/// A barrier that blocks threads until a fixed number of threads is waiting.
+///
+/// = Remarks
+///
+/// The barrier can be reused: Once all threads have been released, the next group of
+/// threads can wait on it again.
+pub struct Barrier {
+    state: Atomic<c_int>,
+    num: c_int,
+}
+
+impl Barrier {
+    /// Creates a new barrier.
+    ///
+    /// [argument, num]
+    /// The number of threads that have to wait before all of them are released.
+    ///
+    /// = Remarks
+    ///
+    /// `num` must be less than `2^20`.
+    pub const fn new(num: c_int) -> Barrier {
+        Barrier {
+            state: Atomic::new(0),
+            num: num,
+        }
+    }
+
+    /// Waits until the configured number of threads is waiting on the barrier.
+    ///
+    /// [return_value]
+    /// Returns `true` for exactly one of the released threads and `false` for all
+    /// others.
+    pub fn wait(&self) -> bool {
+        match self.wait_until(None) {
+            Ok(leader) => leader,
+            _ => false,
+        }
+    }
+
+    /// Waits until the configured number of threads is waiting on the barrier or until
+    /// a certain amount of time has expired.
+    ///
+    /// [argument, time]
+    /// An upper bound for the amount of time until this function returns.
+    ///
+    /// [return_value]
+    /// Returns `true` for exactly one of the released threads, `false` for all others,
+    /// or an error.
+    ///
+    /// = Remarks
+    ///
+    /// If the time expires, the thread leaves the barrier and no longer counts towards
+    /// the number of waiting threads.
+    ///
+    /// The function may take longer to return than allowed by the `time` parameter.
+    pub fn try_wait_until(&self, time: Time) -> Result<bool> {
+        let then = try!(clock::MONO_RAW.get_time()) + time;
+        self.wait_until(Some(then))
+    }
+
+    fn wait_until(&self, deadline: Option<Time>) -> Result<bool> {
+        let state = self.state.add_acquire_release(1) + 1;
+        let generation = state & !COUNT_MASK;
+        if state & COUNT_MASK >= self.num {
+            self.state.store_release(generation.wrapping_add(GENERATION));
+            futex_wake(&self.state, !0);
+            return Ok(true);
+        }
+        loop {
+            let state = self.state.load_acquire();
+            if state & !COUNT_MASK != generation {
+                return Ok(false);
+            }
+            if let Err(e) = wait(&self.state, state, deadline) {
+                if self.leave(generation) {
+                    return Err(e);
+                }
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Removes a thread whose wait has failed from the barrier.
+    ///
+    /// Returns `false` if the thread has been released in the meantime.
+    fn leave(&self, generation: c_int) -> bool {
+        let mut state = self.state.load();
+        loop {
+            // If the count has reached `num`, the barrier is being released.
+            if state & !COUNT_MASK != generation || state & COUNT_MASK >= self.num {
+                return false;
+            }
+            let old = self.state.compare_exchange(state, state - 1);
+            if old == state {
+                return true;
+            }
+            state = old;
+        }
+    }
+}
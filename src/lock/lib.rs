@@ -23,6 +23,8 @@ extern crate lrs_cty as cty;
 #[cfg(not(freestanding))] pub use lock::{Lock, LockGuard, DUMMY, LockStatus};
 #[cfg(not(freestanding))] pub use mutex::{Mutex, MutexGuard};
 #[cfg(not(freestanding))] pub use once::{Once, OnceStatus};
+#[cfg(not(freestanding))] pub use rwlock::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(not(freestanding))] pub use barrier::{Barrier};
 pub use stlock::{SingleThreadLock, SingleThreadLockGuard};
 pub use stmutex::{SingleThreadMutex, SingleThreadMutexGuard};
 pub use spinlock::{SpinLock, SpinLockGuard, SpinLockStatus};
@@ -34,6 +36,8 @@ mod std { pub use fmt::std::*; pub use cty; }
 #[cfg(not(freestanding))] mod lock;
 #[cfg(not(freestanding))] mod mutex;
 #[cfg(not(freestanding))] mod once;
+#[cfg(not(freestanding))] mod rwlock;
+#[cfg(not(freestanding))] mod barrier;
 mod stlock;
 mod stmutex;
 mod spinlock;
@@ -0,0 +1,423 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use io::{Write};
+use fmt::{Debug};
+use base::{error};
+use cell::cell::{Cell};
+use atomic::{Atomic};
+use syscall::{futex_wait, futex_wake};
+use cty::{c_int};
+use time_base::{time_to_timespec, Time, clock};
+
+// The state consists of the number of readers in the lower bits and two flags. If the
+// lock is write-locked, the reader bits are all set. Readers don't acquire the lock
+// while a writer is waiting which makes the lock writer-preferring. Writers sleep on a
+// separate futex so that unlocking can wake a single writer without waking readers.
+
+const MASK:            c_int = (1 << 29) - 1;
+const READ_LOCKED:     c_int = 1;
+const WRITE_LOCKED:    c_int = MASK;
+const MAX_READERS:     c_int = MASK - 1;
+const READERS_WAITING: c_int = 1 << 29;
+const WRITERS_WAITING: c_int = 1 << 30;
+
+fn is_unlocked(state: c_int) -> bool {
+    state & MASK == 0
+}
+
+fn is_read_lockable(state: c_int) -> bool {
+    state & MASK < MAX_READERS && state & (READERS_WAITING | WRITERS_WAITING) == 0
+}
+
+/// Sleeps on a futex until it's woken up or the deadline has passed.
+pub fn wait(addr: &Atomic<c_int>, val: c_int, deadline: Option<Time>) -> Result {
+    let then = match deadline {
+        Some(then) => then,
+        None => {
+            futex_wait(addr, val, None);
+            return Ok(());
+        },
+    };
+    let now = try!(clock::MONO_RAW.get_time());
+    if now >= then {
+        return Err(error::TimedOut);
+    }
+    let spec = time_to_timespec(then - now);
+    match rv!(futex_wait(addr, val, Some(&spec))) {
+        Err(error::TimedOut) => Err(error::TimedOut),
+        _ => Ok(()),
+    }
+}
+
+/// A readers-writer lock protecting some data.
+///
+/// = Remarks
+///
+/// The lock can be held by multiple readers or a single writer at the same time.
+/// Waiting writers are preferred: While a writer is waiting, no new readers can acquire
+/// the lock.
+pub struct RwLock<T> {
+    state: Atomic<c_int>,
+    writer_notify: Atomic<c_int>,
+    data: Cell<T>,
+}
+
+impl<T> RwLock<T> {
+    /// Creates a new readers-writer lock.
+    ///
+    /// [argument, data]
+    /// The data to be protected by the lock.
+    pub const fn new(data: T) -> RwLock<T> {
+        RwLock {
+            state: Atomic::new(0),
+            writer_notify: Atomic::new(0),
+            data: Cell::new(data),
+        }
+    }
+
+    fn read_guard<'a>(&'a self) -> RwLockReadGuard<'a, T> {
+        RwLockReadGuard {
+            lock: self,
+            _marker: (NoSend, NoSync),
+        }
+    }
+
+    fn write_guard<'a>(&'a self) -> RwLockWriteGuard<'a, T> {
+        RwLockWriteGuard {
+            lock: self,
+            _marker: (NoSend, NoSync),
+        }
+    }
+
+    /// Tries to acquire the lock for reading without sleeping.
+    ///
+    /// [return_value]
+    /// Returns a read-guard if the operation succeeded.
+    ///
+    /// = Remarks
+    ///
+    /// This fails if the lock is write-locked or a writer is waiting.
+    pub fn try_read<'a>(&'a self) -> Result<RwLockReadGuard<'a, T>> {
+        let mut state = self.state.load();
+        while is_read_lockable(state) {
+            let old = self.state.compare_exchange(state, state + READ_LOCKED);
+            if old == state {
+                return Ok(self.read_guard());
+            }
+            state = old;
+        }
+        Err(error::ResourceBusy)
+    }
+
+    /// Acquires the lock for reading by sleeping until no writer holds or waits for
+    /// the lock.
+    ///
+    /// [return_value]
+    /// Returns a read-guard.
+    pub fn read<'a>(&'a self) -> RwLockReadGuard<'a, T> {
+        let _ = self.read_until(None);
+        self.read_guard()
+    }
+
+    /// Acquires the lock for reading by sleeping until no writer holds or waits for
+    /// the lock or until a certain amount of time has expired.
+    ///
+    /// [argument, time]
+    /// An upper bound for the amount of time until this function returns.
+    ///
+    /// [return_value]
+    /// Returns a read-guard or an error.
+    ///
+    /// = Remarks
+    ///
+    /// The function may take longer to return than allowed by the `time` parameter.
+    pub fn try_read_until<'a>(&'a self, time: Time) -> Result<RwLockReadGuard<'a, T>> {
+        let then = try!(clock::MONO_RAW.get_time()) + time;
+        try!(self.read_until(Some(then)));
+        Ok(self.read_guard())
+    }
+
+    fn read_until(&self, deadline: Option<Time>) -> Result {
+        let mut state = self.state.load();
+        loop {
+            if is_read_lockable(state) {
+                let old = self.state.compare_exchange(state, state + READ_LOCKED);
+                if old == state {
+                    return Ok(());
+                }
+                state = old;
+                continue;
+            }
+
+            assert!(state & MASK != MAX_READERS);
+
+            if state & READERS_WAITING == 0 {
+                let old = self.state.compare_exchange(state, state | READERS_WAITING);
+                if old != state {
+                    state = old;
+                    continue;
+                }
+            }
+
+            try!(wait(&self.state, state | READERS_WAITING, deadline));
+            state = self.state.load();
+        }
+    }
+
+    /// Tries to acquire the lock for writing without sleeping.
+    ///
+    /// [return_value]
+    /// Returns a write-guard if the operation succeeded.
+    pub fn try_write<'a>(&'a self) -> Result<RwLockWriteGuard<'a, T>> {
+        let mut state = self.state.load();
+        while is_unlocked(state) {
+            let old = self.state.compare_exchange(state, state + WRITE_LOCKED);
+            if old == state {
+                return Ok(self.write_guard());
+            }
+            state = old;
+        }
+        Err(error::ResourceBusy)
+    }
+
+    /// Acquires the lock for writing by sleeping until the lock is unlocked.
+    ///
+    /// [return_value]
+    /// Returns a write-guard.
+    pub fn write<'a>(&'a self) -> RwLockWriteGuard<'a, T> {
+        if self.state.compare_exchange(0, WRITE_LOCKED) != 0 {
+            let _ = self.write_until(None);
+        }
+        self.write_guard()
+    }
+
+    /// Acquires the lock for writing by sleeping until the lock is unlocked or until a
+    /// certain amount of time has expired.
+    ///
+    /// [argument, time]
+    /// An upper bound for the amount of time until this function returns.
+    ///
+    /// [return_value]
+    /// Returns a write-guard or an error.
+    ///
+    /// = Remarks
+    ///
+    /// The function may take longer to return than allowed by the `time` parameter.
+    pub fn try_write_until<'a>(&'a self, time: Time) -> Result<RwLockWriteGuard<'a, T>> {
+        if self.state.compare_exchange(0, WRITE_LOCKED) == 0 {
+            return Ok(self.write_guard());
+        }
+        let then = try!(clock::MONO_RAW.get_time()) + time;
+        try!(self.write_until(Some(then)));
+        Ok(self.write_guard())
+    }
+
+    fn write_until(&self, deadline: Option<Time>) -> Result {
+        let mut state = self.state.load();
+        // Once we have slept, other writers might be sleeping, too. Since we cannot know
+        // for sure, we keep the flag set when we acquire the lock.
+        let mut other_writers_waiting = 0;
+        loop {
+            if is_unlocked(state) {
+                let new = state | WRITE_LOCKED | other_writers_waiting;
+                let old = self.state.compare_exchange(state, new);
+                if old == state {
+                    return Ok(());
+                }
+                state = old;
+                continue;
+            }
+
+            if state & WRITERS_WAITING == 0 {
+                let old = self.state.compare_exchange(state, state | WRITERS_WAITING);
+                if old != state {
+                    state = old;
+                    continue;
+                }
+            }
+
+            other_writers_waiting = WRITERS_WAITING;
+
+            // Load the sequence number before checking the state again so that we don't
+            // miss a wakeup between the check and the wait.
+            let seq = self.writer_notify.load();
+            state = self.state.load();
+            if is_unlocked(state) || state & WRITERS_WAITING == 0 {
+                continue;
+            }
+
+            if let Err(e) = wait(&self.writer_notify, seq, deadline) {
+                // We might have consumed a wakeup that was meant for us. Pass it on if
+                // the lock has been unlocked in the meantime.
+                let state = self.state.load();
+                let waiting = state & (READERS_WAITING | WRITERS_WAITING);
+                if is_unlocked(state) && waiting != 0 {
+                    self.wake_writer_or_readers(state);
+                }
+                return Err(e);
+            }
+            state = self.state.load();
+        }
+    }
+
+    fn read_unlock(&self) {
+        let state = self.state.sub(READ_LOCKED) - READ_LOCKED;
+        if is_unlocked(state) && state & WRITERS_WAITING != 0 {
+            self.wake_writer_or_readers(state);
+        }
+    }
+
+    fn write_unlock(&self) {
+        let state = self.state.sub(WRITE_LOCKED) - WRITE_LOCKED;
+        if state & (READERS_WAITING | WRITERS_WAITING) != 0 {
+            self.wake_writer_or_readers(state);
+        }
+    }
+
+    /// Wakes one writer if there is one and all readers otherwise.
+    ///
+    /// = Remarks
+    ///
+    /// If the lock gets locked in the meantime, nothing happens since the new owner
+    /// will wake the waiters when it unlocks the lock.
+    fn wake_writer_or_readers(&self, mut state: c_int) {
+        if state == WRITERS_WAITING {
+            let old = self.state.compare_exchange(state, 0);
+            if old == state {
+                self.wake_writer();
+                return;
+            }
+            state = old;
+        }
+
+        if state == READERS_WAITING | WRITERS_WAITING {
+            if self.state.compare_exchange(state, READERS_WAITING) != state {
+                return;
+            }
+            if self.wake_writer() {
+                return;
+            }
+            // No writer was sleeping. Wake the readers instead.
+            state = READERS_WAITING;
+        }
+
+        if state == READERS_WAITING {
+            if self.state.compare_exchange(state, 0) == state {
+                futex_wake(&self.state, !0);
+            }
+        }
+    }
+
+    fn wake_writer(&self) -> bool {
+        self.writer_notify.add(1);
+        futex_wake(&self.writer_notify, 1) > 0
+    }
+
+    /// Provides mutable access to the protected data without locking the lock.
+    ///
+    /// = Remarks
+    ///
+    /// This is safe because the availability of a mutable reference implies that there
+    /// are currently no guards borrowing the lock.
+    pub fn data(&mut self) -> &mut T {
+        unsafe { &mut *self.data.ptr() }
+    }
+}
+
+unsafe impl<T> Sync for RwLock<T> where T: Send+Sync { }
+unsafe impl<T> Send for RwLock<T> where T: Send { }
+
+/// A read-guard of a readers-writer lock.
+///
+/// = Remarks
+///
+/// This guard automatically releases the lock when it goes out of scope.
+pub struct RwLockReadGuard<'a, T: 'a> {
+    lock: &'a RwLock<T>,
+    _marker: (NoSend, NoSync),
+}
+
+impl<'a, T> RwLockReadGuard<'a, T> {
+    /// Returns a reference to the underlying lock.
+    pub fn as_rwlock(&self) -> &'a RwLock<T> {
+        self.lock
+    }
+
+    /// Releases the lock and returns a reference to it.
+    pub fn unlock(self) -> &'a RwLock<T> {
+        self.lock
+    }
+}
+
+unsafe impl<'a, T> Sync for RwLockReadGuard<'a, T> where T: Sync { }
+
+impl<'a, T> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.ptr() }
+    }
+}
+
+impl<'a, T> Drop for RwLockReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.read_unlock();
+    }
+}
+
+impl<'a, T: Debug> Debug for RwLockReadGuard<'a, T> {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        self.deref().fmt(w)
+    }
+}
+
+/// A write-guard of a readers-writer lock.
+///
+/// = Remarks
+///
+/// This guard automatically releases the lock when it goes out of scope.
+pub struct RwLockWriteGuard<'a, T: 'a> {
+    lock: &'a RwLock<T>,
+    _marker: (NoSend, NoSync),
+}
+
+impl<'a, T> RwLockWriteGuard<'a, T> {
+    /// Returns a reference to the underlying lock.
+    pub fn as_rwlock(&self) -> &'a RwLock<T> {
+        self.lock
+    }
+
+    /// Releases the lock and returns a reference to it.
+    pub fn unlock(self) -> &'a RwLock<T> {
+        self.lock
+    }
+}
+
+unsafe impl<'a, T> Sync for RwLockWriteGuard<'a, T> where T: Sync { }
+
+impl<'a, T> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.ptr() }
+    }
+}
+
+impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.ptr() }
+    }
+}
+
+impl<'a, T> Drop for RwLockWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.lock.write_unlock();
+    }
+}
+
+impl<'a, T: Debug> Debug for RwLockWriteGuard<'a, T> {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        self.deref().fmt(w)
+    }
+}
@@ -52,7 +52,8 @@
 
 pub use lrs_alloc::{
     MAX_SIZE, empty_ptr, MemPool, Heap, FbHeap, Dummy, TaPool, AlignAlloc,
-    alloc_array, realloc_array, free_array, alloc, free, OncePool,
+    alloc_array, realloc_array, free_array, alloc, free, OncePool, sort, sort_by,
+    sort_by_key,
 };
 
 #[cfg(not(freestanding))] pub use lrs_alloc::{Bda, TlAlc};
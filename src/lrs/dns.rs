@@ -0,0 +1,38 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! DNS name resolution.
+
+pub use lrs_dns::{Config, Resolver};
+pub use lrs_dns::conf::{MAX_NAMESERVERS, PORT};
+
+/// Hosts file lookups.
+pub mod hosts {
+    pub use lrs_dns::hosts::{load, lookup_name, lookup_addr};
+}
+
+/// DNS messages.
+pub mod msg {
+    pub use lrs_dns::msg::{
+        Message, Header, Question, Record, RecordIter, Srv, build_query, read_name,
+        HEADER_SIZE, MAX_UDP_SIZE, MAX_NAME_SIZE, FLAG_QR, FLAG_TC, FLAG_RD,
+    };
+
+    /// Resource record types.
+    pub mod rr_type {
+        pub use lrs_dns::msg::rr_type::{A, Ns, Cname, Soa, Ptr, Mx, Txt, Aaaa, Srv};
+    }
+
+    /// Resource record classes.
+    pub mod rr_class {
+        pub use lrs_dns::msg::rr_class::{In};
+    }
+
+    /// Response codes.
+    pub mod rcode {
+        pub use lrs_dns::msg::rcode::{
+            NoError, FormErr, ServFail, NxDomain, NotImp, Refused,
+        };
+    }
+}
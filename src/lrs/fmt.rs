@@ -5,5 +5,6 @@
 //! Object formatting
 
 pub use lrs_fmt::{
-    Write, LowerHex, UpperHex, Debug, Display,
+    Write, LowerHex, UpperHex, Binary, Octal, LowerExp, UpperExp, Debug, Display, Spec,
+    Align,
 };
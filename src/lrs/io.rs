@@ -6,3 +6,4 @@
 
 pub use lrs_io::{Read, Write, BufRead, BufWrite};
 pub use lrs_buf_reader::{BufReader};
+pub use lrs_buf_writer::{BufWriter};
@@ -133,6 +133,7 @@ extern crate lrs_arch_fns;
 extern crate lrs_atomic;
 extern crate lrs_base;
 extern crate lrs_buf_reader;
+extern crate lrs_buf_writer;
 extern crate lrs_cell;
 extern crate lrs_cty;
 extern crate lrs_varargs;
@@ -174,19 +175,23 @@ extern crate lrs_rand;
 #[cfg(not(freestanding))] extern crate lrs_signal;
 #[cfg(not(freestanding))] extern crate lrs_pipe;
 #[cfg(not(freestanding))] extern crate lrs_queue;
+#[cfg(not(freestanding))] extern crate lrs_channel;
 #[cfg(not(freestanding))] extern crate lrs_swap;
 #[cfg(not(freestanding))] extern crate lrs_inotify;
 #[cfg(not(freestanding))] extern crate lrs_env;
 #[cfg(not(freestanding))] extern crate lrs_fs;
 #[cfg(not(freestanding))] extern crate lrs_socket;
 #[cfg(not(freestanding))] extern crate lrs_netlink;
+#[cfg(not(freestanding))] extern crate lrs_dns;
 #[cfg(not(freestanding))] extern crate lrs_sys;
 #[cfg(not(freestanding))] extern crate lrs_poll;
+#[cfg(not(freestanding))] extern crate lrs_reactor;
 #[cfg(not(freestanding))] extern crate lrs_dev;
 #[cfg(not(freestanding))] extern crate lrs_file;
 #[cfg(not(freestanding))] extern crate lrs_mqueue;
 #[cfg(not(freestanding))] extern crate lrs_tty;
 #[cfg(not(freestanding))] extern crate lrs_thread;
+#[cfg(not(freestanding))] extern crate lrs_thread_pool;
 #[cfg(not(freestanding))] extern crate lrs_process;
 #[cfg(not(freestanding))] extern crate lrs_dir;
 // #[cfg(not(freestanding))] extern crate lrs_user_group;
@@ -232,7 +237,8 @@ pub mod rand;
 #[cfg(not(freestanding))]
 pub use hosted::{
     event, /* user, group, */ poll, sync, process, fd, file, dir, env, tty, fs, netlink,
-    mem_map, signal, sys, thread, inotify, socket, syscall, msg_queue, pipe, swap,
+    mem_map, signal, sys, thread, inotify, socket, syscall, msg_queue, pipe, swap, stdio,
+    dns,
 };
 
 #[cfg(not(freestanding))]
@@ -261,6 +267,8 @@ mod hosted {
     pub mod msg_queue;
     pub mod pipe;
     pub mod swap;
+    pub mod stdio;
+    pub mod dns;
 }
 
 /// The prelude.
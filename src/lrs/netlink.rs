@@ -8,6 +8,9 @@ pub use lrs_netlink::kind::{Kind};
 pub use lrs_netlink::flags::{NlFlags};
 pub use lrs_socket::nl_proto::{Proto};
 pub use lrs_netlink::{MsgHeader, MsgError, Attr};
+pub use lrs_netlink::socket::{NlSocket};
+pub use lrs_netlink::rtnl::{RouteSocket, Link, Address, Route, Event};
+pub use lrs_netlink::genl::{GenlSocket, GenlMsgHeader, Family, McastGroup};
 
 pub mod route {
     pub use lrs_netlink::route::{
@@ -51,6 +54,59 @@ pub mod route {
             Unspec, Peer,
         };
     }
+
+    pub mod link_flags {
+        pub use lrs_netlink::route::link_flags::{
+            Up, Broadcast, Debug, Loopback, PointToPoint, NoTrailers, Running, NoArp,
+            Promisc, AllMulti, Master, Slave, Multicast, PortSel, AutoMedia, Dynamic,
+            LowerUp, Dormant, Echo,
+        };
+    }
+
+    pub mod route_attr {
+        pub use lrs_netlink::route::route_attr::{
+            Unspec, Dst, Src, Iif, Oif, Gateway, Priority, PrefSrc, Metrics, MultiPath,
+            ProtoInfo, Flow, CacheInfo, Session, MpAlgo, Table, Mark, MfcStats,
+        };
+    }
+
+    pub mod route_scope {
+        pub use lrs_netlink::route::route_scope::{
+            Universe, Site, Link, Host, Nowhere,
+        };
+    }
+
+    pub mod group {
+        pub use lrs_netlink::route::group::{
+            Link, Notify, Neigh, Tc, Ipv4Addr, Ipv4MRoute, Ipv4Route, Ipv4Rule, Ipv6Addr,
+            Ipv6MRoute, Ipv6Route, Ipv6IfInfo, Ipv6Prefix, Ipv6Rule,
+        };
+    }
+}
+
+pub mod genl {
+    pub use lrs_netlink::genl::{
+        Ctrl,
+    };
+
+    pub mod ctrl_cmd {
+        pub use lrs_netlink::genl::ctrl_cmd::{
+            Unspec, NewFamily, DelFamily, GetFamily, NewOps, DelOps, GetOps, NewMcastGrp,
+            DelMcastGrp, GetMcastGrp,
+        };
+    }
+
+    pub mod ctrl_attr {
+        pub use lrs_netlink::genl::ctrl_attr::{
+            Unspec, FamilyId, FamilyName, Version, HdrSize, MaxAttr, Ops, McastGroups,
+        };
+    }
+
+    pub mod mcast_grp_attr {
+        pub use lrs_netlink::genl::mcast_grp_attr::{
+            Unspec, Name, Id,
+        };
+    }
 }
 
 pub mod fmt {
@@ -61,7 +117,7 @@ pub mod fmt {
 
 pub mod parse {
     pub use lrs_netlink::parse::{
-        MsgIter, MsgParser,
+        MsgIter, MsgParser, AttrIter,
     };
 }
 
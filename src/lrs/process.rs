@@ -12,7 +12,8 @@ pub use lrs_process::{
 };
 pub use lrs_process::res::{Resource};
 pub use lrs_process::res_user::{ResourceUser};
-pub use lrs_process::exec::{exec};
+pub use lrs_process::exec::{exec, exec_with_env};
+pub use lrs_process::command::{Command, Child, Stdio, Output};
 pub use lrs_process::wait::{
     ChildStatus, WaitFlags, WAIT_EXITED, WAIT_STOPPED, WAIT_CONTINUED, WAIT_DONT_BLOCK,
     WAIT_DONT_REAP, wait_all, wait_id,
@@ -5,3 +5,5 @@
 //! Reference-counted objects.
 
 pub use lrs_rc::{Rc, RcBuf, Arc, ArcBuf};
+pub use lrs_rc::rc::{Weak as RcWeak};
+pub use lrs_rc::arc::{Weak as ArcWeak};
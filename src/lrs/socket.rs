@@ -10,6 +10,12 @@ pub use lrs_socket::flags::{SockFlags};
 pub use lrs_socket::msg::{MsgFlags};
 pub use lrs_socket::socket::{Socket};
 pub use lrs_socket::addr::{SockAddr, AddrType};
+pub use lrs_socket::tcp::{
+    TcpListener, TcpStream, KeepAlive, TcpInfo, DEFAULT_BACKLOG,
+};
+pub use lrs_socket::udp::{UdpSocket};
+pub use lrs_socket::mmsg::{SendMsg, RecvMsg, BATCH_SIZE};
+pub use lrs_socket::unix::{UnixListener, UnixStream};
 
 /// Kernel domain constants.
 ///
@@ -28,8 +34,12 @@ pub mod domain {
 /// Ip sockets.
 pub mod ip {
     pub use lrs_socket::ip_proto::{Proto};
-    pub use lrs_socket::addr::ipv4::{Ipv4Addr, Ipv4SockAddr};
-    pub use lrs_socket::addr::ipv6::{Ipv6Addr, Ipv6SockAddr, Ipv6Scope};
+    pub use lrs_socket::addr::{IpAddr, IpSockAddrBuf};
+    pub use lrs_socket::addr::ipv4::{Ipv4Addr, Ipv4SockAddr, IPV4_SOCK_ADDR_SIZE};
+    pub use lrs_socket::addr::ipv6::{
+        Ipv6Addr, Ipv6SockAddr, Ipv6Scope, IPV6_SOCK_ADDR_SIZE,
+    };
+    pub use lrs_socket::addr::net::{IpNet, IpNetIter};
 
     /// IP protocol constants.
     ///
@@ -88,7 +98,8 @@ pub mod kind {
 /// Control messages.
 pub mod cmsg {
     pub use lrs_socket::cmsg::{
-        CMsgBuf, CMsgIter, CMsg, Credentials,
+        CMsgBuf, CMsgIter, CMsg, Credentials, Timestamp, Timestamping, Ipv4PacketInfo,
+        Ipv6PacketInfo,
     };
 }
 
@@ -113,6 +124,6 @@ pub mod msg {
     pub use lrs_socket::msg::{
         MSG_NONE, MSG_CONFIRM, MSG_DONT_ROUTE, MSG_DONT_BLOCK, MSG_END_OF_RECORD,
         MSG_MORE, MSG_OUT_OF_BAND, MSG_PEEK, MSG_REAL_SIZE, MSG_WAIT_ALL,
-        MSG_CMSG_TRUNCATED,
+        MSG_WAIT_FOR_ONE, MSG_CMSG_TRUNCATED,
     };
 }
@@ -3,7 +3,24 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 //! Standard input/output.
+//!
+//! = Remarks
+//!
+//! The `print!` family of macros writes to `STDOUT` directly and thus issues one
+//! system call per formatted fragment. To batch output, wrap `STDOUT` in a `BufStdout`
+//! and call `flush` when the data has to become visible.
+//!
+//! = Examples
+//!
+//! ----
+//! let mut out = try!(BufStdout::new(STDOUT, 4096));
+//! for i in 0..100 {
+//!     writeln!(out, "{}", i);
+//! }
+//! try!(out.flush());
+//! ----
 
-pub mod raw {
-    pub use lrs_base::raw_stdio::*;
-}
+pub use lrs_fd::{STDIN, STDOUT, STDERR};
+
+/// A buffered writer for stdout.
+pub type BufStdout<Heap = lrs_alloc::Heap> = lrs_buf_writer::BufWriter<lrs_fd::FdIo, Heap>;
@@ -7,8 +7,12 @@
 pub use lrs_lock::{
     RawCondvar, Lock, LockGuard, DUMMY, Mutex,
     MutexGuard, Condvar, LockStatus, Once,
-    SpinLock, SpinLockGuard, SpinLockStatus,
+    SpinLock, SpinLockGuard, SpinLockStatus, RwLock,
+    RwLockReadGuard, RwLockWriteGuard, Barrier,
 };
 pub use lrs_queue::{
     Queue,
 };
+pub use lrs_channel::{
+    channel, sync_channel, Sender, Receiver, SendError, Disconnected,
+};
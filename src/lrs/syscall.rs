@@ -34,6 +34,7 @@ pub use lrs_syscall::{
     mq_getsetattr, sched_setattr, sched_getattr, sched_yield, sched_get_priority_max,
     sched_get_priority_min, sched_rr_get_interval, getpriority, setpriority,
     sched_setaffinity, capget_v3, capset_v3, prctl_pr_capbset_read, prctl_pr_capbset_drop,
-    prctl_pr_get_keepcaps, prctl_pr_set_keepcaps, unshare, getcpu, setns,
+    prctl_pr_get_keepcaps, prctl_pr_set_keepcaps, prctl_pr_set_name, prctl_pr_get_name,
+    unshare, getcpu, setns,
     seccomp_seccomp_set_mode_strict, swapon, swapoff, chroot, pivot_root,
 };
@@ -26,9 +26,9 @@
 //! ----
 
 pub use lrs_thread::{
-    Builder, spawn, scoped, JoinGuard, cpu_count, CpuMask, cpus, set_cpus, unshare,
-    current_cpu, join_namespace, thread_id, exit, deschedule, enter_strict_mode,
-    at_exit,
+    Builder, spawn, spawn_joinable, scoped, JoinGuard, JoinHandle, cpu_count, CpuMask,
+    cpus, set_cpus, unshare, current_cpu, join_namespace, thread_id, exit, deschedule,
+    enter_strict_mode, at_exit, set_name, name,
 };
 pub use lrs_thread::ids::{
     UserIds, GroupIds, drop_user_privileges, drop_group_privileges, set_effective_user_id,
@@ -44,6 +44,7 @@ pub use lrs_thread::cap::{
     Capability, CapSet, capabilities, set_capabilities, has_bounding_cap,
     drop_bounding_cap, keeps_caps, set_keeps_caps,
 };
+pub use lrs_thread_pool::{ThreadPool, Scope};
 
 pub mod flags {
     pub use lrs_thread::sched::{
@@ -9,6 +9,7 @@
 //! This module provides methods for time handling. The essential types are
 //!
 //! * `Time` - An offset from an unspecified point in time,
+//! * `Duration` - A signed length of time,
 //! * `DateTime` - A time represented in a human-readable format,
 //! * `Zone` - A timezone,
 //! * `Clock` - A clock that can be used to measure time.
@@ -65,8 +66,10 @@
 //! // 6
 //! ----
 
-pub use lrs_time_base::{Time};
-pub use lrs_time_ext::{DateTime, Weekday, Zone};
+pub use lrs_time_base::{Time, Duration};
+pub use lrs_time_ext::{DateTime, Weekday, Zone, Abbreviation, RFC3339, RFC2822,
+                       IMF_FIXDATE, is_leap_year, days_in_year, days_in_month,
+                       day_in_year, month_and_day};
 
 #[cfg(not(freestanding))] pub use lrs_time_base::timer::{Timer};
 #[cfg(not(freestanding))] pub use lrs_time_base::clock::{Clock};
@@ -0,0 +1,313 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{mem};
+use base::{error};
+use cty::{GENL_ID_CTRL};
+use vec::{Vec};
+use socket_::socket::{Socket};
+use socket_::nl_proto::{self};
+use fmt::{NlBuf, NlData};
+use parse::{AttrIter, MsgParser};
+use socket::{NlSocket};
+use kind::{Kind};
+use flags::{NlFlags, NLF_REQUEST, NLF_ACK, NLF_DUMP};
+use util::{read, read_string};
+use {MsgHeader};
+
+/// The message type of the generic netlink controller.
+pub const Ctrl: Kind = Kind(GENL_ID_CTRL);
+
+/// The header of generic netlink messages.
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct GenlMsgHeader {
+    pub cmd:      u8,
+    pub version:  u8,
+    pub reserved: u16,
+}
+
+pub mod ctrl_cmd {
+    use cty::{
+        CTRL_CMD_UNSPEC, CTRL_CMD_NEWFAMILY, CTRL_CMD_DELFAMILY, CTRL_CMD_GETFAMILY,
+        CTRL_CMD_NEWOPS, CTRL_CMD_DELOPS, CTRL_CMD_GETOPS, CTRL_CMD_NEWMCAST_GRP,
+        CTRL_CMD_DELMCAST_GRP, CTRL_CMD_GETMCAST_GRP,
+    };
+
+    pub const Unspec      : u8 = CTRL_CMD_UNSPEC;
+    pub const NewFamily   : u8 = CTRL_CMD_NEWFAMILY;
+    pub const DelFamily   : u8 = CTRL_CMD_DELFAMILY;
+    pub const GetFamily   : u8 = CTRL_CMD_GETFAMILY;
+    pub const NewOps      : u8 = CTRL_CMD_NEWOPS;
+    pub const DelOps      : u8 = CTRL_CMD_DELOPS;
+    pub const GetOps      : u8 = CTRL_CMD_GETOPS;
+    pub const NewMcastGrp : u8 = CTRL_CMD_NEWMCAST_GRP;
+    pub const DelMcastGrp : u8 = CTRL_CMD_DELMCAST_GRP;
+    pub const GetMcastGrp : u8 = CTRL_CMD_GETMCAST_GRP;
+}
+
+pub mod ctrl_attr {
+    use cty::{
+        CTRL_ATTR_UNSPEC, CTRL_ATTR_FAMILY_ID, CTRL_ATTR_FAMILY_NAME, CTRL_ATTR_VERSION,
+        CTRL_ATTR_HDRSIZE, CTRL_ATTR_MAXATTR, CTRL_ATTR_OPS, CTRL_ATTR_MCAST_GROUPS,
+    };
+
+    pub const Unspec      : u16 = CTRL_ATTR_UNSPEC;
+    pub const FamilyId    : u16 = CTRL_ATTR_FAMILY_ID;
+    pub const FamilyName  : u16 = CTRL_ATTR_FAMILY_NAME;
+    pub const Version     : u16 = CTRL_ATTR_VERSION;
+    pub const HdrSize     : u16 = CTRL_ATTR_HDRSIZE;
+    pub const MaxAttr     : u16 = CTRL_ATTR_MAXATTR;
+    pub const Ops         : u16 = CTRL_ATTR_OPS;
+    pub const McastGroups : u16 = CTRL_ATTR_MCAST_GROUPS;
+}
+
+pub mod mcast_grp_attr {
+    use cty::{
+        CTRL_ATTR_MCAST_GRP_UNSPEC, CTRL_ATTR_MCAST_GRP_NAME, CTRL_ATTR_MCAST_GRP_ID,
+    };
+
+    pub const Unspec : u16 = CTRL_ATTR_MCAST_GRP_UNSPEC;
+    pub const Name   : u16 = CTRL_ATTR_MCAST_GRP_NAME;
+    pub const Id     : u16 = CTRL_ATTR_MCAST_GRP_ID;
+}
+
+/// A multicast group of a generic netlink family.
+pub struct McastGroup {
+    pub name: Vec<u8>,
+    /// The id that can be passed to `GenlSocket::join_group`.
+    pub id: u32,
+}
+
+/// A generic netlink family.
+pub struct Family {
+    /// The message type of the family.
+    pub id: u16,
+    pub name: Vec<u8>,
+    pub version: u32,
+    /// The size of the family-specific header that follows the generic header.
+    pub hdr_size: u32,
+    /// The largest attribute type understood by the family.
+    pub max_attr: u32,
+    pub groups: Vec<McastGroup>,
+}
+
+impl Family {
+    /// Returns the id of a multicast group.
+    ///
+    /// [argument, name]
+    /// The name of the group.
+    pub fn group(&self, name: &[u8]) -> Option<u32> {
+        for group in &self.groups[..] {
+            if &group.name[..] == name {
+                return Some(group.id);
+            }
+        }
+        None
+    }
+}
+
+/// A generic netlink socket.
+///
+/// = Remarks
+///
+/// Generic netlink multiplexes many families, such as `nl80211` or `taskstats`, over
+/// a single netlink protocol. The message type of a family is assigned dynamically and
+/// has to be resolved by name via `family` before requests can be sent.
+///
+/// = See also
+///
+/// * link:man:genetlink(7)
+pub struct GenlSocket {
+    nl: NlSocket,
+}
+
+impl GenlSocket {
+    /// Creates a new generic netlink socket.
+    pub fn new() -> Result<GenlSocket> {
+        Ok(GenlSocket { nl: try!(NlSocket::new(nl_proto::Generic)) })
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        self.nl.socket()
+    }
+
+    /// Joins a multicast group.
+    ///
+    /// [argument, group]
+    /// The id of the group.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::netlink::genl::Family::group
+    pub fn join_group(&self, group: u32) -> Result {
+        self.nl.join_group(group)
+    }
+
+    /// Leaves a multicast group.
+    ///
+    /// [argument, group]
+    /// The id of the group.
+    pub fn leave_group(&self, group: u32) -> Result {
+        self.nl.leave_group(group)
+    }
+
+    /// Sends a request and collects the reply.
+    ///
+    /// [argument, family]
+    /// The message type of the family.
+    ///
+    /// [argument, cmd]
+    /// The command.
+    ///
+    /// [argument, version]
+    /// The version of the family's interface.
+    ///
+    /// [argument, flags]
+    /// Additional flags of the request, e.g., `NLF_DUMP`.
+    ///
+    /// [argument, build]
+    /// A function that appends the family-specific header and attributes.
+    ///
+    /// [argument, f]
+    /// The function that is called for every message of the reply. It receives the
+    /// generic header and a parser positioned after it.
+    ///
+    /// = Remarks
+    ///
+    /// Requests that are not dumps are sent with the `NLF_ACK` flag so that errors are
+    /// reported and the end of the reply can be detected.
+    pub fn request<B, F>(&mut self, family: u16, cmd: u8, version: u8, flags: NlFlags,
+                         build: B, mut f: F) -> Result
+        where B: FnOnce(&mut NlData) -> Result,
+              F: FnMut(&GenlMsgHeader, MsgParser) -> Result,
+    {
+        let flags = match flags & NLF_DUMP == NLF_DUMP {
+            true => flags,
+            false => flags | NLF_ACK,
+        };
+        let seq = self.nl.next_seq();
+        let mut buf: NlBuf = NlBuf::new();
+        {
+            let mut msg = try!(buf.new_msg(Kind(family), NLF_REQUEST | flags, seq, 0));
+            let hdr = GenlMsgHeader { cmd: cmd, version: version, reserved: 0 };
+            try!(msg.add_raw(unsafe { mem::as_data(&hdr).as_bytes() }));
+            try!(build(&mut *msg));
+        }
+        self.nl.request(&buf, seq, |_, payload| {
+            let mut parser = try!(MsgParser::new(payload));
+            let hdr = *try!(parser.read_raw::<GenlMsgHeader>());
+            f(&hdr, parser)
+        })
+    }
+
+    /// Receives notifications.
+    ///
+    /// [argument, f]
+    /// The function that is called for every message. It receives the netlink header,
+    /// the generic header, and a parser positioned after the generic header.
+    ///
+    /// = Remarks
+    ///
+    /// This receives a single datagram. Malformed messages are skipped.
+    pub fn recv<F>(&mut self, mut f: F) -> Result
+        where F: FnMut(&MsgHeader, &GenlMsgHeader, MsgParser) -> Result,
+    {
+        self.nl.recv(|header, payload| {
+            let mut parser = match MsgParser::new(payload) {
+                Ok(p) => p,
+                _ => return Ok(()),
+            };
+            let hdr = match parser.read_raw::<GenlMsgHeader>() {
+                Ok(h) => *h,
+                _ => return Ok(()),
+            };
+            f(header, &hdr, parser)
+        })
+    }
+
+    /// Resolves a family by name.
+    ///
+    /// [argument, name]
+    /// The name of the family.
+    ///
+    /// = Remarks
+    ///
+    /// Fails with `DoesNotExist` if the family is not registered. Families that are
+    /// provided by modules are only available after the module has been loaded.
+    pub fn family(&mut self, name: &[u8]) -> Result<Family> {
+        let mut family = None;
+        let build = |msg: &mut NlData| msg.add_string(ctrl_attr::FamilyName, name);
+        try!(self.request(GENL_ID_CTRL, ctrl_cmd::GetFamily, 1, NlFlags(0), build,
+                          |hdr, parser| {
+            if hdr.cmd == ctrl_cmd::NewFamily {
+                family = Some(try!(parse_family(parser)));
+            }
+            Ok(())
+        }));
+        match family {
+            Some(f) => Ok(f),
+            None => Err(error::DoesNotExist),
+        }
+    }
+
+    /// Resolves the message type of a family by name.
+    ///
+    /// [argument, name]
+    /// The name of the family.
+    pub fn family_id(&mut self, name: &[u8]) -> Result<u16> {
+        self.family(name).map(|f| f.id)
+    }
+
+    /// Retrieves all registered families.
+    pub fn families(&mut self) -> Result<Vec<Family>> {
+        let mut families = Vec::new();
+        try!(self.request(GENL_ID_CTRL, ctrl_cmd::GetFamily, 1, NLF_DUMP, |_| Ok(()),
+                          |hdr, parser| {
+            match hdr.cmd {
+                ctrl_cmd::NewFamily => families.push(try!(parse_family(parser))),
+                _ => Ok(()),
+            }
+        }));
+        Ok(families)
+    }
+}
+
+fn parse_family(parser: MsgParser) -> Result<Family> {
+    let mut family = Family {
+        id: 0,
+        name: Vec::new(),
+        version: 0,
+        hdr_size: 0,
+        max_attr: 0,
+        groups: Vec::new(),
+    };
+    for (ty, data) in parser.attrs() {
+        match ty {
+            ctrl_attr::FamilyId => family.id = read(data).unwrap_or(0),
+            ctrl_attr::FamilyName => family.name = try!(read_string(data)),
+            ctrl_attr::Version => family.version = read(data).unwrap_or(0),
+            ctrl_attr::HdrSize => family.hdr_size = read(data).unwrap_or(0),
+            ctrl_attr::MaxAttr => family.max_attr = read(data).unwrap_or(0),
+            ctrl_attr::McastGroups => {
+                // Each group is a nested attribute whose type is its position.
+                for (_, group) in AttrIter::new(data) {
+                    let mut grp = McastGroup { name: Vec::new(), id: 0 };
+                    for (ty, data) in AttrIter::new(group) {
+                        match ty {
+                            mcast_grp_attr::Name => grp.name = try!(read_string(data)),
+                            mcast_grp_attr::Id => grp.id = read(data).unwrap_or(0),
+                            _ => { },
+                        }
+                    }
+                    try!(family.groups.push(grp));
+                }
+            },
+            _ => { },
+        }
+    }
+    Ok(family)
+}
@@ -13,6 +13,7 @@ extern crate lrs_cty        as cty;
 extern crate lrs_alloc      as alloc;
 extern crate lrs_vec        as vec;
 extern crate lrs_fmt        as fmt_;
+extern crate lrs_socket     as socket_;
 
 mod std { pub use fmt_::std::*; pub use cty; }
 
@@ -26,6 +27,11 @@ pub mod flags;
 pub mod fmt;
 pub mod route;
 pub mod parse;
+pub mod socket;
+pub mod rtnl;
+pub mod genl;
+
+mod util;
 
 #[repr(C)]
 #[derive(Pod, Eq)]
@@ -5,7 +5,8 @@
 use base::prelude::*;
 use core::{mem};
 use base::{error};
-use {MsgHeader};
+use cty::{NLA_TYPE_MASK};
+use {MsgHeader, Attr};
 
 pub struct MsgIter<'a> {
     data: &'a [u8],
@@ -76,4 +77,73 @@ impl<'a> MsgParser<'a> {
             _ => Err(error::InvalidArgument),
         }
     }
+
+    /// Reads a fixed-size header and advances past it.
+    ///
+    /// = Remarks
+    ///
+    /// The parser is advanced to the next four byte boundary after the header.
+    pub fn read_raw<T>(&mut self) -> Result<&'a T>
+        where T: Pod,
+    {
+        let val = match mem::from_bytes(self.data) {
+            Some(v) => v,
+            _ => return Err(error::InvalidArgument),
+        };
+        let len = (mem::size_of::<T>() + 3) & !3;
+        self.data = match len < self.data.len() {
+            true => &self.data[len..],
+            false => &[],
+        };
+        Ok(val)
+    }
+
+    /// Returns an iterator over the attributes in the remaining data.
+    pub fn attrs(&self) -> AttrIter<'a> {
+        AttrIter::new(self.data)
+    }
+
+    /// Returns the remaining data.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// An iterator over the attributes in a message payload.
+///
+/// = Remarks
+///
+/// The iterator yields the type of each attribute, with the nested and byte-order flags
+/// removed, together with its payload. Iteration stops at the first malformed
+/// attribute.
+pub struct AttrIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AttrIter<'a> {
+    /// Creates a new attribute iterator.
+    ///
+    /// [argument, data]
+    /// The attributes. This must be aligned to a four byte boundary.
+    pub fn new(data: &'a [u8]) -> AttrIter<'a> {
+        AttrIter { data: data }
+    }
+}
+
+impl<'a> Iterator for AttrIter<'a> {
+    type Item = (u16, &'a [u8]);
+    fn next(&mut self) -> Option<(u16, &'a [u8])> {
+        let attr = match mem::from_bytes::<Attr>(self.data) {
+            Some(a) => a,
+            _ => return None,
+        };
+        let len = attr.len as usize;
+        if len > self.data.len() || len < mem::size_of::<Attr>() {
+            self.data = &[];
+            return None;
+        }
+        let payload = &self.data[mem::size_of::<Attr>()..len];
+        self.data = mem::align_for::<u32>(&self.data[len..]);
+        Some((attr.ty & NLA_TYPE_MASK as u16, payload))
+    }
 }
@@ -162,3 +162,95 @@ pub mod veth_info {
     pub const Unspec : u16 = VETH_INFO_UNSPEC;
     pub const Peer   : u16 = VETH_INFO_PEER;
 }
+
+pub mod link_flags {
+    use cty::{
+        IFF_UP, IFF_BROADCAST, IFF_DEBUG, IFF_LOOPBACK, IFF_POINTOPOINT, IFF_NOTRAILERS,
+        IFF_RUNNING, IFF_NOARP, IFF_PROMISC, IFF_ALLMULTI, IFF_MASTER, IFF_SLAVE,
+        IFF_MULTICAST, IFF_PORTSEL, IFF_AUTOMEDIA, IFF_DYNAMIC, IFF_LOWER_UP, IFF_DORMANT,
+        IFF_ECHO,
+    };
+
+    pub const Up           : u32 = IFF_UP;
+    pub const Broadcast    : u32 = IFF_BROADCAST;
+    pub const Debug        : u32 = IFF_DEBUG;
+    pub const Loopback     : u32 = IFF_LOOPBACK;
+    pub const PointToPoint : u32 = IFF_POINTOPOINT;
+    pub const NoTrailers   : u32 = IFF_NOTRAILERS;
+    pub const Running      : u32 = IFF_RUNNING;
+    pub const NoArp        : u32 = IFF_NOARP;
+    pub const Promisc      : u32 = IFF_PROMISC;
+    pub const AllMulti     : u32 = IFF_ALLMULTI;
+    pub const Master       : u32 = IFF_MASTER;
+    pub const Slave        : u32 = IFF_SLAVE;
+    pub const Multicast    : u32 = IFF_MULTICAST;
+    pub const PortSel      : u32 = IFF_PORTSEL;
+    pub const AutoMedia    : u32 = IFF_AUTOMEDIA;
+    pub const Dynamic      : u32 = IFF_DYNAMIC;
+    pub const LowerUp      : u32 = IFF_LOWER_UP;
+    pub const Dormant      : u32 = IFF_DORMANT;
+    pub const Echo         : u32 = IFF_ECHO;
+}
+
+pub mod route_attr {
+    use cty::{
+        RTA_UNSPEC, RTA_DST, RTA_SRC, RTA_IIF, RTA_OIF, RTA_GATEWAY, RTA_PRIORITY,
+        RTA_PREFSRC, RTA_METRICS, RTA_MULTIPATH, RTA_PROTOINFO, RTA_FLOW, RTA_CACHEINFO,
+        RTA_SESSION, RTA_MP_ALGO, RTA_TABLE, RTA_MARK, RTA_MFC_STATS,
+    };
+
+    pub const Unspec    : u16 = RTA_UNSPEC    as u16;
+    pub const Dst       : u16 = RTA_DST       as u16;
+    pub const Src       : u16 = RTA_SRC       as u16;
+    pub const Iif       : u16 = RTA_IIF       as u16;
+    pub const Oif       : u16 = RTA_OIF       as u16;
+    pub const Gateway   : u16 = RTA_GATEWAY   as u16;
+    pub const Priority  : u16 = RTA_PRIORITY  as u16;
+    pub const PrefSrc   : u16 = RTA_PREFSRC   as u16;
+    pub const Metrics   : u16 = RTA_METRICS   as u16;
+    pub const MultiPath : u16 = RTA_MULTIPATH as u16;
+    pub const ProtoInfo : u16 = RTA_PROTOINFO as u16;
+    pub const Flow      : u16 = RTA_FLOW      as u16;
+    pub const CacheInfo : u16 = RTA_CACHEINFO as u16;
+    pub const Session   : u16 = RTA_SESSION   as u16;
+    pub const MpAlgo    : u16 = RTA_MP_ALGO   as u16;
+    pub const Table     : u16 = RTA_TABLE     as u16;
+    pub const Mark      : u16 = RTA_MARK      as u16;
+    pub const MfcStats  : u16 = RTA_MFC_STATS as u16;
+}
+
+pub mod group {
+    use cty::{
+        RTNLGRP_LINK, RTNLGRP_NOTIFY, RTNLGRP_NEIGH, RTNLGRP_TC, RTNLGRP_IPV4_IFADDR,
+        RTNLGRP_IPV4_MROUTE, RTNLGRP_IPV4_ROUTE, RTNLGRP_IPV4_RULE, RTNLGRP_IPV6_IFADDR,
+        RTNLGRP_IPV6_MROUTE, RTNLGRP_IPV6_ROUTE, RTNLGRP_IPV6_IFINFO, RTNLGRP_IPV6_PREFIX,
+        RTNLGRP_IPV6_RULE,
+    };
+
+    pub const Link       : u32 = RTNLGRP_LINK        as u32;
+    pub const Notify     : u32 = RTNLGRP_NOTIFY      as u32;
+    pub const Neigh      : u32 = RTNLGRP_NEIGH       as u32;
+    pub const Tc         : u32 = RTNLGRP_TC          as u32;
+    pub const Ipv4Addr   : u32 = RTNLGRP_IPV4_IFADDR as u32;
+    pub const Ipv4MRoute : u32 = RTNLGRP_IPV4_MROUTE as u32;
+    pub const Ipv4Route  : u32 = RTNLGRP_IPV4_ROUTE  as u32;
+    pub const Ipv4Rule   : u32 = RTNLGRP_IPV4_RULE   as u32;
+    pub const Ipv6Addr   : u32 = RTNLGRP_IPV6_IFADDR as u32;
+    pub const Ipv6MRoute : u32 = RTNLGRP_IPV6_MROUTE as u32;
+    pub const Ipv6Route  : u32 = RTNLGRP_IPV6_ROUTE  as u32;
+    pub const Ipv6IfInfo : u32 = RTNLGRP_IPV6_IFINFO as u32;
+    pub const Ipv6Prefix : u32 = RTNLGRP_IPV6_PREFIX as u32;
+    pub const Ipv6Rule   : u32 = RTNLGRP_IPV6_RULE   as u32;
+}
+
+pub mod route_scope {
+    use cty::{
+        RT_SCOPE_UNIVERSE, RT_SCOPE_SITE, RT_SCOPE_LINK, RT_SCOPE_HOST, RT_SCOPE_NOWHERE,
+    };
+
+    pub const Universe : u8 = RT_SCOPE_UNIVERSE;
+    pub const Site     : u8 = RT_SCOPE_SITE;
+    pub const Link     : u8 = RT_SCOPE_LINK;
+    pub const Host     : u8 = RT_SCOPE_HOST;
+    pub const Nowhere  : u8 = RT_SCOPE_NOWHERE;
+}
@@ -0,0 +1,602 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{mem};
+use base::{error};
+use cty::{
+    c_int, AF_INET, AF_INET6, RT_TABLE_MAIN, RT_TABLE_UNSPEC, RTPROT_STATIC,
+    RT_SCOPE_UNIVERSE, RTN_UNICAST,
+};
+use vec::{Vec};
+use socket_::addr::{IpAddr};
+use socket_::addr::ipv4::{Ipv4Addr};
+use socket_::addr::ipv6::{Ipv6Addr};
+use socket_::socket::{Socket};
+use socket_::nl_proto::{self};
+use fmt::{NlBuf, NlData};
+use parse::{AttrIter};
+use util::{read, read_string, read_bytes};
+use socket::{NlSocket};
+use kind::{Kind};
+use flags::{NlFlags, NLF_REQUEST, NLF_ACK, NLF_DUMP, NLF_CREATE, NLF_EXCL};
+use route::{
+    self, IfInfoMsg, IfAddrMsg, RouteMsg, link_attr, link_info, addr_attr, route_attr,
+    link_flags,
+};
+use {MsgHeader};
+
+/// A network interface.
+pub struct Link {
+    /// The index of the interface.
+    pub index: i32,
+    /// The hardware type of the interface (`ARPHRD_*`).
+    pub ty: u16,
+    /// The flags of the interface.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::netlink::route::link_flags
+    pub flags: u32,
+    /// The name of the interface.
+    pub name: Vec<u8>,
+    pub mtu: Option<u32>,
+    /// The hardware address of the interface.
+    pub address: Vec<u8>,
+    /// The hardware broadcast address of the interface.
+    pub broadcast: Vec<u8>,
+    pub tx_queue_len: Option<u32>,
+    /// The RFC 2863 operational state of the interface.
+    pub operstate: Option<u8>,
+    /// The index of the master interface if this interface is enslaved.
+    pub master: Option<i32>,
+    /// The kind of a virtual interface, e.g., `veth`. Empty for physical interfaces.
+    pub kind: Vec<u8>,
+}
+
+impl Link {
+    /// Returns whether the interface is administratively up.
+    pub fn is_up(&self) -> bool {
+        self.flags & link_flags::Up != 0
+    }
+
+    /// Returns whether the interface is a loopback interface.
+    pub fn is_loopback(&self) -> bool {
+        self.flags & link_flags::Loopback != 0
+    }
+}
+
+/// An address assigned to a network interface.
+pub struct Address {
+    /// The index of the interface.
+    pub index: u32,
+    pub prefix_len: u8,
+    /// The `IFA_F_*` flags of the address.
+    pub flags: u32,
+    pub scope: u8,
+    /// The address. For point-to-point interfaces this is the address of the peer.
+    pub address: Option<IpAddr>,
+    /// The local address.
+    pub local: Option<IpAddr>,
+    pub broadcast: Option<IpAddr>,
+    /// The label of the address. Only used by Ipv4.
+    pub label: Vec<u8>,
+}
+
+/// A routing table entry.
+pub struct Route {
+    /// The address family (`AF_INET` or `AF_INET6`).
+    pub family: u8,
+    pub dst_len: u8,
+    pub src_len: u8,
+    pub tos: u8,
+    /// The routing table.
+    pub table: u32,
+    /// The origin of the route (`RTPROT_*`).
+    pub protocol: u8,
+    /// The scope of the route.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::netlink::route::route_scope
+    pub scope: u8,
+    /// The type of the route (`RTN_*`).
+    pub ty: u8,
+    /// The `RTM_F_*` flags of the route.
+    pub flags: u32,
+    /// The destination network. `None` for the default route.
+    pub dst: Option<IpAddr>,
+    pub src: Option<IpAddr>,
+    pub gateway: Option<IpAddr>,
+    /// The preferred source address.
+    pub pref_src: Option<IpAddr>,
+    /// The index of the output interface.
+    pub oif: Option<u32>,
+    /// The index of the input interface.
+    pub iif: Option<u32>,
+    /// The metric of the route.
+    pub priority: Option<u32>,
+}
+
+impl Route {
+    /// Creates a static unicast route in the main routing table.
+    ///
+    /// [argument, dst]
+    /// The destination network.
+    ///
+    /// [argument, dst_len]
+    /// The prefix length of the destination network.
+    ///
+    /// = Remarks
+    ///
+    /// The route has no gateway and no output interface. At least one of them has to be
+    /// set before the route can be added. Routes to directly connected networks should
+    /// have their scope set to `route_scope::Link`.
+    pub fn new(dst: IpAddr, dst_len: u8) -> Route {
+        Route {
+            family: family(dst),
+            dst_len: dst_len,
+            src_len: 0,
+            tos: 0,
+            table: RT_TABLE_MAIN as u32,
+            protocol: RTPROT_STATIC,
+            scope: RT_SCOPE_UNIVERSE,
+            ty: RTN_UNICAST as u8,
+            flags: 0,
+            dst: Some(dst),
+            src: None,
+            gateway: None,
+            pref_src: None,
+            oif: None,
+            iif: None,
+            priority: None,
+        }
+    }
+}
+
+/// A change notification.
+pub enum Event {
+    NewLink(Link),
+    DelLink(Link),
+    NewAddress(Address),
+    DelAddress(Address),
+    NewRoute(Route),
+    DelRoute(Route),
+}
+
+/// A routing netlink socket.
+///
+/// = Remarks
+///
+/// Notifications that arrive while the socket waits for the reply to a request are
+/// discarded. A socket that is used to watch for changes should therefore not be used
+/// for requests at the same time.
+///
+/// = See also
+///
+/// * link:man:rtnetlink(7)
+pub struct RouteSocket {
+    nl: NlSocket,
+}
+
+impl RouteSocket {
+    /// Creates a new routing socket.
+    pub fn new() -> Result<RouteSocket> {
+        Ok(RouteSocket { nl: try!(NlSocket::new(nl_proto::Route)) })
+    }
+
+    /// Returns the underlying socket.
+    ///
+    /// = Remarks
+    ///
+    /// This can be used to wait for notifications with `poll` or `epoll`.
+    pub fn socket(&self) -> &Socket {
+        self.nl.socket()
+    }
+
+    /// Subscribes to change notifications.
+    ///
+    /// [argument, group]
+    /// The notification group.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::netlink::route::group
+    /// * link:lrs::netlink::RouteSocket::recv_events
+    pub fn join_group(&self, group: u32) -> Result {
+        self.nl.join_group(group)
+    }
+
+    /// Unsubscribes from change notifications.
+    ///
+    /// [argument, group]
+    /// The notification group.
+    pub fn leave_group(&self, group: u32) -> Result {
+        self.nl.leave_group(group)
+    }
+
+    /// Receives change notifications.
+    ///
+    /// [argument, f]
+    /// The function that is called for every notification.
+    ///
+    /// = Remarks
+    ///
+    /// This receives a single datagram which can contain multiple notifications.
+    /// Messages that are not notifications about links, addresses, or routes are
+    /// ignored.
+    pub fn recv_events<F>(&mut self, mut f: F) -> Result
+        where F: FnMut(Event),
+    {
+        self.nl.recv(|header, payload| {
+            let event = match header.ty {
+                route::NewLink => Event::NewLink(try!(parse_link(payload))),
+                route::DelLink => Event::DelLink(try!(parse_link(payload))),
+                route::NewAddr => Event::NewAddress(try!(parse_address(payload))),
+                route::DelAddr => Event::DelAddress(try!(parse_address(payload))),
+                route::NewRoute => Event::NewRoute(try!(parse_route(payload))),
+                route::DelRoute => Event::DelRoute(try!(parse_route(payload))),
+                _ => return Ok(()),
+            };
+            f(event);
+            Ok(())
+        })
+    }
+
+    fn request<T, B, F>(&mut self, ty: Kind, flags: NlFlags, header: &T, build: B,
+                        f: F) -> Result
+        where T: Pod,
+              B: FnOnce(&mut NlData) -> Result,
+              F: FnMut(&MsgHeader, &[u8]) -> Result,
+    {
+        let seq = self.nl.next_seq();
+        let mut buf: NlBuf = NlBuf::new();
+        {
+            let mut msg = try!(buf.new_msg(ty, NLF_REQUEST | flags, seq, 0));
+            try!(msg.add_raw(unsafe { mem::as_data(header).as_bytes() }));
+            try!(build(&mut *msg));
+        }
+        self.nl.request(&buf, seq, f)
+    }
+
+    /// Retrieves all network interfaces.
+    pub fn links(&mut self) -> Result<Vec<Link>> {
+        let hdr: IfInfoMsg = mem::zeroed();
+        let mut links = Vec::new();
+        try!(self.request(route::GetLink, NLF_DUMP, &hdr, |_| Ok(()), |header, payload| {
+            match header.ty {
+                route::NewLink => links.push(try!(parse_link(payload))),
+                _ => Ok(()),
+            }
+        }));
+        Ok(links)
+    }
+
+    /// Retrieves a network interface by its index.
+    ///
+    /// [argument, index]
+    /// The index of the interface.
+    pub fn link(&mut self, index: i32) -> Result<Link> {
+        let mut hdr: IfInfoMsg = mem::zeroed();
+        hdr.index = index;
+        self.get_link(&hdr, None)
+    }
+
+    /// Retrieves a network interface by its name.
+    ///
+    /// [argument, name]
+    /// The name of the interface.
+    pub fn link_by_name(&mut self, name: &[u8]) -> Result<Link> {
+        let hdr: IfInfoMsg = mem::zeroed();
+        self.get_link(&hdr, Some(name))
+    }
+
+    fn get_link(&mut self, hdr: &IfInfoMsg, name: Option<&[u8]>) -> Result<Link> {
+        let mut link = None;
+        let build = |msg: &mut NlData| {
+            match name {
+                Some(name) => msg.add_string(link_attr::IfName, name),
+                None => Ok(()),
+            }
+        };
+        try!(self.request(route::GetLink, NLF_ACK, hdr, build, |header, payload| {
+            if header.ty == route::NewLink {
+                link = Some(try!(parse_link(payload)));
+            }
+            Ok(())
+        }));
+        match link {
+            Some(link) => Ok(link),
+            None => Err(error::DoesNotExist),
+        }
+    }
+
+    /// Sets a network interface up or down.
+    ///
+    /// [argument, index]
+    /// The index of the interface.
+    ///
+    /// [argument, up]
+    /// Whether the interface is set up.
+    pub fn set_link_up(&mut self, index: i32, up: bool) -> Result {
+        let mut hdr: IfInfoMsg = mem::zeroed();
+        hdr.index = index;
+        hdr.flags = if up { link_flags::Up } else { 0 };
+        hdr.change = link_flags::Up;
+        self.request(route::NewLink, NLF_ACK, &hdr, |_| Ok(()), |_, _| Ok(()))
+    }
+
+    /// Sets the MTU of a network interface.
+    ///
+    /// [argument, index]
+    /// The index of the interface.
+    ///
+    /// [argument, mtu]
+    /// The new MTU.
+    pub fn set_link_mtu(&mut self, index: i32, mtu: u32) -> Result {
+        let mut hdr: IfInfoMsg = mem::zeroed();
+        hdr.index = index;
+        let build = |msg: &mut NlData| msg.add_u32(link_attr::Mtu, mtu);
+        self.request(route::NewLink, NLF_ACK, &hdr, build, |_, _| Ok(()))
+    }
+
+    /// Retrieves all Ipv4 and Ipv6 addresses.
+    pub fn addresses(&mut self) -> Result<Vec<Address>> {
+        let hdr: IfAddrMsg = mem::zeroed();
+        let mut addrs = Vec::new();
+        try!(self.request(route::GetAddr, NLF_DUMP, &hdr, |_| Ok(()), |header, payload| {
+            match header.ty {
+                route::NewAddr => addrs.push(try!(parse_address(payload))),
+                _ => Ok(()),
+            }
+        }));
+        Ok(addrs)
+    }
+
+    /// Adds an address to a network interface.
+    ///
+    /// [argument, index]
+    /// The index of the interface.
+    ///
+    /// [argument, addr]
+    /// The address.
+    ///
+    /// [argument, prefix_len]
+    /// The prefix length of the network the address belongs to.
+    pub fn add_address(&mut self, index: u32, addr: IpAddr, prefix_len: u8) -> Result {
+        self.modify_address(route::NewAddr, NLF_CREATE | NLF_EXCL, index, addr,
+                            prefix_len)
+    }
+
+    /// Removes an address from a network interface.
+    ///
+    /// [argument, index]
+    /// The index of the interface.
+    ///
+    /// [argument, addr]
+    /// The address.
+    ///
+    /// [argument, prefix_len]
+    /// The prefix length of the network the address belongs to.
+    pub fn del_address(&mut self, index: u32, addr: IpAddr, prefix_len: u8) -> Result {
+        self.modify_address(route::DelAddr, NlFlags(0), index, addr, prefix_len)
+    }
+
+    fn modify_address(&mut self, ty: Kind, flags: NlFlags, index: u32, addr: IpAddr,
+                      prefix_len: u8) -> Result {
+        let mut hdr: IfAddrMsg = mem::zeroed();
+        hdr.family = family(addr);
+        hdr.prefix = prefix_len;
+        hdr.index = index;
+        let build = |msg: &mut NlData| {
+            try!(add_ip(msg, addr_attr::Local, addr));
+            add_ip(msg, addr_attr::Address, addr)
+        };
+        self.request(ty, NLF_ACK | flags, &hdr, build, |_, _| Ok(()))
+    }
+
+    /// Retrieves all Ipv4 and Ipv6 routes.
+    pub fn routes(&mut self) -> Result<Vec<Route>> {
+        let hdr: RouteMsg = mem::zeroed();
+        let mut routes = Vec::new();
+        try!(self.request(route::GetRoute, NLF_DUMP, &hdr, |_| Ok(()), |header, payload| {
+            match header.ty {
+                route::NewRoute => routes.push(try!(parse_route(payload))),
+                _ => Ok(()),
+            }
+        }));
+        Ok(routes)
+    }
+
+    /// Adds a route.
+    ///
+    /// [argument, route]
+    /// The route.
+    pub fn add_route(&mut self, route: &Route) -> Result {
+        self.modify_route(route::NewRoute, NLF_CREATE | NLF_EXCL, route)
+    }
+
+    /// Removes a route.
+    ///
+    /// [argument, route]
+    /// The route.
+    ///
+    /// = Remarks
+    ///
+    /// The kernel removes the first route that matches all attributes that are set.
+    pub fn del_route(&mut self, route: &Route) -> Result {
+        self.modify_route(route::DelRoute, NlFlags(0), route)
+    }
+
+    fn modify_route(&mut self, ty: Kind, flags: NlFlags, r: &Route) -> Result {
+        let mut hdr: RouteMsg = mem::zeroed();
+        hdr.family = r.family;
+        hdr.dst_len = r.dst_len;
+        hdr.src_len = r.src_len;
+        hdr.tos = r.tos;
+        hdr.table = if r.table < 256 { r.table as u8 } else { RT_TABLE_UNSPEC };
+        hdr.protocol = r.protocol;
+        hdr.scope = r.scope;
+        hdr.ty = r.ty;
+        hdr.flags = r.flags;
+        let build = |msg: &mut NlData| {
+            let addrs = [
+                (route_attr::Dst, r.dst),
+                (route_attr::Src, r.src),
+                (route_attr::Gateway, r.gateway),
+                (route_attr::PrefSrc, r.pref_src),
+            ];
+            for &(ty, addr) in &addrs[..] {
+                if let Some(addr) = addr {
+                    try!(add_ip(msg, ty, addr));
+                }
+            }
+            let ints = [
+                (route_attr::Oif, r.oif),
+                (route_attr::Iif, r.iif),
+                (route_attr::Priority, r.priority),
+            ];
+            for &(ty, val) in &ints[..] {
+                if let Some(val) = val {
+                    try!(msg.add_u32(ty, val));
+                }
+            }
+            msg.add_u32(route_attr::Table, r.table)
+        };
+        self.request(ty, NLF_ACK | flags, &hdr, build, |_, _| Ok(()))
+    }
+}
+
+fn family(addr: IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(..) => AF_INET as u8,
+        IpAddr::V6(..) => AF_INET6 as u8,
+    }
+}
+
+fn add_ip(msg: &mut NlData, ty: u16, addr: IpAddr) -> Result {
+    match addr {
+        IpAddr::V4(addr) => msg.add_data(ty, &addr.to_bytes()),
+        IpAddr::V6(addr) => {
+            let bytes: [u8; 16] = unsafe { mem::cast(addr.to_be_bytes()) };
+            msg.add_data(ty, &bytes)
+        },
+    }
+}
+
+fn read_ip(family: u8, data: &[u8]) -> Option<IpAddr> {
+    match family as c_int {
+        AF_INET => read(data).map(|b| IpAddr::V4(Ipv4Addr::from_bytes(b))),
+        AF_INET6 => read(data).map(|b| IpAddr::V6(Ipv6Addr::from_be_bytes(b))),
+        _ => None,
+    }
+}
+
+fn parse_link(payload: &[u8]) -> Result<Link> {
+    let hdr = match mem::from_bytes::<IfInfoMsg>(payload) {
+        Some(hdr) => hdr,
+        _ => return Err(error::InvalidSequence),
+    };
+    let mut link = Link {
+        index: hdr.index,
+        ty: hdr.ty,
+        flags: hdr.flags,
+        name: Vec::new(),
+        mtu: None,
+        address: Vec::new(),
+        broadcast: Vec::new(),
+        tx_queue_len: None,
+        operstate: None,
+        master: None,
+        kind: Vec::new(),
+    };
+    for (ty, data) in AttrIter::new(&payload[mem::size_of::<IfInfoMsg>()..]) {
+        match ty {
+            link_attr::IfName => link.name = try!(read_string(data)),
+            link_attr::Mtu => link.mtu = read(data),
+            link_attr::Address => link.address = try!(read_bytes(data)),
+            link_attr::Broadcast => link.broadcast = try!(read_bytes(data)),
+            link_attr::TqxLen => link.tx_queue_len = read(data),
+            link_attr::Operstate => link.operstate = read(data),
+            link_attr::Master => link.master = read(data),
+            link_attr::LinkInfo => {
+                for (ty, data) in AttrIter::new(data) {
+                    if ty == link_info::Kind {
+                        link.kind = try!(read_string(data));
+                    }
+                }
+            },
+            _ => { },
+        }
+    }
+    Ok(link)
+}
+
+fn parse_address(payload: &[u8]) -> Result<Address> {
+    let hdr = match mem::from_bytes::<IfAddrMsg>(payload) {
+        Some(hdr) => hdr,
+        _ => return Err(error::InvalidSequence),
+    };
+    let mut addr = Address {
+        index: hdr.index,
+        prefix_len: hdr.prefix,
+        flags: hdr.flags as u32,
+        scope: hdr.scope,
+        address: None,
+        local: None,
+        broadcast: None,
+        label: Vec::new(),
+    };
+    for (ty, data) in AttrIter::new(&payload[mem::size_of::<IfAddrMsg>()..]) {
+        match ty {
+            addr_attr::Address => addr.address = read_ip(hdr.family, data),
+            addr_attr::Local => addr.local = read_ip(hdr.family, data),
+            addr_attr::Broadcast => addr.broadcast = read_ip(hdr.family, data),
+            addr_attr::Label => addr.label = try!(read_string(data)),
+            // The header only has room for the lower eight flags.
+            addr_attr::Flags => addr.flags = read(data).unwrap_or(addr.flags),
+            _ => { },
+        }
+    }
+    Ok(addr)
+}
+
+fn parse_route(payload: &[u8]) -> Result<Route> {
+    let hdr = match mem::from_bytes::<RouteMsg>(payload) {
+        Some(hdr) => hdr,
+        _ => return Err(error::InvalidSequence),
+    };
+    let mut route = Route {
+        family: hdr.family,
+        dst_len: hdr.dst_len,
+        src_len: hdr.src_len,
+        tos: hdr.tos,
+        table: hdr.table as u32,
+        protocol: hdr.protocol,
+        scope: hdr.scope,
+        ty: hdr.ty,
+        flags: hdr.flags,
+        dst: None,
+        src: None,
+        gateway: None,
+        pref_src: None,
+        oif: None,
+        iif: None,
+        priority: None,
+    };
+    for (ty, data) in AttrIter::new(&payload[mem::size_of::<RouteMsg>()..]) {
+        match ty {
+            route_attr::Dst => route.dst = read_ip(hdr.family, data),
+            route_attr::Src => route.src = read_ip(hdr.family, data),
+            route_attr::Gateway => route.gateway = read_ip(hdr.family, data),
+            route_attr::PrefSrc => route.pref_src = read_ip(hdr.family, data),
+            route_attr::Oif => route.oif = read(data),
+            route_attr::Iif => route.iif = read(data),
+            route_attr::Priority => route.priority = read(data),
+            route_attr::Table => route.table = read(data).unwrap_or(route.table),
+            _ => { },
+        }
+    }
+    Ok(route)
+}
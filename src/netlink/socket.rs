@@ -0,0 +1,197 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{mem};
+use base::{error};
+use alloc::{MemPool};
+use vec::{Vec};
+use socket_::socket::{Socket};
+use socket_::nl_proto::{Proto};
+use socket_::flags::{SOCK_CLOSE_ON_EXEC};
+use socket_::msg::{MSG_NONE};
+use fmt::{NlBuf};
+use parse::{MsgIter};
+use {kind, MsgHeader, MsgError};
+
+/// The size of the receive buffer in bytes.
+///
+/// = Remarks
+///
+/// The kernel does not split messages across datagrams and recommends buffers of at
+/// least 8 KB. We use more to reduce the number of system calls during dumps.
+const RECV_BUF_SIZE: usize = 32 * 1024;
+
+/// A netlink socket with request bookkeeping.
+///
+/// = Remarks
+///
+/// This type assigns sequence numbers to requests and collects the replies that belong
+/// to them. Messages with other sequence numbers, such as multicast notifications, are
+/// ignored while waiting for a reply.
+pub struct NlSocket {
+    sock: Socket,
+    seq: u32,
+    /// Stored as `u32` so that received messages are properly aligned.
+    buf: Vec<u32>,
+}
+
+impl NlSocket {
+    /// Creates a new netlink socket.
+    ///
+    /// [argument, proto]
+    /// The netlink protocol of the socket.
+    pub fn new(proto: Proto) -> Result<NlSocket> {
+        let sock = try!(Socket::netlink(proto, SOCK_CLOSE_ON_EXEC));
+        let words = RECV_BUF_SIZE / mem::size_of::<u32>();
+        let mut buf = try!(Vec::with_capacity(words));
+        for _ in 0..words {
+            try!(buf.push(0));
+        }
+        Ok(NlSocket {
+            sock: sock,
+            seq: 0,
+            buf: buf,
+        })
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        &self.sock
+    }
+
+    /// Returns a new sequence number.
+    ///
+    /// = Remarks
+    ///
+    /// The sequence number 0 is never returned because it is used by the kernel for
+    /// notifications.
+    pub fn next_seq(&mut self) -> u32 {
+        self.seq = self.seq.wrapping_add(1);
+        if self.seq == 0 {
+            self.seq = 1;
+        }
+        self.seq
+    }
+
+    /// Joins a multicast group.
+    ///
+    /// [argument, group]
+    /// The group to join.
+    pub fn join_group(&self, group: u32) -> Result {
+        self.sock.netlink_join_group(group)
+    }
+
+    /// Leaves a multicast group.
+    ///
+    /// [argument, group]
+    /// The group to leave.
+    pub fn leave_group(&self, group: u32) -> Result {
+        self.sock.netlink_leave_group(group)
+    }
+
+    /// Sends the messages in a buffer to the kernel.
+    ///
+    /// [argument, buf]
+    /// The buffer containing the messages.
+    pub fn send<H>(&self, buf: &NlBuf<H>) -> Result
+        where H: MemPool,
+    {
+        let data: &[d8] = buf.as_ref();
+        let data = unsafe { data.as_bytes() };
+        let len = try!(self.sock.send(data, MSG_NONE));
+        if len != data.len() {
+            return Err(error::MessageSize);
+        }
+        Ok(())
+    }
+
+    /// Receives one datagram and calls a function for every message in it.
+    ///
+    /// [argument, f]
+    /// The function that is called with the header and payload of every message.
+    ///
+    /// = Remarks
+    ///
+    /// This blocks until a datagram is available unless the socket is non-blocking. If
+    /// `f` returns an error, the remaining messages of the datagram are discarded and
+    /// the error is returned.
+    pub fn recv<F>(&mut self, mut f: F) -> Result
+        where F: FnMut(&MsgHeader, &[u8]) -> Result,
+    {
+        let data = unsafe { mem::as_mut_data(&mut self.buf[..]).as_mut_bytes() };
+        let len = try!(self.sock.recv(data, MSG_NONE));
+        let mut res = Ok(());
+        for (header, payload) in MsgIter::new(&data[..len], Some(&mut res)) {
+            try!(f(header, payload));
+        }
+        res
+    }
+
+    /// Collects the reply to a request.
+    ///
+    /// [argument, seq]
+    /// The sequence number of the request.
+    ///
+    /// [argument, f]
+    /// The function that is called with the header and payload of every message of the
+    /// reply.
+    ///
+    /// = Remarks
+    ///
+    /// This returns once the kernel has acknowledged the request or finished a dump. The
+    /// request should therefore either have the `NLF_ACK` flag set or be a dump request.
+    /// If the kernel reports an error, the error is returned.
+    pub fn reply<F>(&mut self, seq: u32, mut f: F) -> Result
+        where F: FnMut(&MsgHeader, &[u8]) -> Result,
+    {
+        let mut done = false;
+        while !done {
+            try!(self.recv(|header, payload| {
+                if header.sequence != seq || done {
+                    return Ok(());
+                }
+                match header.ty {
+                    kind::ErrorAck => {
+                        done = true;
+                        match mem::from_bytes::<MsgError>(payload) {
+                            Some(e) if e.error == 0 => Ok(()),
+                            Some(e) => Err(error::Errno(-e.error)),
+                            None => Err(error::InvalidSequence),
+                        }
+                    },
+                    kind::Done => {
+                        done = true;
+                        Ok(())
+                    },
+                    kind::NoOp => Ok(()),
+                    _ => f(header, payload),
+                }
+            }));
+        }
+        Ok(())
+    }
+
+    /// Sends a request and collects the reply.
+    ///
+    /// [argument, buf]
+    /// The buffer containing the request.
+    ///
+    /// [argument, seq]
+    /// The sequence number of the request.
+    ///
+    /// [argument, f]
+    /// The function that is called for every message of the reply.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::netlink::NlSocket::reply
+    pub fn request<H, F>(&mut self, buf: &NlBuf<H>, seq: u32, f: F) -> Result
+        where H: MemPool,
+              F: FnMut(&MsgHeader, &[u8]) -> Result,
+    {
+        try!(self.send(buf));
+        self.reply(seq, f)
+    }
+}
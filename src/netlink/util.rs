@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Attribute decoding helpers.
+
+use base::prelude::*;
+use core::{mem};
+use vec::{Vec};
+
+/// Reads a fixed-size attribute. Returns `None` if the attribute is too short.
+pub fn read<T: Pod>(data: &[u8]) -> Option<T> {
+    if data.len() < mem::size_of::<T>() {
+        return None;
+    }
+    let mut val: T = mem::zeroed();
+    mem::copy(mem::as_mut_data(&mut val), data.as_ref());
+    Some(val)
+}
+
+/// Copies a string attribute without the terminating null byte.
+pub fn read_string(data: &[u8]) -> Result<Vec<u8>> {
+    let mut len = 0;
+    while len < data.len() && data[len] != 0 {
+        len += 1;
+    }
+    let mut vec = Vec::new();
+    try!(vec.push_all(&data[..len]));
+    Ok(vec)
+}
+
+/// Copies a binary attribute.
+pub fn read_bytes(data: &[u8]) -> Result<Vec<u8>> {
+    let mut vec = Vec::new();
+    try!(vec.push_all(data));
+    Ok(vec)
+}
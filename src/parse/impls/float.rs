@@ -2,4 +2,593 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-// todo
+use base::prelude::*;
+use base::{error};
+use {Parsable};
+
+// The slow path is the "simple decimal conversion" algorithm. The decimal is stored as a
+// sequence of digits and repeatedly shifted by powers of two until it is in the range
+// [1/2, 1). The exponent and mantissa can then be read off the shifted decimal. This is
+// always correctly rounded as long as the decimal can hold enough digits to represent
+// every halfway point between two adjacent floats exactly.
+
+/// The maximum number of significant digits stored in a decimal.
+const MAX_DIGITS: usize = 768;
+
+/// The maximum absolute value of the decimal point before the value is zero or infinite.
+const DECIMAL_POINT_RANGE: i32 = 2047;
+
+/// Exponents are clamped to this value to avoid overflow.
+const MAX_EXPONENT: i32 = 0x10000;
+
+/// The maximum shift that doesn't overflow a `u64` in `Decimal::left_shift`.
+const MAX_SHIFT: usize = 60;
+
+/// `POWERS[n]` is the number of bits by which a decimal with `n` digits before the
+/// decimal point can be shifted right without becoming smaller than 1.
+const POWERS: [u8; 19] = [0, 3, 6, 9, 13, 16, 19, 23, 26, 29, 33, 36, 39, 43, 46, 49,
+                          53, 56, 59];
+
+/// The IEEE 754 layout of a floating point type.
+struct Format {
+    mantissa_bits: usize,
+    min_exp: i32,
+    inf_power: i32,
+    sign: u64,
+    /// Exact conversion for small mantissas and exponents.
+    fast_path: fn(u64, i32) -> Option<u64>,
+}
+
+const F64: Format = Format {
+    mantissa_bits: 52,
+    min_exp: -1023,
+    inf_power: 0x7FF,
+    sign: 1 << 63,
+    fast_path: fast_f64,
+};
+
+const F32: Format = Format {
+    mantissa_bits: 23,
+    min_exp: -127,
+    inf_power: 0xFF,
+    sign: 1 << 31,
+    fast_path: fast_f32,
+};
+
+impl Format {
+    fn inf(&self) -> u64 {
+        (self.inf_power as u64) << self.mantissa_bits
+    }
+
+    fn nan(&self) -> u64 {
+        self.inf() | (1 << (self.mantissa_bits - 1))
+    }
+
+    fn bits(&self, mantissa: u64, power2: i32) -> u64 {
+        mantissa | ((power2 as u64) << self.mantissa_bits)
+    }
+}
+
+const F64_POWERS: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15,
+    1e16, 1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+const F32_POWERS: [f32; 11] = [1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10];
+
+// If the mantissa and the power of ten are both exactly representable, a single
+// multiplication or division is correctly rounded.
+
+fn fast_f64(mantissa: u64, exp10: i32) -> Option<u64> {
+    if mantissa > 1 << 53 || exp10 < -22 || exp10 > 22 {
+        return None;
+    }
+    let val = mantissa as f64;
+    let val = if exp10 < 0 {
+        val / F64_POWERS[-exp10 as usize]
+    } else {
+        val * F64_POWERS[exp10 as usize]
+    };
+    Some(val.to_bits())
+}
+
+fn fast_f32(mantissa: u64, exp10: i32) -> Option<u64> {
+    if mantissa > 1 << 24 || exp10 < -10 || exp10 > 10 {
+        return None;
+    }
+    let val = mantissa as f32;
+    let val = if exp10 < 0 {
+        val / F32_POWERS[-exp10 as usize]
+    } else {
+        val * F32_POWERS[exp10 as usize]
+    };
+    Some(val.to_bits() as u64)
+}
+
+/// A decimal of the form `0.d1d2d3... * 10^decimal_point`.
+struct Decimal {
+    num_digits: usize,
+    decimal_point: i32,
+    /// Whether non-zero digits have been dropped.
+    truncated: bool,
+    digits: [u8; MAX_DIGITS],
+}
+
+impl Decimal {
+    fn new() -> Decimal {
+        Decimal {
+            num_digits: 0,
+            decimal_point: 0,
+            truncated: false,
+            digits: [0; MAX_DIGITS],
+        }
+    }
+
+    fn push(&mut self, digit: u8) {
+        if self.num_digits < MAX_DIGITS {
+            self.digits[self.num_digits] = digit;
+            self.num_digits += 1;
+        } else if digit != 0 {
+            self.truncated = true;
+        }
+    }
+
+    fn trim(&mut self) {
+        while self.num_digits > 0 && self.digits[self.num_digits - 1] == 0 {
+            self.num_digits -= 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.num_digits = 0;
+        self.decimal_point = 0;
+        self.truncated = false;
+    }
+
+    /// Returns the digits as an integer. There must be at most 19 digits.
+    fn integer(&self) -> u64 {
+        let mut n = 0;
+        for i in 0..self.num_digits {
+            n = n * 10 + self.digits[i] as u64;
+        }
+        n
+    }
+
+    /// Returns the integer part of the decimal rounded to nearest, ties to even.
+    fn round(&self) -> u64 {
+        if self.num_digits == 0 || self.decimal_point < 0 {
+            return 0;
+        } else if self.decimal_point > 18 {
+            return !0;
+        }
+        let dp = self.decimal_point as usize;
+        let mut n = 0;
+        for i in 0..dp {
+            n *= 10;
+            if i < self.num_digits {
+                n += self.digits[i] as u64;
+            }
+        }
+        let mut round_up = false;
+        if dp < self.num_digits {
+            round_up = self.digits[dp] >= 5;
+            if self.digits[dp] == 5 && dp + 1 == self.num_digits {
+                round_up = self.truncated || (dp != 0 && self.digits[dp - 1] & 1 != 0);
+            }
+        }
+        n + round_up as u64
+    }
+
+    /// Multiplies the decimal by `2^shift`.
+    fn left_shift(&mut self, shift: usize) {
+        if self.num_digits == 0 {
+            return;
+        }
+        // The result has at most 19 more digits than the input. The digits are computed
+        // from the least significant one backwards.
+        let mut tmp = [0u8; MAX_DIGITS + 20];
+        let mut write = tmp.len();
+        let mut n = 0u64;
+        let mut read = self.num_digits;
+        while read > 0 {
+            read -= 1;
+            n += (self.digits[read] as u64) << shift;
+            let quo = n / 10;
+            write -= 1;
+            tmp[write] = (n - 10 * quo) as u8;
+            n = quo;
+        }
+        while n > 0 {
+            let quo = n / 10;
+            write -= 1;
+            tmp[write] = (n - 10 * quo) as u8;
+            n = quo;
+        }
+        let len = tmp.len() - write;
+        self.decimal_point += (len - self.num_digits) as i32;
+        self.num_digits = 0;
+        for i in write..tmp.len() {
+            self.push(tmp[i]);
+        }
+        self.trim();
+    }
+
+    /// Divides the decimal by `2^shift`.
+    fn right_shift(&mut self, shift: usize) {
+        let mut read = 0;
+        let mut write = 0;
+        let mut n = 0u64;
+        while n >> shift == 0 {
+            if read < self.num_digits {
+                n = 10 * n + self.digits[read] as u64;
+                read += 1;
+            } else if n == 0 {
+                return;
+            } else {
+                while n >> shift == 0 {
+                    n *= 10;
+                    read += 1;
+                }
+                break;
+            }
+        }
+        self.decimal_point -= read as i32 - 1;
+        if self.decimal_point < -DECIMAL_POINT_RANGE {
+            self.clear();
+            return;
+        }
+        let mask = (1 << shift) - 1;
+        while read < self.num_digits {
+            let digit = (n >> shift) as u8;
+            n = 10 * (n & mask) + self.digits[read] as u64;
+            read += 1;
+            self.digits[write] = digit;
+            write += 1;
+        }
+        while n > 0 {
+            let digit = (n >> shift) as u8;
+            n = 10 * (n & mask);
+            if write < MAX_DIGITS {
+                self.digits[write] = digit;
+                write += 1;
+            } else if digit > 0 {
+                self.truncated = true;
+            }
+        }
+        self.num_digits = write;
+        self.trim();
+    }
+
+    /// Converts the decimal to a mantissa and biased exponent.
+    fn to_binary(&mut self, fmt: &Format) -> (u64, i32) {
+        let shift_for = |n: usize| {
+            if n < POWERS.len() { POWERS[n] as usize } else { MAX_SHIFT }
+        };
+
+        if self.num_digits == 0 || self.decimal_point < -324 {
+            return (0, 0);
+        } else if self.decimal_point >= 310 {
+            return (0, fmt.inf_power);
+        }
+
+        let mut exp2 = 0i32;
+        while self.decimal_point > 0 {
+            let shift = shift_for(self.decimal_point as usize);
+            self.right_shift(shift);
+            if self.decimal_point < -DECIMAL_POINT_RANGE {
+                return (0, 0);
+            }
+            exp2 += shift as i32;
+        }
+        while self.decimal_point <= 0 {
+            let shift = if self.decimal_point == 0 {
+                match self.digits[0] {
+                    d if d >= 5 => break,
+                    0 | 1 => 2,
+                    _ => 1,
+                }
+            } else {
+                shift_for(-self.decimal_point as usize)
+            };
+            self.left_shift(shift);
+            if self.decimal_point > DECIMAL_POINT_RANGE {
+                return (0, fmt.inf_power);
+            }
+            exp2 -= shift as i32;
+        }
+
+        // The decimal is now in [1/2, 1) but the mantissa is in [1, 2).
+        exp2 -= 1;
+        while fmt.min_exp + 1 > exp2 {
+            let mut n = (fmt.min_exp + 1 - exp2) as usize;
+            if n > MAX_SHIFT {
+                n = MAX_SHIFT;
+            }
+            self.right_shift(n);
+            exp2 += n as i32;
+        }
+        if exp2 - fmt.min_exp >= fmt.inf_power {
+            return (0, fmt.inf_power);
+        }
+
+        // Move the hidden bit in front of the decimal point and round.
+        self.left_shift(fmt.mantissa_bits + 1);
+        let mut mantissa = self.round();
+        if mantissa >= 1 << (fmt.mantissa_bits + 1) {
+            // Rounding carried into the next bit.
+            self.right_shift(1);
+            exp2 += 1;
+            mantissa = self.round();
+            if exp2 - fmt.min_exp >= fmt.inf_power {
+                return (0, fmt.inf_power);
+            }
+        }
+        let mut power2 = exp2 - fmt.min_exp;
+        if mantissa < 1 << fmt.mantissa_bits {
+            // Subnormal
+            power2 -= 1;
+        }
+        mantissa &= (1 << fmt.mantissa_bits) - 1;
+        (mantissa, power2)
+    }
+}
+
+fn is_digit(b: u8) -> bool {
+    b'0' <= b && b <= b'9'
+}
+
+fn hex_digit(b: u8) -> Option<u8> {
+    match b {
+        b'0'...b'9' => Some(b - b'0'),
+        b'a'...b'f' => Some(b - b'a' + 10),
+        b'A'...b'F' => Some(b - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn starts_with_ignore_case(bytes: &[u8], prefix: &[u8]) -> bool {
+    if bytes.len() < prefix.len() {
+        return false;
+    }
+    for i in 0..prefix.len() {
+        if bytes[i] | 0x20 != prefix[i] {
+            return false;
+        }
+    }
+    true
+}
+
+/// Parses an exponent of the form `[marker][+-]digits`.
+///
+/// Returns the exponent and the number of bytes consumed. If there is no valid exponent,
+/// nothing is consumed.
+fn exponent(bytes: &[u8], marker: u8) -> (i32, usize) {
+    if bytes.len() == 0 || bytes[0] | 0x20 != marker {
+        return (0, 0);
+    }
+    let mut pos = 1;
+    let mut negative = false;
+    if pos < bytes.len() && (bytes[pos] == b'+' || bytes[pos] == b'-') {
+        negative = bytes[pos] == b'-';
+        pos += 1;
+    }
+    if pos == bytes.len() || !is_digit(bytes[pos]) {
+        return (0, 0);
+    }
+    let mut exp = 0i32;
+    while pos < bytes.len() && is_digit(bytes[pos]) {
+        if exp < MAX_EXPONENT {
+            exp = 10 * exp + (bytes[pos] - b'0') as i32;
+        }
+        pos += 1;
+    }
+    (if negative { -exp } else { exp }, pos)
+}
+
+fn clamp_exponent(exp: i32) -> i32 {
+    if exp > MAX_EXPONENT {
+        MAX_EXPONENT
+    } else if exp < -MAX_EXPONENT {
+        -MAX_EXPONENT
+    } else {
+        exp
+    }
+}
+
+fn decimal(bytes: &[u8], fmt: &Format) -> Result<(u64, usize)> {
+    let mut dec = Decimal::new();
+    let mut pos = 0;
+    let mut digits = 0;
+    let mut significant = 0;
+    let mut decimal_point = 0i32;
+
+    while pos < bytes.len() && is_digit(bytes[pos]) {
+        let digit = bytes[pos] - b'0';
+        if significant > 0 || digit != 0 {
+            dec.push(digit);
+            significant += 1;
+            if decimal_point < MAX_EXPONENT {
+                decimal_point += 1;
+            }
+        }
+        pos += 1;
+        digits += 1;
+    }
+    if pos < bytes.len() && bytes[pos] == b'.' {
+        pos += 1;
+        while pos < bytes.len() && is_digit(bytes[pos]) {
+            let digit = bytes[pos] - b'0';
+            if significant > 0 || digit != 0 {
+                dec.push(digit);
+                significant += 1;
+            } else if decimal_point > -MAX_EXPONENT {
+                decimal_point -= 1;
+            }
+            pos += 1;
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        return Err(error::InvalidArgument);
+    }
+
+    let (exp, len) = exponent(&bytes[pos..], b'e');
+    pos += len;
+
+    if significant == 0 {
+        return Ok((0, pos));
+    }
+
+    dec.trim();
+    dec.decimal_point = clamp_exponent(decimal_point + exp);
+
+    if significant <= 19 {
+        let exp10 = dec.decimal_point - dec.num_digits as i32;
+        if let Some(bits) = (fmt.fast_path)(dec.integer(), exp10) {
+            return Ok((bits, pos));
+        }
+    }
+
+    let (mantissa, power2) = dec.to_binary(fmt);
+    Ok((fmt.bits(mantissa, power2), pos))
+}
+
+/// Rounds `mantissa * 2^exp2` to the nearest float, ties to even.
+///
+/// `sticky` is set if non-zero bits below the mantissa have been dropped.
+fn binary(mut mantissa: u64, mut exp2: i32, sticky: bool, fmt: &Format) -> u64 {
+    let zeros = mantissa.leading_zeros();
+    mantissa <<= zeros;
+    exp2 -= zeros as i32;
+
+    let mut power2 = exp2 + 63 - fmt.min_exp;
+    if power2 >= fmt.inf_power {
+        return fmt.inf();
+    }
+    let mut drop = 63 - fmt.mantissa_bits as i32;
+    if power2 <= 0 {
+        drop += 1 - power2;
+        power2 = 0;
+    }
+    if drop > 64 {
+        return 0;
+    }
+
+    let (kept, rem, half) = if drop == 64 {
+        (0, mantissa, 1 << 63)
+    } else {
+        (mantissa >> drop, mantissa & ((1 << drop) - 1), 1 << (drop - 1))
+    };
+    let round_up = rem > half || (rem == half && (sticky || kept & 1 != 0));
+    let mut kept = kept + round_up as u64;
+
+    if power2 == 0 {
+        // Subnormal. If rounding carried into the hidden bit, this is automatically the
+        // smallest normal number.
+        return kept;
+    }
+    if kept >> (fmt.mantissa_bits + 1) != 0 {
+        kept >>= 1;
+        power2 += 1;
+        if power2 >= fmt.inf_power {
+            return fmt.inf();
+        }
+    }
+    fmt.bits(kept & ((1 << fmt.mantissa_bits) - 1), power2)
+}
+
+fn hex(bytes: &[u8], fmt: &Format) -> Option<(u64, usize)> {
+    let mut pos = 0;
+    let mut digits = 0;
+    let mut mantissa = 0u64;
+    let mut exp2 = 0i32;
+    let mut sticky = false;
+
+    while pos < bytes.len() {
+        let digit = match hex_digit(bytes[pos]) { Some(d) => d, _ => break };
+        if mantissa >> 60 == 0 {
+            mantissa = 16 * mantissa + digit as u64;
+        } else {
+            sticky |= digit != 0;
+            if exp2 < MAX_EXPONENT {
+                exp2 += 4;
+            }
+        }
+        pos += 1;
+        digits += 1;
+    }
+    if pos < bytes.len() && bytes[pos] == b'.' {
+        pos += 1;
+        while pos < bytes.len() {
+            let digit = match hex_digit(bytes[pos]) { Some(d) => d, _ => break };
+            if mantissa >> 60 == 0 {
+                mantissa = 16 * mantissa + digit as u64;
+                if exp2 > -MAX_EXPONENT {
+                    exp2 -= 4;
+                }
+            } else {
+                sticky |= digit != 0;
+            }
+            pos += 1;
+            digits += 1;
+        }
+    }
+    if digits == 0 {
+        return None;
+    }
+
+    let (exp, len) = exponent(&bytes[pos..], b'p');
+    pos += len;
+
+    if mantissa == 0 {
+        return Some((0, pos));
+    }
+    Some((binary(mantissa, clamp_exponent(exp2 + exp), sticky, fmt), pos))
+}
+
+fn special(bytes: &[u8], fmt: &Format) -> Option<(u64, usize)> {
+    if starts_with_ignore_case(bytes, b"infinity") {
+        Some((fmt.inf(), 8))
+    } else if starts_with_ignore_case(bytes, b"inf") {
+        Some((fmt.inf(), 3))
+    } else if starts_with_ignore_case(bytes, b"nan") {
+        Some((fmt.nan(), 3))
+    } else {
+        None
+    }
+}
+
+fn float(bytes: &[u8], fmt: &Format) -> Result<(u64, usize)> {
+    let mut sign = 0;
+    let mut pos = 0;
+    if bytes.len() > 0 && (bytes[0] == b'+' || bytes[0] == b'-') {
+        if bytes[0] == b'-' {
+            sign = fmt.sign;
+        }
+        pos = 1;
+    }
+    let bytes = &bytes[pos..];
+
+    if let Some((bits, len)) = special(bytes, fmt) {
+        return Ok((bits | sign, pos + len));
+    }
+    if bytes.len() > 2 && bytes[0] == b'0' && bytes[1] | 0x20 == b'x' {
+        if let Some((bits, len)) = hex(&bytes[2..], fmt) {
+            return Ok((bits | sign, pos + len + 2));
+        }
+    }
+    let (bits, len) = try!(decimal(bytes, fmt));
+    Ok((bits | sign, pos + len))
+}
+
+impl Parsable for f64 {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(Self, usize)> {
+        float(bytes, &F64).map(|(bits, len)| (f64::from_bits(bits), len))
+    }
+}
+
+impl Parsable for f32 {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(Self, usize)> {
+        float(bytes, &F32).map(|(bits, len)| (f32::from_bits(bits as u32), len))
+    }
+}
@@ -0,0 +1,804 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{mem};
+use base::{error};
+use base::error::{Errno};
+use cty::{c_int, c_char, rlimit64, AT_FDCWD};
+use cty::alias::{ProcessId, UserId, GroupId};
+use syscall::{
+    dup3, fcntl_setfd, fcntl_dupfd_cloexec, chdir, umask, setsid, setpgid, setresuid,
+    setresgid, prlimit, execveat,
+};
+use str_one::{CStr, NoNullStr};
+use str_two::{CString};
+use vec::{Vec};
+use alloc::{self, MemPool};
+use file::{File};
+use file::flags::{Mode, FILE_READ_WRITE, MODE_FILE};
+use pipe::{Pipe};
+use pipe::flags::{PIPE_CLOSE_ON_EXEC};
+use fd::{FdContainer};
+use io::{Read, BufWrite};
+use poll::{Epoll, EMPTY_EVENT, POLL_READ};
+use signal::{Sigset};
+use signal::signals::{self, Signal};
+use rt::{env};
+use wait::{
+    ChildStatus, wait_id, WAIT_EXITED, WAIT_DONT_BLOCK,
+};
+use res::{Resource};
+
+/// The exit code used by the child process if it cannot execute the program.
+const EXEC_FAILED: u8 = 127;
+
+/// The configuration of a standard stream of a child process.
+#[derive(Copy, Eq)]
+pub enum Stdio {
+    /// The stream is inherited from the parent process.
+    Inherit,
+
+    /// The stream is connected to `/dev/null`.
+    Null,
+
+    /// The stream is connected to a new pipe.
+    ///
+    /// = Remarks
+    ///
+    /// The other end of the pipe is stored in the `Child` object.
+    Piped,
+
+    /// The stream is connected to an existing file descriptor.
+    ///
+    /// [field, 1]
+    /// The file descriptor.
+    ///
+    /// = Remarks
+    ///
+    /// The file descriptor is duplicated in the child process and the parent retains
+    /// ownership of it. It must stay open until `spawn` returns.
+    Fd(c_int),
+}
+
+impl Stdio {
+    /// Creates a configuration that connects a stream to an existing file descriptor.
+    ///
+    /// [argument, fd]
+    /// The file descriptor, e.g., a `File` or a `Pipe`.
+    pub fn fd<F: FdContainer>(fd: &F) -> Stdio {
+        Stdio::Fd(fd.borrow())
+    }
+}
+
+/// A builder for spawning child processes.
+///
+/// = Remarks
+///
+/// The program path, the arguments, and the environment are copied into the `Command`.
+/// The program is looked up in `PATH` and all other allocations happen before the
+/// process is forked so that the child only performs system calls between `fork` and
+/// `exec`.
+///
+/// If an error occurs in the child process before the new program has been executed,
+/// the error is sent to the parent through a close-on-exec pipe and returned from
+/// `spawn`.
+///
+/// = Examples
+///
+/// ----
+/// let mut cmd = try!(Command::new("ls"));
+/// try!(cmd.arg("-l"));
+/// cmd.stdout(Stdio::Piped);
+/// let output = try!(try!(cmd.spawn()).wait_with_output());
+/// ----
+///
+/// = See also
+///
+/// * link:lrs::process::exec
+/// * link:lrs::process::fork
+pub struct Command<Heap = alloc::Heap>
+    where Heap: MemPool+OutOf,
+{
+    path: CString<Heap>,
+    args: Vec<CString<Heap>, Heap>,
+    env: Vec<CString<Heap>, Heap>,
+    env_clear: bool,
+    cwd: Option<CString<Heap>>,
+    user: Option<UserId>,
+    group: Option<GroupId>,
+    file_mask: Option<Mode>,
+    process_group: Option<ProcessId>,
+    new_session: bool,
+    limits: Vec<(Resource, u64, u64), Heap>,
+    reset_signal_mask: bool,
+    stdin: Stdio,
+    stdout: Stdio,
+    stderr: Stdio,
+}
+
+impl<H = alloc::Heap> Command<H>
+    where H: MemPool+OutOf,
+{
+    /// Creates a new command.
+    ///
+    /// [argument, path]
+    /// The program that will be executed.
+    ///
+    /// = Remarks
+    ///
+    /// The path is resolved like in `exec`. The path is also used as the first argument
+    /// of the program.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::process::exec
+    pub fn new<S: ?Sized>(path: &S) -> Result<Command<H>>
+        where S: TryAsRef<NoNullStr>,
+    {
+        let mut args = Vec::new();
+        try!(args.push(try!(path.try_to())));
+        Ok(Command {
+            path: try!(path.try_to()),
+            args: args,
+            env: Vec::new(),
+            env_clear: false,
+            cwd: None,
+            user: None,
+            group: None,
+            file_mask: None,
+            process_group: None,
+            new_session: false,
+            limits: Vec::new(),
+            reset_signal_mask: false,
+            stdin: Stdio::Inherit,
+            stdout: Stdio::Inherit,
+            stderr: Stdio::Inherit,
+        })
+    }
+
+    /// Appends an argument.
+    ///
+    /// [argument, arg]
+    /// The argument.
+    pub fn arg<S: ?Sized>(&mut self, arg: &S) -> Result<&mut Command<H>>
+        where S: TryAsRef<NoNullStr>,
+    {
+        try!(self.args.push(try!(arg.try_to())));
+        Ok(self)
+    }
+
+    /// Appends multiple arguments.
+    ///
+    /// [argument, args]
+    /// The arguments.
+    pub fn args<S>(&mut self, args: &[S]) -> Result<&mut Command<H>>
+        where S: TryAsRef<NoNullStr>,
+    {
+        try!(self.args.reserve(args.len()));
+        for arg in args {
+            try!(self.arg(arg));
+        }
+        Ok(self)
+    }
+
+    fn env_key_pos(&self, key: &[u8]) -> Option<usize> {
+        self.env.find(|e| env_key(e.as_ref()) == key)
+    }
+
+    /// Sets an environment variable.
+    ///
+    /// [argument, key]
+    /// The name of the variable.
+    ///
+    /// [argument, val]
+    /// The value of the variable.
+    ///
+    /// = Remarks
+    ///
+    /// Unless `env_clear` has been called, the child inherits all other variables of
+    /// this process.
+    pub fn env<K: ?Sized, V: ?Sized>(&mut self, key: &K,
+                                     val: &V) -> Result<&mut Command<H>>
+        where K: TryAsRef<NoNullStr>,
+              V: TryAsRef<NoNullStr>,
+    {
+        let key: &[u8] = try!(key.try_as_ref()).as_ref();
+        let val: &[u8] = try!(val.try_as_ref()).as_ref();
+        if key.find(|&b| b == b'=').is_some() {
+            return Err(error::InvalidArgument);
+        }
+        let mut var = try!(Vec::with_capacity(key.len() + val.len() + 2));
+        try!(var.push_all(key));
+        try!(var.push_all(b"="));
+        try!(var.push_all(val));
+        try!(var.push_all(b"\0"));
+        let var = unsafe { CString::from_bytes_unchecked(var) };
+        match self.env_key_pos(key) {
+            Some(pos) => self.env[pos] = var,
+            None => try!(self.env.push(var)),
+        }
+        Ok(self)
+    }
+
+    /// Removes an environment variable.
+    ///
+    /// [argument, key]
+    /// The name of the variable.
+    pub fn env_remove<K: ?Sized>(&mut self, key: &K) -> Result<&mut Command<H>>
+        where K: TryAsRef<NoNullStr>,
+    {
+        let var: CString<H> = try!(key.try_to());
+        match self.env_key_pos(var.as_ref()) {
+            Some(pos) => self.env[pos] = var,
+            None => try!(self.env.push(var)),
+        }
+        Ok(self)
+    }
+
+    /// Clears the environment of the child.
+    ///
+    /// = Remarks
+    ///
+    /// Only variables set via `env` after this call will be passed to the child.
+    pub fn env_clear(&mut self) -> &mut Command<H> {
+        self.env.truncate(0);
+        self.env_clear = true;
+        self
+    }
+
+    /// Sets the working directory of the child.
+    ///
+    /// [argument, dir]
+    /// The new working directory.
+    ///
+    /// = Remarks
+    ///
+    /// If `dir` is relative, it is interpreted relative to the working directory of this
+    /// process. The program path is resolved after the working directory has been
+    /// changed.
+    pub fn current_dir<S: ?Sized>(&mut self, dir: &S) -> Result<&mut Command<H>>
+        where S: TryAsRef<NoNullStr>,
+    {
+        self.cwd = Some(try!(dir.try_to()));
+        Ok(self)
+    }
+
+    /// Sets the user id of the child.
+    ///
+    /// [argument, id]
+    /// The real, effective, and saved user id of the child.
+    ///
+    /// = See also
+    ///
+    /// * link:man:setresuid(2)
+    pub fn user(&mut self, id: UserId) -> &mut Command<H> {
+        self.user = Some(id);
+        self
+    }
+
+    /// Sets the group id of the child.
+    ///
+    /// [argument, id]
+    /// The real, effective, and saved group id of the child.
+    ///
+    /// = Remarks
+    ///
+    /// The group id is changed before the user id. The supplementary groups are not
+    /// modified.
+    ///
+    /// = See also
+    ///
+    /// * link:man:setresgid(2)
+    pub fn group(&mut self, id: GroupId) -> &mut Command<H> {
+        self.group = Some(id);
+        self
+    }
+
+    /// Sets the file mode creation mask of the child.
+    ///
+    /// [argument, mode]
+    /// The mask.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::process::set_file_mask
+    pub fn file_mask(&mut self, mode: Mode) -> &mut Command<H> {
+        self.file_mask = Some(mode);
+        self
+    }
+
+    /// Moves the child into a process group.
+    ///
+    /// [argument, group]
+    /// The process group or `0` to create a new process group whose id is the process
+    /// id of the child.
+    ///
+    /// = See also
+    ///
+    /// * link:man:setpgid(2)
+    pub fn process_group(&mut self, group: ProcessId) -> &mut Command<H> {
+        self.process_group = Some(group);
+        self
+    }
+
+    /// Sets whether the child creates a new session.
+    ///
+    /// [argument, val]
+    /// Whether the child creates a new session.
+    ///
+    /// = Remarks
+    ///
+    /// If this is set, `process_group` is ignored.
+    ///
+    /// = See also
+    ///
+    /// * link:man:setsid(2)
+    pub fn new_session(&mut self, val: bool) -> &mut Command<H> {
+        self.new_session = val;
+        self
+    }
+
+    /// Sets a resource limit of the child.
+    ///
+    /// [argument, resource]
+    /// The resource whose limit to set.
+    ///
+    /// [argument, soft]
+    /// The new soft limit.
+    ///
+    /// [argument, hard]
+    /// The new hard limit.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::process::set_resource_limit
+    pub fn resource_limit(&mut self, resource: Resource, soft: u64,
+                          hard: u64) -> Result<&mut Command<H>> {
+        match self.limits.find(|l| l.0 == resource) {
+            Some(pos) => self.limits[pos] = (resource, soft, hard),
+            None => try!(self.limits.push((resource, soft, hard))),
+        }
+        Ok(self)
+    }
+
+    /// Sets whether the signal mask of the child is cleared.
+    ///
+    /// [argument, val]
+    /// Whether all signals are unblocked in the child.
+    ///
+    /// = Remarks
+    ///
+    /// By default, the child inherits the signal mask of the calling thread.
+    pub fn reset_signal_mask(&mut self, val: bool) -> &mut Command<H> {
+        self.reset_signal_mask = val;
+        self
+    }
+
+    /// Configures the standard input of the child.
+    ///
+    /// [argument, cfg]
+    /// The configuration.
+    pub fn stdin(&mut self, cfg: Stdio) -> &mut Command<H> {
+        self.stdin = cfg;
+        self
+    }
+
+    /// Configures the standard output of the child.
+    ///
+    /// [argument, cfg]
+    /// The configuration.
+    pub fn stdout(&mut self, cfg: Stdio) -> &mut Command<H> {
+        self.stdout = cfg;
+        self
+    }
+
+    /// Configures the standard error of the child.
+    ///
+    /// [argument, cfg]
+    /// The configuration.
+    pub fn stderr(&mut self, cfg: Stdio) -> &mut Command<H> {
+        self.stderr = cfg;
+        self
+    }
+
+    /// Searches `PATH` for the program like `exec` does.
+    ///
+    /// Returns `None` if the path is used as is, i.e., if it contains a `/`.
+    fn program(&self) -> Result<Option<CString<H>>> {
+        let file: &[u8] = self.path.as_ref();
+        if file.len() == 0 {
+            return Err(error::InvalidArgument);
+        }
+        if file.find(|&b| b == b'/').is_some() {
+            return Ok(None);
+        }
+        let mut buf = try!(CString::with_pool(H::out_of(())));
+        for path in try!(::env::path()) {
+            try!(buf.set_path(path));
+            try!(buf.push_file(&*self.path));
+            let exists = {
+                let cstr: &CStr = try!(buf.try_as_ref());
+                ::file::exists(cstr) == Ok(true)
+            };
+            if exists {
+                return Ok(Some(buf));
+            }
+        }
+        Err(error::DoesNotExist)
+    }
+
+    fn argv(&self) -> Result<Vec<*const c_char, H>> {
+        let mut argv = try!(Vec::with_capacity(self.args.len() + 1));
+        for arg in self.args.iter() {
+            try!(argv.push(arg.as_ptr()));
+        }
+        try!(argv.push(0 as *const c_char));
+        Ok(argv)
+    }
+
+    fn envp(&self) -> Result<Vec<*const c_char, H>> {
+        let mut envp = Vec::new();
+        if !self.env_clear {
+            for var in env() {
+                if self.env_key_pos(env_key(var.as_ref())).is_none() {
+                    try!(envp.push(var.as_ptr()));
+                }
+            }
+        }
+        for var in self.env.iter() {
+            let bytes: &[u8] = var.as_ref();
+            if bytes.find(|&b| b == b'=').is_some() {
+                try!(envp.push(var.as_ptr()));
+            }
+        }
+        try!(envp.push(0 as *const c_char));
+        Ok(envp)
+    }
+
+    /// Spawns the child process.
+    ///
+    /// [return_value]
+    /// Returns a handle to the child.
+    ///
+    /// = Remarks
+    ///
+    /// This function returns after the child has successfully executed the program or
+    /// after the child has failed to do so. In the latter case, the child has already
+    /// been reaped and the error from the child is returned.
+    pub fn spawn(&self) -> Result<Child> {
+        let program = try!(self.program());
+        let argv = try!(self.argv());
+        let envp = try!(self.envp());
+
+        let (stdin_parent, stdin_child) = try!(stdio_fds(self.stdin, true));
+        let (stdout_parent, stdout_child) = try!(stdio_fds(self.stdout, false));
+        let (stderr_parent, stderr_child) = try!(stdio_fds(self.stderr, false));
+
+        let (err_write, mut err_read) = try!(Pipe::new(PIPE_CLOSE_ON_EXEC));
+
+        let id = match try!(::clone::fork_continue()) {
+            Some(id) => id,
+            None => {
+                let fds = [stdin_child.fd(), stdout_child.fd(), stderr_child.fd()];
+                let path = match program {
+                    Some(ref p) => &**p,
+                    None => &*self.path,
+                };
+                let e = match self.exec_child(path, fds, &argv, &envp) {
+                    Err(e) => e,
+                    _ => error::NoError,
+                };
+                let _ = err_write.write(unsafe { mem::as_data(&e.0).as_bytes() });
+                ::exit(EXEC_FAILED);
+            },
+        };
+
+        drop(err_write);
+        drop(stdin_child);
+        drop(stdout_child);
+        drop(stderr_child);
+
+        let mut errno: c_int = 0;
+        match err_read.read_all(mem::as_mut_data(&mut errno)) {
+            Ok(0) => { },
+            Ok(_) => {
+                let _ = wait_id(id, WAIT_EXITED);
+                return Err(Errno(errno));
+            },
+            Err(e) => {
+                let _ = wait_id(id, WAIT_EXITED);
+                return Err(e);
+            },
+        }
+
+        Ok(Child {
+            id: id,
+            status: None,
+            stdin: stdin_parent,
+            stdout: stdout_parent,
+            stderr: stderr_parent,
+        })
+    }
+
+    /// Spawns the child process and collects its output.
+    ///
+    /// [return_value]
+    /// Returns the exit status and the output of the child.
+    ///
+    /// = Remarks
+    ///
+    /// Standard output and standard error are always captured. Standard input is
+    /// connected to `/dev/null` unless it has been configured explicitly.
+    pub fn output(&mut self) -> Result<Output<H>> {
+        let (stdin, stdout, stderr) = (self.stdin, self.stdout, self.stderr);
+        if stdin == Stdio::Inherit {
+            self.stdin = Stdio::Null;
+        }
+        self.stdout = Stdio::Piped;
+        self.stderr = Stdio::Piped;
+        let child = self.spawn();
+        self.stdin = stdin;
+        self.stdout = stdout;
+        self.stderr = stderr;
+        try!(child).wait_with_output()
+    }
+
+    fn exec_child(&self, path: &CStr, mut fds: [Option<c_int>; 3], argv: &[*const c_char],
+                  envp: &[*const c_char]) -> Result {
+        if self.reset_signal_mask {
+            try!(::signal::set_blocked_signals(Sigset::new()));
+        }
+        if self.new_session {
+            try!(rv!(setsid(), -> ProcessId));
+        } else if let Some(group) = self.process_group {
+            try!(rv!(setpgid(0, group)));
+        }
+        // Move sources that are themselves standard streams out of the way so that
+        // they are not overwritten before they are duplicated.
+        for i in 0..fds.len() {
+            if let Some(fd) = fds[i] {
+                if 0 <= fd && fd < fds.len() as c_int && fd != i as c_int {
+                    fds[i] = Some(try!(rv!(fcntl_dupfd_cloexec(fd, fds.len() as c_int),
+                                           -> c_int)));
+                }
+            }
+        }
+        for i in 0..fds.len() {
+            if let Some(fd) = fds[i] {
+                try!(redirect(fd, i as c_int));
+            }
+        }
+        for &(res, soft, hard) in self.limits.iter() {
+            let limit = rlimit64 { rlim_cur: soft, rlim_max: hard };
+            try!(rv!(prlimit(0, res.0, Some(&limit), None)));
+        }
+        if let Some(mode) = self.file_mask {
+            umask(mode.0);
+        }
+        if let Some(ref cwd) = self.cwd {
+            try!(rv!(chdir(cwd)));
+        }
+        if let Some(group) = self.group {
+            try!(rv!(setresgid(group, group, group)));
+        }
+        if let Some(user) = self.user {
+            try!(rv!(setresuid(user, user, user)));
+        }
+        // Relative paths from `PATH` are interpreted relative to the working directory
+        // of the child.
+        rv!(execveat(AT_FDCWD, path, argv.as_ptr(), envp.as_ptr(), 0))
+    }
+}
+
+/// Returns the part of an environment variable before the first `=`.
+fn env_key(var: &[u8]) -> &[u8] {
+    match var.find(|&b| b == b'=') {
+        Some(pos) => &var[..pos],
+        None => var,
+    }
+}
+
+/// The file descriptor that replaces a standard stream in the child.
+enum ChildFd {
+    None,
+    Borrowed(c_int),
+    File(File),
+    Pipe(Pipe),
+}
+
+impl ChildFd {
+    fn fd(&self) -> Option<c_int> {
+        match *self {
+            ChildFd::None => None,
+            ChildFd::Borrowed(fd) => Some(fd),
+            ChildFd::File(ref f) => Some(f.borrow()),
+            ChildFd::Pipe(ref p) => Some(p.borrow()),
+        }
+    }
+}
+
+fn stdio_fds(cfg: Stdio, input: bool) -> Result<(Option<Pipe>, ChildFd)> {
+    match cfg {
+        Stdio::Inherit => Ok((None, ChildFd::None)),
+        Stdio::Fd(fd) => Ok((None, ChildFd::Borrowed(fd))),
+        Stdio::Null => {
+            let file = try!(File::open("/dev/null\0", FILE_READ_WRITE, MODE_FILE));
+            Ok((None, ChildFd::File(file)))
+        },
+        Stdio::Piped => {
+            let (write, read) = try!(Pipe::new(PIPE_CLOSE_ON_EXEC));
+            if input {
+                Ok((Some(write), ChildFd::Pipe(read)))
+            } else {
+                Ok((Some(read), ChildFd::Pipe(write)))
+            }
+        },
+    }
+}
+
+/// Makes `dst` a copy of `src` that is inherited by the executed program.
+fn redirect(src: c_int, dst: c_int) -> Result {
+    if src == dst {
+        rv!(fcntl_setfd(dst, 0))
+    } else {
+        try!(rv!(dup3(src, dst, 0), -> c_int));
+        rv!(fcntl_setfd(dst, 0))
+    }
+}
+
+/// A handle to a child process.
+pub struct Child {
+    id: ProcessId,
+    status: Option<ChildStatus>,
+
+    /// The write end of the pipe connected to the standard input of the child.
+    ///
+    /// = Remarks
+    ///
+    /// This is only set if standard input was configured with `Stdio::Piped`.
+    pub stdin: Option<Pipe>,
+
+    /// The read end of the pipe connected to the standard output of the child.
+    ///
+    /// = Remarks
+    ///
+    /// This is only set if standard output was configured with `Stdio::Piped`.
+    pub stdout: Option<Pipe>,
+
+    /// The read end of the pipe connected to the standard error of the child.
+    ///
+    /// = Remarks
+    ///
+    /// This is only set if standard error was configured with `Stdio::Piped`.
+    pub stderr: Option<Pipe>,
+}
+
+impl Child {
+    /// Returns the process id of the child.
+    pub fn id(&self) -> ProcessId {
+        self.id
+    }
+
+    /// Sends a signal to the child.
+    ///
+    /// [argument, sig]
+    /// The signal to send.
+    ///
+    /// = Remarks
+    ///
+    /// If the child has already been reaped, an error is returned.
+    pub fn signal(&self, sig: Signal) -> Result {
+        if self.status.is_some() {
+            return Err(error::InvalidArgument);
+        }
+        ::signal::send(self.id, sig)
+    }
+
+    /// Kills the child with `SIGKILL`.
+    pub fn kill(&self) -> Result {
+        self.signal(signals::Kill)
+    }
+
+    /// Waits for the child to exit.
+    ///
+    /// [return_value]
+    /// Returns the exit status of the child.
+    ///
+    /// = Remarks
+    ///
+    /// The standard input pipe of the child, if any, is closed before waiting so that
+    /// the child does not wait for input forever.
+    ///
+    /// Once the child has been reaped, this function returns the same status again.
+    pub fn wait(&mut self) -> Result<ChildStatus> {
+        drop(self.stdin.take());
+        if let Some(status) = self.status {
+            return Ok(status);
+        }
+        let status = try!(wait_id(self.id, WAIT_EXITED));
+        self.status = Some(status);
+        Ok(status)
+    }
+
+    /// Checks whether the child has exited without blocking.
+    ///
+    /// [return_value]
+    /// Returns the exit status of the child or `None` if the child is still running.
+    pub fn try_wait(&mut self) -> Result<Option<ChildStatus>> {
+        if let Some(status) = self.status {
+            return Ok(Some(status));
+        }
+        match try!(wait_id(self.id, WAIT_EXITED | WAIT_DONT_BLOCK)) {
+            ChildStatus::None => Ok(None),
+            status => {
+                self.status = Some(status);
+                Ok(Some(status))
+            },
+        }
+    }
+
+    /// Reads the output of the child until end-of-file and waits for the child to exit.
+    ///
+    /// [return_value]
+    /// Returns the exit status and the captured output of the child.
+    ///
+    /// = Remarks
+    ///
+    /// Only streams that were configured with `Stdio::Piped` are captured. The other
+    /// buffers are empty.
+    pub fn wait_with_output<H = alloc::Heap>(mut self) -> Result<Output<H>>
+        where H: MemPool+OutOf,
+    {
+        const READ_STEP_SIZE: usize = 4096;
+
+        drop(self.stdin.take());
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+
+        match (self.stdout.take(), self.stderr.take()) {
+            (Some(mut out), None) => { try!(stdout.read_to_eof(&mut out)); },
+            (None, Some(mut err)) => { try!(stderr.read_to_eof(&mut err)); },
+            (Some(mut out), Some(mut err)) => {
+                let epoll = try!(Epoll::new());
+                try!(epoll.add(&out, POLL_READ));
+                try!(epoll.add(&err, POLL_READ));
+                let mut open = 2;
+                let mut events = [EMPTY_EVENT; 2];
+                while open > 0 {
+                    for event in try!(epoll.wait(&mut events)) {
+                        let (pipe, buf) = if event.fd() == out.borrow() {
+                            (&mut out, &mut stdout)
+                        } else {
+                            (&mut err, &mut stderr)
+                        };
+                        if try!(buf.read(&mut *pipe, READ_STEP_SIZE)) == 0 {
+                            try!(epoll.remove(&*pipe));
+                            open -= 1;
+                        }
+                    }
+                }
+            },
+            (None, None) => { },
+        }
+
+        let status = try!(self.wait());
+        Ok(Output { status: status, stdout: stdout, stderr: stderr })
+    }
+}
+
+/// The exit status and output of a finished child process.
+pub struct Output<Heap = alloc::Heap>
+    where Heap: MemPool,
+{
+    /// The exit status of the child.
+    pub status: ChildStatus,
+
+    /// The captured standard output of the child.
+    pub stdout: Vec<u8, Heap>,
+
+    /// The captured standard error of the child.
+    pub stderr: Vec<u8, Heap>,
+}
@@ -57,23 +57,61 @@ fn rmo_cstr<'a, S>(s: &'a S,
 /// * {cptrptr}
 pub fn exec<P>(path: P, argv: &[*const c_char]) -> Result
     where P: for<'a> ToRmo<Pool<'a>, CStr, CString<Pool<'a>>>,
+{
+    exec_inner(path, argv, raw_env())
+}
+
+/// Executes a program with a custom environment in place of the current program.
+///
+/// [argument, path]
+/// The path of the file that is going to be executed.
+///
+/// [argument, argv]
+/// The arguments that will be passed to the new program in form of `argv` and `argc`.
+///
+/// [argument, envp]
+/// The environment that will be passed to the new program.
+///
+/// [return_value]
+/// On success, this function does not return.
+///
+/// = Remarks
+///
+/// The last element of `envp` must be a null pointer. The other elements must be
+/// pointers to null-terminated strings of the form `KEY=VALUE`.
+///
+/// The path is resolved as described in `exec`. The `PATH` environment variable of this
+/// process is used for this, not the one in `envp`.
+///
+/// = See also
+///
+/// * link:lrs::process::exec
+pub fn exec_with_env<P>(path: P, argv: &[*const c_char],
+                        envp: &[*const c_char]) -> Result
+    where P: for<'a> ToRmo<Pool<'a>, CStr, CString<Pool<'a>>>,
+{
+    exec_inner(path, argv, envp.as_ptr())
+}
+
+fn exec_inner<P>(path: P, argv: &[*const c_char], envp: *const *const c_char) -> Result
+    where P: for<'a> ToRmo<Pool<'a>, CStr, CString<Pool<'a>>>,
 {
     let mut buf: [d8; PATH_MAX] = unsafe { mem::uninit() };
     let file = try!(rmo_cstr(&path, &mut buf));
     if file.len() == 0 {
         return Err(error::InvalidArgument);
     } else if file[0] == b'/' {
-        return rv!(execveat(-1, &file, argv.as_ptr(), raw_env(), 0));
+        return rv!(execveat(-1, &file, argv.as_ptr(), envp, 0));
     } else if file.len() > 1 && file[0] == b'.' {
         if file[1] == b'/' || (file.len() > 2 && file[1] == b'.' && file[2] == b'/') {
-            return rv!(execveat(AT_FDCWD, &file, argv.as_ptr(), raw_env(), 0));
+            return rv!(execveat(AT_FDCWD, &file, argv.as_ptr(), envp, 0));
         }
     }
 
     // Try first without allocating
 
     let mut abs_buf: [d8; PATH_MAX] = unsafe { mem::uninit() };
-    match exec_rel(&file, OncePool::new(&mut abs_buf), argv) {
+    match exec_rel(&file, OncePool::new(&mut abs_buf), argv, envp) {
         Err(error::NoMemory) => { },
         x => return x,
     }
@@ -81,10 +119,11 @@ pub fn exec<P>(path: P, argv: &[*const c_char]) -> Result
     // NoMemory can come from our stuff or execve but we can't distinguish at this point.
     // Let's just try again with dynamic allocations.
 
-    exec_rel(&file, FbHeap::out_of(()), argv)
+    exec_rel(&file, FbHeap::out_of(()), argv, envp)
 }
 
-fn exec_rel<H>(rel: &CStr, pool: H, args: &[*const c_char]) -> Result
+fn exec_rel<H>(rel: &CStr, pool: H, args: &[*const c_char],
+               envp: *const *const c_char) -> Result
     where H: MemPool,
 {
     let mut buf = try!(CString::with_pool(pool));
@@ -95,7 +134,7 @@ fn exec_rel<H>(rel: &CStr, pool: H, args: &[*const c_char]) -> Result
         if file::exists(cstr) == Ok(true) {
             // Paths in PATH don't have to start with a /. We pass AT_FDCWD so that such
             // paths are interpreted relative to the cwd.
-            return rv!(execveat(AT_FDCWD, cstr, args.as_ptr(), raw_env(), 0));
+            return rv!(execveat(AT_FDCWD, cstr, args.as_ptr(), envp, 0));
         }
     }
     Err(error::DoesNotExist)
@@ -16,11 +16,17 @@ use alloc::{self, MemPool};
 struct Inner<T: ?Sized, H = alloc::Heap>
     where H: MemPool,
 {
+    /// The number of `Arc`s.
     count: Atomic<usize>,
+    /// The number of `Weak`s plus one if `count` is not zero. This is temporarily set
+    /// to `LOCKED` while checking whether an `Arc` is the only reference.
+    weak: Atomic<usize>,
     pool: H,
     val: T,
 }
 
+const LOCKED: usize = !0;
+
 /// A buffer used when creating a new `Arc`.
 pub struct ArcBuf<T, Heap = alloc::Heap>
     where Heap: MemPool,
@@ -114,25 +120,123 @@ impl<T: ?Sized, H = alloc::Heap> Arc<T, H>
             let data_ptr = try!(alloc::alloc::<Inner<T, H>, _>(&mut pool));
             ptr::write(&mut (*data_ptr).pool, pool);
             (*data_ptr).count.store(1);
+            (*data_ptr).weak.store(1);
             Ok(ArcBuf { data: NoAliasMemPtr::new(data_ptr) })
         }
     }
 
+    /// Returns whether this is the only reference to the data, including `Weak`
+    /// references.
+    fn is_unique(&self) -> bool {
+        // Lock the weak count so that no `Weak` can be created from another `Arc` while
+        // we look at the strong count.
+        if self.data.weak.compare_exchange(1, LOCKED) != 1 {
+            return false;
+        }
+        let unique = self.data.count.load() == 1;
+        self.data.weak.store(1);
+        unique
+    }
+
     /// Returns a mutable reference to the contained data if this is the only reference.
+    ///
+    /// = Remarks
+    ///
+    /// This also fails if there are `Weak` references to the data.
     pub fn as_mut(&mut self) -> Option<&mut T> {
         unsafe {
-            match self.data.count.load() {
-                1 => Some(&mut (*self.data.get()).val),
+            match self.is_unique() {
+                true => Some(&mut (*self.data.get()).val),
                 _ => None,
             }
         }
     }
 
+    /// Returns a mutable reference to the contained data, cloning it if necessary.
+    ///
+    /// = Remarks
+    ///
+    /// If this is not the only reference to the data, the data is cloned into a new
+    /// allocation and `self` is replaced by an `Arc` pointing to the clone. Other
+    /// references are not affected.
+    ///
+    /// Like cloning a `Box` or a `Vec`, the new allocation is made from a pool created
+    /// with `H::out_of(())`, not from the pool that owns the current allocation.
+    pub fn make_mut(&mut self) -> Result<&mut T>
+        where T: TryTo + Sized,
+              H: OutOf,
+    {
+        if !self.is_unique() {
+            let buf = try!(Arc::new());
+            *self = buf.set(try!(self.deref().try_to()));
+        }
+        unsafe { Ok(&mut (*self.data.get()).val) }
+    }
+
+    /// Returns the contained data if this is the only `Arc` pointing to it.
+    ///
+    /// [return_value]
+    /// Returns the data or `self` if there are other `Arc`s pointing to the data.
+    ///
+    /// = Remarks
+    ///
+    /// `Weak` references to the data cannot be upgraded after this function succeeds.
+    pub fn try_unwrap(self) -> Result<T, Arc<T, H>>
+        where T: Sized,
+    {
+        if self.data.count.compare_exchange(1, 0) != 1 {
+            return Err(self);
+        }
+        unsafe {
+            let data = self.data;
+            intrinsics::forget(self);
+            let val = ptr::read(&data.val);
+            release_weak(data);
+            Ok(val)
+        }
+    }
+
+    /// Returns whether two `Arc`s point to the same data.
+    ///
+    /// [argument, other]
+    /// The other `Arc`.
+    pub fn ptr_eq(&self, other: &Arc<T, H>) -> bool {
+        self.data.get() == other.data.get()
+    }
+
     /// Adds a new reference, returning an `Arc` that points to the same data.
     pub fn add_ref(&self) -> Arc<T, H> {
         self.data.count.add(1);
         Arc { data: self.data }
     }
+
+    /// Creates a new weak reference to the data.
+    ///
+    /// = Remarks
+    ///
+    /// Weak references do not keep the data alive but they keep the memory in which
+    /// the data is stored alive.
+    pub fn downgrade(&self) -> Weak<T, H> {
+        loop {
+            let weak = self.data.weak.load();
+            if weak != LOCKED && self.data.weak.compare_exchange(weak, weak + 1) == weak {
+                return Weak { data: self.data };
+            }
+        }
+    }
+
+    /// Returns the number of `Arc`s that point to the data.
+    pub fn strong_count(&self) -> usize {
+        self.data.count.load()
+    }
+
+    /// Returns the number of `Weak`s that point to the data.
+    pub fn weak_count(&self) -> usize {
+        match self.data.weak.load() {
+            LOCKED => 0,
+            n => n - 1,
+        }
+    }
 }
 
 impl<T: ?Sized, U: ?Sized, H> CoerceUnsized<Arc<U, H>> for Arc<T, H>
@@ -179,13 +283,20 @@ impl<T: ?Sized, H> Drop for Arc<T, H>
                 if mem::needs_drop::<T>() {
                     ptr::drop(&mut (*self.data.get()).val);
                 }
-                let mut pool = ptr::read(&self.data.pool);
-                alloc::free(&mut pool, self.data.get());
+                release_weak(self.data);
             }
         }
     }
 }
 
+/// Drops one weak reference and frees the memory if it was the last one.
+unsafe fn release_weak<T: ?Sized, H: MemPool>(data: NoAliasObjPtr<Inner<T, H>>) {
+    if data.weak.sub(1) == 1 {
+        let mut pool = ptr::read(&data.pool);
+        alloc::free(&mut pool, data.get());
+    }
+}
+
 impl<T: ?Sized, H> Deref for Arc<T, H>
     where H: MemPool,
           T: Leak,
@@ -223,3 +334,108 @@ impl<T: ?Sized, H> Debug for Arc<T, H>
         write!(w, "Arc {{ data: {:?} }}", self.deref())
     }
 }
+
+/// A weak reference to the data of an `Arc`.
+///
+/// = Remarks
+///
+/// A weak reference does not keep the data alive. It can be upgraded to an `Arc` as
+/// long as at least one `Arc` points to the data. The memory in which the data is
+/// stored is returned to the pool once the last `Arc` and the last `Weak` have been
+/// dropped.
+///
+/// = See also
+///
+/// * link:lrs::rc::Arc::downgrade
+pub struct Weak<T: ?Sized, Heap = alloc::Heap>
+    where Heap: MemPool,
+          T: Leak,
+{
+    data: NoAliasObjPtr<Inner<T, Heap>>,
+}
+
+impl<T: ?Sized, H> Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    /// Tries to create a new `Arc` that points to the data.
+    ///
+    /// [return_value]
+    /// Returns the new `Arc` or `None` if the data has already been dropped.
+    pub fn upgrade(&self) -> Option<Arc<T, H>> {
+        loop {
+            let count = self.data.count.load();
+            if count == 0 {
+                return None;
+            }
+            if self.data.count.compare_exchange(count, count + 1) == count {
+                return Some(Arc { data: self.data });
+            }
+        }
+    }
+
+    /// Returns the number of `Arc`s that point to the data.
+    pub fn strong_count(&self) -> usize {
+        self.data.count.load()
+    }
+
+    /// Returns the number of `Weak`s that point to the data.
+    ///
+    /// = Remarks
+    ///
+    /// The value can be inaccurate if other threads modify the reference counts
+    /// concurrently.
+    pub fn weak_count(&self) -> usize {
+        let weak = self.data.weak.load();
+        match self.data.count.load() {
+            0 => weak,
+            _ => weak - 1,
+        }
+    }
+}
+
+impl<T: ?Sized, U: ?Sized, H> CoerceUnsized<Weak<U, H>> for Weak<T, H>
+    where T: Unsize<U> + Leak,
+          U: Leak,
+          H: MemPool,
+{}
+
+unsafe impl<T: ?Sized, H> Send for Weak<T, H> where T: Sync+Send+Leak, H:Send+MemPool { }
+unsafe impl<T: ?Sized, H> Sync for Weak<T, H> where T: Sync+Send+Leak, H: MemPool { }
+
+impl<T: ?Sized, H> Drop for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn drop(&mut self) {
+        unsafe { release_weak(self.data); }
+    }
+}
+
+impl<T: ?Sized, H> To for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn to(&self) -> Weak<T, H> {
+        self.data.weak.add(1);
+        Weak { data: self.data }
+    }
+}
+
+impl<T: ?Sized, H> TryTo for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn try_to(&self) -> Result<Weak<T, H>> {
+        Ok(self.to())
+    }
+}
+
+impl<T: ?Sized, H> Debug for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn fmt<W: Write+?Sized>(&self, w: &mut W) -> Result {
+        w.write_all(b"(Weak)").ignore_ok()
+    }
+}
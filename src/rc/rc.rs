@@ -16,7 +16,10 @@ use alloc::{self, MemPool};
 struct Inner<T: ?Sized, H = alloc::ThreadHeap>
     where H: MemPool,
 {
+    /// The number of `Rc`s.
     count: Cell<usize>,
+    /// The number of `Weak`s plus one if `count` is not zero.
+    weak: Cell<usize>,
     pool: H,
     val: T,
 }
@@ -114,15 +117,20 @@ impl<T: ?Sized, H = alloc::ThreadHeap> Rc<T, H>
             let data_ptr = try!(alloc::alloc::<Inner<T, H>, _>(&mut pool));
             ptr::write(&mut (*data_ptr).pool, pool);
             (*data_ptr).count.set(1);
+            (*data_ptr).weak.set(1);
             Ok(RcBuf { data: NoAliasMemPtr::new(data_ptr) })
         }
     }
 
     /// Returns a mutable reference to the contained data if this is the only reference.
+    ///
+    /// = Remarks
+    ///
+    /// This also fails if there are `Weak` references to the data.
     pub fn as_mut(&mut self) -> Option<&mut T> {
         unsafe {
-            match self.data.count.get() {
-                1 => Some(&mut (*self.data.get()).val),
+            match (self.data.count.get(), self.data.weak.get()) {
+                (1, 1) => Some(&mut (*self.data.get()).val),
                 _ => None,
             }
         }
@@ -133,6 +141,27 @@ impl<T: ?Sized, H = alloc::ThreadHeap> Rc<T, H>
         self.data.count.set(self.data.count.get() + 1);
         Rc { data: self.data }
     }
+
+    /// Creates a new weak reference to the data.
+    ///
+    /// = Remarks
+    ///
+    /// Weak references do not keep the data alive but they keep the memory in which
+    /// the data is stored alive.
+    pub fn downgrade(&self) -> Weak<T, H> {
+        self.data.weak.set(self.data.weak.get() + 1);
+        Weak { data: self.data }
+    }
+
+    /// Returns the number of `Rc`s that point to the data.
+    pub fn strong_count(&self) -> usize {
+        self.data.count.get()
+    }
+
+    /// Returns the number of `Weak`s that point to the data.
+    pub fn weak_count(&self) -> usize {
+        self.data.weak.get() - 1
+    }
 }
 
 impl<T: ?Sized, U: ?Sized, H> CoerceUnsized<Rc<U, H>> for Rc<T, H>
@@ -180,13 +209,22 @@ impl<T: ?Sized, H> Drop for Rc<T, H>
                 if mem::needs_drop::<T>() {
                     ptr::drop(&mut (*self.data.get()).val);
                 }
-                let mut pool = ptr::read(&self.data.pool);
-                alloc::free(&mut pool, self.data.get());
+                release_weak(self.data);
             }
         }
     }
 }
 
+/// Drops one weak reference and frees the memory if it was the last one.
+unsafe fn release_weak<T: ?Sized, H: MemPool>(data: NoAliasObjPtr<Inner<T, H>>) {
+    let weak = data.weak.get();
+    data.weak.set(weak - 1);
+    if weak == 1 {
+        let mut pool = ptr::read(&data.pool);
+        alloc::free(&mut pool, data.get());
+    }
+}
+
 impl<T: ?Sized, H> Deref for Rc<T, H>
     where H: MemPool,
           T: Leak,
@@ -224,3 +262,99 @@ impl<T: ?Sized, H> Debug for Rc<T, H>
         write!(w, "Rc {{ data: {:?} }}", self.deref())
     }
 }
+
+/// A weak reference to the data of an `Rc`.
+///
+/// = Remarks
+///
+/// A weak reference does not keep the data alive. It can be upgraded to an `Rc` as
+/// long as at least one `Rc` points to the data. The memory in which the data is
+/// stored is returned to the pool once the last `Rc` and the last `Weak` have been
+/// dropped.
+///
+/// = See also
+///
+/// * link:lrs::rc::Rc::downgrade
+pub struct Weak<T: ?Sized, Heap = alloc::ThreadHeap>
+    where Heap: MemPool,
+          T: Leak,
+{
+    data: NoAliasObjPtr<Inner<T, Heap>>,
+}
+
+impl<T: ?Sized, H> Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    /// Tries to create a new `Rc` that points to the data.
+    ///
+    /// [return_value]
+    /// Returns the new `Rc` or `None` if the data has already been dropped.
+    pub fn upgrade(&self) -> Option<Rc<T, H>> {
+        match self.data.count.get() {
+            0 => None,
+            n => {
+                self.data.count.set(n + 1);
+                Some(Rc { data: self.data })
+            },
+        }
+    }
+
+    /// Returns the number of `Rc`s that point to the data.
+    pub fn strong_count(&self) -> usize {
+        self.data.count.get()
+    }
+
+    /// Returns the number of `Weak`s that point to the data.
+    pub fn weak_count(&self) -> usize {
+        match self.data.count.get() {
+            0 => self.data.weak.get(),
+            _ => self.data.weak.get() - 1,
+        }
+    }
+}
+
+impl<T: ?Sized, U: ?Sized, H> CoerceUnsized<Weak<U, H>> for Weak<T, H>
+    where T: Unsize<U> + Leak,
+          U: Leak,
+          H: MemPool,
+{}
+
+impl<T: ?Sized, H> !Send for Weak<T, H> { }
+
+impl<T: ?Sized, H> Drop for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn drop(&mut self) {
+        unsafe { release_weak(self.data); }
+    }
+}
+
+impl<T: ?Sized, H> From for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn from(t: &Weak<T, H>) -> Self {
+        t.data.weak.set(t.data.weak.get() + 1);
+        Weak { data: t.data }
+    }
+}
+
+impl<T: ?Sized, H> TryFrom for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn try_from(t: &Weak<T, H>) -> Result<Self> {
+        Ok(Weak::from(t))
+    }
+}
+
+impl<T: ?Sized, H> Debug for Weak<T, H>
+    where H: MemPool,
+          T: Leak,
+{
+    fn fmt<W: Write+?Sized>(&self, w: &mut W) -> Result {
+        w.write_all(b"(Weak)").ignore_ok()
+    }
+}
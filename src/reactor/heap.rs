@@ -0,0 +1,93 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A binary min-heap of timer expirations.
+
+use base::prelude::*;
+use core::{mem};
+use vec::{Vec};
+use time_base::{Time};
+
+/// A pending expiration of a timer.
+#[derive(Copy)]
+pub struct Expiry {
+    pub deadline: Time,
+    /// The slot of the timer.
+    pub idx: usize,
+    /// The generation of the timer. Used to detect expirations of cancelled timers.
+    pub gen: u64,
+}
+
+/// Adds an expiration to the heap.
+pub fn push(heap: &mut Vec<Expiry>, e: Expiry) -> Result {
+    try!(heap.push(e));
+    let pos = heap.len() - 1;
+    sift_up(heap, pos);
+    Ok(())
+}
+
+/// Returns the earliest expiration without removing it.
+pub fn peek(heap: &Vec<Expiry>) -> Option<Expiry> {
+    match heap.len() {
+        0 => None,
+        _ => Some(heap[0]),
+    }
+}
+
+/// Removes the earliest expiration from the heap.
+pub fn pop(heap: &mut Vec<Expiry>) -> Option<Expiry> {
+    match heap.len() {
+        0 => None,
+        _ => Some(remove(heap, 0)),
+    }
+}
+
+/// Removes the expiration at a position in the heap.
+pub fn remove(heap: &mut Vec<Expiry>, pos: usize) -> Expiry {
+    let last = heap.pop().unwrap();
+    if pos == heap.len() {
+        return last;
+    }
+    let e = mem::replace(&mut heap[pos], last);
+    sift_down(heap, pos);
+    sift_up(heap, pos);
+    e
+}
+
+fn sift_up(heap: &mut Vec<Expiry>, mut pos: usize) {
+    while pos > 0 {
+        let parent = (pos - 1) / 2;
+        if heap[parent].deadline <= heap[pos].deadline {
+            break;
+        }
+        swap(heap, parent, pos);
+        pos = parent;
+    }
+}
+
+fn sift_down(heap: &mut Vec<Expiry>, mut pos: usize) {
+    let len = heap.len();
+    loop {
+        let left = 2 * pos + 1;
+        let right = left + 1;
+        let mut min = pos;
+        if left < len && heap[left].deadline < heap[min].deadline {
+            min = left;
+        }
+        if right < len && heap[right].deadline < heap[min].deadline {
+            min = right;
+        }
+        if min == pos {
+            break;
+        }
+        swap(heap, pos, min);
+        pos = min;
+    }
+}
+
+fn swap(heap: &mut Vec<Expiry>, i: usize, j: usize) {
+    let tmp = heap[i];
+    heap[i] = heap[j];
+    heap[j] = tmp;
+}
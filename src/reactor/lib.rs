@@ -0,0 +1,632 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![crate_name = "lrs_reactor"]
+#![crate_type = "lib"]
+#![feature(custom_derive, associated_consts)]
+#![no_std]
+
+extern crate lrs_base as base;
+extern crate lrs_cty as cty;
+extern crate lrs_fmt as fmt;
+extern crate lrs_alloc as alloc;
+extern crate lrs_box as bx;
+extern crate lrs_vec as vec;
+extern crate lrs_rc as rc;
+extern crate lrs_ringbuf as ringbuf;
+extern crate lrs_time_base as time_base;
+extern crate lrs_fd as fd;
+extern crate lrs_poll as poll;
+extern crate lrs_signal as signal;
+extern crate lrs_event as event;
+
+use base::prelude::*;
+use base::{error};
+use bx::{Box};
+use vec::{Vec};
+use rc::{Arc};
+use ringbuf::{DynRingBuf};
+use time_base::{Time, Timer, clock};
+use fd::{FdContainer};
+use poll::{Epoll, Event, PollFlags, EMPTY_EVENT, POLL_READ};
+use signal::{Sigset, block_signal, unblock_signal};
+use signal::signals::{Signal};
+use signal::sigfd::{Sigfd, SigfdInfo};
+use signal::sigfd::flags::{SIGFD_DONT_BLOCK, SIGFD_CLOSE_ON_EXEC};
+use event::{Eventfd};
+use event::flags::{EFD_DONT_BLOCK, EFD_CLOSE_ON_EXEC};
+use heap::{Expiry};
+
+pub mod std { pub use fmt::std::*; pub use cty; }
+
+mod heap;
+
+/// A user-defined value identifying a file descriptor registered without a callback.
+///
+/// [field, 1]
+/// The value.
+#[derive(Pod, Eq)]
+pub struct Token(pub u64);
+
+/// A handle to a timer.
+#[derive(Pod, Eq)]
+pub struct TimerId {
+    idx: usize,
+    gen: u64,
+}
+
+trait FdHandler {
+    fn call(&mut self, reactor: &mut Reactor, event: Event);
+}
+
+impl<F> FdHandler for F
+    where F: FnMut(&mut Reactor, Event),
+{
+    fn call(&mut self, reactor: &mut Reactor, event: Event) {
+        self(reactor, event)
+    }
+}
+
+trait TimerHandler {
+    fn call(&mut self, reactor: &mut Reactor);
+}
+
+impl<F> TimerHandler for F
+    where F: FnMut(&mut Reactor),
+{
+    fn call(&mut self, reactor: &mut Reactor) {
+        self(reactor)
+    }
+}
+
+trait SignalHandler {
+    fn call(&mut self, reactor: &mut Reactor, info: &SigfdInfo);
+}
+
+impl<F> SignalHandler for F
+    where F: FnMut(&mut Reactor, &SigfdInfo),
+{
+    fn call(&mut self, reactor: &mut Reactor, info: &SigfdInfo) {
+        self(reactor, info)
+    }
+}
+
+struct Source {
+    token: Token,
+    gen: u64,
+    callback: bool,
+    // `None` while the callback is running.
+    handler: Option<Box<FdHandler>>,
+}
+
+struct TimerSlot {
+    gen: u64,
+    period: Option<Time>,
+    // `None` while the callback is running.
+    handler: Option<Box<TimerHandler>>,
+}
+
+/// A handle that can be used to wake up a reactor from another thread.
+pub struct Waker {
+    event: Arc<Eventfd>,
+}
+
+impl Waker {
+    /// Wakes up the reactor.
+    ///
+    /// = Remarks
+    ///
+    /// If the reactor is not currently waiting, the next call to `run_once` returns
+    /// without blocking.
+    pub fn wake(&self) -> Result {
+        self.event.add(1)
+    }
+}
+
+impl To for Waker {
+    fn to(&self) -> Waker {
+        Waker { event: self.event.add_ref() }
+    }
+}
+
+/// A single-threaded event loop.
+///
+/// = Remarks
+///
+/// The reactor dispatches events of registered file descriptors, timers, and signals.
+/// File descriptors can either be registered with a callback that is invoked when an
+/// event occurs, or with a token in which case the event is queued and can be retrieved
+/// with `next_event`.
+///
+/// Whether a file descriptor is level- or edge-triggered is determined by the presence
+/// of the `POLL_EDGE_TRIGGERED` flag. In the edge-triggered case, the callback has to
+/// consume all available data since it will not be invoked again until new data
+/// arrives.
+///
+/// All timers are multiplexed onto a single monotonic timerfd.
+///
+/// = Examples
+///
+/// ----
+/// let mut reactor = Reactor::new().unwrap();
+/// reactor.add_timer(Time::seconds(1), |r| {
+///     println!("tick");
+///     r.stop();
+/// }).unwrap();
+/// reactor.run().unwrap();
+/// ----
+pub struct Reactor {
+    epoll: Epoll,
+    // Indexed by file descriptor.
+    sources: Vec<Option<Source>>,
+    ready: DynRingBuf<(Token, Event)>,
+    timer: Timer,
+    timers: Vec<Option<TimerSlot>>,
+    free_timers: Vec<usize>,
+    heap: Vec<Expiry>,
+    sigfd: Option<Sigfd>,
+    sigset: Sigset,
+    // Indexed by signal number.
+    signals: Vec<Option<Box<SignalHandler>>>,
+    wake: Arc<Eventfd>,
+    next_gen: u64,
+    stopped: bool,
+}
+
+impl Reactor {
+    /// Creates a new reactor.
+    pub fn new() -> Result<Reactor> {
+        let epoll = try!(Epoll::new());
+        let timer = try!(clock::MONO.timer_non_blocking());
+        try!(epoll.add(&timer, POLL_READ));
+        let wake = try!(Eventfd::new(EFD_DONT_BLOCK | EFD_CLOSE_ON_EXEC));
+        try!(epoll.add(&wake, POLL_READ));
+        Ok(Reactor {
+            epoll: epoll,
+            sources: Vec::new(),
+            ready: DynRingBuf::new(),
+            timer: timer,
+            timers: Vec::new(),
+            free_timers: Vec::new(),
+            heap: Vec::new(),
+            sigfd: None,
+            sigset: Sigset::new(),
+            signals: Vec::new(),
+            wake: try!(Arc::new()).set(wake),
+            next_gen: 0,
+            stopped: false,
+        })
+    }
+
+    fn gen(&mut self) -> u64 {
+        self.next_gen += 1;
+        self.next_gen
+    }
+
+    /// Registers a file descriptor whose events will be queued.
+    ///
+    /// [argument, fd]
+    /// The file descriptor.
+    ///
+    /// [argument, flags]
+    /// The events to wait for.
+    ///
+    /// [argument, token]
+    /// The token returned together with the events of this file descriptor.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::poll::Reactor::next_event
+    pub fn register<T>(&mut self, fd: &T, flags: PollFlags, token: Token) -> Result
+        where T: FdContainer,
+    {
+        self.register_common(fd, flags, token, None)
+    }
+
+    /// Registers a file descriptor with a callback.
+    ///
+    /// [argument, fd]
+    /// The file descriptor.
+    ///
+    /// [argument, flags]
+    /// The events to wait for.
+    ///
+    /// [argument, f]
+    /// The callback that is invoked when an event occurs.
+    pub fn register_fn<T, F>(&mut self, fd: &T, flags: PollFlags, f: F) -> Result
+        where T: FdContainer,
+              F: FnMut(&mut Reactor, Event) + 'static,
+    {
+        let handler: Box<FdHandler> = try!(Box::new()).set(f);
+        self.register_common(fd, flags, Token(0), Some(handler))
+    }
+
+    fn register_common<T>(&mut self, fd: &T, flags: PollFlags, token: Token,
+                          handler: Option<Box<FdHandler>>) -> Result
+        where T: FdContainer,
+    {
+        if fd.borrow() < 0 {
+            return Err(error::InvalidArgument);
+        }
+        let idx = fd.borrow() as usize;
+        if self.sources.len() <= idx {
+            try!(self.sources.reserve(idx + 1 - self.sources.len()));
+            while self.sources.len() <= idx {
+                try!(self.sources.push(None));
+            }
+        }
+        try!(self.epoll.add(fd, flags));
+        let gen = self.gen();
+        self.sources[idx] = Some(Source {
+            token: token,
+            gen: gen,
+            callback: handler.is_some(),
+            handler: handler,
+        });
+        Ok(())
+    }
+
+    /// Changes the events a registered file descriptor waits for.
+    ///
+    /// [argument, fd]
+    /// The file descriptor.
+    ///
+    /// [argument, flags]
+    /// The new events to wait for.
+    pub fn reregister<T>(&mut self, fd: &T, flags: PollFlags) -> Result
+        where T: FdContainer,
+    {
+        self.epoll.modify(fd, flags)
+    }
+
+    /// Removes a file descriptor from the reactor.
+    ///
+    /// [argument, fd]
+    /// The file descriptor.
+    ///
+    /// = Remarks
+    ///
+    /// Events of this file descriptor that have already been queued are not removed.
+    pub fn deregister<T>(&mut self, fd: &T) -> Result
+        where T: FdContainer,
+    {
+        try!(self.epoll.remove(fd));
+        let idx = fd.borrow() as usize;
+        if idx < self.sources.len() {
+            self.sources[idx] = None;
+        }
+        Ok(())
+    }
+
+    /// Retrieves the next queued event of a file descriptor registered with a token.
+    pub fn next_event(&mut self) -> Option<(Token, Event)> {
+        self.ready.pop_left()
+    }
+
+    /// Adds a one-shot timer.
+    ///
+    /// [argument, delay]
+    /// The time after which the timer expires.
+    ///
+    /// [argument, f]
+    /// The callback that is invoked when the timer expires.
+    pub fn add_timer<F>(&mut self, delay: Time, f: F) -> Result<TimerId>
+        where F: FnMut(&mut Reactor) + 'static,
+    {
+        self.add_timer_common(delay, None, try!(Box::new()).set(f))
+    }
+
+    /// Adds a periodic timer.
+    ///
+    /// [argument, period]
+    /// The interval in which the timer expires.
+    ///
+    /// [argument, f]
+    /// The callback that is invoked whenever the timer expires.
+    pub fn add_periodic<F>(&mut self, period: Time, f: F) -> Result<TimerId>
+        where F: FnMut(&mut Reactor) + 'static,
+    {
+        self.add_timer_common(period, Some(period), try!(Box::new()).set(f))
+    }
+
+    fn add_timer_common(&mut self, delay: Time, period: Option<Time>,
+                        handler: Box<TimerHandler>) -> Result<TimerId> {
+        let deadline = try!(clock::MONO.get_time()) + delay;
+        try!(self.heap.reserve(1));
+        let idx = match self.free_timers.pop() {
+            Some(idx) => idx,
+            None => {
+                // Make sure that every slot can be returned to the free list without
+                // allocating.
+                let free = self.timers.len() + 1 - self.free_timers.len();
+                try!(self.free_timers.reserve(free));
+                try!(self.timers.push(None));
+                self.timers.len() - 1
+            },
+        };
+        let gen = self.gen();
+        self.timers[idx] = Some(TimerSlot {
+            gen: gen,
+            period: period,
+            handler: Some(handler),
+        });
+        let expiry = Expiry { deadline: deadline, idx: idx, gen: gen };
+        try!(heap::push(&mut self.heap, expiry));
+        if let Some(first) = heap::peek(&self.heap) {
+            if first.gen == gen {
+                try!(self.arm_timer());
+            }
+        }
+        Ok(TimerId { idx: idx, gen: gen })
+    }
+
+    /// Cancels a timer.
+    ///
+    /// [argument, id]
+    /// The timer to cancel.
+    ///
+    /// [return_value]
+    /// Returns whether the timer was still active.
+    ///
+    /// = Remarks
+    ///
+    /// This can be called from the timer's own callback.
+    pub fn cancel_timer(&mut self, id: TimerId) -> bool {
+        let active = id.idx < self.timers.len() && match self.timers[id.idx] {
+            Some(ref slot) => slot.gen == id.gen,
+            _ => false,
+        };
+        if active {
+            self.timers[id.idx] = None;
+            // Cannot fail since the capacity has been reserved in `add_timer_common`.
+            let _ = self.free_timers.push(id.idx);
+            // The expiration is not in the heap if a one-shot timer is cancelled from
+            // its own callback.
+            if let Some(pos) = self.heap.find(|e| e.idx == id.idx && e.gen == id.gen) {
+                heap::remove(&mut self.heap, pos);
+                if pos == 0 {
+                    // If this fails, the timerfd fires early and is re-armed in
+                    // `dispatch_timers`.
+                    let _ = self.arm_timer();
+                }
+            }
+        }
+        active
+    }
+
+    fn arm_timer(&mut self) -> Result {
+        match heap::peek(&self.heap) {
+            Some(first) => self.timer.once_at(first.deadline),
+            None => self.timer.disable(),
+        }
+    }
+
+    /// Adds a signal handler.
+    ///
+    /// [argument, sig]
+    /// The signal to handle.
+    ///
+    /// [argument, f]
+    /// The callback that is invoked when the signal is received.
+    ///
+    /// = Remarks
+    ///
+    /// The signal is blocked in the calling thread so that it's delivered via a signalfd.
+    /// If the signal already has a handler, the handler is replaced.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::poll::Reactor::remove_signal
+    pub fn add_signal<F>(&mut self, sig: Signal, f: F) -> Result
+        where F: FnMut(&mut Reactor, &SigfdInfo) + 'static,
+    {
+        let handler: Box<SignalHandler> = try!(Box::new()).set(f);
+        let idx = sig.0 as usize;
+        while self.signals.len() <= idx {
+            try!(self.signals.push(None));
+        }
+        let mut set = self.sigset;
+        try!(set.set(sig));
+        if self.sigfd.is_none() {
+            let sigfd = try!(Sigfd::new(set, SIGFD_DONT_BLOCK | SIGFD_CLOSE_ON_EXEC));
+            try!(self.epoll.add(&sigfd, POLL_READ));
+            self.sigfd = Some(sigfd);
+        }
+        if let Some(ref sigfd) = self.sigfd {
+            try!(sigfd.set_mask(set));
+        }
+        try!(block_signal(sig));
+        self.sigset = set;
+        self.signals[idx] = Some(handler);
+        Ok(())
+    }
+
+    /// Removes a signal handler.
+    ///
+    /// [argument, sig]
+    /// The signal whose handler will be removed.
+    ///
+    /// = Remarks
+    ///
+    /// The signal is unblocked in the calling thread.
+    pub fn remove_signal(&mut self, sig: Signal) -> Result {
+        let mut set = self.sigset;
+        try!(set.unset(sig));
+        if let Some(ref sigfd) = self.sigfd {
+            try!(sigfd.set_mask(set));
+        }
+        self.sigset = set;
+        if (sig.0 as usize) < self.signals.len() {
+            self.signals[sig.0 as usize] = None;
+        }
+        unblock_signal(sig).ignore_ok()
+    }
+
+    /// Returns a handle that can be used to wake up the reactor from another thread.
+    pub fn waker(&self) -> Waker {
+        Waker { event: self.wake.add_ref() }
+    }
+
+    /// Makes `run` return after the current iteration.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Runs the reactor until `stop` is called.
+    pub fn run(&mut self) -> Result {
+        self.stopped = false;
+        self.run_until(|r| r.stopped)
+    }
+
+    /// Runs the reactor until a condition is satisfied.
+    ///
+    /// [argument, done]
+    /// The stop condition. It's checked before every iteration.
+    pub fn run_until<F>(&mut self, mut done: F) -> Result
+        where F: FnMut(&mut Reactor) -> bool,
+    {
+        while !done(self) {
+            try!(self.run_once(None));
+        }
+        Ok(())
+    }
+
+    /// Waits for events and dispatches them.
+    ///
+    /// [argument, timeout]
+    /// The maximum time to wait for events.
+    ///
+    /// = Remarks
+    ///
+    /// This function returns after one batch of events has been dispatched, after the
+    /// reactor has been woken up via a `Waker`, or after the timeout has expired.
+    pub fn run_once(&mut self, timeout: Option<Time>) -> Result {
+        let mut events = [EMPTY_EVENT; 32];
+        let num = match timeout {
+            Some(timeout) => try!(self.epoll.wait_timeout(&mut events, timeout)).len(),
+            None => try!(self.epoll.wait(&mut events)).len(),
+        };
+        for &event in &events[..num] {
+            let fd = event.fd();
+            if fd == self.timer.borrow() {
+                try!(self.dispatch_timers());
+            } else if fd == self.wake.borrow() {
+                try!(self.wake.get());
+            } else if Some(fd) == self.sigfd.as_ref().map(|s| s.borrow()) {
+                try!(self.dispatch_signals());
+            } else {
+                try!(self.dispatch_fd(event));
+            }
+        }
+        Ok(())
+    }
+
+    fn dispatch_fd(&mut self, event: Event) -> Result {
+        let idx = event.fd() as usize;
+        if idx >= self.sources.len() {
+            return Ok(());
+        }
+        let (token, gen, callback, handler) = match self.sources[idx] {
+            Some(ref mut src) => (src.token, src.gen, src.callback, src.handler.take()),
+            _ => return Ok(()),
+        };
+        if !callback {
+            return self.ready.try_push_right((token, event));
+        }
+        if let Some(mut handler) = handler {
+            handler.call(self, event);
+            // The callback can deregister the file descriptor.
+            if let Some(ref mut src) = self.sources[idx] {
+                if src.gen == gen {
+                    src.handler = Some(handler);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn dispatch_timers(&mut self) -> Result {
+        let _ = self.timer.ticks();
+        let now = try!(clock::MONO.get_time());
+        loop {
+            match heap::peek(&self.heap) {
+                Some(first) if first.deadline <= now => { },
+                _ => break,
+            }
+            let e = match heap::pop(&mut self.heap) {
+                Some(e) => e,
+                _ => break,
+            };
+            let (mut handler, period) = match self.timers[e.idx] {
+                Some(ref mut slot) if slot.gen == e.gen => match slot.handler.take() {
+                    Some(handler) => (handler, slot.period),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            if let Some(period) = period {
+                let mut next = e.deadline + period;
+                if next <= now {
+                    next = now + period;
+                }
+                try!(heap::push(&mut self.heap,
+                                Expiry { deadline: next, idx: e.idx, gen: e.gen }));
+            }
+            handler.call(self);
+            let current = match self.timers[e.idx] {
+                Some(ref slot) => slot.gen == e.gen,
+                _ => false,
+            };
+            if current {
+                match period {
+                    Some(_) => {
+                        if let Some(ref mut slot) = self.timers[e.idx] {
+                            slot.handler = Some(handler);
+                        }
+                    },
+                    None => {
+                        self.timers[e.idx] = None;
+                        // Cannot fail. See `cancel_timer`.
+                        let _ = self.free_timers.push(e.idx);
+                    },
+                }
+            }
+        }
+        self.arm_timer()
+    }
+
+    fn dispatch_signals(&mut self) -> Result {
+        let mut buf = [SigfdInfo::new(); 8];
+        loop {
+            let num = match self.sigfd {
+                Some(ref sigfd) => match sigfd.read(&mut buf) {
+                    Ok(infos) => infos.len(),
+                    Err(error::WouldBlock) => return Ok(()),
+                    Err(e) => return Err(e),
+                },
+                _ => return Ok(()),
+            };
+            if num == 0 {
+                return Ok(());
+            }
+            for info in &buf[..num] {
+                let idx = info.signo as usize;
+                if idx >= self.signals.len() {
+                    continue;
+                }
+                if let Some(mut handler) = self.signals[idx].take() {
+                    handler.call(self, info);
+                    // The callback can remove or replace the handler.
+                    let active = match self.sigset.is_set(info.signal()) {
+                        Ok(active) => active,
+                        _ => false,
+                    };
+                    if active && self.signals[idx].is_none() {
+                        self.signals[idx] = Some(handler);
+                    }
+                }
+            }
+        }
+    }
+}
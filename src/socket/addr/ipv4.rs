@@ -9,7 +9,9 @@ use cty::{
     AF_INET, sa_family_t, c_int, sockaddr_in, in_addr,
 };
 use addr::{SockAddr};
-use fmt::{Debug, Write};
+use addr::text::{parse_port};
+use fmt::{Debug, Display, Write};
+use parse::{Parsable};
 
 /// The size of an Ipv4 socket address.
 pub const IPV4_SOCK_ADDR_SIZE: usize = 16; // == size_of::<sockaddr_in>()
@@ -177,6 +179,31 @@ impl Ipv4SockAddr {
         Ok(unsafe { mem::cast(&mut bytes[..IPV4_SOCK_ADDR_SIZE]) })
     }
 
+    /// Creates a new Ipv4 socket address from its textual representation.
+    ///
+    /// [argument, bytes]
+    /// The buffer in which the address will be stored.
+    ///
+    /// [argument, text]
+    /// The text of the form `a.b.c.d:port`.
+    ///
+    /// = Remarks
+    ///
+    /// Socket addresses are unsized and can therefore not implement `Parsable`.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::ip::Ipv4SockAddr::from_addr_port
+    pub fn from_text<'a>(bytes: &'a mut [u8],
+                         text: &[u8]) -> Result<&'a mut Ipv4SockAddr> {
+        let (addr, len) = try!(Ipv4Addr::parse_bytes_init(text));
+        if text.len() == len || text[len] != b':' {
+            return Err(error::InvalidSequence);
+        }
+        let port = try!(parse_port(&text[len + 1..]));
+        Ipv4SockAddr::from_addr_port(bytes, addr, port)
+    }
+
     /// Returns the Ipv4 address of the socket address.
     pub fn addr(&self) -> Ipv4Addr {
         let mut addr = 0;
@@ -230,6 +257,54 @@ impl Debug for Ipv4Addr {
     }
 }
 
+impl Display for Ipv4Addr {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Debug::fmt(self, w)
+    }
+}
+
+/// Parses a decimal octet without leading zeros.
+fn parse_octet(bytes: &[u8]) -> Result<(u8, usize)> {
+    let mut val: u32 = 0;
+    let mut len = 0;
+    while len < bytes.len() && len < 3 {
+        match bytes[len] {
+            b'0'...b'9' => val = val * 10 + (bytes[len] - b'0') as u32,
+            _ => break,
+        }
+        len += 1;
+    }
+    if len == 0 || val > 255 || (len > 1 && bytes[0] == b'0') {
+        return Err(error::InvalidSequence);
+    }
+    Ok((val as u8, len))
+}
+
+impl Parsable for Ipv4Addr {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(Ipv4Addr, usize)> {
+        let mut octets = [0; 4];
+        let mut pos = 0;
+        for i in 0..4 {
+            if i > 0 {
+                if pos == bytes.len() || bytes[pos] != b'.' {
+                    return Err(error::InvalidSequence);
+                }
+                pos += 1;
+            }
+            let (octet, len) = try!(parse_octet(&bytes[pos..]));
+            octets[i] = octet;
+            pos += len;
+        }
+        Ok((Ipv4Addr::from_bytes(octets), pos))
+    }
+}
+
+impl Display for Ipv4SockAddr {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        write!(w, "{}:{}", self.addr(), self.port())
+    }
+}
+
 impl Debug for Ipv4SockAddr {
     fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
         let addr = self.addr();
@@ -9,7 +9,13 @@ use cty::{
 };
 use base::{error};
 use addr::{SockAddr};
-use fmt::{Debug, Write};
+use addr::ipv4::{Ipv4Addr};
+use addr::text::{parse_dec, parse_port};
+use fmt::{Debug, Display, Write};
+use parse::{Parsable};
+use socket::{Socket};
+use flags::{SOCK_CLOSE_ON_EXEC};
+use {domain, kind};
 
 /// The size of an Ipv6 socket address in bytes.
 pub const IPV6_SOCK_ADDR_SIZE: usize = 28; // == size_of::<sockaddr_in6>().
@@ -286,7 +292,7 @@ impl Ipv6SockAddr {
         }
         let addr = sockaddr_in6 {
             sin6_family: AF_INET6 as sa_family_t,
-            sin6_port: port,
+            sin6_port: port.to_be(),
             sin6_addr: in6_addr { u6_addr16: addr.to_be_bytes() },
             .. mem::zeroed()
         };
@@ -294,6 +300,49 @@ impl Ipv6SockAddr {
         Ok(unsafe { mem::cast(&mut bytes[..IPV6_SOCK_ADDR_SIZE]) })
     }
 
+    /// Creates a new Ipv6 socket address from its textual representation.
+    ///
+    /// [argument, bytes]
+    /// The buffer in which the address will be stored.
+    ///
+    /// [argument, text]
+    /// The text of the form `[addr]:port` or `[addr%zone]:port`.
+    ///
+    /// = Remarks
+    ///
+    /// The zone is either a numeric scope id or the name of an interface whose index
+    /// is used as the scope id.
+    ///
+    /// Socket addresses are unsized and can therefore not implement `Parsable`.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::ip::Ipv6SockAddr::from_addr_port
+    /// * link:lrs::socket::Socket::interface_index
+    pub fn from_text<'a>(bytes: &'a mut [u8],
+                         text: &[u8]) -> Result<&'a mut Ipv6SockAddr> {
+        if text.len() == 0 || text[0] != b'[' {
+            return Err(error::InvalidSequence);
+        }
+        let (addr, len) = try!(Ipv6Addr::parse_bytes_init(&text[1..]));
+        let mut pos = len + 1;
+        let mut scope_id = 0;
+        if pos < text.len() && text[pos] == b'%' {
+            let start = pos + 1;
+            while pos < text.len() && text[pos] != b']' {
+                pos += 1;
+            }
+            scope_id = try!(parse_zone(&text[start..pos]));
+        }
+        if pos + 1 >= text.len() || text[pos] != b']' || text[pos + 1] != b':' {
+            return Err(error::InvalidSequence);
+        }
+        let port = try!(parse_port(&text[pos + 2..]));
+        let sa = try!(Ipv6SockAddr::from_addr_port(bytes, addr, port));
+        sa.set_scope_id(scope_id);
+        Ok(sa)
+    }
+
     /// Returns the Ipv6 address of an Ipv6 socket address.
     pub fn addr(&self) -> Ipv6Addr {
         let mut addr = [0; 8];
@@ -311,9 +360,9 @@ impl Ipv6SockAddr {
 
     /// Returns the port of an Ipv6 socket address.
     pub fn port(&self) -> u16 {
-        let mut port = 0;
+        let mut port: u16 = 0;
         mem::copy(port.as_mut(), &self.data[PORT_OFF..]);
-        port
+        port.from_be()
     }
 
     /// Sets the port of an Ipv6 socket address.
@@ -340,10 +389,14 @@ impl Ipv6SockAddr {
     }
 
     /// Returns the scope id of an Ipv6 socket address.
+    ///
+    /// = Remarks
+    ///
+    /// For link-local addresses, this is the index of the interface.
     pub fn scope_id(&self) -> u32 {
         let mut id: u32 = 0;
         mem::copy(id.as_mut(), &self.data[SCOP_OFF..]);
-        id.from_be()
+        id
     }
 
     /// Sets the scope id of an Ipv6 socket address.
@@ -351,7 +404,7 @@ impl Ipv6SockAddr {
     /// [argument, id]
     /// The new scope id.
     pub fn set_scope_id(&mut self, id: u32) {
-        mem::copy(&mut self.data[SCOP_OFF..], id.to_be().as_ref());
+        mem::copy(&mut self.data[SCOP_OFF..], id.as_ref());
     }
 }
 
@@ -376,83 +429,175 @@ impl AsMut<SockAddr> for Ipv6SockAddr {
 }
 impl_try_as_mut!(SockAddr, Ipv6SockAddr);
 
-impl Debug for Ipv6Addr {
-    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
-        let is_ipv4 = if self.is_ipv4_compatible() {
-            try!(write!(w, "::"));
-            true
-        } else if self.is_ipv4_mapped() {
-            try!(write!(w, "::ffff:"));
-            true
-        } else if self.is_ipv4_translated() {
-            try!(write!(w, "::ffff:0:"));
-            true
-        } else {
-            false
+/// Maps a zone to a scope id.
+fn parse_zone(zone: &[u8]) -> Result<u32> {
+    if zone.len() == 0 {
+        return Err(error::InvalidSequence);
+    }
+    if zone[0] >= b'0' && zone[0] <= b'9' {
+        return parse_dec(zone, u32::max());
+    }
+    let sock = try!(Socket::new(domain::Unix, kind::Datagram, 0, SOCK_CLOSE_ON_EXEC));
+    sock.interface_index(zone)
+}
+
+/// Parses up to four hexadecimal digits.
+fn parse_segment(bytes: &[u8]) -> (u16, usize) {
+    let mut val = 0;
+    let mut len = 0;
+    while len < bytes.len() && len < 4 {
+        let digit = match bytes[len] {
+            b @ b'0'...b'9' => b - b'0',
+            b @ b'a'...b'f' => b - b'a' + 10,
+            b @ b'A'...b'F' => b - b'A' + 10,
+            _ => break,
         };
+        val = val << 4 | digit as u16;
+        len += 1;
+    }
+    (val, len)
+}
 
-        if is_ipv4 {
-            let ab: [u8; 2] = unsafe { mem::cast(self.6.to_be()) };
-            let cd: [u8; 2] = unsafe { mem::cast(self.6.to_be()) };
-            return write!(w, "{}.{}.{}.{}", ab[0], ab[1], cd[0], cd[1]);
+impl Parsable for Ipv6Addr {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(Ipv6Addr, usize)> {
+        let mut segs = [0; 8];
+        let mut num = 0;
+        let mut gap = None;
+        let mut pos = 0;
+
+        if bytes.len() >= 2 && bytes[0] == b':' && bytes[1] == b':' {
+            gap = Some(0);
+            pos = 2;
         }
 
-        let bytes = self.to_bytes();
-
-        // Find the longest sequence of zeros, favoring sequences at the end and start.
-        let (start, end) = {
-            let mut start = 0;
-            let mut end = 0;
-            let mut cur = 8;
-            for i in 0..8usize {
-                match bytes[i] {
-                    0 if cur == 8 => cur = i,
-                    0 => { },
-                    _ => {
-                        if cur != 8 && i - cur > end - start {
-                            start = cur;
-                            end = i;
-                        }
-                        cur = 8;
-                    },
+        // Whether the previous token was `::`.
+        let mut after_gap = gap.is_some();
+        while num < 8 {
+            let (seg, len) = parse_segment(&bytes[pos..]);
+            if len == 0 {
+                if after_gap {
+                    break;
                 }
+                return Err(error::InvalidSequence);
             }
-            if (8 - cur > end - start) || ((8 - cur == end - start) && start > 0) {
-                start = cur;
-                end = 8;
-            }
-            if end == start + 1 {
-                (0, 0)
-            } else {
-                (start, end)
+            if pos + len < bytes.len() && bytes[pos + len] == b'.' {
+                // Embedded Ipv4 address in the last 32 bits.
+                if num > 6 {
+                    return Err(error::InvalidSequence);
+                }
+                let (v4, len) = try!(Ipv4Addr::parse_bytes_init(&bytes[pos..]));
+                let v4 = v4.to_bytes();
+                segs[num] = (v4[0] as u16) << 8 | v4[1] as u16;
+                segs[num + 1] = (v4[2] as u16) << 8 | v4[3] as u16;
+                num += 2;
+                pos += len;
+                break;
             }
-        };
+            segs[num] = seg;
+            num += 1;
+            pos += len;
+            after_gap = false;
 
-        if start == end {
-            for i in 0..7usize {
-                try!(write!(w, "{:x}:", bytes[i]))
+            if num == 8 || pos == bytes.len() || bytes[pos] != b':' {
+                break;
             }
-            try!(write!(w, "{:x}", bytes[7]))
-        } else {
-            if start == 0 {
-                try!(write!(w, ":"));
-            } else {
-                for i in 0..start {
-                    try!(write!(w, "{:x}:", bytes[i]))
+            if pos + 1 < bytes.len() && bytes[pos + 1] == b':' {
+                if gap.is_some() {
+                    return Err(error::InvalidSequence);
                 }
-            }
-            if end == 8 {
-                try!(write!(w, ":"));
+                gap = Some(num);
+                after_gap = true;
+                pos += 2;
             } else {
-                for i in end..8 {
-                    try!(write!(w, ":{:x}", bytes[i]))
+                pos += 1;
+            }
+        }
+
+        match gap {
+            Some(gap) if num < 8 => {
+                let tail = num - gap;
+                for i in 0..tail {
+                    segs[7 - i] = segs[num - 1 - i];
                 }
+                for i in gap..8 - tail {
+                    segs[i] = 0;
+                }
+            },
+            None if num == 8 => { },
+            _ => return Err(error::InvalidSequence),
+        }
+
+        Ok((Ipv6Addr::from_bytes(segs), pos))
+    }
+}
+
+impl Debug for Ipv6Addr {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        if self.is_ipv4_mapped() {
+            let v4 = Ipv4Addr((self.6 >> 8) as u8, self.6 as u8, (self.7 >> 8) as u8,
+                              self.7 as u8);
+            return write!(w, "::ffff:{}", v4);
+        }
+
+        let segs = self.to_bytes();
+
+        // RFC 5952: Compress the first longest run of at least two zero segments.
+        let (mut start, mut end) = (0, 0);
+        let mut i = 0;
+        while i < 8 {
+            if segs[i] != 0 {
+                i += 1;
+                continue;
+            }
+            let mut j = i;
+            while j < 8 && segs[j] == 0 {
+                j += 1;
             }
+            if j - i > end - start {
+                start = i;
+                end = j;
+            }
+            i = j;
+        }
+        if end - start < 2 {
+            start = 8;
+            end = 8;
+        }
+
+        for i in 0..start {
+            if i > 0 {
+                try!(w.write_all(b":"));
+            }
+            try!(write!(w, "{:x}", segs[i]));
+        }
+        if start < 8 {
+            try!(w.write_all(b"::"));
+        }
+        for i in end..8 {
+            if i > end {
+                try!(w.write_all(b":"));
+            }
+            try!(write!(w, "{:x}", segs[i]));
         }
         Ok(())
     }
 }
 
+impl Display for Ipv6Addr {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Debug::fmt(self, w)
+    }
+}
+
+impl Display for Ipv6SockAddr {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        match self.scope_id() {
+            0 => write!(w, "[{}]:{}", self.addr(), self.port()),
+            id => write!(w, "[{}%{}]:{}", self.addr(), id, self.port()),
+        }
+    }
+}
+
 impl Debug for Ipv6SockAddr {
     fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
         let addr = self.addr();
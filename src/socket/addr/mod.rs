@@ -8,11 +8,36 @@ use cty::{
     BYTES_PER_SHORT, AF_UNIX, AF_INET, AF_INET6, sa_family_t, c_int,
 };
 use base::{error};
-use fmt::{Debug, Write};
+use fmt::{Debug, Display, Write};
+use parse::{Parsable};
 
 pub mod unix;
 pub mod ipv4;
 pub mod ipv6;
+pub mod net;
+
+mod text;
+
+/// An Ipv4 or Ipv6 address.
+#[derive(Copy, Eq)]
+pub enum IpAddr {
+    /// An Ipv4 address.
+    V4(ipv4::Ipv4Addr),
+    /// An Ipv6 address.
+    V6(ipv6::Ipv6Addr),
+}
+
+/// An owned Ipv4 or Ipv6 socket address.
+///
+/// = Remarks
+///
+/// Unlike `Ipv4SockAddr` and `Ipv6SockAddr`, this type is sized and can be stored in
+/// collections. It can be passed to all functions that accept socket addresses.
+#[derive(Copy)]
+pub struct IpSockAddrBuf {
+    data: [u8; ipv6::IPV6_SOCK_ADDR_SIZE],
+    len: usize,
+}
 
 /// A socket address.
 pub struct SockAddr { data: [u8] }
@@ -199,6 +224,81 @@ impl SockAddr {
     }
 }
 
+impl IpSockAddrBuf {
+    /// Creates a new socket address from an address and a port.
+    ///
+    /// [argument, addr]
+    /// The address of the socket.
+    ///
+    /// [argument, port]
+    /// The port of the socket.
+    pub fn new(addr: IpAddr, port: u16) -> IpSockAddrBuf {
+        let mut buf = IpSockAddrBuf { data: [0; ipv6::IPV6_SOCK_ADDR_SIZE], len: 0 };
+        buf.len = match addr {
+            IpAddr::V4(a) => {
+                ipv4::Ipv4SockAddr::from_addr_port(&mut buf.data, a, port).unwrap();
+                ipv4::IPV4_SOCK_ADDR_SIZE
+            },
+            IpAddr::V6(a) => {
+                ipv6::Ipv6SockAddr::from_addr_port(&mut buf.data, a, port).unwrap();
+                ipv6::IPV6_SOCK_ADDR_SIZE
+            },
+        };
+        buf
+    }
+
+    /// Returns the address of the socket address.
+    pub fn addr(&self) -> IpAddr {
+        let sa: &SockAddr = self.as_ref();
+        match sa.addr_type() {
+            AddrType::Ipv4 => IpAddr::V4(sa.as_ipv4().unwrap().addr()),
+            _ => IpAddr::V6(sa.as_ipv6().unwrap().addr()),
+        }
+    }
+
+    /// Returns the port of the socket address.
+    pub fn port(&self) -> u16 {
+        let sa: &SockAddr = self.as_ref();
+        match sa.addr_type() {
+            AddrType::Ipv4 => sa.as_ipv4().unwrap().port(),
+            _ => sa.as_ipv6().unwrap().port(),
+        }
+    }
+
+    /// Sets the port of the socket address.
+    ///
+    /// [argument, port]
+    /// The new port.
+    pub fn set_port(&mut self, port: u16) {
+        let sa: &mut SockAddr = self.as_mut();
+        match sa.addr_type() {
+            AddrType::Ipv4 => sa.as_mut_ipv4().unwrap().set_port(port),
+            _ => sa.as_mut_ipv6().unwrap().set_port(port),
+        }
+    }
+}
+
+impl AsRef<[u8]> for IpSockAddrBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+impl_try_as_ref!([u8], IpSockAddrBuf);
+
+impl AsRef<SockAddr> for IpSockAddrBuf {
+    fn as_ref(&self) -> &SockAddr {
+        unsafe { SockAddr::from_bytes_unchecked(&self.data[..self.len]) }
+    }
+}
+impl_try_as_ref!(SockAddr, IpSockAddrBuf);
+
+impl AsMut<SockAddr> for IpSockAddrBuf {
+    fn as_mut(&mut self) -> &mut SockAddr {
+        unsafe { SockAddr::from_mut_bytes_unchecked(&mut self.data[..self.len]) }
+    }
+}
+impl_try_as_mut!(SockAddr, IpSockAddrBuf);
+
 impl AsRef<[u8]> for SockAddr {
     fn as_ref(&self) -> &[u8] {
         &self.data
@@ -209,9 +309,51 @@ impl_try_as_ref!([u8], SockAddr);
 impl Debug for SockAddr {
     fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
         match self.addr_type() {
-            AddrType::Unix => self.as_unix().unwrap().fmt(w),
-            AddrType::Ipv4 => self.as_ipv4().unwrap().fmt(w),
-            AddrType::Ipv6 => self.as_ipv6().unwrap().fmt(w),
+            AddrType::Unix => Debug::fmt(self.as_unix().unwrap(), w),
+            AddrType::Ipv4 => Debug::fmt(self.as_ipv4().unwrap(), w),
+            AddrType::Ipv6 => Debug::fmt(self.as_ipv6().unwrap(), w),
+        }
+    }
+}
+
+impl Debug for IpAddr {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        match *self {
+            IpAddr::V4(ref a) => Debug::fmt(a, w),
+            IpAddr::V6(ref a) => Debug::fmt(a, w),
+        }
+    }
+}
+
+impl Display for IpAddr {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Debug::fmt(self, w)
+    }
+}
+
+impl Parsable for IpAddr {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(IpAddr, usize)> {
+        // No Ipv6 address starts with a complete Ipv4 address.
+        if let Ok((addr, len)) = ipv4::Ipv4Addr::parse_bytes_init(bytes) {
+            return Ok((IpAddr::V4(addr), len));
+        }
+        ipv6::Ipv6Addr::parse_bytes_init(bytes).map(|(addr, len)| (IpAddr::V6(addr), len))
+    }
+}
+
+impl Debug for IpSockAddrBuf {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        let sa: &SockAddr = self.as_ref();
+        Debug::fmt(sa, w)
+    }
+}
+
+impl Display for IpSockAddrBuf {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        let sa: &SockAddr = self.as_ref();
+        match sa.addr_type() {
+            AddrType::Ipv4 => Display::fmt(sa.as_ipv4().unwrap(), w),
+            _ => Display::fmt(sa.as_ipv6().unwrap(), w),
         }
     }
 }
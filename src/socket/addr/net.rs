@@ -0,0 +1,232 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+use fmt::{Debug, Display, Write};
+use parse::{Parsable};
+use addr::{IpAddr};
+use addr::ipv4::{Ipv4Addr};
+use addr::ipv6::{Ipv6Addr};
+use addr::text::{parse_dec};
+
+/// An Ip network.
+///
+/// = Remarks
+///
+/// The network consists of an address and a prefix length. The textual representation
+/// is CIDR notation, e.g., `10.0.0.0/8` or `fe80::/10`. The host bits of the address
+/// are retained but ignored by all methods except `addr`.
+#[derive(Copy, Eq)]
+pub struct IpNet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+fn v4_bits(addr: Ipv4Addr) -> u32 {
+    (addr.0 as u32) << 24 | (addr.1 as u32) << 16 | (addr.2 as u32) << 8 | addr.3 as u32
+}
+
+fn v4_from_bits(bits: u32) -> Ipv4Addr {
+    Ipv4Addr((bits >> 24) as u8, (bits >> 16) as u8, (bits >> 8) as u8, bits as u8)
+}
+
+fn v4_mask(prefix_len: u8) -> u32 {
+    match prefix_len {
+        0 => 0,
+        n => !0 << (32 - n as u32),
+    }
+}
+
+fn v6_bits(addr: Ipv6Addr) -> (u64, u64) {
+    let s = addr.to_bytes();
+    let mut hi = 0;
+    let mut lo = 0;
+    for i in 0..4 {
+        hi = hi << 16 | s[i] as u64;
+        lo = lo << 16 | s[i + 4] as u64;
+    }
+    (hi, lo)
+}
+
+fn v6_from_bits((hi, lo): (u64, u64)) -> Ipv6Addr {
+    Ipv6Addr((hi >> 48) as u16, (hi >> 32) as u16, (hi >> 16) as u16, hi as u16,
+             (lo >> 48) as u16, (lo >> 32) as u16, (lo >> 16) as u16, lo as u16)
+}
+
+fn v6_mask(prefix_len: u8) -> (u64, u64) {
+    match prefix_len {
+        0 => (0, 0),
+        n if n <= 64 => (!0 << (64 - n as u32), 0),
+        n => (!0, !0 << (128 - n as u32)),
+    }
+}
+
+impl IpNet {
+    /// Creates a new network.
+    ///
+    /// [argument, addr]
+    /// An address in the network.
+    ///
+    /// [argument, prefix_len]
+    /// The number of leading bits that make up the network part of the address.
+    ///
+    /// = Remarks
+    ///
+    /// This fails if the prefix length exceeds the number of bits in the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Result<IpNet> {
+        let max = match addr {
+            IpAddr::V4(..) => 32,
+            IpAddr::V6(..) => 128,
+        };
+        if prefix_len > max {
+            return Err(error::InvalidArgument);
+        }
+        Ok(IpNet { addr: addr, prefix_len: prefix_len })
+    }
+
+    /// Returns the address the network was created with.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the prefix length.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns the netmask, e.g., `255.0.0.0` for `10.0.0.0/8`.
+    pub fn netmask(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(..) => IpAddr::V4(v4_from_bits(v4_mask(self.prefix_len))),
+            IpAddr::V6(..) => IpAddr::V6(v6_from_bits(v6_mask(self.prefix_len))),
+        }
+    }
+
+    /// Returns the first address of the network.
+    pub fn network(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(a) => {
+                IpAddr::V4(v4_from_bits(v4_bits(a) & v4_mask(self.prefix_len)))
+            },
+            IpAddr::V6(a) => {
+                let (hi, lo) = v6_bits(a);
+                let (mhi, mlo) = v6_mask(self.prefix_len);
+                IpAddr::V6(v6_from_bits((hi & mhi, lo & mlo)))
+            },
+        }
+    }
+
+    /// Returns the last address of the network.
+    ///
+    /// = Remarks
+    ///
+    /// For Ipv4 networks, this is the broadcast address. Ipv6 has no broadcast
+    /// addresses but the last address is returned nonetheless.
+    pub fn broadcast(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(a) => {
+                IpAddr::V4(v4_from_bits(v4_bits(a) | !v4_mask(self.prefix_len)))
+            },
+            IpAddr::V6(a) => {
+                let (hi, lo) = v6_bits(a);
+                let (mhi, mlo) = v6_mask(self.prefix_len);
+                IpAddr::V6(v6_from_bits((hi | !mhi, lo | !mlo)))
+            },
+        }
+    }
+
+    /// Returns whether the network contains an address.
+    ///
+    /// [argument, addr]
+    /// The address to check.
+    ///
+    /// = Remarks
+    ///
+    /// Addresses of the other family are never contained.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.addr, addr) {
+            (IpAddr::V4(a), IpAddr::V4(b)) => {
+                let mask = v4_mask(self.prefix_len);
+                v4_bits(a) & mask == v4_bits(b) & mask
+            },
+            (IpAddr::V6(a), IpAddr::V6(b)) => {
+                let ((ahi, alo), (bhi, blo)) = (v6_bits(a), v6_bits(b));
+                let (mhi, mlo) = v6_mask(self.prefix_len);
+                ahi & mhi == bhi & mhi && alo & mlo == blo & mlo
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns an iterator over all addresses in the network.
+    ///
+    /// = Remarks
+    ///
+    /// The iterator yields all addresses from `network` to `broadcast`, inclusive.
+    pub fn iter(&self) -> IpNetIter {
+        IpNetIter { next: Some(self.network()), last: self.broadcast() }
+    }
+}
+
+/// An iterator over the addresses in a network.
+pub struct IpNetIter {
+    next: Option<IpAddr>,
+    last: IpAddr,
+}
+
+impl Iterator for IpNetIter {
+    type Item = IpAddr;
+    fn next(&mut self) -> Option<IpAddr> {
+        let cur = match self.next {
+            Some(cur) => cur,
+            _ => return None,
+        };
+        self.next = match cur {
+            _ if cur == self.last => None,
+            IpAddr::V4(a) => Some(IpAddr::V4(v4_from_bits(v4_bits(a) + 1))),
+            IpAddr::V6(a) => {
+                let (hi, lo) = v6_bits(a);
+                let next = match lo {
+                    lo if lo == !0 => (hi + 1, 0),
+                    lo => (hi, lo + 1),
+                };
+                Some(IpAddr::V6(v6_from_bits(next)))
+            },
+        };
+        Some(cur)
+    }
+}
+
+impl Debug for IpNet {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        write!(w, "{:?}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl Display for IpNet {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Debug::fmt(self, w)
+    }
+}
+
+impl Parsable for IpNet {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(IpNet, usize)> {
+        let (addr, mut pos) = try!(IpAddr::parse_bytes_init(bytes));
+        if pos == bytes.len() || bytes[pos] != b'/' {
+            return Err(error::InvalidSequence);
+        }
+        pos += 1;
+        let start = pos;
+        while pos < bytes.len() && pos - start < 3 && bytes[pos] >= b'0' &&
+                bytes[pos] <= b'9' {
+            pos += 1;
+        }
+        let prefix_len = try!(parse_dec(&bytes[start..pos], 128));
+        match IpNet::new(addr, prefix_len as u8) {
+            Ok(net) => Ok((net, pos)),
+            _ => Err(error::InvalidSequence),
+        }
+    }
+}
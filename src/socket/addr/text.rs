@@ -0,0 +1,29 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+
+/// Parses a decimal number that makes up all of `bytes`.
+pub fn parse_dec(bytes: &[u8], max: u32) -> Result<u32> {
+    if bytes.len() == 0 {
+        return Err(error::InvalidSequence);
+    }
+    let mut val: u64 = 0;
+    for &b in bytes {
+        match b {
+            b'0'...b'9' => val = val * 10 + (b - b'0') as u64,
+            _ => return Err(error::InvalidSequence),
+        }
+        if val > max as u64 {
+            return Err(error::InvalidSequence);
+        }
+    }
+    Ok(val as u32)
+}
+
+/// Parses a port number that makes up all of `bytes`.
+pub fn parse_port(bytes: &[u8]) -> Result<u16> {
+    parse_dec(bytes, u16::max() as u32).map(|p| p as u16)
+}
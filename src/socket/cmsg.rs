@@ -8,13 +8,17 @@ use core::{mem, slice, ptr};
 use core::ptr::{NoAliasMemPtr};
 use cty::{
     cmsghdr, c_int, SCM_RIGHTS, SCM_CREDENTIALS, SOL_SOCKET, user_size_t,
-    SO_TIMESTAMPNS, timespec, IPPROTO_IP, IP_OPTIONS,
+    SO_TIMESTAMPNS, timespec, IPPROTO_IP, IP_OPTIONS, IP_TTL, IP_PKTINFO, in_pktinfo,
+    IPPROTO_IPV6, IPV6_HOPLIMIT, IPV6_PKTINFO, in6_pktinfo, SCM_TIMESTAMPING,
+    scm_timestamping,
 };
 use cty::alias::{ProcessId, UserId, GroupId};
 use io::{BufRead};
 use fmt::{Debug, Write};
 use time_base::{self, Time};
 use alloc::{self, Dummy, MemPool};
+use addr::ipv4::{Ipv4Addr};
+use addr::ipv6::{Ipv6Addr};
 
 const PTR_MASK: usize = usize::bytes() - 1;
 
@@ -92,6 +96,123 @@ impl Debug for Timestamp {
     }
 }
 
+/// A set of timestamps.
+///
+/// = Remarks
+///
+/// Which timestamps are available depends on the flags passed to `set_timestamping`.
+///
+/// = See also
+///
+/// * link:man:socket(7) and SO_TIMESTAMPING therein
+/// * link:lrs::socket::Socket::set_timestamping
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct Timestamping {
+    data: scm_timestamping,
+}
+
+impl Timestamping {
+    fn get(&self, idx: usize) -> Option<Time> {
+        let ts = self.data.ts[idx];
+        if ts.tv_sec == 0 && ts.tv_nsec == 0 {
+            None
+        } else {
+            Some(time_base::time_from_timespec(ts))
+        }
+    }
+
+    /// Returns the timestamp generated by the kernel, if any.
+    pub fn software(&self) -> Option<Time> {
+        self.get(0)
+    }
+
+    /// Returns the raw timestamp generated by the network adapter, if any.
+    pub fn hardware(&self) -> Option<Time> {
+        self.get(2)
+    }
+}
+
+impl Debug for Timestamping {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        try!(write!(w, "Timestamping {{"));
+        if let Some(t) = self.software() {
+            try!(write!(w, " software: {:?}", t));
+        }
+        if let Some(t) = self.hardware() {
+            try!(write!(w, " hardware: {:?}", t));
+        }
+        write!(w, " }}")
+    }
+}
+
+/// Ipv4 packet information.
+///
+/// = See also
+///
+/// * link:man:ip(7) and IP_PKTINFO therein
+/// * link:lrs::socket::Socket::ipv4_set_receive_packet_info
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct Ipv4PacketInfo {
+    data: in_pktinfo,
+}
+
+impl Ipv4PacketInfo {
+    /// Returns the index of the interface on which the packet was received.
+    pub fn interface(&self) -> u32 {
+        self.data.ipi_ifindex as u32
+    }
+
+    /// Returns the local address the packet was routed to.
+    pub fn local_addr(&self) -> Ipv4Addr {
+        Ipv4Addr::from_be(self.data.ipi_spec_dst.s_addr)
+    }
+
+    /// Returns the destination address in the header of the packet.
+    pub fn dest_addr(&self) -> Ipv4Addr {
+        Ipv4Addr::from_be(self.data.ipi_addr.s_addr)
+    }
+}
+
+impl Debug for Ipv4PacketInfo {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        write!(w, "Ipv4PacketInfo {{ interface: {}, local_addr: {:?}, dest_addr: {:?} }}",
+               self.interface(), self.local_addr(), self.dest_addr())
+    }
+}
+
+/// Ipv6 packet information.
+///
+/// = See also
+///
+/// * link:man:ipv6(7) and IPV6_PKTINFO therein
+/// * link:lrs::socket::Socket::ipv6_set_receive_packet_info
+#[repr(C)]
+#[derive(Pod, Eq)]
+pub struct Ipv6PacketInfo {
+    data: in6_pktinfo,
+}
+
+impl Ipv6PacketInfo {
+    /// Returns the index of the interface on which the packet was received.
+    pub fn interface(&self) -> u32 {
+        self.data.ipi6_ifindex as u32
+    }
+
+    /// Returns the destination address in the header of the packet.
+    pub fn dest_addr(&self) -> Ipv6Addr {
+        Ipv6Addr::from_be_bytes(self.data.ipi6_addr.u6_addr16)
+    }
+}
+
+impl Debug for Ipv6PacketInfo {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        write!(w, "Ipv6PacketInfo {{ interface: {}, dest_addr: {:?} }}",
+               self.interface(), self.dest_addr())
+    }
+}
+
 /// A generic control message.
 #[derive(Copy, Eq)]
 pub enum CMsg<'a> {
@@ -133,6 +254,35 @@ pub enum CMsg<'a> {
     /// [field, 1]
     /// The options.
     Ipv4Options(&'a [u8]),
+
+    /// A set of timestamps.
+    ///
+    /// [field, 1]
+    /// The timestamps.
+    Timestamping(&'a Timestamping),
+
+    /// The hop limit of a received Ipv4 or Ipv6 packet.
+    ///
+    /// [field, 1]
+    /// The hop limit.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_RECVTTL therein
+    /// * link:man:ipv6(7) and IPV6_RECVHOPLIMIT therein
+    HopLimit(u8),
+
+    /// Ipv4 packet information.
+    ///
+    /// [field, 1]
+    /// The packet information.
+    Ipv4PacketInfo(&'a Ipv4PacketInfo),
+
+    /// Ipv6 packet information.
+    ///
+    /// [field, 1]
+    /// The packet information.
+    Ipv6PacketInfo(&'a Ipv6PacketInfo),
 }
 
 impl<'a> Debug for CMsg<'a> {
@@ -143,6 +293,10 @@ impl<'a> Debug for CMsg<'a> {
             CMsg::Credentials(c) => write!(w, "{:?}", c),
             CMsg::Timestamp(c) => write!(w, "{:?}", c),
             CMsg::Ipv4Options(c) => write!(w, "{:?}", c),
+            CMsg::Timestamping(c) => write!(w, "{:?}", c),
+            CMsg::HopLimit(c) => write!(w, "HopLimit({})", c),
+            CMsg::Ipv4PacketInfo(c) => write!(w, "{:?}", c),
+            CMsg::Ipv6PacketInfo(c) => write!(w, "{:?}", c),
         }
     }
 }
@@ -338,6 +492,46 @@ impl<'a> CMsgIter<'a> {
     fn ipv4_options(&self, len: usize) -> Option<CMsg<'a>> {
         Some(CMsg::Ipv4Options(&self.data[..len]))
     }
+
+    fn timestamping(&self, len: usize) -> Option<CMsg<'a>> {
+        if len != mem::size_of::<Timestamping>() {
+            return None;
+        }
+        unsafe {
+            let ptr = self.data.as_ptr() as *const Timestamping;
+            Some(CMsg::Timestamping(&*ptr))
+        }
+    }
+
+    fn hop_limit(&self, len: usize) -> Option<CMsg<'a>> {
+        if len != mem::size_of::<c_int>() {
+            return None;
+        }
+        unsafe {
+            let ptr = self.data.as_ptr() as *const c_int;
+            Some(CMsg::HopLimit(*ptr as u8))
+        }
+    }
+
+    fn ipv4_packet_info(&self, len: usize) -> Option<CMsg<'a>> {
+        if len != mem::size_of::<Ipv4PacketInfo>() {
+            return None;
+        }
+        unsafe {
+            let ptr = self.data.as_ptr() as *const Ipv4PacketInfo;
+            Some(CMsg::Ipv4PacketInfo(&*ptr))
+        }
+    }
+
+    fn ipv6_packet_info(&self, len: usize) -> Option<CMsg<'a>> {
+        if len != mem::size_of::<Ipv6PacketInfo>() {
+            return None;
+        }
+        unsafe {
+            let ptr = self.data.as_ptr() as *const Ipv6PacketInfo;
+            Some(CMsg::Ipv6PacketInfo(&*ptr))
+        }
+    }
 }
 
 impl<'a> Iterator for CMsgIter<'a> {
@@ -369,8 +563,13 @@ impl<'a> Iterator for CMsgIter<'a> {
             (SOL_SOCKET, SCM_RIGHTS) => self.fds(data_len),
             (SOL_SOCKET, SCM_CREDENTIALS) => self.credentials(data_len),
             (SOL_SOCKET, SO_TIMESTAMPNS) => self.timestamp(data_len),
+            (SOL_SOCKET, SCM_TIMESTAMPING) => self.timestamping(data_len),
             (IPPROTO_IP, IP_OPTIONS) => self.ipv4_options(data_len),
-            _ => Some(CMsg::Unknown(&self.data[..len])),
+            (IPPROTO_IP, IP_TTL) => self.hop_limit(data_len),
+            (IPPROTO_IP, IP_PKTINFO) => self.ipv4_packet_info(data_len),
+            (IPPROTO_IPV6, IPV6_HOPLIMIT) => self.hop_limit(data_len),
+            (IPPROTO_IPV6, IPV6_PKTINFO) => self.ipv6_packet_info(data_len),
+            _ => Some(CMsg::Unknown(&self.data[..data_len])),
         };
         if rv.is_none() {
             self.data.consume(self.data.len());
@@ -0,0 +1,33 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+use addr::{SockAddr, AddrType};
+use socket::{Socket};
+use domain::{self, Domain};
+use kind::{Kind};
+use flags::{SOCK_CLOSE_ON_EXEC};
+
+/// Creates a socket in the domain of an Ip address.
+///
+/// [argument, addr]
+/// The address the socket will be bound or connected to.
+///
+/// [argument, kind]
+/// The kind of the socket.
+///
+/// = Remarks
+///
+/// If the address is not an Ipv4 or Ipv6 address, an error is returned.
+pub fn socket_for<A: ?Sized>(addr: &A, kind: Kind) -> Result<Socket>
+    where A: AsRef<SockAddr>,
+{
+    let domain: Domain = match addr.as_ref().addr_type() {
+        AddrType::Ipv4 => domain::Ipv4,
+        AddrType::Ipv6 => domain::Ipv6,
+        AddrType::Unix => return Err(error::InvalidArgument),
+    };
+    Socket::new(domain, kind, 0, SOCK_CLOSE_ON_EXEC)
+}
@@ -20,11 +20,17 @@ extern crate lrs_alloc      as alloc;
 extern crate lrs_saturating as saturating;
 extern crate lrs_io         as io;
 extern crate lrs_syscall    as syscall;
+extern crate lrs_parse      as parse;
+extern crate lrs_poll       as poll;
 
-pub use addr::{SockAddr, AddrType};
+pub use addr::{SockAddr, AddrType, IpAddr, IpSockAddrBuf};
 pub use addr::unix::{UnixSockAddr, UnixAddrType};
 pub use addr::ipv4::{Ipv4Addr, Ipv4SockAddr, IPV4_SOCK_ADDR_SIZE};
 pub use addr::ipv6::{Ipv6Addr, Ipv6SockAddr, IPV6_SOCK_ADDR_SIZE, Ipv6Scope};
+pub use addr::net::{IpNet, IpNetIter};
+pub use tcp::{TcpListener, TcpStream, KeepAlive, TcpInfo};
+pub use udp::{UdpSocket};
+pub use unix::{UnixListener, UnixStream};
 
 mod std { pub use fmt::std::*; pub use cty; }
 
@@ -37,3 +43,9 @@ pub mod ip_proto;
 pub mod nl_proto;
 pub mod domain;
 pub mod msg;
+pub mod mmsg;
+pub mod tcp;
+pub mod udp;
+pub mod unix;
+
+mod conn;
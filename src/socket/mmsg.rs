@@ -0,0 +1,326 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use core::{mem, cmp};
+use cty::{msghdr, mmsghdr, c_void, c_int, c_uint, iovec, MSG_NOSIGNAL, MSG_DONTWAIT};
+use syscall::{sendmmsg, recvmmsg};
+use fd::{FdContainer};
+use rv::{retry};
+use saturating::{SaturatingCast};
+use time_base::{self, Time};
+use addr::{self, SockAddr};
+use cmsg::{CMsgIter};
+use socket::{Socket};
+use msg::{MsgFlags};
+
+/// The maximum number of messages passed to the kernel in a single system call.
+pub const BATCH_SIZE: usize = 64;
+
+const PTR_MASK: usize = usize::bytes() - 1;
+
+fn ptr_or_null(buf: &[u8]) -> *mut c_void {
+    match buf.len() {
+        0 => 0 as *mut c_void,
+        _ => buf.as_ptr() as *mut c_void,
+    }
+}
+
+/// A message that is sent as part of a batch.
+///
+/// = See also
+///
+/// * link:lrs::socket::Socket::send_many
+pub struct SendMsg<'a> {
+    buf: &'a [&'a [u8]],
+    addr: &'a [u8],
+    ctrl: &'a [u8],
+    len: usize,
+}
+
+impl<'a> SendMsg<'a> {
+    /// Creates a new message.
+    ///
+    /// [argument, buf]
+    /// The buffers containing the message.
+    pub fn new(buf: &'a [&'a [u8]]) -> SendMsg<'a> {
+        SendMsg { buf: buf, addr: &[], ctrl: &[], len: 0 }
+    }
+
+    /// Sets the destination of the message.
+    ///
+    /// [argument, addr]
+    /// The destination of the message.
+    ///
+    /// = Remarks
+    ///
+    /// This must not be used on connected sockets.
+    pub fn set_addr<A: ?Sized>(&mut self, addr: &'a A)
+        where A: AsRef<[u8]>,
+    {
+        self.addr = addr.as_ref();
+    }
+
+    /// Sets the control messages sent with the message.
+    ///
+    /// [argument, ctrl]
+    /// The control messages, e.g., a `CMsgBuf`.
+    pub fn set_ctrl<C: ?Sized>(&mut self, ctrl: &'a C)
+        where C: AsRef<[u8]>,
+    {
+        self.ctrl = ctrl.as_ref();
+    }
+
+    /// Returns the number of bytes sent.
+    ///
+    /// = Remarks
+    ///
+    /// This is only meaningful if the message was part of the messages reported as sent
+    /// by `send_many`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    fn header(&self) -> mmsghdr {
+        mmsghdr {
+            msg_hdr: msghdr {
+                msg_name:       ptr_or_null(self.addr),
+                msg_namelen:    self.addr.len().saturating_cast(),
+                msg_iov:        self.buf.as_ptr() as *mut iovec,
+                msg_iovlen:     self.buf.len().saturating_cast(),
+                msg_control:    ptr_or_null(self.ctrl),
+                msg_controllen: self.ctrl.len().saturating_cast(),
+                msg_flags:      0,
+            },
+            msg_len: 0,
+        }
+    }
+}
+
+/// A message that is received as part of a batch.
+///
+/// = See also
+///
+/// * link:lrs::socket::Socket::recv_many
+pub struct RecvMsg<'a> {
+    buf: &'a mut [&'a mut [u8]],
+    addr: &'a mut [u8],
+    ctrl: &'a mut [u8],
+    len: usize,
+    addr_len: usize,
+    ctrl_len: usize,
+    flags: MsgFlags,
+}
+
+impl<'a> RecvMsg<'a> {
+    /// Creates a new message.
+    ///
+    /// [argument, buf]
+    /// The buffers in which the message will be stored.
+    pub fn new(buf: &'a mut [&'a mut [u8]]) -> RecvMsg<'a> {
+        RecvMsg {
+            buf: buf,
+            addr: &mut [],
+            ctrl: &mut [],
+            len: 0,
+            addr_len: 0,
+            ctrl_len: 0,
+            flags: MsgFlags(0),
+        }
+    }
+
+    /// Sets the buffer in which the address of the sender will be stored.
+    ///
+    /// [argument, addr]
+    /// The buffer in which the address of the sender will be stored.
+    pub fn set_addr(&mut self, addr: &'a mut [u8]) {
+        self.addr = addr;
+    }
+
+    /// Sets the buffer in which the control messages will be stored.
+    ///
+    /// [argument, ctrl]
+    /// The buffer in which the control messages will be stored.
+    ///
+    /// = Remarks
+    ///
+    /// If the buffer is not properly aligned, the unaligned prefix is not used.
+    pub fn set_ctrl(&mut self, ctrl: &'a mut [u8]) {
+        let ptr = ctrl.as_ptr() as usize;
+        let pad = ((ptr + PTR_MASK) & !PTR_MASK) - ptr;
+        self.ctrl = match pad <= ctrl.len() {
+            true => &mut ctrl[pad..],
+            _ => &mut [],
+        };
+    }
+
+    /// Returns the number of bytes received.
+    ///
+    /// = Remarks
+    ///
+    /// If the `MSG_REAL_SIZE` flag was passed to `recv_many`, this can be larger than
+    /// the size of the buffers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the address of the sender.
+    ///
+    /// [return_value]
+    /// Returns the address of the sender or `None` if no address buffer has been set or
+    /// the address type is not supported.
+    pub fn addr(&self) -> Option<&SockAddr> {
+        let buf = &self.addr[..self.addr_len];
+        match addr::type_supported(buf) {
+            true => unsafe { Some(SockAddr::from_bytes_unchecked(buf)) },
+            _ => None,
+        }
+    }
+
+    /// Returns an iterator over the received control messages.
+    pub fn ctrl(&self) -> CMsgIter {
+        let buf: &[u8] = match self.ctrl_len {
+            0 => { let empty: &[usize] = &[]; empty.as_ref() },
+            n => &self.ctrl[..n],
+        };
+        CMsgIter::new(buf).unwrap()
+    }
+
+    /// Returns the flags of the received message.
+    pub fn flags(&self) -> MsgFlags {
+        self.flags
+    }
+
+    fn header(&mut self) -> mmsghdr {
+        mmsghdr {
+            msg_hdr: msghdr {
+                msg_name:       ptr_or_null(self.addr),
+                msg_namelen:    self.addr.len().saturating_cast(),
+                msg_iov:        self.buf.as_mut_ptr() as *mut iovec,
+                msg_iovlen:     self.buf.len().saturating_cast(),
+                msg_control:    ptr_or_null(self.ctrl),
+                msg_controllen: self.ctrl.len().saturating_cast(),
+                msg_flags:      0,
+            },
+            msg_len: 0,
+        }
+    }
+
+    fn update(&mut self, hdr: &mmsghdr) {
+        self.len = hdr.msg_len as usize;
+        self.addr_len = cmp::min(hdr.msg_hdr.msg_namelen as usize, self.addr.len());
+        self.ctrl_len = cmp::min(hdr.msg_hdr.msg_controllen as usize, self.ctrl.len());
+        self.flags = MsgFlags(hdr.msg_hdr.msg_flags as c_int);
+    }
+}
+
+impl Socket {
+    /// Sends multiple messages.
+    ///
+    /// [argument, msgs]
+    /// The messages to send.
+    ///
+    /// [argument, flags]
+    /// Flags that can change the behavior of the operation.
+    ///
+    /// [return_value]
+    /// Returns the number of messages sent.
+    ///
+    /// = Remarks
+    ///
+    /// The messages are passed to the kernel in batches of `BATCH_SIZE` messages. The
+    /// number of bytes sent is stored in each message. If fewer messages than requested
+    /// were sent, the remaining messages can be sent with another call. An error is only
+    /// returned if no message could be sent.
+    ///
+    /// = See also
+    ///
+    /// * link:man:sendmmsg(2)
+    pub fn send_many(&self, msgs: &mut [SendMsg], flags: MsgFlags) -> Result<usize> {
+        let flags = (flags.0 | MSG_NOSIGNAL) as c_uint;
+        let mut hdrs: [mmsghdr; BATCH_SIZE] = mem::zeroed();
+        let total = msgs.len();
+        let mut done = 0;
+        while done < total {
+            let chunk = &mut msgs[done..cmp::min(done + BATCH_SIZE, total)];
+            for i in 0..chunk.len() {
+                hdrs[i] = chunk[i].header();
+            }
+            let hdrs = &hdrs[..chunk.len()];
+            let num = match retry(|| sendmmsg(self.borrow(), hdrs, flags)) {
+                Ok(n) => n as usize,
+                Err(e) => if done > 0 { break } else { return Err(e) },
+            };
+            for i in 0..num {
+                chunk[i].len = hdrs[i].msg_len as usize;
+            }
+            done += num;
+            if num < chunk.len() {
+                break;
+            }
+        }
+        Ok(done)
+    }
+
+    /// Receives multiple messages.
+    ///
+    /// [argument, msgs]
+    /// The messages in which the received data will be stored.
+    ///
+    /// [argument, flags]
+    /// Flags that can change the behavior of the operation.
+    ///
+    /// [argument, timeout]
+    /// The maximum time to wait for messages or `None` to wait until all messages have
+    /// been received.
+    ///
+    /// [return_value]
+    /// Returns the number of messages received.
+    ///
+    /// = Remarks
+    ///
+    /// The length, address of the sender, control messages, and flags of each received
+    /// message are stored in the message. The messages are passed to the kernel in
+    /// batches of `BATCH_SIZE` messages. Only the first batch blocks; the following
+    /// batches only receive messages which are already queued.
+    ///
+    /// The timeout is only checked after a message has been received. Use the
+    /// `MSG_WAIT_FOR_ONE` flag to return as soon as at least one message is available.
+    ///
+    /// An error is only returned if no message has been received.
+    ///
+    /// = See also
+    ///
+    /// * link:man:recvmmsg(2)
+    pub fn recv_many(&self, msgs: &mut [RecvMsg], flags: MsgFlags,
+                     timeout: Option<Time>) -> Result<usize> {
+        let mut flags = flags.0 as c_uint;
+        let mut timeout = timeout.map(|t| time_base::time_to_timespec(t));
+        let mut hdrs: [mmsghdr; BATCH_SIZE] = mem::zeroed();
+        let total = msgs.len();
+        let mut done = 0;
+        while done < total {
+            let chunk = &mut msgs[done..cmp::min(done + BATCH_SIZE, total)];
+            for i in 0..chunk.len() {
+                hdrs[i] = chunk[i].header();
+            }
+            let hdrs = &mut hdrs[..chunk.len()];
+            let res = retry(|| recvmmsg(self.borrow(), hdrs, flags, timeout.as_mut()));
+            let num = match res {
+                Ok(n) => n as usize,
+                Err(e) => if done > 0 { break } else { return Err(e) },
+            };
+            for i in 0..num {
+                chunk[i].update(&hdrs[i]);
+            }
+            done += num;
+            if num < chunk.len() {
+                break;
+            }
+            flags |= MSG_DONTWAIT as c_uint;
+            timeout = None;
+        }
+        Ok(done)
+    }
+}
@@ -231,6 +231,16 @@ create! {
     #[doc = "* link:man:recvmsg(2) and MSG_WAITALL therein"]
     flag MSG_WAIT_ALL = cty::MSG_WAITALL;
 
+    #[doc = "Returns after the first message has been received."]
+    #[doc = ""]
+    #[doc = "= Remarks"]
+    #[doc = ""]
+    #[doc = "This can be used with `recv_many`. After the first message has been received,"]
+    #[doc = "the call no longer blocks and only returns messages that are already queued."]
+    #[doc = ""]
+    #[doc = "= See also"]
+    #[doc = ""]
+    #[doc = "* link:man:recvmmsg(2) and MSG_WAITFORONE therein"]
     flag MSG_WAIT_FOR_ONE = cty::MSG_WAITFORONE;
 
     #[doc = "Marks truncated control messages."]
@@ -12,25 +12,31 @@ use cty::{
     SO_SNDTIMEO, SO_RCVTIMEO, SO_SNDBUF, SO_SNDBUFFORCE, SO_RCVBUF, SO_RCVBUFFORCE,
     timeval, SO_PRIORITY, SO_PEERCRED, SO_PEEK_OFF, SO_PASSCRED, SO_OOBINLINE,
     SO_MARK, SO_LINGER, SO_DONTROUTE, SO_KEEPALIVE, k_int, SO_ERROR, linger, INT_MAX,
-    SO_PROTOCOL, SO_TIMESTAMPNS, SO_TYPE,
+    SO_PROTOCOL, SO_TIMESTAMPNS, SO_TYPE, SO_TIMESTAMPING, c_uint,
     IPPROTO_IP, IP_ADD_MEMBERSHIP, IP_DROP_MEMBERSHIP, IP_ADD_SOURCE_MEMBERSHIP,
     IP_DROP_SOURCE_MEMBERSHIP, IP_MULTICAST_ALL, IP_MULTICAST_TTL, IP_BLOCK_SOURCE,
     IP_UNBLOCK_SOURCE, in_addr, IP_RECVOPTS, IP_RETOPTS, IP_MULTICAST_LOOP, ip_mreqn,
-    IP_OPTIONS, IP_TTL, IP_MTU, IP_HDRINCL, timespec, ip_mreq_source,
+    IP_OPTIONS, IP_TTL, IP_MTU, IP_HDRINCL, timespec, ip_mreq_source, IP_TOS, IP_RECVTTL,
+    IP_PKTINFO, IP_TRANSPARENT,
     IPPROTO_IPV6, IPV6_MTU, IPV6_MULTICAST_HOPS, IPV6_ADD_MEMBERSHIP,
     IPV6_DROP_MEMBERSHIP, IPV6_UNICAST_HOPS, IPV6_V6ONLY, AF_INET, IPV6_ADDRFORM,
-    ipv6_mreq, in6_addr, IPV6_MULTICAST_LOOP,
-    IPPROTO_TCP, TCP_CORK, IPPROTO_UDP, UDP_CORK,
+    ipv6_mreq, in6_addr, IPV6_MULTICAST_LOOP, IPV6_RECVHOPLIMIT, IPV6_RECVPKTINFO,
+    IPPROTO_TCP, TCP_CORK, TCP_NODELAY, TCP_KEEPIDLE, TCP_KEEPINTVL, TCP_KEEPCNT,
+    TCP_USER_TIMEOUT, TCP_FASTOPEN, TCP_CONGESTION, TCP_INFO, tcp_info,
+    IPPROTO_UDP, UDP_CORK,
+    SOL_NETLINK, NETLINK_ADD_MEMBERSHIP, NETLINK_DROP_MEMBERSHIP, ifreq,
 };
 use time_base::{Time};
 use core::{slice, mem};
 use syscall::{
     socket, bind, getsockname, getpeername, connect, close, shutdown, listen, sendto,
     sendmsg, recvfrom, recvmsg, getsockopt, setsockopt, ioctl_siocgstampns, ioctl_siocinq,
-    ioctl_siocoutq, accept4,
+    ioctl_siocoutq, accept4, ioctl_siocgifindex,
 };
 use str_one::{CStr, NoNullStr};
 use fd::{FdContainer};
+use fd::flags::{FD_DONT_BLOCK};
+use poll::{Epoll, POLL_WRITE, EMPTY_EVENT};
 use rv::{retry};
 use saturating::{SaturatingCast};
 
@@ -38,6 +44,7 @@ use addr::{self, SockAddr};
 use addr::ipv4::{Ipv4Addr};
 use addr::ipv6::{Ipv6Addr};
 use cmsg::{CMsgIter, Credentials};
+use tcp::{TcpInfo};
 use time_base::{self};
 
 use ip_proto::{self};
@@ -332,6 +339,53 @@ impl Socket {
         rv!(connect(self.fd, addr.as_ref().as_ref()))
     }
 
+    /// Connects this socket to a peer with a timeout.
+    ///
+    /// [argument, addr]
+    /// The address of the peer.
+    ///
+    /// [argument, timeout]
+    /// The maximum time to wait for the connection to be established.
+    ///
+    /// = Remarks
+    ///
+    /// The connection is initiated in non-blocking mode and the socket is then polled
+    /// for writability. If the connection has not been established when the timeout
+    /// expires, `TimedOut` is returned. The blocking mode of the socket is restored
+    /// before this method returns.
+    ///
+    /// = See also
+    ///
+    /// * link:man:connect(2) and EINPROGRESS therein
+    /// * link:lrs::socket::Socket::connect
+    pub fn connect_timeout<A: ?Sized>(&self, addr: &A, timeout: Time) -> Result
+        where A: AsRef<[u8]>,
+    {
+        let flags = try!(self.description_flags());
+        try!(self.set_description_flags(flags | FD_DONT_BLOCK));
+        let res = self.connect_non_blocking(addr.as_ref(), timeout);
+        let restored = self.set_description_flags(flags);
+        try!(res);
+        restored
+    }
+
+    fn connect_non_blocking(&self, addr: &[u8], timeout: Time) -> Result {
+        match self.connect(addr) {
+            Err(error::OperationInitiated) => { },
+            res => return res,
+        }
+        let epoll = try!(Epoll::new());
+        try!(epoll.add(self, POLL_WRITE));
+        let mut events = [EMPTY_EVENT];
+        if try!(epoll.wait_timeout(&mut events, timeout)).len() == 0 {
+            return Err(error::TimedOut);
+        }
+        match try!(self.error()) {
+            Errno(0) => Ok(()),
+            e => Err(e),
+        }
+    }
+
     /// Disconnects a connected, connection-less socket.
     ///
     /// = Remarks
@@ -803,6 +857,34 @@ impl Socket {
         rv!(setsockopt(self.fd, SOL_SOCKET, SO_BINDTODEVICE, cstr))
     }
 
+    /// Retrieves the index of a network interface.
+    ///
+    /// [argument, device]
+    /// The name of the interface.
+    ///
+    /// = Remarks
+    ///
+    /// This works with any kind of socket. The index can be used as the scope id of
+    /// Ipv6 socket addresses.
+    ///
+    /// = See also
+    ///
+    /// * link:man:netdevice(7) and SIOCGIFINDEX therein
+    pub fn interface_index<D: ?Sized>(&self, device: &D) -> Result<u32>
+        where D: TryAsRef<NoNullStr>,
+    {
+        let mut req: ifreq = mem::zeroed();
+        let name: &[u8] = try!(device.try_as_ref()).as_ref();
+        if req.ifr_name.len() < name.len() + 1 {
+            return Err(error::InvalidArgument);
+        }
+        mem::copy::<d8>(req.ifr_name.as_mut(), name.as_ref());
+        try!(rv!(ioctl_siocgifindex(self.fd, &mut req)));
+        let mut index: c_int = 0;
+        mem::copy::<d8>(index.as_mut(), req.ifr_ifru.as_ref());
+        Ok(index as u32)
+    }
+
     /// Retrieves the name of the device this socket is bound to, if any.
     ///
     /// [argumnet, buf]
@@ -1502,6 +1584,41 @@ impl Socket {
         self.set_bool(SOL_SOCKET, SO_TIMESTAMPNS, val)
     }
 
+    /// Retrieves the flags of the `timestamping` option.
+    ///
+    /// [return_value]
+    /// Returns the `SOF_TIMESTAMPING_*` flags set on this socket.
+    ///
+    /// = See also
+    ///
+    /// * link:man:socket(7) and SO_TIMESTAMPING therein
+    /// * link:lrs::socket::Socket::set_timestamping
+    pub fn timestamping(&self) -> Result<c_uint> {
+        let mut val: c_uint = 0;
+        try!(rv!(getsockopt(self.fd, SOL_SOCKET, SO_TIMESTAMPING, val.as_mut(),
+                            &mut 0)));
+        Ok(val)
+    }
+
+    /// Sets the flags of the `timestamping` option.
+    ///
+    /// [argument, flags]
+    /// The `SOF_TIMESTAMPING_*` flags from `lrs::cty`.
+    ///
+    /// = Remarks
+    ///
+    /// If reporting flags are set, received messages are accompanied by a
+    /// `Timestamping` control message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:socket(7) and SO_TIMESTAMPING therein
+    /// * link:lrs::socket::Socket::timestamping
+    /// * link:lrs::socket::cmsg::CMsg
+    pub fn set_timestamping(&self, flags: c_uint) -> Result {
+        rv!(setsockopt(self.fd, SOL_SOCKET, SO_TIMESTAMPING, flags.as_ref()))
+    }
+
     /// Retrieves the kind of the socket.
     ///
     /// [return_value]
@@ -1605,6 +1722,136 @@ impl Socket {
         rv!(setsockopt(self.fd, IPPROTO_IP, IP_TTL, val.as_ref()))
     }
 
+    /// Retrieves the type-of-service field of Ipv4 packets sent over this socket.
+    ///
+    /// [return_value]
+    /// Returns the type-of-service field of Ipv4 packets sent over this socket.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_TOS therein
+    /// * link:lrs::socket::Socket::ipv4_set_type_of_service
+    pub fn ipv4_type_of_service(&self) -> Result<u8> {
+        let mut val: c_int = 0;
+        try!(rv!(getsockopt(self.fd, IPPROTO_IP, IP_TOS, val.as_mut(), &mut 0)));
+        Ok(val as u8)
+    }
+
+    /// Sets the type-of-service field of Ipv4 packets sent over this socket.
+    ///
+    /// [argument, val]
+    /// The type-of-service field of Ipv4 packets sent over this socket.
+    ///
+    /// = Remarks
+    ///
+    /// The upper six bits contain the DSCP and the lower two bits the ECN field. The
+    /// kernel controls the ECN bits of Tcp sockets.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_TOS therein
+    /// * link:lrs::socket::Socket::ipv4_type_of_service
+    pub fn ipv4_set_type_of_service(&self, val: u8) -> Result {
+        let val = val as c_int;
+        rv!(setsockopt(self.fd, IPPROTO_IP, IP_TOS, val.as_ref()))
+    }
+
+    /// Retrieves whether all received messages are accompanied by a hop limit control
+    /// message.
+    ///
+    /// [return_value]
+    /// Returns whether all received messages are accompanied by a hop limit control
+    /// message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_RECVTTL therein
+    /// * link:lrs::socket::Socket::ipv4_set_receive_hop_limit
+    pub fn ipv4_is_receive_hop_limit(&self) -> Result<bool> {
+        self.get_bool(IPPROTO_IP, IP_RECVTTL)
+    }
+
+    /// Sets whether all received messages are accompanied by a hop limit control
+    /// message.
+    ///
+    /// [argument, val]
+    /// Whether all received messages are accompanied by a hop limit control message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_RECVTTL therein
+    /// * link:lrs::socket::Socket::ipv4_is_receive_hop_limit
+    /// * link:lrs::socket::cmsg::CMsg
+    pub fn ipv4_set_receive_hop_limit(&self, val: bool) -> Result {
+        self.set_bool(IPPROTO_IP, IP_RECVTTL, val)
+    }
+
+    /// Retrieves whether all received messages are accompanied by a packet info control
+    /// message.
+    ///
+    /// [return_value]
+    /// Returns whether all received messages are accompanied by a packet info control
+    /// message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_PKTINFO therein
+    /// * link:lrs::socket::Socket::ipv4_set_receive_packet_info
+    pub fn ipv4_is_receive_packet_info(&self) -> Result<bool> {
+        self.get_bool(IPPROTO_IP, IP_PKTINFO)
+    }
+
+    /// Sets whether all received messages are accompanied by a packet info control
+    /// message.
+    ///
+    /// [argument, val]
+    /// Whether all received messages are accompanied by a packet info control message.
+    ///
+    /// = Remarks
+    ///
+    /// The control message contains the interface on which the packet was received
+    /// and its destination address.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_PKTINFO therein
+    /// * link:lrs::socket::Socket::ipv4_is_receive_packet_info
+    /// * link:lrs::socket::cmsg::Ipv4PacketInfo
+    pub fn ipv4_set_receive_packet_info(&self, val: bool) -> Result {
+        self.set_bool(IPPROTO_IP, IP_PKTINFO, val)
+    }
+
+    /// Retrieves whether this socket is transparent.
+    ///
+    /// [return_value]
+    /// Returns whether this socket is transparent.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_TRANSPARENT therein
+    /// * link:lrs::socket::Socket::ipv4_set_transparent
+    pub fn ipv4_is_transparent(&self) -> Result<bool> {
+        self.get_bool(IPPROTO_IP, IP_TRANSPARENT)
+    }
+
+    /// Sets whether this socket is transparent.
+    ///
+    /// [argument, val]
+    /// Whether this socket is transparent.
+    ///
+    /// = Remarks
+    ///
+    /// A transparent socket can bind to and send from non-local addresses. This is
+    /// used by transparent proxies and requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ip(7) and IP_TRANSPARENT therein
+    /// * link:lrs::socket::Socket::ipv4_is_transparent
+    pub fn ipv4_set_transparent(&self, val: bool) -> Result {
+        self.set_bool(IPPROTO_IP, IP_TRANSPARENT, val)
+    }
+
     /// Retrieves whether packets sent over this socket already have a header included.
     ///
     /// [return_value]
@@ -2272,6 +2519,66 @@ impl Socket {
         rv!(setsockopt(self.fd, IPPROTO_IPV6, IPV6_UNICAST_HOPS, val.as_ref()))
     }
 
+    /// Retrieves whether all received messages are accompanied by a hop limit control
+    /// message.
+    ///
+    /// [return_value]
+    /// Returns whether all received messages are accompanied by a hop limit control
+    /// message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ipv6(7) and IPV6_RECVHOPLIMIT therein
+    /// * link:lrs::socket::Socket::ipv6_set_receive_hop_limit
+    pub fn ipv6_is_receive_hop_limit(&self) -> Result<bool> {
+        self.get_bool(IPPROTO_IPV6, IPV6_RECVHOPLIMIT)
+    }
+
+    /// Sets whether all received messages are accompanied by a hop limit control
+    /// message.
+    ///
+    /// [argument, val]
+    /// Whether all received messages are accompanied by a hop limit control message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ipv6(7) and IPV6_RECVHOPLIMIT therein
+    /// * link:lrs::socket::Socket::ipv6_is_receive_hop_limit
+    /// * link:lrs::socket::cmsg::CMsg
+    pub fn ipv6_set_receive_hop_limit(&self, val: bool) -> Result {
+        self.set_bool(IPPROTO_IPV6, IPV6_RECVHOPLIMIT, val)
+    }
+
+    /// Retrieves whether all received messages are accompanied by a packet info control
+    /// message.
+    ///
+    /// [return_value]
+    /// Returns whether all received messages are accompanied by a packet info control
+    /// message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ipv6(7) and IPV6_RECVPKTINFO therein
+    /// * link:lrs::socket::Socket::ipv6_set_receive_packet_info
+    pub fn ipv6_is_receive_packet_info(&self) -> Result<bool> {
+        self.get_bool(IPPROTO_IPV6, IPV6_RECVPKTINFO)
+    }
+
+    /// Sets whether all received messages are accompanied by a packet info control
+    /// message.
+    ///
+    /// [argument, val]
+    /// Whether all received messages are accompanied by a packet info control message.
+    ///
+    /// = See also
+    ///
+    /// * link:man:ipv6(7) and IPV6_RECVPKTINFO therein
+    /// * link:lrs::socket::Socket::ipv6_is_receive_packet_info
+    /// * link:lrs::socket::cmsg::Ipv6PacketInfo
+    pub fn ipv6_set_receive_packet_info(&self, val: bool) -> Result {
+        self.set_bool(IPPROTO_IPV6, IPV6_RECVPKTINFO, val)
+    }
+
     /// Retrieves whether this socket only handles Ipv6 packets.
     ///
     /// [return_value]
@@ -2330,6 +2637,295 @@ impl Socket {
         self.set_bool(IPPROTO_TCP, TCP_CORK, val)
     }
 
+    /// Retrieves whether Nagle's algorithm is disabled.
+    ///
+    /// [return_value]
+    /// Returns whether Nagle's algorithm is disabled.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_NODELAY therein
+    /// * link:lrs::socket::Socket::tcp_set_no_delay
+    pub fn tcp_is_no_delay(&self) -> Result<bool> {
+        self.get_bool(IPPROTO_TCP, TCP_NODELAY)
+    }
+
+    /// Disables or enables Nagle's algorithm.
+    ///
+    /// [argument, val]
+    /// Whether Nagle's algorithm is disabled.
+    ///
+    /// = Remarks
+    ///
+    /// If Nagle's algorithm is disabled, small segments are sent immediately instead of
+    /// being coalesced.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_NODELAY therein
+    /// * link:lrs::socket::Socket::tcp_is_no_delay
+    pub fn tcp_set_no_delay(&self, val: bool) -> Result {
+        self.set_bool(IPPROTO_TCP, TCP_NODELAY, val)
+    }
+
+    fn get_u32(&self, level: c_int, opt: c_int) -> Result<u32> {
+        let mut val: c_int = 0;
+        try!(rv!(getsockopt(self.fd, level, opt, val.as_mut(), &mut 0)));
+        Ok(val as u32)
+    }
+
+    fn set_u32(&self, level: c_int, opt: c_int, val: u32) -> Result {
+        let val: c_int = val.saturating_cast();
+        rv!(setsockopt(self.fd, level, opt, val.as_ref()))
+    }
+
+    /// Retrieves the idle time before keep-alive probes are sent.
+    ///
+    /// [return_value]
+    /// Returns the idle time in seconds.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_KEEPIDLE therein
+    /// * link:lrs::socket::Socket::tcp_set_keep_alive_idle
+    pub fn tcp_keep_alive_idle(&self) -> Result<u32> {
+        self.get_u32(IPPROTO_TCP, TCP_KEEPIDLE)
+    }
+
+    /// Sets the idle time before keep-alive probes are sent.
+    ///
+    /// [argument, seconds]
+    /// The idle time in seconds.
+    ///
+    /// = Remarks
+    ///
+    /// Keep-alive probes are only sent if the `keep_alive` option is set.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_KEEPIDLE therein
+    /// * link:lrs::socket::Socket::tcp_keep_alive_idle
+    /// * link:lrs::socket::Socket::set_keep_alive
+    pub fn tcp_set_keep_alive_idle(&self, seconds: u32) -> Result {
+        self.set_u32(IPPROTO_TCP, TCP_KEEPIDLE, seconds)
+    }
+
+    /// Retrieves the time between two keep-alive probes.
+    ///
+    /// [return_value]
+    /// Returns the interval in seconds.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_KEEPINTVL therein
+    /// * link:lrs::socket::Socket::tcp_set_keep_alive_interval
+    pub fn tcp_keep_alive_interval(&self) -> Result<u32> {
+        self.get_u32(IPPROTO_TCP, TCP_KEEPINTVL)
+    }
+
+    /// Sets the time between two keep-alive probes.
+    ///
+    /// [argument, seconds]
+    /// The interval in seconds.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_KEEPINTVL therein
+    /// * link:lrs::socket::Socket::tcp_keep_alive_interval
+    pub fn tcp_set_keep_alive_interval(&self, seconds: u32) -> Result {
+        self.set_u32(IPPROTO_TCP, TCP_KEEPINTVL, seconds)
+    }
+
+    /// Retrieves the number of unanswered keep-alive probes before the connection is
+    /// dropped.
+    ///
+    /// [return_value]
+    /// Returns the number of probes.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_KEEPCNT therein
+    /// * link:lrs::socket::Socket::tcp_set_keep_alive_count
+    pub fn tcp_keep_alive_count(&self) -> Result<u32> {
+        self.get_u32(IPPROTO_TCP, TCP_KEEPCNT)
+    }
+
+    /// Sets the number of unanswered keep-alive probes before the connection is
+    /// dropped.
+    ///
+    /// [argument, count]
+    /// The number of probes.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_KEEPCNT therein
+    /// * link:lrs::socket::Socket::tcp_keep_alive_count
+    pub fn tcp_set_keep_alive_count(&self, count: u32) -> Result {
+        self.set_u32(IPPROTO_TCP, TCP_KEEPCNT, count)
+    }
+
+    /// Retrieves the maximum time transmitted data may remain unacknowledged.
+    ///
+    /// [return_value]
+    /// Returns the timeout or `None` if the system default is used.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_USER_TIMEOUT therein
+    /// * link:lrs::socket::Socket::tcp_set_user_timeout
+    pub fn tcp_user_timeout(&self) -> Result<Option<Time>> {
+        match try!(self.get_u32(IPPROTO_TCP, TCP_USER_TIMEOUT)) {
+            0 => Ok(None),
+            n => Ok(Some(Time::milliseconds(n as i64))),
+        }
+    }
+
+    /// Sets the maximum time transmitted data may remain unacknowledged.
+    ///
+    /// [argument, val]
+    /// The timeout or `None` to use the system default.
+    ///
+    /// = Remarks
+    ///
+    /// If data remains unacknowledged for longer than the timeout, the connection is
+    /// closed and the `TimedOut` error is returned. The timeout has millisecond
+    /// precision.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_USER_TIMEOUT therein
+    /// * link:lrs::socket::Socket::tcp_user_timeout
+    pub fn tcp_set_user_timeout(&self, val: Option<Time>) -> Result {
+        let val = match val {
+            Some(v) => {
+                let ms = v.seconds.checked_mul(1000).unwrap_or(i64::max());
+                ms.saturating_add(v.nanoseconds / 1_000_000).saturating_cast()
+            },
+            _ => 0,
+        };
+        self.set_u32(IPPROTO_TCP, TCP_USER_TIMEOUT, val)
+    }
+
+    /// Retrieves the length of the queue of pending Tcp Fast Open requests.
+    ///
+    /// [return_value]
+    /// Returns the length of the queue or `0` if Fast Open is disabled.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_FASTOPEN therein
+    /// * link:lrs::socket::Socket::tcp_set_fast_open
+    pub fn tcp_fast_open(&self) -> Result<u32> {
+        self.get_u32(IPPROTO_TCP, TCP_FASTOPEN)
+    }
+
+    /// Enables Tcp Fast Open on a listening socket.
+    ///
+    /// [argument, queue_len]
+    /// The maximum number of pending Fast Open requests.
+    ///
+    /// = Remarks
+    ///
+    /// This must be set before the socket starts listening. Fast Open must also be
+    /// enabled in `/proc/sys/net/ipv4/tcp_fastopen`.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_FASTOPEN therein
+    /// * link:lrs::socket::Socket::tcp_fast_open
+    pub fn tcp_set_fast_open(&self, queue_len: u32) -> Result {
+        self.set_u32(IPPROTO_TCP, TCP_FASTOPEN, queue_len)
+    }
+
+    /// Retrieves the congestion control algorithm of this socket.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the name of the algorithm will be stored.
+    ///
+    /// [return_value]
+    /// Returns the initial part of the buffer that has been filled with the name.
+    ///
+    /// = Remarks
+    ///
+    /// The name uses at most 16 bytes.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_CONGESTION therein
+    /// * link:lrs::socket::Socket::tcp_set_congestion
+    pub fn tcp_congestion<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut [u8]> {
+        let mut len = 0;
+        try!(rv!(getsockopt(self.fd, IPPROTO_TCP, TCP_CONGESTION, buf.as_mut(),
+                            &mut len)));
+        let len = buf[..len].find(|&b| b == 0).unwrap_or(len);
+        Ok(&mut buf[..len])
+    }
+
+    /// Sets the congestion control algorithm of this socket.
+    ///
+    /// [argument, name]
+    /// The name of the algorithm, e.g., `cubic` or `reno`.
+    ///
+    /// = Remarks
+    ///
+    /// Unprivileged processes can only select the algorithms listed in
+    /// `/proc/sys/net/ipv4/tcp_allowed_congestion_control`.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_CONGESTION therein
+    /// * link:lrs::socket::Socket::tcp_congestion
+    pub fn tcp_set_congestion(&self, name: &[u8]) -> Result {
+        rv!(setsockopt(self.fd, IPPROTO_TCP, TCP_CONGESTION, name.as_ref()))
+    }
+
+    /// Retrieves information about the state of a Tcp connection.
+    ///
+    /// [return_value]
+    /// Returns the decoded `TCP_INFO` structure.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tcp(7) and TCP_INFO therein
+    /// * link:lrs::socket::TcpInfo
+    pub fn tcp_info(&self) -> Result<TcpInfo> {
+        let mut info: tcp_info = mem::zeroed();
+        try!(rv!(getsockopt(self.fd, IPPROTO_TCP, TCP_INFO, info.as_mut(), &mut 0)));
+        let us = |v: u32| Time::microseconds(v as i64);
+        let ms = |v: u32| Time::milliseconds(v as i64);
+        Ok(TcpInfo {
+            state:          info.tcpi_state,
+            retransmits:    info.tcpi_retransmits,
+            probes:         info.tcpi_probes,
+            backoff:        info.tcpi_backoff,
+            options:        info.tcpi_options,
+            send_wscale:    info.tcpi_wscale & 0xf,
+            recv_wscale:    info.tcpi_wscale >> 4,
+            rto:            us(info.tcpi_rto),
+            ato:            us(info.tcpi_ato),
+            send_mss:       info.tcpi_snd_mss,
+            recv_mss:       info.tcpi_rcv_mss,
+            unacked:        info.tcpi_unacked,
+            sacked:         info.tcpi_sacked,
+            lost:           info.tcpi_lost,
+            retrans:        info.tcpi_retrans,
+            last_data_sent: ms(info.tcpi_last_data_sent),
+            last_data_recv: ms(info.tcpi_last_data_recv),
+            last_ack_recv:  ms(info.tcpi_last_ack_recv),
+            path_mtu:       info.tcpi_pmtu,
+            recv_ssthresh:  info.tcpi_rcv_ssthresh,
+            rtt:            us(info.tcpi_rtt),
+            rtt_var:        us(info.tcpi_rttvar),
+            send_ssthresh:  info.tcpi_snd_ssthresh,
+            send_cwnd:      info.tcpi_snd_cwnd,
+            advertised_mss: info.tcpi_advmss,
+            reordering:     info.tcpi_reordering,
+            recv_rtt:       us(info.tcpi_rcv_rtt),
+            recv_space:     info.tcpi_rcv_space,
+            total_retrans:  info.tcpi_total_retrans,
+        })
+    }
+
     /// Retrieves the status of the `udp_cork` option.
     ///
     /// [return_value]
@@ -2355,6 +2951,37 @@ impl Socket {
     pub fn udp_set_cork(&self, val: bool) -> Result {
         self.set_bool(IPPROTO_UDP, UDP_CORK, val)
     }
+
+    /// Joins a netlink multicast group.
+    ///
+    /// [argument, group]
+    /// The group to join.
+    ///
+    /// = Remarks
+    ///
+    /// Unlike the `nl_groups` field of the socket address, this accepts groups larger
+    /// than 32.
+    ///
+    /// = See also
+    ///
+    /// * link:man:netlink(7) and NETLINK_ADD_MEMBERSHIP therein
+    /// * link:lrs::socket::Socket::netlink_leave_group
+    pub fn netlink_join_group(&self, group: u32) -> Result {
+        rv!(setsockopt(self.fd, SOL_NETLINK, NETLINK_ADD_MEMBERSHIP, group.as_ref()))
+    }
+
+    /// Leaves a netlink multicast group.
+    ///
+    /// [argument, group]
+    /// The group to leave.
+    ///
+    /// = See also
+    ///
+    /// * link:man:netlink(7) and NETLINK_DROP_MEMBERSHIP therein
+    /// * link:lrs::socket::Socket::netlink_join_group
+    pub fn netlink_leave_group(&self, group: u32) -> Result {
+        rv!(setsockopt(self.fd, SOL_NETLINK, NETLINK_DROP_MEMBERSHIP, group.as_ref()))
+    }
 }
 
 unsafe impl UndefState for Socket {
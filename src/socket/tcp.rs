@@ -0,0 +1,396 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use cty::{c_int};
+use io::{Read, Write};
+use fd::{FdContainer};
+use time_base::{Time};
+use addr::{SockAddr};
+use socket::{Socket};
+use kind::{self};
+use flags::{SOCK_CLOSE_ON_EXEC};
+use msg::{MSG_NONE};
+use conn::{socket_for};
+
+/// The backlog used by the listener constructors.
+pub const DEFAULT_BACKLOG: u32 = 128;
+
+/// A Tcp socket that listens for connections.
+///
+/// = Remarks
+///
+/// The underlying socket can be accessed via the `socket` method to set further
+/// options.
+///
+/// = See also
+///
+/// * link:man:tcp(7)
+pub struct TcpListener {
+    sock: Socket,
+}
+
+impl TcpListener {
+    /// Creates a listener bound to an address.
+    ///
+    /// [argument, addr]
+    /// The Ipv4 or Ipv6 address to listen on.
+    ///
+    /// = Remarks
+    ///
+    /// The `reuse_addr` option is set before the socket is bound so that the address
+    /// can be reused immediately after the listener has been closed. The backlog is
+    /// `DEFAULT_BACKLOG`.
+    pub fn bind<A: ?Sized>(addr: &A) -> Result<TcpListener>
+        where A: AsRef<SockAddr>,
+    {
+        TcpListener::bind_common(addr, false)
+    }
+
+    /// Creates a listener bound to an address that can be shared with other sockets.
+    ///
+    /// [argument, addr]
+    /// The Ipv4 or Ipv6 address to listen on.
+    ///
+    /// = Remarks
+    ///
+    /// This is like `bind` but additionally sets the `reuse_port` option. Incoming
+    /// connections are distributed between all listeners bound to the same address.
+    ///
+    /// = See also
+    ///
+    /// * link:man:socket(7) and SO_REUSEPORT therein
+    pub fn bind_reuse_port<A: ?Sized>(addr: &A) -> Result<TcpListener>
+        where A: AsRef<SockAddr>,
+    {
+        TcpListener::bind_common(addr, true)
+    }
+
+    fn bind_common<A: ?Sized>(addr: &A, reuse_port: bool) -> Result<TcpListener>
+        where A: AsRef<SockAddr>,
+    {
+        let sock = try!(socket_for(addr, kind::Stream));
+        try!(sock.set_reuse_addr(true));
+        if reuse_port {
+            try!(sock.set_reuse_port(true));
+        }
+        let addr: &SockAddr = addr.as_ref();
+        try!(sock.bind(addr));
+        try!(sock.listen(DEFAULT_BACKLOG));
+        Ok(TcpListener { sock: sock })
+    }
+
+    /// Accepts a connection.
+    pub fn accept(&self) -> Result<TcpStream> {
+        let sock = try!(self.sock.accept(SOCK_CLOSE_ON_EXEC));
+        Ok(TcpStream { sock: sock })
+    }
+
+    /// Accepts a connection and returns the address of the peer.
+    ///
+    /// [argument, addr]
+    /// A buffer in which the address of the peer will be stored.
+    ///
+    /// = Remarks
+    ///
+    /// A buffer of size `IPV6_SOCK_ADDR_SIZE` is large enough for all peers.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::Socket::accept_addr
+    pub fn accept_addr<'a>(&self,
+                           addr: &'a mut [u8]) -> Result<(TcpStream, &'a mut SockAddr)> {
+        let (addr, sock) = try!(self.sock.accept_addr(addr, SOCK_CLOSE_ON_EXEC));
+        Ok((TcpStream { sock: sock }, try!(addr)))
+    }
+
+    /// Retrieves the address the listener is bound to.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the address will be stored.
+    pub fn local_addr<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut SockAddr> {
+        self.sock.get_addr(buf)
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        &self.sock
+    }
+}
+
+impl Into<c_int> for TcpListener {
+    fn into(self) -> c_int {
+        self.sock.into()
+    }
+}
+
+impl FdContainer for TcpListener {
+    fn is_owned(&self) -> bool { self.sock.is_owned() }
+    fn borrow(&self) -> c_int { self.sock.borrow() }
+    fn from_owned(fd: c_int) -> TcpListener {
+        TcpListener { sock: Socket::from_owned(fd) }
+    }
+    fn from_borrowed(fd: c_int) -> TcpListener {
+        TcpListener { sock: Socket::from_borrowed(fd) }
+    }
+}
+
+/// Keep-alive parameters of a Tcp connection.
+#[derive(Pod, Eq)]
+pub struct KeepAlive {
+    /// The number of seconds the connection has to be idle before probes are sent.
+    pub idle: u32,
+    /// The number of seconds between two probes.
+    pub interval: u32,
+    /// The number of unanswered probes after which the connection is dropped.
+    pub count: u32,
+}
+
+/// Information about the state of a Tcp connection.
+///
+/// = Remarks
+///
+/// Timestamps such as `last_data_sent` are relative to the time the information was
+/// retrieved. Sizes are in bytes unless they are documented as segment counts.
+///
+/// = See also
+///
+/// * link:lrs::socket::Socket::tcp_info
+/// * link:man:tcp(7) and TCP_INFO therein
+#[derive(Pod, Eq)]
+pub struct TcpInfo {
+    /// The state of the connection, e.g., `1` for an established connection.
+    pub state: u8,
+    /// The number of unrecovered retransmission timeouts.
+    pub retransmits: u8,
+    /// The number of unanswered zero-window probes.
+    pub probes: u8,
+    /// The exponential backoff of the retransmission timer.
+    pub backoff: u8,
+    /// The `TCPI_OPT_*` options negotiated with the peer.
+    pub options: u8,
+    /// The window scale used for sending.
+    pub send_wscale: u8,
+    /// The window scale used for receiving.
+    pub recv_wscale: u8,
+    /// The retransmission timeout.
+    pub rto: Time,
+    /// The delayed acknowledgement timeout.
+    pub ato: Time,
+    /// The maximum segment size used for sending.
+    pub send_mss: u32,
+    /// The estimated maximum segment size of the peer.
+    pub recv_mss: u32,
+    /// The number of unacknowledged segments.
+    pub unacked: u32,
+    /// The number of segments acknowledged via selective acknowledgements.
+    pub sacked: u32,
+    /// The number of segments considered lost.
+    pub lost: u32,
+    /// The number of segments being retransmitted.
+    pub retrans: u32,
+    /// The time since data was last sent.
+    pub last_data_sent: Time,
+    /// The time since data was last received.
+    pub last_data_recv: Time,
+    /// The time since an acknowledgement was last received.
+    pub last_ack_recv: Time,
+    /// The path MTU.
+    pub path_mtu: u32,
+    /// The slow start threshold of the receiving side.
+    pub recv_ssthresh: u32,
+    /// The smoothed round-trip time.
+    pub rtt: Time,
+    /// The variance of the round-trip time.
+    pub rtt_var: Time,
+    /// The slow start threshold of the sending side in segments.
+    pub send_ssthresh: u32,
+    /// The congestion window in segments.
+    pub send_cwnd: u32,
+    /// The advertised maximum segment size.
+    pub advertised_mss: u32,
+    /// The reordering metric in segments.
+    pub reordering: u32,
+    /// The round-trip time estimated by the receiving side.
+    pub recv_rtt: Time,
+    /// The receive buffer space.
+    pub recv_space: u32,
+    /// The total number of retransmitted segments.
+    pub total_retrans: u32,
+}
+
+/// A connected Tcp socket.
+///
+/// = Remarks
+///
+/// The underlying socket can be accessed via the `socket` method to set further
+/// options. Writing to a connection that has been closed by the peer returns an error
+/// instead of raising `SIGPIPE`.
+///
+/// = See also
+///
+/// * link:man:tcp(7)
+pub struct TcpStream {
+    sock: Socket,
+}
+
+impl TcpStream {
+    /// Connects to a peer.
+    ///
+    /// [argument, addr]
+    /// The Ipv4 or Ipv6 address of the peer.
+    pub fn connect<A: ?Sized>(addr: &A) -> Result<TcpStream>
+        where A: AsRef<SockAddr>,
+    {
+        let sock = try!(socket_for(addr, kind::Stream));
+        let addr: &SockAddr = addr.as_ref();
+        try!(sock.connect(addr));
+        Ok(TcpStream { sock: sock })
+    }
+
+    /// Connects to a peer with a timeout.
+    ///
+    /// [argument, addr]
+    /// The Ipv4 or Ipv6 address of the peer.
+    ///
+    /// [argument, timeout]
+    /// The maximum time to wait for the connection to be established.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::Socket::connect_timeout
+    pub fn connect_timeout<A: ?Sized>(addr: &A, timeout: Time) -> Result<TcpStream>
+        where A: AsRef<SockAddr>,
+    {
+        let sock = try!(socket_for(addr, kind::Stream));
+        let addr: &SockAddr = addr.as_ref();
+        try!(sock.connect_timeout(addr, timeout));
+        Ok(TcpStream { sock: sock })
+    }
+
+    /// Retrieves the address of the local end of the connection.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the address will be stored.
+    pub fn local_addr<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut SockAddr> {
+        self.sock.get_addr(buf)
+    }
+
+    /// Retrieves the address of the peer.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the address will be stored.
+    pub fn peer_addr<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut SockAddr> {
+        self.sock.get_peer_addr(buf)
+    }
+
+    /// Disables or enables Nagle's algorithm.
+    ///
+    /// [argument, val]
+    /// Whether Nagle's algorithm is disabled.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::Socket::tcp_set_no_delay
+    pub fn set_no_delay(&self, val: bool) -> Result {
+        self.sock.tcp_set_no_delay(val)
+    }
+
+    /// Enables or disables keep-alive probes.
+    ///
+    /// [argument, val]
+    /// The keep-alive parameters or `None` to disable keep-alive probes.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::Socket::set_keep_alive
+    /// * link:lrs::socket::Socket::tcp_set_keep_alive_idle
+    /// * link:lrs::socket::Socket::tcp_set_keep_alive_interval
+    /// * link:lrs::socket::Socket::tcp_set_keep_alive_count
+    pub fn set_keep_alive(&self, val: Option<KeepAlive>) -> Result {
+        if let Some(ka) = val {
+            try!(self.sock.tcp_set_keep_alive_idle(ka.idle));
+            try!(self.sock.tcp_set_keep_alive_interval(ka.interval));
+            try!(self.sock.tcp_set_keep_alive_count(ka.count));
+        }
+        self.sock.set_keep_alive(val.is_some())
+    }
+
+    /// Sets the timeouts of reading and writing operations.
+    ///
+    /// [argument, val]
+    /// The timeout or `None` if operations should never time out.
+    ///
+    /// = Remarks
+    ///
+    /// Operations that time out return `WouldBlock`.
+    pub fn set_timeout(&self, val: Option<Time>) -> Result {
+        try!(self.sock.set_recv_timeout(val));
+        self.sock.set_send_timeout(val)
+    }
+
+    /// Shuts down the sending end of the connection.
+    ///
+    /// = Remarks
+    ///
+    /// The peer will read end-of-file after it has read all data sent so far.
+    pub fn shutdown_outgoing(&self) -> Result {
+        self.sock.shutdown_outgoing()
+    }
+
+    /// Retrieves information about the state of the connection.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::Socket::tcp_info
+    pub fn info(&self) -> Result<TcpInfo> {
+        self.sock.tcp_info()
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        &self.sock
+    }
+}
+
+impl Read for TcpStream {
+    fn scatter_read(&mut self, bufs: &mut [&mut [d8]]) -> Result<usize> {
+        self.sock.as_fdio().scatter_read(bufs)
+    }
+}
+
+impl<'a> Read for &'a TcpStream {
+    fn scatter_read(&mut self, bufs: &mut [&mut [d8]]) -> Result<usize> {
+        self.sock.as_fdio().scatter_read(bufs)
+    }
+}
+
+impl Write for TcpStream {
+    fn gather_write(&mut self, bufs: &[&[u8]]) -> Result<usize> {
+        self.sock.gather_send(bufs, MSG_NONE)
+    }
+}
+
+impl<'a> Write for &'a TcpStream {
+    fn gather_write(&mut self, bufs: &[&[u8]]) -> Result<usize> {
+        self.sock.gather_send(bufs, MSG_NONE)
+    }
+}
+
+impl Into<c_int> for TcpStream {
+    fn into(self) -> c_int {
+        self.sock.into()
+    }
+}
+
+impl FdContainer for TcpStream {
+    fn is_owned(&self) -> bool { self.sock.is_owned() }
+    fn borrow(&self) -> c_int { self.sock.borrow() }
+    fn from_owned(fd: c_int) -> TcpStream {
+        TcpStream { sock: Socket::from_owned(fd) }
+    }
+    fn from_borrowed(fd: c_int) -> TcpStream {
+        TcpStream { sock: Socket::from_borrowed(fd) }
+    }
+}
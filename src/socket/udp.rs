@@ -0,0 +1,171 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+use cty::{c_int};
+use fd::{FdContainer};
+use addr::{SockAddr};
+use socket::{Socket};
+use kind::{self};
+use msg::{MSG_NONE};
+use conn::{socket_for};
+
+/// A Udp socket.
+///
+/// = Remarks
+///
+/// The underlying socket can be accessed via the `socket` method to set further
+/// options.
+///
+/// = See also
+///
+/// * link:man:udp(7)
+pub struct UdpSocket {
+    sock: Socket,
+}
+
+impl UdpSocket {
+    /// Creates a socket bound to an address.
+    ///
+    /// [argument, addr]
+    /// The Ipv4 or Ipv6 address to bind to.
+    ///
+    /// = Remarks
+    ///
+    /// If the port of the address is `0`, an unused port is chosen by the kernel.
+    pub fn bind<A: ?Sized>(addr: &A) -> Result<UdpSocket>
+        where A: AsRef<SockAddr>,
+    {
+        UdpSocket::bind_common(addr, false)
+    }
+
+    /// Creates a socket bound to an address that can be shared with other sockets.
+    ///
+    /// [argument, addr]
+    /// The Ipv4 or Ipv6 address to bind to.
+    ///
+    /// = Remarks
+    ///
+    /// This is like `bind` but additionally sets the `reuse_port` option. Incoming
+    /// datagrams are distributed between all sockets bound to the same address.
+    ///
+    /// = See also
+    ///
+    /// * link:man:socket(7) and SO_REUSEPORT therein
+    pub fn bind_reuse_port<A: ?Sized>(addr: &A) -> Result<UdpSocket>
+        where A: AsRef<SockAddr>,
+    {
+        UdpSocket::bind_common(addr, true)
+    }
+
+    fn bind_common<A: ?Sized>(addr: &A, reuse_port: bool) -> Result<UdpSocket>
+        where A: AsRef<SockAddr>,
+    {
+        let sock = try!(socket_for(addr, kind::Datagram));
+        if reuse_port {
+            try!(sock.set_reuse_port(true));
+        }
+        let addr: &SockAddr = addr.as_ref();
+        try!(sock.bind(addr));
+        Ok(UdpSocket { sock: sock })
+    }
+
+    /// Sets the default peer of the socket.
+    ///
+    /// [argument, addr]
+    /// The address of the peer.
+    ///
+    /// = Remarks
+    ///
+    /// Afterwards, `send` sends datagrams to this peer and `recv` only receives
+    /// datagrams from this peer.
+    pub fn connect<A: ?Sized>(&self, addr: &A) -> Result
+        where A: AsRef<SockAddr>,
+    {
+        let addr: &SockAddr = addr.as_ref();
+        self.sock.connect(addr)
+    }
+
+    /// Sends a datagram to the default peer.
+    ///
+    /// [argument, buf]
+    /// The contents of the datagram.
+    pub fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.sock.send(buf, MSG_NONE)
+    }
+
+    /// Sends a datagram to a peer.
+    ///
+    /// [argument, buf]
+    /// The contents of the datagram.
+    ///
+    /// [argument, addr]
+    /// The address of the peer.
+    pub fn send_to<A: ?Sized>(&self, buf: &[u8], addr: &A) -> Result<usize>
+        where A: AsRef<SockAddr>,
+    {
+        let addr: &SockAddr = addr.as_ref();
+        self.sock.send_to(buf, addr, MSG_NONE)
+    }
+
+    /// Receives a datagram.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the datagram will be stored.
+    ///
+    /// [return_value]
+    /// Returns the size of the datagram.
+    pub fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.sock.recv(buf, MSG_NONE)
+    }
+
+    /// Receives a datagram and the address of its sender.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the datagram will be stored.
+    ///
+    /// [argument, addr]
+    /// The buffer in which the address of the sender will be stored.
+    ///
+    /// [return_value]
+    /// Returns the size of the datagram and the address of the sender.
+    pub fn recv_from<'a>(&self, buf: &mut [u8],
+                         addr: &'a mut [u8]) -> Result<(usize, &'a mut SockAddr)> {
+        match try!(self.sock.recv_from(buf, addr, MSG_NONE)) {
+            (len, Some(addr)) => Ok((len, addr)),
+            _ => Err(error::NoMemory),
+        }
+    }
+
+    /// Retrieves the address the socket is bound to.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the address will be stored.
+    pub fn local_addr<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut SockAddr> {
+        self.sock.get_addr(buf)
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        &self.sock
+    }
+}
+
+impl Into<c_int> for UdpSocket {
+    fn into(self) -> c_int {
+        self.sock.into()
+    }
+}
+
+impl FdContainer for UdpSocket {
+    fn is_owned(&self) -> bool { self.sock.is_owned() }
+    fn borrow(&self) -> c_int { self.sock.borrow() }
+    fn from_owned(fd: c_int) -> UdpSocket {
+        UdpSocket { sock: Socket::from_owned(fd) }
+    }
+    fn from_borrowed(fd: c_int) -> UdpSocket {
+        UdpSocket { sock: Socket::from_borrowed(fd) }
+    }
+}
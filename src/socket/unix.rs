@@ -0,0 +1,198 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use cty::{c_int};
+use io::{Read, Write};
+use fd::{FdContainer};
+use time_base::{Time};
+use addr::{SockAddr};
+use addr::unix::{UnixSockAddr};
+use socket::{Socket};
+use flags::{SOCK_CLOSE_ON_EXEC};
+use msg::{MSG_NONE};
+use tcp::{DEFAULT_BACKLOG};
+
+/// A Unix stream socket that listens for connections.
+///
+/// = Remarks
+///
+/// The underlying socket can be accessed via the `socket` method to set further
+/// options.
+///
+/// = See also
+///
+/// * link:man:unix(7)
+pub struct UnixListener {
+    sock: Socket,
+}
+
+impl UnixListener {
+    /// Creates a listener bound to an address.
+    ///
+    /// [argument, addr]
+    /// The address to listen on.
+    ///
+    /// = Remarks
+    ///
+    /// If the address is a path, the socket file must not exist yet. The backlog is
+    /// `DEFAULT_BACKLOG`.
+    pub fn bind(addr: &UnixSockAddr) -> Result<UnixListener> {
+        let sock = try!(Socket::unix_stream(SOCK_CLOSE_ON_EXEC));
+        try!(sock.bind(addr));
+        try!(sock.listen(DEFAULT_BACKLOG));
+        Ok(UnixListener { sock: sock })
+    }
+
+    /// Accepts a connection.
+    pub fn accept(&self) -> Result<UnixStream> {
+        let sock = try!(self.sock.accept(SOCK_CLOSE_ON_EXEC));
+        Ok(UnixStream { sock: sock })
+    }
+
+    /// Retrieves the address the listener is bound to.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the address will be stored.
+    pub fn local_addr<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut SockAddr> {
+        self.sock.get_addr(buf)
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        &self.sock
+    }
+}
+
+impl Into<c_int> for UnixListener {
+    fn into(self) -> c_int {
+        self.sock.into()
+    }
+}
+
+impl FdContainer for UnixListener {
+    fn is_owned(&self) -> bool { self.sock.is_owned() }
+    fn borrow(&self) -> c_int { self.sock.borrow() }
+    fn from_owned(fd: c_int) -> UnixListener {
+        UnixListener { sock: Socket::from_owned(fd) }
+    }
+    fn from_borrowed(fd: c_int) -> UnixListener {
+        UnixListener { sock: Socket::from_borrowed(fd) }
+    }
+}
+
+/// A connected Unix stream socket.
+///
+/// = Remarks
+///
+/// The underlying socket can be accessed via the `socket` method to set further
+/// options, e.g., to retrieve the credentials of the peer.
+///
+/// = See also
+///
+/// * link:man:unix(7)
+pub struct UnixStream {
+    sock: Socket,
+}
+
+impl UnixStream {
+    /// Connects to a peer.
+    ///
+    /// [argument, addr]
+    /// The address of the peer.
+    pub fn connect(addr: &UnixSockAddr) -> Result<UnixStream> {
+        let sock = try!(Socket::unix_stream(SOCK_CLOSE_ON_EXEC));
+        try!(sock.connect(addr));
+        Ok(UnixStream { sock: sock })
+    }
+
+    /// Connects to a peer with a timeout.
+    ///
+    /// [argument, addr]
+    /// The address of the peer.
+    ///
+    /// [argument, timeout]
+    /// The maximum time to wait for the connection to be established.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::socket::Socket::connect_timeout
+    pub fn connect_timeout(addr: &UnixSockAddr, timeout: Time) -> Result<UnixStream> {
+        let sock = try!(Socket::unix_stream(SOCK_CLOSE_ON_EXEC));
+        try!(sock.connect_timeout(addr, timeout));
+        Ok(UnixStream { sock: sock })
+    }
+
+    /// Retrieves the address of the peer.
+    ///
+    /// [argument, buf]
+    /// The buffer in which the address will be stored.
+    pub fn peer_addr<'a>(&self, buf: &'a mut [u8]) -> Result<&'a mut SockAddr> {
+        self.sock.get_peer_addr(buf)
+    }
+
+    /// Sets the timeouts of reading and writing operations.
+    ///
+    /// [argument, val]
+    /// The timeout or `None` if operations should never time out.
+    ///
+    /// = Remarks
+    ///
+    /// Operations that time out return `WouldBlock`.
+    pub fn set_timeout(&self, val: Option<Time>) -> Result {
+        try!(self.sock.set_recv_timeout(val));
+        self.sock.set_send_timeout(val)
+    }
+
+    /// Shuts down the sending end of the connection.
+    pub fn shutdown_outgoing(&self) -> Result {
+        self.sock.shutdown_outgoing()
+    }
+
+    /// Returns the underlying socket.
+    pub fn socket(&self) -> &Socket {
+        &self.sock
+    }
+}
+
+impl Read for UnixStream {
+    fn scatter_read(&mut self, bufs: &mut [&mut [d8]]) -> Result<usize> {
+        self.sock.as_fdio().scatter_read(bufs)
+    }
+}
+
+impl<'a> Read for &'a UnixStream {
+    fn scatter_read(&mut self, bufs: &mut [&mut [d8]]) -> Result<usize> {
+        self.sock.as_fdio().scatter_read(bufs)
+    }
+}
+
+impl Write for UnixStream {
+    fn gather_write(&mut self, bufs: &[&[u8]]) -> Result<usize> {
+        self.sock.gather_send(bufs, MSG_NONE)
+    }
+}
+
+impl<'a> Write for &'a UnixStream {
+    fn gather_write(&mut self, bufs: &[&[u8]]) -> Result<usize> {
+        self.sock.gather_send(bufs, MSG_NONE)
+    }
+}
+
+impl Into<c_int> for UnixStream {
+    fn into(self) -> c_int {
+        self.sock.into()
+    }
+}
+
+impl FdContainer for UnixStream {
+    fn is_owned(&self) -> bool { self.sock.is_owned() }
+    fn borrow(&self) -> c_int { self.sock.borrow() }
+    fn from_owned(fd: c_int) -> UnixStream {
+        UnixStream { sock: Socket::from_owned(fd) }
+    }
+    fn from_borrowed(fd: c_int) -> UnixStream {
+        UnixStream { sock: Socket::from_borrowed(fd) }
+    }
+}
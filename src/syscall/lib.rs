@@ -32,8 +32,8 @@ use cty::{
     TIOCEXCL, TIOCCONS, TIOCGDEV, TIOCVHANGUP, TIOCSETD, TIOCGETD, TIOCGSID, TIOCSPGRP,
     TIOCGPGRP, TCFLSH, TIOCOUTQ, TCXONC, TCGETS2, termios2, TCSETS2, mq_attr, sched_attr,
     __user_cap_data_struct, __user_cap_header_struct, _LINUX_CAPABILITY_VERSION_3,
-    PR_CAPBSET_READ, PR_CAPBSET_DROP, PR_GET_KEEPCAPS, PR_SET_KEEPCAPS,
-    SECCOMP_SET_MODE_STRICT,
+    PR_CAPBSET_READ, PR_CAPBSET_DROP, PR_GET_KEEPCAPS, PR_SET_KEEPCAPS, PR_SET_NAME,
+    PR_GET_NAME, SECCOMP_SET_MODE_STRICT, SIOCGIFINDEX, ifreq,
 };
 
 pub use r::{StatType, StatfsType};
@@ -2506,6 +2506,26 @@ pub fn ioctl_siocoutq(fd: c_int, unread: &mut usize) -> c_int {
     rv
 }
 
+/// Executes ioctl with the SIOCGIFINDEX option.
+///
+/// [argument, fd]
+/// The file descriptor of a socket.
+///
+/// [argument, req]
+/// The request containing the interface name. The index will be stored in the first
+/// integer of the union.
+///
+/// [return_value]
+/// Returns success or an error value.
+///
+/// = See also
+///
+/// * link:man:ioctl(2)
+/// * link:man:netdevice(7) and SIOCGIFINDEX therein
+pub fn ioctl_siocgifindex(fd: c_int, req: &mut ifreq) -> c_int {
+    unsafe { r::ioctl(fd as k_uint, SIOCGIFINDEX as k_uint, req as *mut _ as k_ulong) }
+}
+
 /// Modifies or inspects the process signal mask.
 ///
 /// [argument, how]
@@ -3976,6 +3996,30 @@ pub fn prctl_pr_set_keepcaps(keep: bool) -> c_int {
     unsafe { r::prctl(PR_SET_KEEPCAPS, keep as k_ulong, 0, 0, 0) }
 }
 
+/// Sets the name of this thread.
+///
+/// [argument, name]
+/// The new name. The name is truncated at the first null byte.
+///
+/// = See also
+///
+/// * link:man:prctl(2) and PR_SET_NAME therein
+pub fn prctl_pr_set_name(name: &[u8; 16]) -> c_int {
+    unsafe { r::prctl(PR_SET_NAME, name.as_ptr() as k_ulong, 0, 0, 0) }
+}
+
+/// Retrieves the name of this thread.
+///
+/// [argument, name]
+/// The buffer in which the null-terminated name will be stored.
+///
+/// = See also
+///
+/// * link:man:prctl(2) and PR_GET_NAME therein
+pub fn prctl_pr_get_name(name: &mut [u8; 16]) -> c_int {
+    unsafe { r::prctl(PR_GET_NAME, name.as_mut_ptr() as k_ulong, 0, 0, 0) }
+}
+
 /// Disassociate parts of the thread's execution context.
 ///
 /// [argument, flags]
@@ -0,0 +1,303 @@
+//! Benchmarks.
+
+use std::cmp::{Ordering};
+use std::cmp::Ordering::{Less, Equal, Greater};
+use std::mem::{self};
+use std::ptr::{volatile_load};
+use std::file::{File};
+use std::file::flags::{FILE_READ_ONLY, FILE_WRITE_ONLY, FILE_CREATE, FILE_TRUNCATE};
+use std::file::mode::{MODE_FILE};
+use std::io::{Write, BufWrite};
+use std::string::{CStr};
+use std::parse::{Parse};
+use std::time::{self};
+use std::vec::{Vec};
+use std::error::{self};
+
+/// The minimum duration of a single sample.
+const SAMPLE_NS: i64 = 1_000_000;
+
+/// The number of samples per round.
+const SAMPLES: usize = 50;
+
+/// The maximum time spent on a single benchmark.
+const MAX_NS: i64 = 3_000_000_000;
+
+/// Prevents the optimizer from removing a computation whose result is unused.
+pub fn black_box<T>(dummy: T) -> T {
+    let ret = volatile_load(&dummy);
+    mem::forget(dummy);
+    ret
+}
+
+/// The source of timestamps.
+#[derive(Copy)]
+pub enum Source {
+    /// The `MONO_RAW` clock in nanoseconds.
+    MonoRaw,
+    /// The time-stamp counter in cycles.
+    Tsc,
+}
+
+impl Source {
+    fn now(self) -> u64 {
+        match self {
+            Source::MonoRaw => {
+                let t = time::MONO_RAW.get_time().unwrap();
+                t.seconds as u64 * 1_000_000_000 + t.nanoseconds as u64
+            },
+            Source::Tsc => tsc().unwrap(),
+        }
+    }
+
+    /// Returns the unit of the timestamps.
+    pub fn unit(self) -> &'static str {
+        match self {
+            Source::MonoRaw => "ns",
+            Source::Tsc => "cycles",
+        }
+    }
+}
+
+/// Reads the time-stamp counter.
+#[cfg(target_arch = "x86_64")]
+pub fn tsc() -> Option<u64> {
+    let lo: u32;
+    let hi: u32;
+    unsafe { asm!("rdtsc" : "={eax}"(lo), "={edx}"(hi) : : : "volatile"); }
+    Some((hi as u64) << 32 | lo as u64)
+}
+
+/// Reads the time-stamp counter.
+#[cfg(not(target_arch = "x86_64"))]
+pub fn tsc() -> Option<u64> {
+    None
+}
+
+/// The argument of benchmark functions.
+pub struct Bencher {
+    iterations: u64,
+    source: Source,
+    /// The ticks spent in the last call to `iter`.
+    elapsed: u64,
+    called: bool,
+}
+
+impl Bencher {
+    /// Measures a closure.
+    ///
+    /// [argument, f]
+    /// The closure. It's called repeatedly and its return value is passed to
+    /// `black_box`.
+    pub fn iter<T, F>(&mut self, mut f: F)
+        where F: FnMut() -> T,
+    {
+        let start = self.source.now();
+        for _ in 0..self.iterations {
+            black_box(f());
+        }
+        self.elapsed = self.source.now() - start;
+        self.called = true;
+    }
+}
+
+/// Runs a benchmark function once as part of the test suite.
+pub fn run_once(f: fn(&mut Bencher)) {
+    let mut b = Bencher {
+        iterations: 1,
+        source: Source::MonoRaw,
+        elapsed: 0,
+        called: false,
+    };
+    f(&mut b);
+}
+
+/// Statistics over the per-iteration time of a benchmark.
+#[derive(Copy)]
+pub struct Stats {
+    pub mean: f64,
+    pub median: f64,
+    pub stddev: f64,
+    /// The median absolute deviation, scaled to be consistent with `stddev` for normal
+    /// distributions.
+    pub mad: f64,
+}
+
+fn cmp_f64(a: &f64, b: &f64) -> Ordering {
+    if a < b {
+        Less
+    } else if a > b {
+        Greater
+    } else {
+        Equal
+    }
+}
+
+fn median(sorted: &[f64]) -> f64 {
+    let n = sorted.len();
+    if n % 2 == 1 {
+        sorted[n / 2]
+    } else {
+        (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0
+    }
+}
+
+impl Stats {
+    /// Computes statistics over a non-empty set of samples.
+    ///
+    /// = Remarks
+    ///
+    /// The samples are sorted in the process.
+    pub fn new(samples: &mut [f64]) -> Stats {
+        let n = samples.len() as f64;
+        samples.sort_by(cmp_f64);
+        let median = median(samples);
+
+        let mut sum = 0.0;
+        for &s in &samples[..] {
+            sum += s;
+        }
+        let mean = sum / n;
+
+        let mut sq = 0.0;
+        for &s in &samples[..] {
+            sq += (s - mean) * (s - mean);
+        }
+        let stddev = if samples.len() > 1 { (sq / (n - 1.0)).sqrt() } else { 0.0 };
+
+        for s in &mut samples[..] {
+            *s = (*s - median).abs();
+        }
+        samples.sort_by(cmp_f64);
+        let mad = 1.4826 * self::median(samples);
+
+        Stats { mean: mean, median: median, stddev: stddev, mad: mad }
+    }
+
+    /// Returns whether these statistics are significantly slower than a baseline.
+    ///
+    /// = Remarks
+    ///
+    /// The difference of the medians must exceed three times the combined MAD and one
+    /// percent of the baseline.
+    pub fn regressed(&self, base: &Stats) -> bool {
+        let diff = self.median - base.median;
+        let noise = (self.mad * self.mad + base.mad * base.mad).sqrt();
+        diff > 3.0 * noise && diff > 0.01 * base.median
+    }
+}
+
+fn sample(f: fn(&mut Bencher), b: &mut Bencher) -> Option<f64> {
+    b.called = false;
+    f(b);
+    match b.called {
+        true => Some(b.elapsed as f64 / b.iterations as f64),
+        false => None,
+    }
+}
+
+fn wall_ns() -> i64 {
+    let t = time::MONO_RAW.get_time().unwrap();
+    t.seconds * 1_000_000_000 + t.nanoseconds
+}
+
+/// Runs a benchmark until the median of consecutive rounds stabilises.
+///
+/// [return_value]
+/// Returns `None` if the benchmark does not call `Bencher::iter`.
+pub fn measure(f: fn(&mut Bencher), source: Source) -> Option<Stats> {
+    let mut b = Bencher {
+        iterations: 1,
+        source: source,
+        elapsed: 0,
+        called: false,
+    };
+
+    // Find a number of iterations that makes a sample take at least SAMPLE_NS.
+    loop {
+        let start = wall_ns();
+        if sample(f, &mut b).is_none() {
+            return None;
+        }
+        if wall_ns() - start >= SAMPLE_NS || b.iterations >= 1 << 40 {
+            break;
+        }
+        b.iterations *= 2;
+    }
+
+    let start = wall_ns();
+    let mut samples = [0.0; SAMPLES];
+    let mut prev: Option<Stats> = None;
+    loop {
+        for s in &mut samples[..] {
+            *s = match sample(f, &mut b) {
+                Some(s) => s,
+                None => return None,
+            };
+        }
+        let stats = Stats::new(&mut samples);
+        if let Some(prev) = prev {
+            let change = (stats.median - prev.median).abs();
+            if change <= 0.01 * prev.median || wall_ns() - start >= MAX_NS {
+                return Some(stats);
+            }
+        }
+        prev = Some(stats);
+    }
+}
+
+/// A saved result of a benchmark.
+pub struct Baseline {
+    pub name: Vec<u8>,
+    pub stats: Stats,
+}
+
+/// Loads a baseline file.
+///
+/// = Remarks
+///
+/// Every line has the form `name median mad` where the numbers are in thousandths of the
+/// unit.
+pub fn load_baseline(path: &CStr) -> Result<Vec<Baseline>> {
+    let mut file = try!(File::open(path, FILE_READ_ONLY, MODE_FILE));
+    let mut buf: Vec<u8> = Vec::new();
+    try!(buf.read_to_eof(&mut file));
+
+    let mut res = Vec::new();
+    for line in buf.split(|&b| b == b'\n') {
+        if line.len() == 0 {
+            continue;
+        }
+        let mut fields = line.split(|&b| b == b' ');
+        let (name, median, mad) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(n), Some(median), Some(mad)) => (n, median, mad),
+            _ => return Err(error::InvalidArgument),
+        };
+        let median: u64 = try!(median.parse());
+        let mad: u64 = try!(mad.parse());
+        let mut n = Vec::new();
+        try!(n.push_all(name));
+        try!(res.push(Baseline {
+            name: n,
+            stats: Stats {
+                mean: 0.0,
+                median: median as f64 / 1000.0,
+                stddev: 0.0,
+                mad: mad as f64 / 1000.0,
+            },
+        }));
+    }
+    Ok(res)
+}
+
+/// Saves benchmark results in the format read by `load_baseline`.
+pub fn save_baseline(path: &CStr, results: &[(&'static str, Stats)]) -> Result {
+    let mut buf: Vec<u8> = Vec::new();
+    for &(name, ref stats) in results {
+        try!(write!(&mut buf, "{} {} {}\n", name, (stats.median * 1000.0) as u64,
+                    (stats.mad * 1000.0) as u64));
+    }
+    let mut file = try!(File::open(path, FILE_WRITE_ONLY | FILE_CREATE | FILE_TRUNCATE,
+                                   MODE_FILE));
+    file.write_all(&buf).ignore_ok()
+}
@@ -1,15 +1,40 @@
 #![crate_name = "test"]
 #![crate_type = "rlib"]
+#![feature(asm)]
 
-use std::process::{fork, wait_id, ChildStatus, WAIT_EXITED, set_resource_limit, self};
+use std::process::{fork, wait_id, ChildStatus, ProcessId, WAIT_EXITED, WAIT_DONT_BLOCK,
+                   set_resource_limit, self};
 use std::process::resource::{CoreDumpSize};
-use std::signal::{signals, SigHandler, Sigset, Signal, SigInfo, set_handler};
+use std::signal::{self, signals, SigHandler, Sigset, Signal, SigInfo, set_handler};
 use std::signal::flags::{SA_NONE};
 use std::iter::{IteratorExt};
+use std::env::{self};
+use std::pipe::{Pipe};
+use std::pipe::flags::{PIPE_CLOSE_ON_EXEC};
+use std::poll::{Epoll, EMPTY_EVENT, POLL_READ};
+use std::fd::{FdContainer, STDOUT};
+use std::io::{Write, BufWrite};
+use std::syscall::{dup3};
+use std::time::{self, Time};
+use std::vec::{Vec};
+use std::error::{self};
+use options::{Options};
+use report::{Record, Outcome};
+
+pub use bench::{Bencher, black_box};
+
+pub mod options;
+pub mod report;
+pub mod bench;
 
 pub struct StaticTestName(pub &'static str);
 
-pub struct StaticTestFn(pub fn());
+pub enum TestFn {
+    StaticTestFn(fn()),
+    StaticBenchFn(fn(&mut Bencher)),
+}
+
+pub use TestFn::{StaticTestFn, StaticBenchFn};
 
 pub enum ShouldPanic {
     No,
@@ -34,55 +59,426 @@ pub struct TestDesc {
 
 pub struct TestDescAndFn {
     pub desc: TestDesc,
-    pub testfn: StaticTestFn,
+    pub testfn: TestFn,
+}
+
+/// The maximum time we sleep before checking whether children have exited.
+const POLL_INTERVAL_MS: i64 = 100;
+
+/// How much output we read from a child at once.
+const READ_STEP_SIZE: usize = 4096;
+
+/// Parses the command line or exits.
+fn options() -> Options {
+    let mut args = Vec::new();
+    for arg in env::args().consume(1) {
+        if let Err(e) = args.push(arg) {
+            errln!("could not read the arguments ({:?})", e);
+            process::exit(2);
+        }
+    }
+    match Options::parse(&args) {
+        Ok(opts) => opts,
+        Err(error::InvalidArgument) => usage(),
+        Err(e) => {
+            errln!("could not parse the arguments ({:?})", e);
+            process::exit(2);
+        },
+    }
+}
+
+fn usage() -> ! {
+    errln!("usage: test [-j JOBS] [-t TIMEOUT_SECONDS] [--nocapture] [--tap FILE] \
+            [--junit FILE] [--bench [--tsc] [--baseline FILE] [--save-baseline FILE]] \
+            [FILTER]*");
+    process::exit(2);
+}
+
+/// A test that is running in a child process.
+struct Running<'a> {
+    test: &'a TestDescAndFn,
+    id: ProcessId,
+    /// The read end of the pipe connected to the child's stdout and stderr. `None` if
+    /// the output is not captured or if the child has closed its end.
+    pipe: Option<Pipe>,
+    output: Vec<u8>,
+    started: Time,
+    deadline: Option<Time>,
+    timed_out: bool,
+    status: Option<ChildStatus>,
+}
+
+struct Summary {
+    passed: usize,
+    failed: usize,
+    ignored: usize,
 }
 
 pub fn test_main_static(tests: &[TestDescAndFn]) {
     // disable core-dumps
     set_resource_limit(0, CoreDumpSize, 0, 0).unwrap();
 
-    for t in tests {
-        if std::env::arg_count() > 1 {
-            let mut found = false;
-            for arg in std::env::args().consume(1) {
-                if t.desc.name.0.as_bytes().starts_with(arg.as_ref()) {
-                    found = true;
-                    break;
+    let opts = options();
+    if opts.bench {
+        bench_main(tests, &opts);
+    }
+
+    let epoll = Epoll::new().unwrap();
+    let mut slots: Vec<Option<Running>> = Vec::with_capacity(opts.jobs).unwrap();
+    for _ in 0..opts.jobs {
+        slots.push(None).unwrap();
+    }
+    let mut summary = Summary { passed: 0, failed: 0, ignored: 0 };
+    let mut records: Vec<Record> = Vec::new();
+    let mut tests = tests.iter().filter(|t| opts.selected(t.desc.name.0));
+    let mut exhausted = false;
+
+    loop {
+        // Start new tests in the free slots.
+        for slot in &mut slots[..] {
+            while slot.is_none() && !exhausted {
+                let t = match tests.next() {
+                    Some(t) => t,
+                    None => { exhausted = true; break; },
+                };
+                if t.desc.ignore {
+                    finish(&mut summary, &mut records, &opts, Record {
+                        name: t.desc.name.0,
+                        outcome: Outcome::Ignored,
+                        duration: Time::nanoseconds(0),
+                        status: None,
+                        output: Vec::new(),
+                    });
+                    continue;
+                }
+                match start(t, &opts, &epoll) {
+                    Ok(r) => *slot = Some(r),
+                    Err(e) => {
+                        let mut output = Vec::new();
+                        let _ = write!(&mut output, "could not fork ({:?})\n", e);
+                        finish(&mut summary, &mut records, &opts, Record {
+                            name: t.desc.name.0,
+                            outcome: Outcome::Failed,
+                            duration: Time::nanoseconds(0),
+                            status: None,
+                            output: output,
+                        });
+                    },
                 }
-            }
-            if !found {
-                continue;
             }
         }
 
-        print!("testing {} ... ", t.desc.name.0);
+        if slots.iter().filter(|s| s.is_some()).next().is_none() {
+            break;
+        }
 
-        let id = match fork(|| {
-            extern fn abort_handler(_: Signal, _: &SigInfo, _: usize) {
-                process::exit(1);
+        // Collect output until a child might have exited or a deadline has passed.
+        let now = time::MONO.get_time().unwrap();
+        let mut wait = Time::milliseconds(POLL_INTERVAL_MS);
+        for slot in &slots[..] {
+            let deadline = match *slot {
+                Some(ref r) if !r.timed_out => r.deadline,
+                _ => None,
+            };
+            if let Some(deadline) = deadline {
+                if deadline <= now {
+                    wait = Time::nanoseconds(0);
+                } else if deadline - now < wait {
+                    wait = deadline - now;
+                }
+            }
+        }
+        let mut events = [EMPTY_EVENT; 16];
+        for event in epoll.wait_timeout(&mut events, wait).unwrap() {
+            for slot in &mut slots[..] {
+                if let Some(ref mut r) = *slot {
+                    if r.pipe.as_ref().map(|p| p.borrow()) == Some(event.fd()) {
+                        read_output(r, &epoll);
+                    }
+                }
             }
+        }
 
-            set_handler(signals::Illegal, Sigset::new(), SigHandler::Func(abort_handler),
-                        SA_NONE);
-            set_handler(signals::Breakpoint, Sigset::new(),
-                        SigHandler::Func(abort_handler), SA_NONE);
-            set_handler(signals::InvalidAddress, Sigset::new(),
-                        SigHandler::Func(abort_handler), SA_NONE);
+        // Kill overdue children and reap the ones that have exited.
+        let now = time::MONO.get_time().unwrap();
+        for slot in &mut slots[..] {
+            let outcome = match *slot {
+                Some(ref mut r) => match poll_child(r, now, &epoll) {
+                    Some(outcome) => outcome,
+                    None => continue,
+                },
+                None => continue,
+            };
+            let r = slot.take().unwrap();
+            finish(&mut summary, &mut records, &opts, Record {
+                name: r.test.desc.name.0,
+                outcome: outcome,
+                duration: now - r.started,
+                status: r.status,
+                output: r.output,
+            });
+        }
+    }
 
-            t.testfn.0()
-        }) {
-            Ok(id) => id,
-            Err(e) => {
-                println!("could not fork ({:?})", e);
-                break;
+    if summary.failed > 0 {
+        println!("\nfailures:");
+        for record in &records[..] {
+            match record.outcome {
+                Outcome::Failed | Outcome::TimedOut => println!("    {}", record.name),
+                _ => { },
             }
+        }
+    }
+    println!("\ntest result: {}. {} passed; {} failed; {} ignored",
+             if summary.failed == 0 { "ok" } else { "FAILED" },
+             summary.passed, summary.failed, summary.ignored);
+
+    let mut ok = summary.failed == 0;
+    if let Some(path) = opts.tap {
+        if let Err(e) = report::write_tap(path, &records) {
+            errln!("could not write TAP report ({:?})", e);
+            ok = false;
+        }
+    }
+    if let Some(path) = opts.junit {
+        if let Err(e) = report::write_junit(path, &records) {
+            errln!("could not write JUnit report ({:?})", e);
+            ok = false;
+        }
+    }
+    if !ok {
+        process::exit(1);
+    }
+}
+
+/// Prints the result of a test and adds it to the records.
+fn finish(summary: &mut Summary, records: &mut Vec<Record>, opts: &Options,
+          record: Record) {
+    print_result(&record, opts);
+    match record.outcome {
+        Outcome::Passed => summary.passed += 1,
+        Outcome::Ignored => summary.ignored += 1,
+        _ => summary.failed += 1,
+    }
+    // If we're out of memory, the record is missing from the reports but the summary
+    // is still correct.
+    let _ = records.push(record);
+}
+
+/// Forks a child that runs a test.
+fn start<'a>(t: &'a TestDescAndFn, opts: &Options, epoll: &Epoll) -> Result<Running<'a>> {
+    let pipe = if opts.capture {
+        Some(try!(Pipe::new(PIPE_CLOSE_ON_EXEC)))
+    } else {
+        None
+    };
+
+    let id = try!(fork(|| {
+        extern fn abort_handler(_: Signal, _: &SigInfo, _: usize) {
+            process::exit(1);
+        }
+
+        set_handler(signals::Illegal, Sigset::new(), SigHandler::Func(abort_handler),
+                    SA_NONE);
+        set_handler(signals::Breakpoint, Sigset::new(),
+                    SigHandler::Func(abort_handler), SA_NONE);
+        set_handler(signals::InvalidAddress, Sigset::new(),
+                    SigHandler::Func(abort_handler), SA_NONE);
+
+        if let Some((ref write, _)) = pipe {
+            dup3(write.borrow(), 1, 0);
+            dup3(write.borrow(), 2, 0);
+        }
+
+        match t.testfn {
+            StaticTestFn(f) => f(),
+            StaticBenchFn(f) => bench::run_once(f),
+        }
+    }));
+
+    // Drop the write end so that we see the end of the output when the child exits.
+    let pipe = pipe.map(|(_, read)| read);
+    if let Some(ref read) = pipe {
+        try!(epoll.add(read, POLL_READ));
+    }
+
+    let started = try!(time::MONO.get_time());
+    let deadline = match opts.timeout {
+        Some(timeout) => Some(started + timeout),
+        None => None,
+    };
+
+    Ok(Running {
+        test: t,
+        id: id,
+        pipe: pipe,
+        output: Vec::new(),
+        started: started,
+        deadline: deadline,
+        timed_out: false,
+        status: None,
+    })
+}
+
+/// Reads available output of a child.
+fn read_output(r: &mut Running, epoll: &Epoll) {
+    let eof = match r.pipe {
+        Some(ref mut pipe) => match r.output.read(pipe, READ_STEP_SIZE) {
+            Ok(0) | Err(_) => true,
+            _ => false,
+        },
+        None => return,
+    };
+    if eof {
+        if let Some(pipe) = r.pipe.take() {
+            let _ = epoll.remove(&pipe);
+        }
+    }
+}
+
+/// Kills the child if its deadline has passed and checks whether it has exited.
+fn poll_child(r: &mut Running, now: Time, epoll: &Epoll) -> Option<Outcome> {
+    if let Some(deadline) = r.deadline {
+        if !r.timed_out && deadline <= now {
+            let _ = signal::send(r.id, signals::Kill);
+            r.timed_out = true;
+        }
+    }
+
+    let status = match wait_id(r.id, WAIT_EXITED | WAIT_DONT_BLOCK) {
+        Ok(ChildStatus::None) => return None,
+        Ok(status) => {
+            r.status = Some(status);
+            Ok(status)
+        },
+        Err(e) => Err(e),
+    };
+
+    // Collect the output that is still buffered in the pipe. If the child has passed
+    // its end of the pipe on to another process, we don't wait for it.
+    loop {
+        let pending = match r.pipe {
+            Some(ref pipe) => match pipe.len() {
+                Ok(len) => len > 0,
+                _ => false,
+            },
+            None => false,
         };
+        if !pending {
+            break;
+        }
+        read_output(r, epoll);
+    }
+    if let Some(pipe) = r.pipe.take() {
+        let _ = epoll.remove(&pipe);
+    }
 
-        match wait_id(id, WAIT_EXITED) {
-            Ok(ChildStatus::Exited(0)) if !t.desc.should_panic.yes() => println!("ok"),
-            Ok(ChildStatus::Exited(1)) if t.desc.should_panic.yes() => println!("ok"),
-            Ok(e) => println!("FAILURE ({:?})", e),
-            Err(e) => println!("FAILURE ({:?})", e),
+    let should_panic = r.test.desc.should_panic.yes();
+    let outcome = match status {
+        _ if r.timed_out => Outcome::TimedOut,
+        Ok(ChildStatus::Exited(0)) if !should_panic => Outcome::Passed,
+        Ok(ChildStatus::Exited(1)) if should_panic => Outcome::Passed,
+        Ok(status) => {
+            let _ = write!(&mut r.output, "test exited with status {:?}\n", status);
+            Outcome::Failed
+        },
+        Err(e) => {
+            let _ = write!(&mut r.output, "could not wait for test ({:?})\n", e);
+            Outcome::Failed
+        },
+    };
+    Some(outcome)
+}
+
+fn print_result(r: &Record, opts: &Options) {
+    match r.outcome {
+        Outcome::Passed => println!("testing {} ... ok", r.name),
+        Outcome::Failed => println!("testing {} ... FAILURE", r.name),
+        Outcome::Ignored => println!("testing {} ... ignored", r.name),
+        Outcome::TimedOut => {
+            let secs = opts.timeout.map(|t| t.seconds).unwrap_or(0);
+            println!("testing {} ... TIMEOUT (killed after {}s)", r.name, secs);
+        },
+    }
+    let failed = match r.outcome {
+        Outcome::Failed | Outcome::TimedOut => true,
+        _ => false,
+    };
+    if failed && r.output.len() > 0 {
+        println!("---- output of {} ----", r.name);
+        let _ = STDOUT.write_all(&r.output);
+        if r.output[r.output.len() - 1] != b'\n' {
+            println!("");
+        }
+    }
+}
+
+/// Runs all selected benchmarks and exits.
+fn bench_main(tests: &[TestDescAndFn], opts: &Options) -> ! {
+    let baseline = match opts.baseline {
+        Some(path) => match bench::load_baseline(path) {
+            Ok(b) => b,
+            Err(e) => {
+                errln!("could not load baseline ({:?})", e);
+                process::exit(2);
+            },
+        },
+        None => Vec::new(),
+    };
+
+    let unit = opts.source.unit();
+    let mut results = Vec::new();
+    let mut regressions = 0;
+    for t in tests {
+        let f = match t.testfn {
+            StaticBenchFn(f) if !t.desc.ignore && opts.selected(t.desc.name.0) => f,
+            _ => continue,
+        };
+        let name = t.desc.name.0;
+        let stats = match bench::measure(f, opts.source) {
+            Some(stats) => stats,
+            None => {
+                println!("bench {} ... no iterations", name);
+                continue;
+            },
         };
+        print!("bench {} ... {:.1} {}/iter (median {:.1}, stddev {:.1}, mad {:.1})",
+               name, stats.mean, unit, stats.median, stats.stddev, stats.mad);
+        for base in &baseline[..] {
+            if &base.name[..] != name.as_bytes() {
+                continue;
+            }
+            let regressed = stats.regressed(&base.stats);
+            if regressed {
+                print!(" REGRESSION");
+                regressions += 1;
+            }
+            let base_median = base.stats.median;
+            if base_median > 0.0 {
+                let change = 100.0 * (stats.median - base_median) / base_median;
+                let sign = if change >= 0.0 { "+" } else { "" };
+                print!(" {}{:.1}%", sign, change);
+            } else {
+                print!(" (baseline median is 0)");
+            }
+        }
+        println!("");
+        if let Err(e) = results.push((name, stats)) {
+            errln!("could not record the results ({:?})", e);
+            process::exit(2);
+        }
+    }
+
+    if let Some(path) = opts.save_baseline {
+        if let Err(e) = bench::save_baseline(path, &results) {
+            errln!("could not save baseline ({:?})", e);
+            process::exit(2);
+        }
+    }
+
+    if regressions > 0 {
+        println!("\n{} regressions", regressions);
+        process::exit(1);
     }
+    process::exit(0);
 }
@@ -0,0 +1,108 @@
+//! Command line options.
+
+use std::getopt::{Getopt};
+use std::string::{CStr};
+use std::time::{Time};
+use std::thread::{cpu_count};
+use std::vec::{Vec};
+use std::error::{self};
+use bench::{self, Source};
+
+/// The default per-test timeout in seconds.
+const DEFAULT_TIMEOUT: i64 = 60;
+
+pub struct Options {
+    pub jobs: usize,
+    /// `None` if tests can run forever.
+    pub timeout: Option<Time>,
+    pub capture: bool,
+    /// The file to which a TAP report is written.
+    pub tap: Option<&'static CStr>,
+    /// The file to which a JUnit XML report is written.
+    pub junit: Option<&'static CStr>,
+    /// Whether benchmarks are measured instead of running the tests.
+    pub bench: bool,
+    /// The timestamp source of benchmarks.
+    pub source: Source,
+    /// The file from which benchmark results are loaded for comparison.
+    pub baseline: Option<&'static CStr>,
+    /// The file to which benchmark results are saved.
+    pub save_baseline: Option<&'static CStr>,
+    /// The prefixes of the tests to run. All tests are run if this is empty.
+    pub filters: Vec<&'static CStr>,
+}
+
+impl Options {
+    /// Parses the command line arguments that follow the program name.
+    ///
+    /// = Remarks
+    ///
+    /// Returns `InvalidArgument` if the arguments are not valid.
+    pub fn parse(args: &[&'static CStr]) -> Result<Options> {
+        let mut opts = Options {
+            jobs: match cpu_count() {
+                Ok(n) if n > 0 => n,
+                _ => 1,
+            },
+            timeout: Some(Time::seconds(DEFAULT_TIMEOUT)),
+            capture: true,
+            tap: None,
+            junit: None,
+            bench: false,
+            source: Source::MonoRaw,
+            baseline: None,
+            save_baseline: None,
+            filters: Vec::new(),
+        };
+
+        let params = [
+            (Some('j'), Some("jobs"), false),
+            (Some('t'), Some("timeout"), false),
+            (None, Some("tap"), false),
+            (None, Some("junit"), false),
+            (None, Some("baseline"), false),
+            (None, Some("save-baseline"), false),
+        ];
+        let mut getopt = Getopt::new(args.iter().map(|a| *a), &params);
+        for (arg, param) in &mut getopt {
+            match arg.as_ref() {
+                b"j" | b"jobs" => {
+                    opts.jobs = match param.map(|p| p.parse()) {
+                        Some(Ok(n)) if n > 0 => n,
+                        _ => return Err(error::InvalidArgument),
+                    };
+                },
+                b"t" | b"timeout" => {
+                    opts.timeout = match param.map(|p| p.parse()) {
+                        Some(Ok(0)) => None,
+                        Some(Ok(n)) if n > 0 => Some(Time::seconds(n)),
+                        _ => return Err(error::InvalidArgument),
+                    };
+                },
+                b"tap" if param.is_some() => opts.tap = param,
+                b"junit" if param.is_some() => opts.junit = param,
+                b"nocapture" => opts.capture = false,
+                b"bench" => opts.bench = true,
+                b"tsc" if bench::tsc().is_some() => opts.source = Source::Tsc,
+                b"baseline" if param.is_some() => opts.baseline = param,
+                b"save-baseline" if param.is_some() => opts.save_baseline = param,
+                _ => return Err(error::InvalidArgument),
+            }
+        }
+        try!(opts.filters.push_all(&args[getopt.used()..]));
+        Ok(opts)
+    }
+
+    /// Returns whether the test with this name is selected by the filters.
+    pub fn selected(&self, name: &str) -> bool {
+        if self.filters.len() == 0 {
+            return true;
+        }
+        for filter in &self.filters[..] {
+            if name.as_bytes().starts_with(filter.as_ref()) {
+                return true;
+            }
+        }
+        false
+    }
+}
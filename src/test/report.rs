@@ -0,0 +1,182 @@
+//! Machine-readable test reports.
+
+use std::process::{ChildStatus};
+use std::signal::{Signal};
+use std::file::{File};
+use std::file::flags::{FILE_WRITE_ONLY, FILE_CREATE, FILE_TRUNCATE};
+use std::file::mode::{MODE_FILE};
+use std::io::{Write};
+use std::iter::{IteratorExt};
+use std::string::{CStr};
+use std::time::{Time};
+use std::vec::{Vec};
+
+/// The outcome of a single test.
+#[derive(Copy, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    TimedOut,
+    Ignored,
+}
+
+/// The result of a single test.
+pub struct Record {
+    pub name: &'static str,
+    pub outcome: Outcome,
+    /// The wall-clock time between forking and reaping the child.
+    pub duration: Time,
+    /// `None` if the test was not run or could not be waited for.
+    pub status: Option<ChildStatus>,
+    /// The captured stdout and stderr of the child.
+    pub output: Vec<u8>,
+}
+
+/// Writes a TAP version 13 report to a file.
+pub fn write_tap(path: &CStr, records: &[Record]) -> Result {
+    write_file(path, &try!(tap(records)))
+}
+
+/// Writes a JUnit XML report to a file.
+pub fn write_junit(path: &CStr, records: &[Record]) -> Result {
+    write_file(path, &try!(junit(records)))
+}
+
+/// Creates a TAP version 13 report.
+pub fn tap(records: &[Record]) -> Result<Vec<u8>> {
+    let mut buf: Vec<u8> = Vec::new();
+    try!(write!(&mut buf, "TAP version 13\n1..{}\n", records.len()));
+    for (i, r) in records.iter().enumerate() {
+        let num = i + 1;
+        let duration = milliseconds(r.duration);
+        match r.outcome {
+            Outcome::Passed => {
+                try!(write!(&mut buf, "ok {} - {}\n", num, r.name));
+            },
+            Outcome::Ignored => {
+                try!(write!(&mut buf, "ok {} - {} # SKIP ignored\n", num, r.name));
+                continue;
+            },
+            _ => {
+                try!(write!(&mut buf, "not ok {} - {}\n", num, r.name));
+            },
+        }
+        try!(write!(&mut buf, "  ---\n  duration_ms: {}\n", duration));
+        if r.outcome == Outcome::TimedOut {
+            try!(write!(&mut buf, "  timeout: true\n"));
+        }
+        try!(write!(&mut buf, "  status: \""));
+        try!(write_status(&mut buf, r.status));
+        try!(write!(&mut buf, "\"\n"));
+        if r.output.len() > 0 {
+            try!(write!(&mut buf, "  output: |\n"));
+            let mut output = &r.output[..];
+            if output[output.len() - 1] == b'\n' {
+                output = &output[..output.len() - 1];
+            }
+            for line in output.split(|&b| b == b'\n') {
+                try!(write!(&mut buf, "    "));
+                try!(write_text(&mut buf, line, false));
+                try!(write!(&mut buf, "\n"));
+            }
+        }
+        try!(write!(&mut buf, "  ...\n"));
+    }
+    Ok(buf)
+}
+
+/// Creates a JUnit XML report.
+pub fn junit(records: &[Record]) -> Result<Vec<u8>> {
+    let mut failures = 0;
+    let mut skipped = 0;
+    let mut total = Time::nanoseconds(0);
+    for r in records {
+        match r.outcome {
+            Outcome::Failed | Outcome::TimedOut => failures += 1,
+            Outcome::Ignored => skipped += 1,
+            Outcome::Passed => { },
+        }
+        total = total + r.duration;
+    }
+
+    let mut buf: Vec<u8> = Vec::new();
+    try!(write!(&mut buf, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+    try!(write!(&mut buf, "<testsuite name=\"lrs\" tests=\"{}\" failures=\"{}\" \
+                           skipped=\"{}\" time=\"", records.len(), failures, skipped));
+    try!(write_seconds(&mut buf, total));
+    try!(write!(&mut buf, "\">\n"));
+    for r in records {
+        try!(write!(&mut buf, "  <testcase name=\""));
+        try!(write_text(&mut buf, r.name.as_bytes(), true));
+        try!(write!(&mut buf, "\" time=\""));
+        try!(write_seconds(&mut buf, r.duration));
+        try!(write!(&mut buf, "\">\n"));
+        match r.outcome {
+            Outcome::Passed => { },
+            Outcome::Ignored => try!(write!(&mut buf, "    <skipped/>\n")),
+            Outcome::Failed | Outcome::TimedOut => {
+                let kind = match r.outcome {
+                    Outcome::TimedOut => "timeout",
+                    _ => "failure",
+                };
+                try!(write!(&mut buf, "    <failure type=\"{}\" message=\"", kind));
+                let mut msg = Vec::new();
+                try!(write_status(&mut msg, r.status));
+                try!(write_text(&mut buf, &msg, true));
+                try!(write!(&mut buf, "\"/>\n"));
+            },
+        }
+        if r.output.len() > 0 {
+            try!(write!(&mut buf, "    <system-out>"));
+            try!(write_text(&mut buf, &r.output, true));
+            try!(write!(&mut buf, "</system-out>\n"));
+        }
+        try!(write!(&mut buf, "  </testcase>\n"));
+    }
+    try!(write!(&mut buf, "</testsuite>\n"));
+    Ok(buf)
+}
+
+fn write_file(path: &CStr, buf: &[u8]) -> Result {
+    let mut file = try!(File::open(path, FILE_WRITE_ONLY | FILE_CREATE | FILE_TRUNCATE,
+                                   MODE_FILE));
+    file.write_all(buf).ignore_ok()
+}
+
+fn milliseconds(t: Time) -> i64 {
+    let t = t.normalize();
+    t.seconds * 1_000 + t.nanoseconds / 1_000_000
+}
+
+fn write_seconds(buf: &mut Vec<u8>, t: Time) -> Result {
+    let ms = milliseconds(t);
+    write!(buf, "{}.{:03}", ms / 1_000, ms % 1_000)
+}
+
+fn write_status(buf: &mut Vec<u8>, status: Option<ChildStatus>) -> Result {
+    match status {
+        Some(ChildStatus::Exited(n)) => write!(buf, "exited with status {}", n),
+        Some(ChildStatus::Killed(n)) | Some(ChildStatus::Dumped(n)) => {
+            write!(buf, "terminated by signal {:?}", Signal(n as u8))
+        },
+        Some(status) => write!(buf, "{:?}", status),
+        None => write!(buf, "not run"),
+    }
+}
+
+/// Writes text, replacing control characters and optionally escaping XML markup.
+fn write_text(buf: &mut Vec<u8>, text: &[u8], xml: bool) -> Result {
+    for &b in text {
+        let escaped: &[u8] = match b {
+            b'&' if xml => b"&amp;",
+            b'<' if xml => b"&lt;",
+            b'>' if xml => b"&gt;",
+            b'"' if xml => b"&quot;",
+            b'\n' | b'\t' => { try!(buf.write_all(&[b])); continue; },
+            0...31 | 127 => b"?",
+            _ => { try!(buf.write_all(&[b])); continue; },
+        };
+        try!(buf.write_all(escaped));
+    }
+    Ok(())
+}
@@ -0,0 +1,389 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::error::{self, Errno, InvalidArgument};
+use core::{ptr, mem};
+use core::marker::{Leak};
+use cty::{c_int};
+use atomic::{Atomic};
+use syscall::{futex_wait, futex_wake};
+use sched::{self, SchedAttr};
+use {imp, JoinGuard, CpuMask, set_cpus, set_name_raw};
+
+/// The size of the CPU mask that can be stored in a `Builder`.
+///
+/// This is enough for 512 CPUs.
+const CPU_MASK_BYTES: usize = 512 / 8;
+
+/// Attributes that are applied in the new thread before the closure runs.
+struct Attrs {
+    /// The null-terminated name or all zeros.
+    name: [u8; 16],
+    cpus: Option<[u8; CPU_MASK_BYTES]>,
+    sched: Option<SchedAttr>,
+}
+
+impl Attrs {
+    fn is_default(&self) -> bool {
+        self.name[0] == 0 && self.cpus.is_none() && self.sched.is_none()
+    }
+
+    fn apply(&self) -> Result {
+        if self.name[0] != 0 {
+            try!(set_name_raw(&self.name));
+        }
+        if let Some(ref cpus) = self.cpus {
+            try!(set_cpus(0, CpuMask::new(&cpus[..])));
+        }
+        if let Some(attr) = self.sched {
+            try!(sched::set_scheduler(0, attr));
+        }
+        Ok(())
+    }
+}
+
+/// The status of a thread that has not yet reported whether it could apply its
+/// attributes.
+const STARTING: c_int = -1;
+
+/// Lives on the stack of the spawning thread until the new thread has applied its
+/// attributes.
+struct Start {
+    /// `STARTING`, `0` on success, or an error number.
+    status: Atomic<c_int>,
+    /// The address of the new thread's `Packet`.
+    packet: Atomic<usize>,
+}
+
+impl Start {
+    fn new() -> Start {
+        Start {
+            status: Atomic::new(STARTING),
+            packet: Atomic::new(0),
+        }
+    }
+
+    /// Waits for the new thread to report and returns the address of its `Packet`.
+    fn wait(&self) -> Result<usize> {
+        loop {
+            match self.status.load() {
+                STARTING => { futex_wait(&self.status, STARTING, None); },
+                0 => return Ok(self.packet.load()),
+                e => return Err(Errno(e)),
+            }
+        }
+    }
+}
+
+struct StartPtr(*const Start);
+
+unsafe impl Send for StartPtr { }
+
+/// Applies the attributes in the new thread and reports the result.
+///
+/// [return_value]
+/// Returns whether the closure should run.
+///
+/// = Remarks
+///
+/// The `Start` object must not be accessed after this function returns since the
+/// spawning thread is free to continue.
+unsafe fn setup(start: StartPtr, attrs: &Attrs, packet: usize) -> bool {
+    let start = &*start.0;
+    let status = match attrs.apply() {
+        Ok(()) => 0,
+        Err(e) => e.0,
+    };
+    start.packet.store(packet);
+    start.status.store(status);
+    futex_wake(&start.status, 1);
+    status == 0
+}
+
+const WAITING:  c_int = 0;
+const JOINING:  c_int = 1;
+const DETACHED: c_int = 2;
+
+/// Lives on the stack of a joinable thread until the thread has handed its result to
+/// the `JoinHandle`.
+struct Packet<T> {
+    state: Atomic<c_int>,
+    /// The `Option<T>` the result is written to if the state is `JOINING`.
+    dst: Atomic<usize>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> Packet<T> {
+    fn new() -> Packet<T> {
+        Packet {
+            state: Atomic::new(WAITING),
+            dst: Atomic::new(0),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Waits until the thread is joined or detached and hands over the result.
+    fn complete(&self, val: T) {
+        let mut state = self.state.load();
+        while state == WAITING {
+            futex_wait(&self.state, WAITING, None);
+            state = self.state.load();
+        }
+        if state == JOINING {
+            unsafe { ptr::write(self.dst.load() as *mut Option<T>, Some(val)); }
+        }
+    }
+
+    fn set_state(&self, state: c_int) {
+        self.state.store(state);
+        futex_wake(&self.state, 1);
+    }
+}
+
+/// A handle to a thread that can be joined to retrieve its result.
+///
+/// = Remarks
+///
+/// If the handle is dropped without calling `join`, the thread is detached and its
+/// result is dropped.
+pub struct JoinHandle<T> {
+    guard: Option<JoinGuard<'static>>,
+    packet: *const Packet<T>,
+}
+
+impl<T> JoinHandle<T> {
+    /// Waits for the thread to exit.
+    ///
+    /// [return_value]
+    /// Returns the value returned by the thread's closure.
+    ///
+    /// = Remarks
+    ///
+    /// If the thread exited without returning from the closure, e.g., by calling
+    /// `exit`, the error `OperationCanceled` is returned.
+    pub fn join(mut self) -> Result<T> {
+        let mut res = None;
+        unsafe {
+            let packet = &*self.packet;
+            packet.dst.store(&mut res as *mut Option<T> as usize);
+            packet.set_state(JOINING);
+        }
+        drop(self.guard.take());
+        match res {
+            Some(val) => Ok(val),
+            None => Err(error::OperationCanceled),
+        }
+    }
+
+    /// Detaches the thread.
+    ///
+    /// = Remarks
+    ///
+    /// This is equivalent to dropping the handle.
+    pub fn detach(self) { }
+}
+
+unsafe impl<T> Send for JoinHandle<T> where T: Send { }
+unsafe impl<T> Leak for JoinHandle<T> { }
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(guard) = self.guard.take() {
+            unsafe { (*self.packet).set_state(DETACHED); }
+            guard.detach();
+        }
+    }
+}
+
+/// A thread-builder
+///
+/// = Remarks
+///
+/// This can be used to modify properties of the thread before spawning it.
+pub struct Builder {
+    imp: imp::Builder,
+    attrs: Attrs,
+}
+
+impl Builder {
+    /// Creates a new thread-builder.
+    pub fn new() -> Result<Builder> {
+        Ok(Builder {
+            imp: try!(imp::Builder::new()),
+            attrs: Attrs {
+                name: [0; 16],
+                cpus: None,
+                sched: None,
+            },
+        })
+    }
+
+    /// Sets the size of the guard page at the end of the thread's stack.
+    ///
+    /// [argument, size]
+    /// The size of the guard page.
+    pub fn set_guard_size(&mut self, size: usize) -> Result {
+        self.imp.set_guard_size(size)
+    }
+
+    /// Returns the size of the guard page at the end of the thread's stack.
+    pub fn guard_size(&mut self) -> Result<usize> {
+        self.imp.guard_size()
+    }
+
+    /// Sets the size of the thread's stack.
+    ///
+    /// [argument, size]
+    /// The size of the thread's stack.
+    pub fn set_stack_size(&mut self, size: usize) -> Result {
+        self.imp.set_stack_size(size)
+    }
+
+    /// Returns the size of the thread's stack.
+    pub fn stack_size(&mut self) -> Result<usize> {
+        self.imp.stack_size()
+    }
+
+    /// Sets the name of the thread.
+    ///
+    /// [argument, name]
+    /// The name of the thread.
+    ///
+    /// = Remarks
+    ///
+    /// The name can be at most 15 bytes long and must not contain null bytes.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::thread::set_name
+    pub fn set_name(&mut self, name: &[u8]) -> Result {
+        if name.len() > 15 || name.find(|&b| b == 0).is_some() {
+            return Err(InvalidArgument);
+        }
+        self.attrs.name = [0; 16];
+        mem::copy(&mut self.attrs.name[..], name);
+        Ok(())
+    }
+
+    /// Sets the CPUs the thread is allowed to run on.
+    ///
+    /// [argument, cpus]
+    /// The CPU mask of the thread.
+    ///
+    /// = Remarks
+    ///
+    /// The mask can contain at most 512 CPUs.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::thread::set_cpus
+    pub fn set_cpus(&mut self, cpus: &CpuMask) -> Result {
+        if cpus.len() > CPU_MASK_BYTES * 8 {
+            return Err(InvalidArgument);
+        }
+        let mut buf = [0; CPU_MASK_BYTES];
+        mem::copy(&mut buf[..], cpus);
+        self.attrs.cpus = Some(buf);
+        Ok(())
+    }
+
+    /// Sets the scheduler of the thread.
+    ///
+    /// [argument, attributes]
+    /// The scheduler and attributes to set.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::thread::set_scheduler
+    pub fn set_scheduler(&mut self, attributes: SchedAttr) {
+        self.attrs.sched = Some(attributes);
+    }
+
+    /// Spawns a new thread.
+    ///
+    /// [argument, f]
+    /// The closure that will be run in the new thread.
+    ///
+    /// = Remarks
+    ///
+    /// If the attributes of the thread cannot be applied, the closure is not run and an
+    /// error is returned.
+    pub fn spawn<F>(self, f: F) -> Result
+        where F: FnOnce() + Send + 'static
+    {
+        if self.attrs.is_default() {
+            return self.imp.spawn(f);
+        }
+        let start = Start::new();
+        let ptr = StartPtr(&start);
+        let attrs = self.attrs;
+        try!(self.imp.spawn(move || {
+            if unsafe { setup(ptr, &attrs, 0) } {
+                f();
+            }
+        }));
+        start.wait().ignore_ok()
+    }
+
+    /// Spawns a new scoped thread.
+    ///
+    /// [argument, f]
+    /// The closure that will be run in the new thread.
+    ///
+    /// = Remarks
+    ///
+    /// The thread will automatically be joined when the guard's destructor runs. If the
+    /// attributes of the thread cannot be applied, the closure is not run and an error
+    /// is returned.
+    pub fn scoped<'a, F>(self, f: F) -> Result<JoinGuard<'a>>
+        where F: FnOnce() + Send + 'a
+    {
+        if self.attrs.is_default() {
+            return self.imp.scoped(f);
+        }
+        let start = Start::new();
+        let ptr = StartPtr(&start);
+        let attrs = self.attrs;
+        let guard = try!(self.imp.scoped(move || {
+            if unsafe { setup(ptr, &attrs, 0) } {
+                f();
+            }
+        }));
+        try!(start.wait());
+        Ok(guard)
+    }
+
+    /// Spawns a new joinable thread.
+    ///
+    /// [argument, f]
+    /// The closure that will be run in the new thread.
+    ///
+    /// [return_value]
+    /// Returns a handle that can be used to retrieve the return value of the closure.
+    ///
+    /// = Remarks
+    ///
+    /// If the attributes of the thread cannot be applied, the closure is not run and an
+    /// error is returned.
+    pub fn spawn_joinable<T, F>(self, f: F) -> Result<JoinHandle<T>>
+        where F: FnOnce() -> T + Send + 'static,
+              T: Send + 'static,
+    {
+        let start = Start::new();
+        let ptr = StartPtr(&start);
+        let attrs = self.attrs;
+        let guard = try!(self.imp.scoped(move || {
+            let packet = Packet::new();
+            if unsafe { setup(ptr, &attrs, &packet as *const Packet<T> as usize) } {
+                packet.complete(f());
+            }
+        }));
+        let packet = try!(start.wait());
+        Ok(JoinHandle {
+            guard: Some(guard),
+            packet: packet as *const Packet<T>,
+        })
+    }
+}
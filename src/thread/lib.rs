@@ -24,6 +24,7 @@ extern crate lrs_signal as signal;
 #[cfg(not(no_libc))] extern crate lrs_libc as libc;
 
 use base::prelude::*;
+use base::{error};
 use core::ops::{Index};
 use core::{mem};
 use cty::{c_int};
@@ -38,12 +39,14 @@ mod std { pub use fmt::std::*; pub use cty; pub use fd; }
 #[cfg(not(no_libc))] #[path = "libc/mod.rs"] mod imp;
 #[cfg(no_libc)] #[path = "no_libc/mod.rs"] mod imp;
 
-pub use imp::{Builder, JoinGuard};
+pub use imp::{JoinGuard};
+pub use builder::{Builder, JoinHandle};
 
 pub mod ids;
 pub mod sched;
 pub mod cap;
 pub mod at_exit_;
+mod builder;
 
 /// Spawns a new thread.
 ///
@@ -52,7 +55,21 @@ pub mod at_exit_;
 pub fn spawn<F>(f: F) -> Result
     where F: FnOnce() + Send + 'static
 {
-    imp::Builder::new().chain(|b| b.spawn(f))
+    Builder::new().chain(|b| b.spawn(f))
+}
+
+/// Spawns a new joinable thread.
+///
+/// [argument, f]
+/// The closure that will be run in the new thread.
+///
+/// [return_value]
+/// Returns a handle that can be used to retrieve the return value of the closure.
+pub fn spawn_joinable<T, F>(f: F) -> Result<JoinHandle<T>>
+    where F: FnOnce() -> T + Send + 'static,
+          T: Send + 'static,
+{
+    Builder::new().chain(|b| b.spawn_joinable(f))
 }
 
 /// Spawns a new scoped thread.
@@ -66,7 +83,7 @@ pub fn spawn<F>(f: F) -> Result
 pub fn scoped<'a, F>(f: F) -> Result<JoinGuard<'a>>
     where F: FnOnce() + Send + 'a
 {
-    imp::Builder::new().chain(|b| b.scoped(f))
+    Builder::new().chain(|b| b.scoped(f))
 }
 
 /// Adds a closure to be run when the thread exits.
@@ -236,6 +253,48 @@ pub fn set_cpus(thread: ProcessId, cpus: &CpuMask) -> Result {
     rv!(syscall::sched_setaffinity(thread, cpus.buf.as_ref()))
 }
 
+/// Sets the name of the calling thread.
+///
+/// [argument, name]
+/// The new name of the thread.
+///
+/// = Remarks
+///
+/// The name can be at most 15 bytes long and must not contain null bytes.
+///
+/// = See also
+///
+/// * link:man:prctl(2) and PR_SET_NAME therein
+pub fn set_name(name: &[u8]) -> Result {
+    if name.len() > 15 || name.find(|&b| b == 0).is_some() {
+        return Err(error::InvalidArgument);
+    }
+    let mut buf = [0; 16];
+    mem::copy(&mut buf[..], name);
+    set_name_raw(&buf)
+}
+
+fn set_name_raw(name: &[u8; 16]) -> Result {
+    rv!(syscall::prctl_pr_set_name(name))
+}
+
+/// Retrieves the name of the calling thread.
+///
+/// [argument, buf]
+/// The buffer in which the name will be stored.
+///
+/// [return_value]
+/// Returns the name without the terminating null byte.
+///
+/// = See also
+///
+/// * link:man:prctl(2) and PR_GET_NAME therein
+pub fn name(buf: &mut [u8; 16]) -> Result<&[u8]> {
+    try!(rv!(syscall::prctl_pr_get_name(buf)));
+    let len = buf.find(|&b| b == 0).unwrap_or(buf.len());
+    Ok(&buf[..len])
+}
+
 /// Returns the thread id of the calling thread.
 ///
 /// = See also
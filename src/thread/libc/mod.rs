@@ -20,6 +20,15 @@ pub struct JoinGuard<'a> {
     _marker: PhantomData<&'a ()>,
 }
 
+impl JoinGuard<'static> {
+    pub fn detach(self) {
+        unsafe {
+            libc::pthread_detach(self.thread);
+            mem::unsafe_forget(self);
+        }
+    }
+}
+
 impl<'a> Drop for JoinGuard<'a> {
     fn drop(&mut self) {
         unsafe { libc::pthread_join(self.thread, 0 as *mut _); }
@@ -176,6 +176,7 @@ create_flags! {
 /// = See also
 ///
 /// * link:man:sched_setattr(2)
+#[derive(Pod)]
 pub struct SchedAttr {
     /// The scheduler itself.
     pub scheduler: Scheduler,
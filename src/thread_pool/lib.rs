@@ -0,0 +1,414 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+#![crate_name = "lrs_thread_pool"]
+#![crate_type = "lib"]
+#![feature(thread_local)]
+#![no_std]
+
+extern crate lrs_base as base;
+extern crate lrs_cty as cty;
+extern crate lrs_atomic as atomic;
+extern crate lrs_lock as lock;
+extern crate lrs_alloc as alloc;
+extern crate lrs_box as bx;
+extern crate lrs_vec as vec;
+extern crate lrs_rc as rc;
+extern crate lrs_ringbuf as ringbuf;
+extern crate lrs_syscall as syscall;
+extern crate lrs_thread as thread;
+
+use base::prelude::*;
+use core::{mem, slice};
+use cty::{c_int};
+use atomic::{Atomic};
+use lock::{Mutex};
+use bx::{Box};
+use vec::{Vec};
+use rc::{Arc};
+use ringbuf::{DynRingBuf};
+use syscall::{futex_wait, futex_wake};
+use thread::{Builder, JoinHandle, CpuMask};
+
+pub mod std { pub use base::std::*; }
+
+/// A closure that has been moved to the heap.
+trait Task {
+    fn run(&mut self);
+}
+
+impl<F> Task for Option<F>
+    where F: FnOnce(),
+{
+    fn run(&mut self) {
+        if let Some(f) = self.take() {
+            f();
+        }
+    }
+}
+
+type Job = Box<Task + Send + 'static>;
+
+thread_local! {
+    // The address of the `Shared` object of the pool this thread belongs to.
+    static CURRENT_POOL: Atomic<usize> = Atomic::new(0);
+    // The index of this thread in the pool.
+    static CURRENT_INDEX: Atomic<usize> = Atomic::new(0);
+}
+
+struct Shared {
+    // One deque per worker. The owner pushes and pops at the right end. Other threads
+    // steal from the left end.
+    deques: Vec<Mutex<DynRingBuf<Job>>>,
+    // Incremented whenever new work is available, a scope has completed, or the pool
+    // is shut down. Idle threads sleep on it.
+    seq: Atomic<c_int>,
+    sleeping: Atomic<c_int>,
+    shutdown: Atomic<u8>,
+    // The deque of the next job pushed from outside of the pool.
+    next: Atomic<usize>,
+}
+
+unsafe impl Send for Shared { }
+unsafe impl Sync for Shared { }
+
+impl Shared {
+    /// Returns the index of the calling thread if it's a worker of this pool.
+    fn current_index(&self) -> Option<usize> {
+        match CURRENT_POOL.load() == self as *const Shared as usize {
+            true => Some(CURRENT_INDEX.load()),
+            false => None,
+        }
+    }
+
+    fn push(&self, job: Job) -> Result {
+        let idx = match self.current_index() {
+            Some(idx) => idx,
+            None => self.next.add(1) % self.deques.len(),
+        };
+        {
+            let mut deque = self.deques[idx].lock();
+            try!(deque.reserve(1));
+            deque.push_right(job);
+        }
+        self.notify(1);
+        Ok(())
+    }
+
+    /// Pops a job from the own deque or steals one from another deque.
+    fn pop(&self) -> Option<Job> {
+        let start = match self.current_index() {
+            Some(idx) => {
+                if let Some(job) = self.deques[idx].lock().pop_right() {
+                    return Some(job);
+                }
+                idx + 1
+            },
+            None => 0,
+        };
+        let n = self.deques.len();
+        for i in 0..n {
+            if let Some(job) = self.deques[(start + i) % n].lock().pop_left() {
+                return Some(job);
+            }
+        }
+        None
+    }
+
+    fn notify(&self, n: usize) {
+        self.seq.add(1);
+        if self.sleeping.load() > 0 {
+            futex_wake(&self.seq, n);
+        }
+    }
+
+    /// Runs jobs until `done` returns true.
+    fn run_until<F>(&self, mut done: F)
+        where F: FnMut() -> bool,
+    {
+        loop {
+            let seq = self.seq.load();
+            if let Some(mut job) = self.pop() {
+                job.run();
+                continue;
+            }
+            if done() {
+                return;
+            }
+            self.sleeping.add(1);
+            futex_wait(&self.seq, seq, None);
+            self.sleeping.sub(1);
+        }
+    }
+}
+
+fn worker(shared: Arc<Shared>, idx: usize) {
+    CURRENT_POOL.store(&*shared as *const Shared as usize);
+    CURRENT_INDEX.store(idx);
+    shared.run_until(|| shared.shutdown.load() != 0);
+}
+
+/// A pool of worker threads.
+///
+/// = Remarks
+///
+/// Every worker has its own deque of jobs. Jobs submitted by a worker are pushed to its
+/// own deque and idle workers steal jobs from the deques of other workers.
+///
+/// When the pool is dropped, all jobs that have already been submitted are run and the
+/// workers are joined.
+pub struct ThreadPool {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    /// Creates a new thread pool with one worker per available CPU.
+    pub fn new() -> Result<ThreadPool> {
+        Self::with_threads(try!(thread::cpu_count()), false)
+    }
+
+    /// Creates a new thread pool.
+    ///
+    /// [argument, threads]
+    /// The number of worker threads.
+    ///
+    /// [argument, pin]
+    /// Whether each worker should be pinned to one of the CPUs available to the calling
+    /// thread.
+    ///
+    /// = Remarks
+    ///
+    /// If `threads` is `0`, the pool is created with one thread.
+    pub fn with_threads(threads: usize, pin: bool) -> Result<ThreadPool> {
+        let threads = if threads == 0 { 1 } else { threads };
+
+        let mut deques = try!(Vec::with_capacity(threads));
+        for _ in 0..threads {
+            try!(deques.push(Mutex::new(DynRingBuf::new())));
+        }
+        let shared = try!(Arc::new()).set(Shared {
+            deques: deques,
+            seq: Atomic::new(0),
+            sleeping: Atomic::new(0),
+            shutdown: Atomic::new(0),
+            next: Atomic::new(0),
+        });
+
+        let mut cpus = [0; 512 / 8];
+        let cpus = match pin {
+            true => Some(try!(thread::cpus(0, &mut cpus))),
+            false => None,
+        };
+
+        // If spawning a worker fails, the destructor shuts down the workers that have
+        // already been spawned.
+        let mut pool = ThreadPool {
+            shared: shared,
+            workers: try!(Vec::with_capacity(threads)),
+        };
+        for idx in 0..threads {
+            let mut builder = try!(Builder::new());
+            if let Some(ref cpus) = cpus {
+                let mut mask = [0; 512 / 8];
+                CpuMask::new_mut(&mut mask).set(nth_cpu(cpus, idx));
+                try!(builder.set_cpus(CpuMask::new(&mask)));
+            }
+            let shared = pool.shared.add_ref();
+            let handle = try!(builder.spawn_joinable(move || worker(shared, idx)));
+            try!(pool.workers.push(handle));
+        }
+        Ok(pool)
+    }
+
+    /// Returns the number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.shared.deques.len()
+    }
+
+    /// Runs a closure on one of the workers.
+    ///
+    /// [argument, f]
+    /// The closure to run.
+    pub fn execute<F>(&self, f: F) -> Result
+        where F: FnOnce() + Send + 'static,
+    {
+        let job: Job = try!(Box::new()).set(Some(f));
+        self.shared.push(job)
+    }
+
+    /// Creates a scope in which closures that borrow from the stack can be run.
+    ///
+    /// [argument, f]
+    /// The closure that is called with the scope.
+    ///
+    /// [return_value]
+    /// Returns the value returned by `f`.
+    ///
+    /// = Remarks
+    ///
+    /// This function returns once all closures spawned in the scope have completed.
+    /// While waiting, the calling thread helps running jobs of the pool.
+    ///
+    /// = Examples
+    ///
+    /// ----
+    /// let mut a = 0;
+    /// let mut b = 0;
+    /// pool.scope(|s| {
+    ///     s.spawn(|| a = 1).unwrap();
+    ///     s.spawn(|| b = 2).unwrap();
+    /// });
+    /// ----
+    pub fn scope<'a, F, R>(&'a self, f: F) -> R
+        where F: FnOnce(&Scope<'a>) -> R,
+    {
+        let scope = Scope {
+            shared: &self.shared,
+            pending: Atomic::new(0),
+            _marker: PhantomData,
+        };
+        let res = f(&scope);
+        scope.wait();
+        res
+    }
+
+    /// Calls a closure on every element of a slice in parallel.
+    ///
+    /// [argument, slice]
+    /// The slice whose elements will be passed to the closure.
+    ///
+    /// [argument, f]
+    /// The closure.
+    ///
+    /// = Remarks
+    ///
+    /// The slice is split into chunks which are processed by the workers. If a chunk
+    /// cannot be submitted to the pool, it's processed by the calling thread.
+    pub fn for_each<T, F>(&self, slice: &mut [T], f: F)
+        where T: Send,
+              F: Fn(&mut T) + Sync,
+    {
+        let chunks = self.threads() * 4;
+        let size = (slice.len() + chunks - 1) / chunks;
+        let ptr = slice.as_mut_ptr();
+        let len = slice.len();
+        let f = &f;
+        self.scope(|s| {
+            let mut start = 0;
+            while start < len {
+                let n = if len - start < size { len - start } else { size };
+                let chunk = ChunkPtr(unsafe { ptr.add(start) }, n);
+                if s.spawn(move || unsafe { chunk.run(f) }).is_err() {
+                    unsafe { chunk.run(f); }
+                }
+                start += n;
+            }
+        });
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.shared.shutdown.store(1);
+        self.shared.notify(!0);
+        while let Some(worker) = self.workers.pop() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// A chunk of a slice passed to another thread.
+struct ChunkPtr<T>(*mut T, usize);
+
+impl<T> Copy for ChunkPtr<T> { }
+unsafe impl<T> Send for ChunkPtr<T> where T: Send { }
+
+impl<T> ChunkPtr<T> {
+    /// Calls `f` on every element of the chunk.
+    ///
+    /// = Remarks
+    ///
+    /// The caller must ensure that no other thread accesses the chunk at the same time.
+    unsafe fn run<F>(self, f: &F)
+        where F: Fn(&mut T),
+    {
+        for el in slice::from_ptr(self.0, self.1) {
+            f(el);
+        }
+    }
+}
+
+/// Returns the id of the `n`-th CPU in a mask, counting modulo the number of CPUs.
+///
+/// Returns CPU `0` if the mask is empty.
+fn nth_cpu(cpus: &CpuMask, n: usize) -> usize {
+    let count = cpus.count();
+    if count == 0 {
+        return 0;
+    }
+    let mut n = n % count;
+    for i in 0..cpus.len() {
+        if cpus[i] {
+            if n == 0 {
+                return i;
+            }
+            n -= 1;
+        }
+    }
+    0
+}
+
+/// A scope created by `ThreadPool::scope`.
+pub struct Scope<'a> {
+    shared: &'a Shared,
+    pending: Atomic<usize>,
+    // Make the scope invariant in `'a`.
+    _marker: PhantomData<fn(&'a ()) -> &'a ()>,
+}
+
+impl<'a> Scope<'a> {
+    /// Runs a closure on one of the workers.
+    ///
+    /// [argument, f]
+    /// The closure to run.
+    ///
+    /// = Remarks
+    ///
+    /// The closure can borrow data that outlives the scope.
+    pub fn spawn<F>(&self, f: F) -> Result
+        where F: FnOnce() + Send + 'a,
+    {
+        let buf = try!(Box::new());
+        self.pending.add(1);
+        let scope = ScopePtr(self);
+        let job: Box<Task + Send + 'a> = buf.set(Some(move || {
+            f();
+            unsafe { (*scope.0).complete(); }
+        }));
+        // The scope waits for all jobs to complete before `'a` ends.
+        let job: Job = unsafe { mem::cast(job) };
+        if let Err(e) = self.shared.push(job) {
+            self.pending.sub(1);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn complete(&self) {
+        // The scope can be destroyed as soon as `pending` reaches zero.
+        let shared = self.shared;
+        if self.pending.sub(1) == 1 {
+            shared.notify(!0);
+        }
+    }
+
+    fn wait(&self) {
+        self.shared.run_until(|| self.pending.load() == 0);
+    }
+}
+
+struct ScopePtr<'a>(*const Scope<'a>);
+
+unsafe impl<'a> Send for ScopePtr<'a> { }
@@ -0,0 +1,333 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+use core::ops::{Add, Sub, Neg, PartialOrd, Ordering};
+use core::cmp::{Ord};
+use fmt::{Debug, Display, Write};
+use parse::{Parsable};
+
+use {Time, NANOS_PER_SEC, NANOS_PER_MILLI, NANOS_PER_MICRO, SECS_PER_MIN, SECS_PER_HOUR};
+
+/// A signed length of time.
+///
+/// = Remarks
+///
+/// This is a thin wrapper around a `Time` that is interpreted as a length of time. The
+/// `Display` implementation uses the format `1h2m3.5s` and the `Parsable`
+/// implementation accepts this format.
+///
+/// The format is a sequence of decimal numbers with optional fractions, each followed
+/// by a unit, e.g., `1.5h` or `2m30s`. The sequence can be preceded by a sign. Valid
+/// units are `h`, `m`, `s`, `ms`, `us`, and `ns`. `0` is also accepted.
+#[derive(Pod, Eq)]
+pub struct Duration(pub Time);
+
+impl Duration {
+    /// Creates a `Duration` that represents a number of nanoseconds.
+    ///
+    /// [argument, n]
+    /// The number of nanoseconds.
+    pub fn nanoseconds(n: i64) -> Duration {
+        Duration(Time::nanoseconds(n))
+    }
+
+    /// Creates a `Duration` that represents a number of microseconds.
+    ///
+    /// [argument, m]
+    /// The number of microseconds.
+    pub fn microseconds(m: i64) -> Duration {
+        Duration(Time::microseconds(m))
+    }
+
+    /// Creates a `Duration` that represents a number of milliseconds.
+    ///
+    /// [argument, m]
+    /// The number of milliseconds.
+    pub fn milliseconds(m: i64) -> Duration {
+        Duration(Time::milliseconds(m))
+    }
+
+    /// Creates a `Duration` that represents a number of seconds.
+    ///
+    /// [argument, s]
+    /// The number of seconds.
+    pub fn seconds(s: i64) -> Duration {
+        Duration(Time::seconds(s))
+    }
+
+    /// Creates a `Duration` that represents a number of minutes.
+    ///
+    /// [argument, m]
+    /// The number of minutes.
+    pub fn minutes(m: i64) -> Duration {
+        Duration(Time::minutes(m))
+    }
+
+    /// Creates a `Duration` that represents a number of hours.
+    ///
+    /// [argument, h]
+    /// The number of hours.
+    pub fn hours(h: i64) -> Duration {
+        Duration(Time::hours(h))
+    }
+
+    /// Creates a `Duration` that represents a number of days.
+    ///
+    /// [argument, d]
+    /// The number of days.
+    pub fn days(d: i64) -> Duration {
+        Duration(Time::days(d))
+    }
+
+    /// Returns whether the duration is negative.
+    pub fn is_negative(&self) -> bool {
+        self.0.normalize().seconds < 0
+    }
+
+    /// Returns the absolute value of the duration.
+    ///
+    /// = Remarks
+    ///
+    /// The absolute value of the smallest representable duration is one nanosecond
+    /// larger than the largest representable duration. In this case, the result
+    /// saturates at the largest representable duration. The same applies to
+    /// negation.
+    pub fn abs(&self) -> Duration {
+        match self.is_negative() {
+            true => -*self,
+            _ => Duration(self.0.normalize()),
+        }
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration(self.0 + other.0)
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration(self.0 - other.0)
+    }
+}
+
+impl Neg for Duration {
+    type Output = Duration;
+
+    fn neg(self) -> Duration {
+        let time = self.0.normalize();
+        // -(s + n) = (-s - 1) + (1 - n) and -s - 1 = !s cannot overflow.
+        let time = match (time.seconds, time.nanoseconds) {
+            (s, 0) if s == i64::min() => Time { seconds: i64::max(), ..time },
+            (s, 0) => Time { seconds: -s, ..time },
+            (s, n) => Time { seconds: !s, nanoseconds: NANOS_PER_SEC - n },
+        };
+        Duration(time)
+    }
+}
+
+impl Add<Duration> for Time {
+    type Output = Time;
+
+    fn add(self, other: Duration) -> Time {
+        self + other.0
+    }
+}
+
+impl Sub<Duration> for Time {
+    type Output = Time;
+
+    fn sub(self, other: Duration) -> Time {
+        self - other.0
+    }
+}
+
+impl PartialOrd for Duration {
+    fn partial_cmp(&self, other: &Duration) -> Option<Ordering> {
+        Some(self.cmp(&other))
+    }
+}
+
+impl Ord for Duration {
+    fn cmp(&self, other: &Duration) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+/// Writes `int.frac` where `frac` has `digits` digits and trailing zeros are removed.
+fn write_frac<W: Write>(mut w: &mut W, int: i64, mut frac: i64, digits: usize) -> Result {
+    try!(write!(w, "{}", int));
+    if frac == 0 {
+        return Ok(());
+    }
+    let mut buf = [b'0'; 9];
+    let mut len = digits;
+    for i in 0..digits {
+        buf[digits - i - 1] = b'0' + (frac % 10) as u8;
+        frac /= 10;
+    }
+    while buf[len - 1] == b'0' {
+        len -= 1;
+    }
+    try!(w.write_all(b"."));
+    w.write_all(&buf[..len]).ignore_ok()
+}
+
+impl Display for Duration {
+    fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
+        let time = self.abs().0;
+        if self.is_negative() {
+            try!(w.write_all(b"-"));
+        }
+        let (secs, nanos) = (time.seconds, time.nanoseconds);
+        if secs == 0 {
+            return match nanos {
+                0 => w.write_all(b"0s").ignore_ok(),
+                1 ... 999 => write!(w, "{}ns", nanos),
+                1_000 ... 999_999 => {
+                    try!(write_frac(w, nanos / NANOS_PER_MICRO, nanos % NANOS_PER_MICRO,
+                                    3));
+                    w.write_all(b"us").ignore_ok()
+                },
+                _ => {
+                    try!(write_frac(w, nanos / NANOS_PER_MILLI, nanos % NANOS_PER_MILLI,
+                                    6));
+                    w.write_all(b"ms").ignore_ok()
+                },
+            };
+        }
+        let hours = secs / SECS_PER_HOUR;
+        let minutes = secs % SECS_PER_HOUR / SECS_PER_MIN;
+        if hours > 0 {
+            try!(write!(w, "{}h", hours));
+        }
+        if hours > 0 || minutes > 0 {
+            try!(write!(w, "{}m", minutes));
+        }
+        try!(write_frac(w, secs % SECS_PER_MIN, nanos, 9));
+        w.write_all(b"s").ignore_ok()
+    }
+}
+
+impl Debug for Duration {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Display::fmt(self, w)
+    }
+}
+
+/// Returns the number of nanoseconds in a unit and the length of the unit.
+fn unit(bytes: &[u8]) -> Result<(i64, usize)> {
+    let (first, second) = match bytes.len() {
+        0 => (0, 0),
+        1 => (bytes[0], 0),
+        _ => (bytes[0], bytes[1]),
+    };
+    match (first, second) {
+        (b'n', b's') => Ok((1, 2)),
+        (b'u', b's') => Ok((NANOS_PER_MICRO, 2)),
+        (b'm', b's') => Ok((NANOS_PER_MILLI, 2)),
+        (b's', _) => Ok((NANOS_PER_SEC, 1)),
+        (b'm', _) => Ok((SECS_PER_MIN * NANOS_PER_SEC, 1)),
+        (b'h', _) => Ok((SECS_PER_HOUR * NANOS_PER_SEC, 1)),
+        _ => Err(error::InvalidSequence),
+    }
+}
+
+impl Parsable for Duration {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(Duration, usize)> {
+        let mut pos = 0;
+        let negative = match bytes.len() > 0 {
+            true if bytes[0] == b'-' => { pos += 1; true },
+            true if bytes[0] == b'+' => { pos += 1; false },
+            _ => false,
+        };
+        if pos + 1 == bytes.len() && bytes[pos] == b'0' {
+            return Ok((Duration::seconds(0), bytes.len()));
+        }
+
+        let mut secs: i64 = 0;
+        let mut nanos: i64 = 0;
+        let mut parts = 0;
+        loop {
+            let start = pos;
+            let mut int: i64 = 0;
+            while pos < bytes.len() && bytes[pos] >= b'0' && bytes[pos] <= b'9' {
+                let digit = (bytes[pos] - b'0') as i64;
+                int = match int.checked_mul(10).chain(|i| i.checked_add(digit)) {
+                    Some(int) => int,
+                    _ => return Err(error::Overflow),
+                };
+                pos += 1;
+            }
+            let int_digits = pos - start;
+            let mut frac_digits = 0;
+            let frac_start = pos + 1;
+            if pos < bytes.len() && bytes[pos] == b'.' {
+                pos += 1;
+                while pos < bytes.len() && bytes[pos] >= b'0' && bytes[pos] <= b'9' {
+                    pos += 1;
+                }
+                frac_digits = pos - frac_start;
+            }
+            if int_digits == 0 && frac_digits == 0 {
+                if parts == 0 {
+                    return Err(error::InvalidSequence);
+                }
+                pos = start;
+                break;
+            }
+            let (unit_nanos, unit_len) = match unit(&bytes[pos..]) {
+                Ok(u) => u,
+                _ if parts > 0 => { pos = start; break; },
+                Err(e) => return Err(e),
+            };
+            pos += unit_len;
+
+            // Integer part.
+            if unit_nanos >= NANOS_PER_SEC {
+                let s = int.checked_mul(unit_nanos / NANOS_PER_SEC);
+                secs = match s.chain(|s| secs.checked_add(s)) {
+                    Some(s) => s,
+                    _ => return Err(error::Overflow),
+                };
+            } else {
+                let per_sec = NANOS_PER_SEC / unit_nanos;
+                secs = match secs.checked_add(int / per_sec) {
+                    Some(s) => s,
+                    _ => return Err(error::Overflow),
+                };
+                nanos += int % per_sec * unit_nanos;
+            }
+
+            // Fractional part.
+            let mut scale = unit_nanos;
+            for i in 0..frac_digits {
+                scale /= 10;
+                nanos += (bytes[frac_start + i] - b'0') as i64 * scale;
+            }
+
+            let (s, n) = nanos.div_rem(NANOS_PER_SEC);
+            secs = match secs.checked_add(s) {
+                Some(s) => s,
+                _ => return Err(error::Overflow),
+            };
+            nanos = n;
+            parts += 1;
+        }
+
+        let time = Time { seconds: secs, nanoseconds: nanos };
+        let duration = match negative {
+            true => -Duration(time),
+            _ => Duration(time),
+        };
+        Ok((duration, pos))
+    }
+}
@@ -12,6 +12,7 @@ extern crate lrs_base as base;
 extern crate lrs_cty as cty;
 extern crate lrs_fmt as fmt;
 extern crate lrs_rv as rv;
+extern crate lrs_parse as parse;
 #[cfg(not(freestanding))] extern crate lrs_fd as fd;
 #[cfg(not(freestanding))] extern crate lrs_syscall as syscall;
 
@@ -23,6 +24,10 @@ use core::cmp::{Ord};
 use cty::{timespec, time_t, k_long};
 use fmt::{Debug, Write};
 
+pub use duration::{Duration};
+
+mod duration;
+
 #[cfg(not(freestanding))] pub mod clock;
 #[cfg(not(freestanding))] pub mod timer;
 
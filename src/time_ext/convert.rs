@@ -6,32 +6,51 @@ use base::prelude::*;
 use core::ops::{Ordering};
 use core::cmp::{Ord};
 
-use super::{Zone, DateTime, Weekday, Time};
+use super::{Zone, DateTime, Weekday, Time, Abbreviation};
 
-const SECS_PER_MIN:         i64 = 60;
+pub const SECS_PER_MIN:     i64 = 60;
 const MINS_PER_HOUR:        i64 = 60;
 const HOURS_PER_DAY:        i64 = 24;
 const DAYS_PER_WEEK:        i64 = 7;
 const DAYS_PER_NORMAL_YEAR: i64 = 365;
 const DAYS_PER_LEAP_YEAR:   i64 = 366;
-const SECS_PER_HOUR:        i64 = SECS_PER_MIN * MINS_PER_HOUR;
-const SECS_PER_DAY:         i64 = SECS_PER_HOUR * HOURS_PER_DAY;
+pub const SECS_PER_HOUR:    i64 = SECS_PER_MIN * MINS_PER_HOUR;
+pub const SECS_PER_DAY:     i64 = SECS_PER_HOUR * HOURS_PER_DAY;
 const MONTHS_PER_YEAR:      i64 = 12;
 
+pub const NANOS_PER_SEC:    i64 = 1_000_000_000;
+
 const EPOCH_YEAR: i64 = 1970;
 const EPOCH_WEEK_DAY: Weekday = Weekday::Thursday;
 
-static DAYS_PER_MONTH: [[i8; 12]; 2] = [
+pub static DAYS_PER_MONTH: [[i8; 12]; 2] = [
 	[31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
 	[31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31],
 ];
 
-static DAYS_PER_YEAR: [i64; 2] = [
+pub static DAYS_PER_YEAR: [i64; 2] = [
     DAYS_PER_NORMAL_YEAR, DAYS_PER_LEAP_YEAR,
 ];
 
+/// A state of a time zone.
+#[derive(Copy, Eq)]
+pub struct State {
+    /// The offset from UTC in seconds.
+    pub offset:       i64,
+    /// Whether the state is summer time.
+    pub summer_time:  bool,
+    /// The abbreviation of the state.
+    pub abbreviation: Abbreviation,
+}
+
+impl TryFrom for State {
+    fn try_from(s: &State) -> Result<State> {
+        Ok(*s)
+    }
+}
+
 /// Returns `1` is the year is a leap year, `0` otherwise.
-fn is_leap(year: i64) -> usize {
+pub fn is_leap(year: i64) -> usize {
     (year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)) as usize
 }
 
@@ -44,9 +63,183 @@ fn leap_years_to(year: i64) -> i64 {
     }
 }
 
-pub fn explode(zone: &Zone, time: i64) -> DateTime {
-    let state = if zone.transitions.len() == 0 || time < zone.transitions[0].0 {
-        match zone.states.find(|s| !s.1) {
+/// Returns the number of days between the epoch and the first day of the year.
+pub fn year_start(year: i64) -> i64 {
+    (year - EPOCH_YEAR) * DAYS_PER_NORMAL_YEAR + leap_years_to(year - 1) -
+        leap_years_to(EPOCH_YEAR - 1)
+}
+
+/// Splits a number of days since the epoch into the year and the day in the year
+/// starting at 0.
+pub fn split_days(mut days: i64) -> (i64, i64) {
+    let mut year = EPOCH_YEAR;
+    while days < 0 || days >= DAYS_PER_YEAR[is_leap(year)] {
+        let next_year = year + match days / DAYS_PER_LEAP_YEAR {
+            0 if days < 0 => -1,
+            0 => 1,
+            n => n,
+        };
+        let leap_days = leap_years_to(next_year - 1) - leap_years_to(year - 1);
+        days -= (next_year - year) * DAYS_PER_NORMAL_YEAR;
+        days -= leap_days;
+        year = next_year;
+    }
+    (year, days)
+}
+
+/// Returns the weekday of a day in a year starting at 0.
+pub fn weekday(year: i64, day: i64) -> Weekday {
+    let weekday = ((((EPOCH_WEEK_DAY as i64) + ((year - EPOCH_YEAR) % DAYS_PER_WEEK) *
+                    (DAYS_PER_NORMAL_YEAR % DAYS_PER_WEEK) + leap_years_to(year - 1) -
+                    leap_years_to(EPOCH_YEAR - 1) + day) % DAYS_PER_WEEK) +
+                    DAYS_PER_WEEK) % DAYS_PER_WEEK;
+    match weekday {
+        0 => Weekday::Monday,
+        1 => Weekday::Tuesday,
+        2 => Weekday::Wednesday,
+        3 => Weekday::Thursday,
+        4 => Weekday::Friday,
+        5 => Weekday::Saturday,
+        _ => Weekday::Sunday,
+    }
+}
+
+/// Returns the day in the year starting at 0 of a month starting at 1 and a day in the
+/// month starting at 1.
+pub fn day_in_year(year: i64, month: i64, day: i64) -> i64 {
+    let mut days = day - 1;
+    for i in 0..(month - 1) as usize {
+        days += DAYS_PER_MONTH[is_leap(year)][i] as i64;
+    }
+    days
+}
+
+/// Returns the number of days in a month starting at 1.
+pub fn days_in_month(year: i64, month: i64) -> i64 {
+    DAYS_PER_MONTH[is_leap(year)][month as usize - 1] as i64
+}
+
+/// Splits a day in the year starting at 0 into a month starting at 1 and a day in the
+/// month starting at 1.
+pub fn month_and_day(year: i64, mut day: i64) -> (i64, i64) {
+    let mut month = 0;
+    while day >= DAYS_PER_MONTH[is_leap(year)][month] as i64 {
+        day -= DAYS_PER_MONTH[is_leap(year)][month] as i64;
+        month += 1;
+    }
+    (month as i64 + 1, day + 1)
+}
+
+pub fn floor_div_rem(a: i64, b: i64) -> (i64, i64) {
+    let (div, rem) = a.div_rem(b);
+    match rem < 0 {
+        true => (div - 1, rem + b),
+        _ => (div, rem),
+    }
+}
+
+/// The largest absolute year for which calendar arithmetic is supported. This keeps
+/// the number of days since the epoch far away from overflowing.
+const MAX_YEAR: i64 = 1 << 48;
+
+/// Returns whether the year, month, and day of the date are valid.
+fn is_valid(date: &DateTime) -> bool {
+    date.year >= -MAX_YEAR && date.year <= MAX_YEAR &&
+        date.month >= 1 && date.month <= 12 && date.day >= 1 &&
+        date.day as i64 <= days_in_month(date.year, date.month as i64)
+}
+
+/// Returns the number of days between the epoch and the date or `None` if the date is
+/// invalid.
+pub fn days_since_epoch(date: &DateTime) -> Option<i64> {
+    if !is_valid(date) {
+        return None;
+    }
+    Some(year_start(date.year) +
+         day_in_year(date.year, date.month as i64, date.day as i64))
+}
+
+/// Replaces the year, month, and day of the date and recalculates `day_in_year` and
+/// `weekday`.
+fn set_date(date: &DateTime, year: i64, month: i64, day: i64) -> DateTime {
+    let day0 = day_in_year(year, month, day);
+    let mut res = *date;
+    res.year = year;
+    res.month = month as i8;
+    res.day = day as i8;
+    res.day_in_year = day0 as i16 + 1;
+    res.weekday = weekday(year, day0);
+    res
+}
+
+pub fn add_days(date: &DateTime, days: i64) -> Option<DateTime> {
+    let days = match days_since_epoch(date).chain(|d| d.checked_add(days)) {
+        Some(d) if d >= year_start(-MAX_YEAR) && d < year_start(MAX_YEAR + 1) => d,
+        _ => return None,
+    };
+    let (year, day0) = split_days(days);
+    let (month, day) = month_and_day(year, day0);
+    Some(set_date(date, year, month, day))
+}
+
+pub fn add_months(date: &DateTime, months: i64) -> Option<DateTime> {
+    if !is_valid(date) {
+        return None;
+    }
+    let total = date.year * MONTHS_PER_YEAR + date.month as i64 - 1;
+    let (year, month) = match total.checked_add(months) {
+        Some(m) => floor_div_rem(m, MONTHS_PER_YEAR),
+        _ => return None,
+    };
+    if year < -MAX_YEAR || year > MAX_YEAR {
+        return None;
+    }
+    let month = month + 1;
+    let day = match date.day as i64 {
+        d if d > days_in_month(year, month) => days_in_month(year, month),
+        d => d,
+    };
+    Some(set_date(date, year, month, day))
+}
+
+/// Returns the ISO 8601 year and week of the date or `None` if the date is invalid.
+pub fn iso_week(date: &DateTime) -> Option<(i64, i8)> {
+    if !is_valid(date) {
+        return None;
+    }
+    let day0 = day_in_year(date.year, date.month as i64, date.day as i64);
+    // Weekdays are numbered from 1 (Monday) to 7 (Sunday).
+    let wd = weekday(date.year, day0) as i64 + 1;
+    let week = (day0 + 1 - wd + 10) / DAYS_PER_WEEK;
+    let res = if week < 1 {
+        (date.year - 1, weeks_in_year(date.year - 1) as i8)
+    } else if week > weeks_in_year(date.year) {
+        (date.year + 1, 1)
+    } else {
+        (date.year, week as i8)
+    };
+    Some(res)
+}
+
+/// Returns the number of ISO 8601 weeks in the year.
+fn weeks_in_year(year: i64) -> i64 {
+    match weekday(year, 0) {
+        Weekday::Thursday => 53,
+        Weekday::Wednesday if is_leap(year) == 1 => 53,
+        _ => 52,
+    }
+}
+
+/// Returns the state that is in effect at the given time.
+fn state_at(zone: &Zone, time: i64) -> State {
+    if let Some(ref rule) = zone.rule {
+        match zone.transitions.last() {
+            Some(t) if time < t.0 => { },
+            _ => return rule.state_at(time),
+        }
+    }
+    if zone.transitions.len() == 0 || time < zone.transitions[0].0 {
+        match zone.states.find(|s| !s.summer_time) {
             Some(i) => zone.states[i],
             _ => zone.states[0],
         }
@@ -55,7 +248,11 @@ pub fn explode(zone: &Zone, time: i64) -> DateTime {
             (Some(n), _) => zone.states[zone.transitions[n].1],
             (_, n) => zone.states[zone.transitions[n - 1].1],
         }
-    };
+    }
+}
+
+pub fn explode(zone: &Zone, time: i64) -> DateTime {
+    let state = state_at(zone, time);
 
     let mut is_leap_second = false;
     let leap_seconds = match zone.leap_seconds.find_reverse(|l| time >= l.0) {
@@ -66,22 +263,10 @@ pub fn explode(zone: &Zone, time: i64) -> DateTime {
         _ => 0,
     };
 
-    let mut year = EPOCH_YEAR;
-    let (mut days, mut secs) = time.div_rem(SECS_PER_DAY);
+    let (days, mut secs) = time.div_rem(SECS_PER_DAY);
+    let (mut year, mut days) = split_days(days);
 
-    while days < 0 || days >= DAYS_PER_YEAR[is_leap(year)] {
-        let next_year = year + match days / DAYS_PER_LEAP_YEAR {
-            0 if days < 0 => -1,
-            0 => 1,
-            n => n,
-        };
-        let leap_days = leap_years_to(next_year - 1) - leap_years_to(year - 1);
-        days -= (next_year - year) * DAYS_PER_NORMAL_YEAR;
-        days -= leap_days;
-        year = next_year;
-    }
-
-    secs += state.0 - leap_seconds;
+    secs += state.offset - leap_seconds;
     let (div_secs, mut secs) = secs.div_rem(SECS_PER_DAY);
     days += div_secs;
     if secs < 0 {
@@ -95,53 +280,37 @@ pub fn explode(zone: &Zone, time: i64) -> DateTime {
     }
     while days >= DAYS_PER_YEAR[is_leap(year)] {
         days -= DAYS_PER_YEAR[is_leap(year)];
-        year -= 1;
+        year += 1;
     }
 
-    let weekday = ((((EPOCH_WEEK_DAY as i64) + ((year - EPOCH_YEAR) % DAYS_PER_WEEK) *
-                    (DAYS_PER_NORMAL_YEAR % DAYS_PER_WEEK) + leap_years_to(year - 1) -
-                    leap_years_to(EPOCH_YEAR - 1) + days) % DAYS_PER_WEEK) +
-                    DAYS_PER_WEEK) % DAYS_PER_WEEK;
-    let weekday = match weekday {
-        0 => Weekday::Monday,
-        1 => Weekday::Tuesday,
-        2 => Weekday::Wednesday,
-        3 => Weekday::Thursday,
-        4 => Weekday::Friday,
-        5 => Weekday::Saturday,
-        _ => Weekday::Sunday,
-    };
+    let weekday = weekday(year, days);
 
     let (hour, secs) = secs.div_rem(SECS_PER_HOUR);
     let (min, mut secs) = secs.div_rem(SECS_PER_MIN);
     secs += is_leap_second as i64;
 
-    let mut day_in_month = days;
-
-    let mut month = 0;
-    while day_in_month >= DAYS_PER_MONTH[is_leap(year)][month] as i64 {
-        day_in_month -= DAYS_PER_MONTH[is_leap(year)][month] as i64;
-        month += 1;
-    }
-    day_in_month += 1;
-    month += 1;
+    let (month, day_in_month) = month_and_day(year, days);
 
     DateTime {
-        offset:      state.0,
-        year:        year,
-        day_in_year: days as i16 + 1,
-        month:       month as i8,
-        day:         day_in_month as i8,
-        hour:        hour as i8,
-        minute:      min as i8,
-        second:      secs as i8,
-        weekday:     weekday,
-        summer_time: state.1,
+        offset:       state.offset,
+        year:         year,
+        day_in_year:  days as i16 + 1,
+        month:        month as i8,
+        day:          day_in_month as i8,
+        hour:         hour as i8,
+        minute:       min as i8,
+        second:       secs as i8,
+        nanosecond:   0,
+        weekday:      weekday,
+        summer_time:  state.summer_time,
+        abbreviation: state.abbreviation,
     }
 }
 
 pub fn compact(zone: &Zone, mut date: DateTime) -> (DateTime, Time) {
     normalize(&mut date);
+    let offset = date.offset;
+    let nanos = date.nanosecond;
 
     let transition = find_transition(zone, &date);
 
@@ -155,7 +324,7 @@ pub fn compact(zone: &Zone, mut date: DateTime) -> (DateTime, Time) {
             }
         },
         None => {
-            let state = match zone.states.find(|s| !s.1) {
+            let state = match zone.states.find(|s| !s.summer_time) {
                 Some(i) => zone.states[i],
                 _ => zone.states[0],
             };
@@ -167,8 +336,9 @@ pub fn compact(zone: &Zone, mut date: DateTime) -> (DateTime, Time) {
         },
     };
 
-    date.offset = state.0;
-    date.summer_time = state.1;
+    date.offset = state.offset;
+    date.summer_time = state.summer_time;
+    date.abbreviation = state.abbreviation;
 
     let mut secs = 0;
     let mut res = date;
@@ -183,7 +353,19 @@ pub fn compact(zone: &Zone, mut date: DateTime) -> (DateTime, Time) {
         }
     }
 
-    (res, Time { seconds: secs, nanoseconds: 0 })
+    // If the date occurs twice, e.g., when summer time ends, use the offset to choose
+    // between the two times.
+    if res.offset != offset && dt_cmp(&res, &date) == Ordering::Equal {
+        let alt_secs = secs.wrapping_add(res.offset - offset);
+        let alt = explode(zone, alt_secs);
+        if alt.offset == offset && dt_cmp(&alt, &date) == Ordering::Equal {
+            secs = alt_secs;
+            res = alt;
+        }
+    }
+
+    res.nanosecond = nanos;
+    (res, Time { seconds: secs, nanoseconds: nanos as i64 })
 }
 
 /// Find the largest transition such that the transition time expands to a DateTime which
@@ -218,9 +400,16 @@ fn dt_cmp(one: &DateTime, two: &DateTime) -> Ordering {
     Ordering::Equal
 }
 
-/// Normalizes `second`, `minute`, `hour`, `day`, `month`, and `year`.
+/// Normalizes `nanosecond`, `second`, `minute`, `hour`, `day`, `month`, and `year`.
 fn normalize(date: &mut DateTime) {
-    let (mut min, mut sec) = (date.second as i64).div_rem(SECS_PER_MIN);
+    let (mut sec, mut nano) = (date.nanosecond as i64).div_rem(NANOS_PER_SEC);
+    if nano < 0 {
+        sec -= 1;
+        nano += NANOS_PER_SEC;
+    }
+    date.nanosecond = nano as i32;
+
+    let (mut min, mut sec) = (date.second as i64 + sec).div_rem(SECS_PER_MIN);
     if sec < 0 {
         min -= 1;
         sec += SECS_PER_MIN;
@@ -0,0 +1,108 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+use fmt::{Write};
+
+use super::{DateTime, Weekday};
+
+pub static WEEKDAY_NAMES: [&'static str; 7] = [
+    "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday",
+];
+
+pub static MONTH_NAMES: [&'static str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August",
+    "September", "October", "November", "December",
+];
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    WEEKDAY_NAMES[weekday as usize]
+}
+
+fn month_name(month: i8) -> &'static str {
+    match month {
+        1 ... 12 => MONTH_NAMES[month as usize - 1],
+        _ => "???",
+    }
+}
+
+fn write_offset<W: Write>(mut w: &mut W, offset: i64, colon: bool) -> Result {
+    let (sign, offset) = match offset < 0 {
+        true => ('-', -offset),
+        _ => ('+', offset),
+    };
+    let minutes = offset / 60;
+    match colon {
+        true => write!(w, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60),
+        _ => write!(w, "{}{:02}{:02}", sign, minutes / 60, minutes % 60),
+    }
+}
+
+pub fn format<W: Write>(date: &DateTime, mut pattern: &[u8], mut w: &mut W) -> Result {
+    while pattern.len() > 0 {
+        let len = match pattern.find(|&c| c == b'%') {
+            Some(len) => len,
+            _ => pattern.len(),
+        };
+        try!(w.write_all(&pattern[..len]).ignore_ok());
+        pattern = &pattern[len..];
+        if pattern.len() == 0 {
+            break;
+        }
+        if pattern.len() < 2 {
+            return Err(error::InvalidArgument);
+        }
+        let hour12 = match date.hour % 12 {
+            0 => 12,
+            n => n,
+        };
+        let mut consumed = 2;
+        let res = match pattern[1] {
+            b'a' => w.write_str(&weekday_name(date.weekday)[..3]).ignore_ok(),
+            b'A' => w.write_str(weekday_name(date.weekday)).ignore_ok(),
+            b'b' | b'h' => w.write_str(&month_name(date.month)[..3]).ignore_ok(),
+            b'B' => w.write_str(month_name(date.month)).ignore_ok(),
+            b'c' => format(date, b"%a %b %e %H:%M:%S %Y", w),
+            b'C' => write!(w, "{:02}", date.year / 100),
+            b'd' => write!(w, "{:02}", date.day),
+            b'D' => format(date, b"%m/%d/%y", w),
+            b'e' => match date.day < 10 {
+                true => write!(w, " {}", date.day),
+                _ => write!(w, "{}", date.day),
+            },
+            b'F' => format(date, b"%Y-%m-%d", w),
+            b'H' => write!(w, "{:02}", date.hour),
+            b'I' => write!(w, "{:02}", hour12),
+            b'j' => write!(w, "{:03}", date.day_in_year),
+            b'm' => write!(w, "{:02}", date.month),
+            b'M' => write!(w, "{:02}", date.minute),
+            b'n' => w.write_all(b"\n").ignore_ok(),
+            b'N' => write!(w, "{:09}", date.nanosecond),
+            b'p' => match date.hour < 12 {
+                true => w.write_all(b"AM").ignore_ok(),
+                _ => w.write_all(b"PM").ignore_ok(),
+            },
+            b'R' => format(date, b"%H:%M", w),
+            b'S' => write!(w, "{:02}", date.second),
+            b't' => w.write_all(b"\t").ignore_ok(),
+            b'T' => format(date, b"%H:%M:%S", w),
+            b'u' => write!(w, "{}", date.weekday as u8 + 1),
+            b'w' => write!(w, "{}", (date.weekday as u8 + 1) % 7),
+            b'y' => write!(w, "{:02}", (date.year % 100 + 100) % 100),
+            b'Y' => write!(w, "{:04}", date.year),
+            b'z' => write_offset(w, date.offset, false),
+            b'Z' => w.write_all(&date.abbreviation).ignore_ok(),
+            b'%' => w.write_all(b"%").ignore_ok(),
+            b':' if pattern.len() > 2 && pattern[2] == b'z' => {
+                consumed = 3;
+                write_offset(w, date.offset, true)
+            },
+            _ => Err(error::InvalidArgument),
+        };
+        try!(res);
+        pattern = &pattern[consumed..];
+    }
+    Ok(())
+}
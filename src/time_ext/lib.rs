@@ -13,22 +13,129 @@ extern crate lrs_str_one as str_one;
 extern crate lrs_time_base as time_base;
 extern crate lrs_io as io;
 extern crate lrs_vec as vec;
+extern crate lrs_parse as parse;
 #[cfg(not(freestanding))] extern crate lrs_file as file;
+#[cfg(not(freestanding))] extern crate lrs_env as env;
 
 use base::prelude::*;
 mod std { pub use vec::std::*; }
 
 pub use time_base::{Time};
 
-use fmt::{Debug, Write};
+use core::{mem};
+use fmt::{Debug, Display, Write};
 use vec::{Vec};
+use str_one::{ByteStr};
+use convert::{State};
+use posix::{Rule};
 
+#[cfg(not(freestanding))] use base::{error};
 #[cfg(not(freestanding))] use str_one::{NoNullStr};
 #[cfg(not(freestanding))] use io::{BufWrite};
 #[cfg(not(freestanding))] use file::{File};
 
-mod parse;
+mod tzif;
 mod convert;
+mod posix;
+mod format;
+mod parse_date;
+
+/// The format of RFC 3339 timestamps, e.g., `2015-06-30T23:59:60+02:00`.
+///
+/// = See also
+///
+/// * link:lrs::time::DateTime::format
+pub const RFC3339: &'static str = "%Y-%m-%dT%H:%M:%S%:z";
+
+/// The format of RFC 2822 timestamps, e.g., `Tue, 30 Jun 2015 23:59:60 +0200`.
+///
+/// = See also
+///
+/// * link:lrs::time::DateTime::format
+pub const RFC2822: &'static str = "%a, %d %b %Y %H:%M:%S %z";
+
+/// The format of HTTP timestamps, e.g., `Tue, 30 Jun 2015 21:59:60 GMT`.
+///
+/// = Remarks
+///
+/// The date must have been expanded in the UTC time zone.
+///
+/// = See also
+///
+/// * link:lrs::time::DateTime::format
+pub const IMF_FIXDATE: &'static str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Returns whether a year is a leap year.
+///
+/// [argument, year]
+/// The year.
+pub fn is_leap_year(year: i64) -> bool {
+    convert::is_leap(year) == 1
+}
+
+/// Returns the number of days in a year.
+///
+/// [argument, year]
+/// The year.
+pub fn days_in_year(year: i64) -> i16 {
+    convert::DAYS_PER_YEAR[convert::is_leap(year)] as i16
+}
+
+/// Returns the number of days in a month.
+///
+/// [argument, year]
+/// The year.
+///
+/// [argument, month]
+/// The month in the year starting at 1.
+///
+/// = Remarks
+///
+/// If the month is not in the range [1, 12], the process is aborted.
+pub fn days_in_month(year: i64, month: i8) -> i8 {
+    assert!(month >= 1 && month <= 12);
+    convert::days_in_month(year, month as i64) as i8
+}
+
+/// Returns the day in the year of a date.
+///
+/// [argument, year]
+/// The year.
+///
+/// [argument, month]
+/// The month in the year starting at 1.
+///
+/// [argument, day]
+/// The day in the month starting at 1.
+///
+/// [return_value]
+/// Returns the day in the year starting at 1 or `None` if the date is invalid.
+pub fn day_in_year(year: i64, month: i8, day: i8) -> Option<i16> {
+    if month < 1 || month > 12 || day < 1 ||
+            day as i64 > convert::days_in_month(year, month as i64) {
+        return None;
+    }
+    Some(convert::day_in_year(year, month as i64, day as i64) as i16 + 1)
+}
+
+/// Returns the month and the day in the month of a day in a year.
+///
+/// [argument, year]
+/// The year.
+///
+/// [argument, day_in_year]
+/// The day in the year starting at 1.
+///
+/// [return_value]
+/// Returns the month and the day in the month, both starting at 1, or `None` if the
+/// day is not in the year.
+pub fn month_and_day(year: i64, day_in_year: i16) -> Option<(i8, i8)> {
+    if day_in_year < 1 || day_in_year > days_in_year(year) {
+        return None;
+    }
+    let (month, day) = convert::month_and_day(year, day_in_year as i64 - 1);
+    Some((month as i8, day as i8))
+}
 
 /// A weekday.
 #[derive(Copy, Eq)]
@@ -49,38 +156,324 @@ pub enum Weekday {
     Sunday,
 }
 
+const ABBREVIATION_MAX: usize = 15;
+
+/// The abbreviation of a time zone, e.g., `CET` or `PDT`.
+///
+/// = Remarks
+///
+/// Abbreviations longer than 15 bytes are truncated.
+#[derive(Copy, Eq)]
+pub struct Abbreviation {
+    len: u8,
+    buf: [u8; ABBREVIATION_MAX],
+}
+
+impl Abbreviation {
+    /// Creates a new abbreviation.
+    ///
+    /// [argument, abbr]
+    /// The abbreviation.
+    pub fn new(abbr: &[u8]) -> Abbreviation {
+        let mut buf = [0; ABBREVIATION_MAX];
+        let len = mem::copy(&mut buf, abbr);
+        Abbreviation { len: len as u8, buf: buf }
+    }
+}
+
+impl TryFrom for Abbreviation {
+    fn try_from(a: &Abbreviation) -> Result<Abbreviation> {
+        Ok(*a)
+    }
+}
+
+impl Deref for Abbreviation {
+    type Target = ByteStr;
+    fn deref(&self) -> &ByteStr {
+        self.buf[..self.len as usize].as_ref()
+    }
+}
+
+impl Debug for Abbreviation {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Debug::fmt(self.deref(), w)
+    }
+}
+
+impl Display for Abbreviation {
+    fn fmt<W: Write>(&self, w: &mut W) -> Result {
+        Display::fmt(self.deref(), w)
+    }
+}
+
 /// An expanded date.
+///
+/// = Remarks
+///
+/// Dates can be parsed from RFC 3339 and RFC 2822 timestamps. A date without a time
+/// is parsed as midnight UTC. A date with a time but without an offset from UTC is
+/// rejected.
 #[derive(Copy, Eq)]
 pub struct DateTime {
     /// The offset from UTC in seconds.
-    pub offset:      i64,
+    pub offset:       i64,
     /// The year.
-    pub year:        i64,
-    /// The day in the year starting at 0.
-    pub day_in_year: i16,
+    pub year:         i64,
+    /// The day in the year starting at 1.
+    pub day_in_year:  i16,
     /// The month in the year starting at 1.
-    pub month:       i8,
+    pub month:        i8,
     /// The day in the month starting at 1.
-    pub day:         i8,
+    pub day:          i8,
     /// The hour.
-    pub hour:        i8,
+    pub hour:         i8,
     /// The minute.
-    pub minute:      i8,
+    pub minute:       i8,
     /// The second.
-    pub second:      i8,
+    pub second:       i8,
+    /// The nanosecond.
+    pub nanosecond:   i32,
     /// The weekday.
-    pub weekday:     Weekday,
+    pub weekday:      Weekday,
     /// Whether the date falls into summer time.
-    pub summer_time: bool,
+    pub summer_time:  bool,
+    /// The abbreviation of the time zone at the date, e.g., `CET` or `PDT`.
+    pub abbreviation: Abbreviation,
+}
+
+impl DateTime {
+    /// Formats the date according to a pattern.
+    ///
+    /// [argument, pattern]
+    /// The pattern.
+    ///
+    /// [argument, w]
+    /// The writer to which the formatted date will be written.
+    ///
+    /// = Remarks
+    ///
+    /// The pattern supports the following directives of `strftime`:
+    ///
+    /// |===
+    /// | *Directive* | *Meaning*
+    ///
+    /// | `%a` | The abbreviated weekday, e.g., `Mon`.
+    /// | `%A` | The weekday, e.g., `Monday`.
+    /// | `%b`, `%h` | The abbreviated month, e.g., `Jan`.
+    /// | `%B` | The month, e.g., `January`.
+    /// | `%c` | Same as `%a %b %e %H:%M:%S %Y`.
+    /// | `%C` | The century as a two digit number.
+    /// | `%d` | The day in the month as a two digit number.
+    /// | `%D` | Same as `%m/%d/%y`.
+    /// | `%e` | The day in the month padded with a space.
+    /// | `%F` | Same as `%Y-%m-%d`.
+    /// | `%H` | The hour as a two digit number.
+    /// | `%I` | The hour on a 12-hour clock as a two digit number.
+    /// | `%j` | The day in the year as a three digit number.
+    /// | `%m` | The month as a two digit number.
+    /// | `%M` | The minute as a two digit number.
+    /// | `%n` | A newline.
+    /// | `%N` | The nanosecond as a nine digit number.
+    /// | `%p` | `AM` or `PM`.
+    /// | `%R` | Same as `%H:%M`.
+    /// | `%S` | The second as a two digit number.
+    /// | `%t` | A tab.
+    /// | `%T` | Same as `%H:%M:%S`.
+    /// | `%u` | The weekday as a number where Monday is `1` and Sunday is `7`.
+    /// | `%w` | The weekday as a number where Sunday is `0` and Saturday is `6`.
+    /// | `%y` | The last two digits of the year.
+    /// | `%Y` | The year as an at least four digit number.
+    /// | `%z` | The offset from UTC in the form `+hhmm`.
+    /// | `%:z` | The offset from UTC in the form `+hh:mm`.
+    /// | `%Z` | The abbreviation of the time zone.
+    /// | `%%` | A `%` character.
+    /// |===
+    ///
+    /// Other directives cause an error. Names are always in English. The `RFC3339`,
+    /// `RFC2822`, and `IMF_FIXDATE` constants contain common patterns.
+    ///
+    /// = Examples
+    ///
+    /// ----
+    /// let date = Zone::utc().unwrap().expand(Time::seconds(1435708799));
+    /// let mut buf: Vec<u8> = Vec::new();
+    /// date.format(time::RFC3339, &mut buf).unwrap();
+    /// assert!(buf == "2015-06-30T23:59:59+00:00");
+    /// ----
+    ///
+    /// = See also
+    ///
+    /// * link:man:strftime(3)
+    pub fn format<S: ?Sized, W: Write>(&self, pattern: &S, w: &mut W) -> Result
+        where S: AsRef<[u8]>,
+    {
+        format::format(self, pattern.as_ref(), w)
+    }
+
+    /// Adds a number of days to the date.
+    ///
+    /// [argument, days]
+    /// The number of days to add.
+    ///
+    /// [return_value]
+    /// Returns the new date or `None` if the date is invalid or the result overflows.
+    ///
+    /// = Remarks
+    ///
+    /// The time of day, the offset, and the abbreviation are not modified. The
+    /// `day_in_year` and `weekday` fields are recalculated.
+    pub fn checked_add_days(&self, days: i64) -> Option<DateTime> {
+        convert::add_days(self, days)
+    }
+
+    /// Subtracts a number of days from the date.
+    ///
+    /// [argument, days]
+    /// The number of days to subtract.
+    ///
+    /// [return_value]
+    /// Returns the new date or `None` if the date is invalid or the result overflows.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::time::DateTime::checked_add_days
+    pub fn checked_sub_days(&self, days: i64) -> Option<DateTime> {
+        match days == i64::min() {
+            true => None,
+            _ => convert::add_days(self, -days),
+        }
+    }
+
+    /// Adds a number of months to the date.
+    ///
+    /// [argument, months]
+    /// The number of months to add.
+    ///
+    /// [return_value]
+    /// Returns the new date or `None` if the date is invalid or the result overflows.
+    ///
+    /// = Remarks
+    ///
+    /// If the day does not exist in the new month, it is clamped to the last day of the
+    /// month. For example, January 31 plus one month is February 28 or 29.
+    ///
+    /// The time of day, the offset, and the abbreviation are not modified. The
+    /// `day_in_year` and `weekday` fields are recalculated.
+    pub fn checked_add_months(&self, months: i64) -> Option<DateTime> {
+        convert::add_months(self, months)
+    }
+
+    /// Subtracts a number of months from the date.
+    ///
+    /// [argument, months]
+    /// The number of months to subtract.
+    ///
+    /// [return_value]
+    /// Returns the new date or `None` if the date is invalid or the result overflows.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::time::DateTime::checked_add_months
+    pub fn checked_sub_months(&self, months: i64) -> Option<DateTime> {
+        match months == i64::min() {
+            true => None,
+            _ => convert::add_months(self, -months),
+        }
+    }
+
+    /// Adds a number of years to the date.
+    ///
+    /// [argument, years]
+    /// The number of years to add.
+    ///
+    /// [return_value]
+    /// Returns the new date or `None` if the date is invalid or the result overflows.
+    ///
+    /// = Remarks
+    ///
+    /// February 29 becomes February 28 if the new year is not a leap year.
+    ///
+    /// The time of day, the offset, and the abbreviation are not modified. The
+    /// `day_in_year` and `weekday` fields are recalculated.
+    pub fn checked_add_years(&self, years: i64) -> Option<DateTime> {
+        years.checked_mul(12).chain(|m| convert::add_months(self, m))
+    }
+
+    /// Subtracts a number of years from the date.
+    ///
+    /// [argument, years]
+    /// The number of years to subtract.
+    ///
+    /// [return_value]
+    /// Returns the new date or `None` if the date is invalid or the result overflows.
+    ///
+    /// = See also
+    ///
+    /// * link:lrs::time::DateTime::checked_add_years
+    pub fn checked_sub_years(&self, years: i64) -> Option<DateTime> {
+        years.checked_mul(-12).chain(|m| convert::add_months(self, m))
+    }
+
+    /// Returns the number of days between 1970-01-01 and the date.
+    ///
+    /// [return_value]
+    /// Returns the number of days or `None` if the date is invalid.
+    ///
+    /// = Remarks
+    ///
+    /// Only the year, month, and day fields are used. The result is negative for dates
+    /// before 1970.
+    pub fn days_since_epoch(&self) -> Option<i64> {
+        convert::days_since_epoch(self)
+    }
+
+    /// Returns the number of days between another date and this date.
+    ///
+    /// [argument, other]
+    /// The other date.
+    ///
+    /// [return_value]
+    /// Returns the number of days or `None` if one of the dates is invalid.
+    ///
+    /// = Remarks
+    ///
+    /// The result is positive if `other` is before `self`. Only the year, month, and
+    /// day fields are used.
+    pub fn days_since(&self, other: &DateTime) -> Option<i64> {
+        match (self.days_since_epoch(), other.days_since_epoch()) {
+            (Some(one), Some(two)) => Some(one - two),
+            _ => None,
+        }
+    }
+
+    /// Returns the ISO 8601 week of the date.
+    ///
+    /// [return_value]
+    /// Returns the week-based year and the week in that year starting at 1 or `None` if
+    /// the date is invalid.
+    ///
+    /// = Remarks
+    ///
+    /// Weeks start on Monday and the first week of a year is the week that contains the
+    /// first Thursday of the year. Hence, the first days of January can belong to the
+    /// last week of the previous year and the last days of December can belong to the
+    /// first week of the next year.
+    pub fn iso_week(&self) -> Option<(i64, i8)> {
+        convert::iso_week(self)
+    }
 }
 
 impl Debug for DateTime {
     fn fmt<W: Write>(&self, mut w: &mut W) -> Result {
-        let offset_minutes = self.offset / 60;
+        let (sign, offset) = match self.offset < 0 {
+            true => ('-', -self.offset),
+            _ => ('+', self.offset),
+        };
+        let offset_minutes = offset / 60;
         let offset_hours = offset_minutes / 60;
         let offset_minutes = offset_minutes % 60;
-        write!(w, "{}-{:02}-{:02}T{:02}:{:02}:{:02}+{:02}:{:02}", self.year, self.month,
-               self.day, self.hour, self.minute, self.second, offset_hours,
+        write!(w, "{}-{:02}-{:02}T{:02}:{:02}:{:02}{}{:02}:{:02}", self.year, self.month,
+               self.day, self.hour, self.minute, self.second, sign, offset_hours,
                offset_minutes)
     }
 }
@@ -88,12 +481,14 @@ impl Debug for DateTime {
 /// A time zone.
 #[derive(TryTo, Eq)]
 pub struct Zone {
-    /// (UTC offset, summer time) (at least one exists in every zone)
-    states: Vec<(i64, bool)>,
+    /// (at least one exists in every zone)
+    states: Vec<State>,
     /// (transition time, index into states)
     transitions: Vec<(i64, usize)>,
     /// (leap second time, number of leap seconds)
     leap_seconds: Vec<(i64, i64)>,
+    /// The rule for times after the last transition
+    rule: Option<Rule>,
 }
 
 impl Zone {
@@ -109,8 +504,51 @@ impl Zone {
     ///
     /// [argument, zone]
     /// The zone to parse.
+    ///
+    /// = Remarks
+    ///
+    /// The zone must be in the TZif format. If the data contains a footer with a rule in
+    /// the format of the `TZ` environment variable, the rule is used for times after
+    /// the last transition.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tzfile(5)
     pub fn load_bytes(mut zone: &[u8]) -> Result<Zone> {
-        parse::parse(&mut zone)
+        tzif::parse(&mut zone)
+    }
+
+    /// Creates a time zone from a rule in the format of the `TZ` environment variable.
+    ///
+    /// [argument, tz]
+    /// The rule.
+    ///
+    /// = Remarks
+    ///
+    /// For example: "CET-1CEST,M3.5.0,M10.5.0/3", "EST5EDT,M3.2.0,M11.1.0", "UTC0". The
+    /// format is `std offset [dst [offset] [,start[/time],end[/time]]]` where the
+    /// offsets are positive west of Greenwich. If a summer time but no rule is given,
+    /// the rule of the United States is used.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tzset(3)
+    pub fn from_posix_tz<S: ?Sized>(tz: &S) -> Result<Zone>
+        where S: AsRef<[u8]>,
+    {
+        let rule = try!(posix::parse(tz.as_ref()));
+        let (std, dst) = rule.states();
+        let mut states = try!(Vec::with_capacity(2));
+        states.push(std);
+        if let Some(dst) = dst {
+            states.push(dst);
+        }
+        Ok(Zone {
+            states:       states,
+            transitions:  Vec::new(),
+            leap_seconds: Vec::new(),
+            rule:         Some(rule),
+        })
     }
 
     /// Loads a time zone from a well known name.
@@ -142,9 +580,64 @@ impl Zone {
     }
 
     /// Loads the local time zone.
+    ///
+    /// = Remarks
+    ///
+    /// Like libc, this function honors the `TZ` environment variable. If `TZ` is not
+    /// set, the zone is loaded from `/etc/localtime`. Otherwise the zone is loaded with
+    /// `from_tz`.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tzset(3)
+    /// * link:lrs::time::Zone::from_tz
     #[cfg(not(freestanding))]
     pub fn local() -> Result<Zone> {
-        Zone::load_from(b"/etc/localtime\0")
+        match env::var("TZ") {
+            Ok(tz) => Zone::from_tz(tz),
+            _ => Zone::load_from(b"/etc/localtime\0"),
+        }
+    }
+
+    /// Loads the time zone described by a value of the `TZ` environment variable.
+    ///
+    /// [argument, tz]
+    /// The value of `TZ`.
+    ///
+    /// = Remarks
+    ///
+    /// * If `tz` is empty or `:`, the zone is UTC.
+    /// * If `tz` starts with `:`, the rest is the name of a zone or an absolute path to a
+    ///   zone file.
+    /// * Otherwise `tz` is first tried as the name of a zone or an absolute path. If no
+    ///   such file exists, `tz` is parsed as a rule as accepted by `from_posix_tz`.
+    ///
+    /// = See also
+    ///
+    /// * link:man:tzset(3)
+    #[cfg(not(freestanding))]
+    pub fn from_tz<S: ?Sized>(tz: &S) -> Result<Zone>
+        where S: AsRef<[u8]>,
+    {
+        let tz = tz.as_ref();
+        if tz.len() == 0 || tz == ":" {
+            return Zone::from_posix_tz("UTC0");
+        }
+        if tz[0] == b':' {
+            return Zone::load_file(&tz[1..]);
+        }
+        match Zone::load_file(tz) {
+            Err(error::DoesNotExist) => Zone::from_posix_tz(tz),
+            res => res,
+        }
+    }
+
+    #[cfg(not(freestanding))]
+    fn load_file(name: &[u8]) -> Result<Zone> {
+        match name[0] {
+            b'/' => Zone::load_from(name),
+            _ => Zone::load(name),
+        }
     }
 
     /// Expands a time since the epoch to a `DateTime` in the given time zone.
@@ -152,7 +645,10 @@ impl Zone {
     /// [argument, time]
     /// The time to expand.
     pub fn expand(&self, time: Time) -> DateTime {
-        convert::explode(self, time.seconds)
+        let time = time.normalize();
+        let mut date = convert::explode(self, time.seconds);
+        date.nanosecond = time.nanoseconds as i32;
+        date
     }
 
     /// Returns a normalized version of a `DateTime` and a time that expands to the
@@ -164,7 +660,10 @@ impl Zone {
     /// = Remarks
     ///
     /// This function looks at the following fields: year, month, day, hour, minute,
-    /// second. All other fields will be calculated from these fields. Note that, if the
+    /// second, nanosecond. All other fields will be calculated from these fields. If the
+    /// date occurs twice in the time zone, e.g., because it falls into the hour that is
+    /// repeated when summer time ends, the offset field is used to choose between the
+    /// two times. Note that, if the
     /// date cannot be represented as a time in the given timezone (e.g. because the year
     /// is too large or the time falls into the one hour hole between winter and summer
     /// time) then the return value will not agree with the given date in the fields
@@ -173,6 +672,8 @@ impl Zone {
     ///
     /// The fields mentioned above are normalized according to the following algorithm:
     ///
+    /// * nanosecond is reduced so that it is in the [0, 1_000_000_000) range and second
+    ///   is adjusted accordingly.
     /// * second is reduced so that it is in the [0, 60) range and minute is adjusted
     ///   accordingly (this means that times that coincide with leap seconds will not be
     ///   normalized correctly.)
@@ -0,0 +1,274 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+use parse::{Parsable};
+
+use super::{DateTime, Abbreviation};
+use convert::{self, DAYS_PER_MONTH, SECS_PER_HOUR, SECS_PER_MIN, is_leap};
+use format::{WEEKDAY_NAMES, MONTH_NAMES};
+
+/// (name, offset in hours, summer time)
+static RFC2822_ZONES: [(&'static str, i64, bool); 11] = [
+    ("UT",  0,  false),
+    ("GMT", 0,  false),
+    ("Z",   0,  false),
+    ("EST", -5, false),
+    ("EDT", -4, true),
+    ("CST", -6, false),
+    ("CDT", -5, true),
+    ("MST", -7, false),
+    ("MDT", -6, true),
+    ("PST", -8, false),
+    ("PDT", -7, true),
+];
+
+fn peek(ip: &[u8]) -> u8 {
+    match ip.len() {
+        0 => 0,
+        _ => ip[0],
+    }
+}
+
+fn is_digit(c: u8) -> bool {
+    c >= b'0' && c <= b'9'
+}
+
+fn expect(ip: &mut &[u8], c: u8) -> Result {
+    if peek(ip) != c {
+        return Err(error::InvalidSequence);
+    }
+    *ip = &ip[1..];
+    Ok(())
+}
+
+/// Parses between `min` and `max` digits.
+fn digits(ip: &mut &[u8], min: usize, max: usize) -> Result<i64> {
+    let mut val = 0;
+    let mut len = 0;
+    while len < max && len < ip.len() && is_digit(ip[len]) {
+        val = val * 10 + (ip[len] - b'0') as i64;
+        len += 1;
+    }
+    if len < min {
+        return Err(error::InvalidSequence);
+    }
+    *ip = &ip[len..];
+    Ok(val)
+}
+
+/// Skips spaces and tabs and returns whether anything was skipped.
+fn skip_ws(ip: &mut &[u8]) -> bool {
+    let mut len = 0;
+    while len < ip.len() && (ip[len] == b' ' || ip[len] == b'\t') {
+        len += 1;
+    }
+    *ip = &ip[len..];
+    len > 0
+}
+
+/// Parses a three letter abbreviation of one of the names.
+fn name(ip: &mut &[u8], names: &[&'static str]) -> Result<usize> {
+    if ip.len() < 3 {
+        return Err(error::InvalidSequence);
+    }
+    for i in 0..names.len() {
+        let name: &[u8] = names[i].as_ref();
+        let mut eq = true;
+        for j in 0..3 {
+            eq &= (ip[j] | 0x20) == (name[j] | 0x20);
+        }
+        if eq {
+            *ip = &ip[3..];
+            return Ok(i);
+        }
+    }
+    Err(error::InvalidSequence)
+}
+
+/// Validates the fields and calculates the remaining fields.
+fn new_date(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64,
+            nanosecond: i64, offset: i64) -> Result<DateTime> {
+    if month < 1 || month > 12 || day < 1 ||
+            day > DAYS_PER_MONTH[is_leap(year)][month as usize - 1] as i64 ||
+            hour > 23 || minute > 59 || second > 60 {
+        return Err(error::InvalidSequence);
+    }
+    let day_in_year = convert::day_in_year(year, month, day);
+    Ok(DateTime {
+        offset:       offset,
+        year:         year,
+        day_in_year:  day_in_year as i16 + 1,
+        month:        month as i8,
+        day:          day as i8,
+        hour:         hour as i8,
+        minute:       minute as i8,
+        second:       second as i8,
+        nanosecond:   nanosecond as i32,
+        weekday:      convert::weekday(year, day_in_year),
+        summer_time:  false,
+        abbreviation: Abbreviation::new(&[]),
+    })
+}
+
+/// Parses `+hh:mm`, `+hhmm`, or `+hh` and returns the offset in seconds.
+fn numeric_offset(ip: &mut &[u8]) -> Result<i64> {
+    let sign = match peek(ip) {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return Err(error::InvalidSequence),
+    };
+    *ip = &ip[1..];
+    let hours = try!(digits(ip, 2, 2));
+    if peek(ip) == b':' {
+        *ip = &ip[1..];
+    }
+    let minutes = match is_digit(peek(ip)) {
+        true => try!(digits(ip, 2, 2)),
+        _ => 0,
+    };
+    if hours > 23 || minutes > 59 {
+        return Err(error::InvalidSequence);
+    }
+    Ok(sign * (hours * SECS_PER_HOUR + minutes * SECS_PER_MIN))
+}
+
+/// Parses `YYYY-MM-DD[Thh:mm:ss[.fraction](Z|+hh:mm)]`.
+fn parse_iso(ip: &mut &[u8]) -> Result<DateTime> {
+    let year = try!(digits(ip, 4, 4));
+    try!(expect(ip, b'-'));
+    let month = try!(digits(ip, 2, 2));
+    try!(expect(ip, b'-'));
+    let day = try!(digits(ip, 2, 2));
+
+    match peek(ip) {
+        b'T' | b't' | b' ' if ip.len() > 1 && is_digit(ip[1]) => *ip = &ip[1..],
+        _ => return new_date(year, month, day, 0, 0, 0, 0, 0),
+    }
+
+    let hour = try!(digits(ip, 2, 2));
+    try!(expect(ip, b':'));
+    let minute = try!(digits(ip, 2, 2));
+    try!(expect(ip, b':'));
+    let second = try!(digits(ip, 2, 2));
+
+    let mut nanosecond = 0;
+    if (peek(ip) == b'.' || peek(ip) == b',') && ip.len() > 1 && is_digit(ip[1]) {
+        *ip = &ip[1..];
+        let mut scale = 100_000_000;
+        while is_digit(peek(ip)) {
+            nanosecond += (ip[0] - b'0') as i64 * scale;
+            scale /= 10;
+            *ip = &ip[1..];
+        }
+    }
+
+    let (offset, abbr) = match peek(ip) {
+        b'Z' | b'z' => {
+            *ip = &ip[1..];
+            (0, "UTC")
+        },
+        b'+' | b'-' => (try!(numeric_offset(ip)), ""),
+        // The local time is ambiguous without an offset.
+        _ => return Err(error::InvalidSequence),
+    };
+
+    let mut date = try!(new_date(year, month, day, hour, minute, second, nanosecond,
+                                 offset));
+    date.abbreviation = Abbreviation::new(abbr.as_ref());
+    Ok(date)
+}
+
+/// Parses `[Day,] DD Mon YYYY hh:mm[:ss] zone`.
+fn parse_rfc2822(ip: &mut &[u8]) -> Result<DateTime> {
+    skip_ws(ip);
+    if !is_digit(peek(ip)) {
+        try!(name(ip, &WEEKDAY_NAMES));
+        skip_ws(ip);
+        try!(expect(ip, b','));
+        skip_ws(ip);
+    }
+
+    let day = try!(digits(ip, 1, 2));
+    if !skip_ws(ip) {
+        return Err(error::InvalidSequence);
+    }
+    let month = try!(name(ip, &MONTH_NAMES)) as i64 + 1;
+    if !skip_ws(ip) {
+        return Err(error::InvalidSequence);
+    }
+    let len = ip.len();
+    let mut year = try!(digits(ip, 2, 9));
+    match len - ip.len() {
+        2 if year < 50 => year += 2000,
+        2 | 3 => year += 1900,
+        _ => { },
+    }
+    if !skip_ws(ip) {
+        return Err(error::InvalidSequence);
+    }
+
+    let hour = try!(digits(ip, 2, 2));
+    try!(expect(ip, b':'));
+    let minute = try!(digits(ip, 2, 2));
+    let second = match peek(ip) {
+        b':' => {
+            *ip = &ip[1..];
+            try!(digits(ip, 2, 2))
+        },
+        _ => 0,
+    };
+    if !skip_ws(ip) {
+        return Err(error::InvalidSequence);
+    }
+
+    let (offset, abbr, summer_time) = match peek(ip) {
+        b'+' | b'-' => {
+            let offset = try!(numeric_offset_rfc2822(ip));
+            (offset, "", false)
+        },
+        _ => {
+            let mut zone = None;
+            for i in 0..RFC2822_ZONES.len() {
+                let name: &[u8] = RFC2822_ZONES[i].0.as_ref();
+                if ip.starts_with(name) {
+                    zone = Some(RFC2822_ZONES[i]);
+                    *ip = &ip[name.len()..];
+                    break;
+                }
+            }
+            match zone {
+                Some((name, hours, dst)) => (hours * SECS_PER_HOUR, name, dst),
+                _ => return Err(error::InvalidSequence),
+            }
+        },
+    };
+
+    let mut date = try!(new_date(year, month, day, hour, minute, second, 0, offset));
+    date.abbreviation = Abbreviation::new(abbr.as_ref());
+    date.summer_time = summer_time;
+    Ok(date)
+}
+
+/// Parses `+hhmm`.
+fn numeric_offset_rfc2822(ip: &mut &[u8]) -> Result<i64> {
+    if ip.len() < 5 || !is_digit(ip[3]) || !is_digit(ip[4]) {
+        return Err(error::InvalidSequence);
+    }
+    numeric_offset(ip)
+}
+
+impl Parsable for DateTime {
+    fn parse_bytes_init(bytes: &[u8]) -> Result<(DateTime, usize)> {
+        let is_iso = bytes.len() > 4 && bytes[4] == b'-' &&
+                        bytes[..4].find(|&c| !is_digit(c)).is_none();
+        let mut ip = bytes;
+        let date = match is_iso {
+            true => try!(parse_iso(&mut ip)),
+            _ => try!(parse_rfc2822(&mut ip)),
+        };
+        Ok((date, bytes.len() - ip.len()))
+    }
+}
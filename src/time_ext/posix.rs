@@ -0,0 +1,285 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use base::prelude::*;
+use base::{error};
+
+use super::{Abbreviation};
+use convert::{
+    State, SECS_PER_MIN, SECS_PER_HOUR, SECS_PER_DAY, DAYS_PER_MONTH, DAYS_PER_YEAR,
+    is_leap, year_start, split_days, floor_div_rem,
+};
+
+/// The day of the year on which summer time starts or ends.
+#[derive(Copy, Eq)]
+enum Day {
+    /// `Jn`: The day in the year starting at 1. February 29 is never counted.
+    Julian(i64),
+    /// `n`: The day in the year starting at 0. February 29 is counted in leap years.
+    Zero(i64),
+    /// `Mm.w.d`: Weekday `d` (0 is Sunday) of week `w` (5 is the last week) of month
+    /// `m`.
+    Month(i64, i64, i64),
+}
+
+impl Day {
+    /// Returns the day in the year starting at 0.
+    fn in_year(self, year: i64) -> i64 {
+        let leap = is_leap(year);
+        match self {
+            Day::Julian(n) => n - 1 + (leap == 1 && n >= 60) as i64,
+            Day::Zero(n) => n,
+            Day::Month(m, w, d) => {
+                let mut first = 0;
+                for i in 0..(m - 1) as usize {
+                    first += DAYS_PER_MONTH[leap][i] as i64;
+                }
+                let len = DAYS_PER_MONTH[leap][(m - 1) as usize] as i64;
+                // The epoch was a Thursday.
+                let first_weekday = ((year_start(year) + first + 4) % 7 + 7) % 7;
+                let mut day = first + (d - first_weekday + 7) % 7 + (w - 1) * 7;
+                while day >= first + len {
+                    day -= 7;
+                }
+                day
+            },
+        }
+    }
+}
+
+/// A change between standard time and summer time.
+#[derive(Copy, Eq)]
+struct Change {
+    day: Day,
+    /// Local time of the change in seconds after midnight.
+    time: i64,
+}
+
+impl Change {
+    /// Returns the time of the change in seconds after the start of the year.
+    fn in_year(self, year: i64) -> i64 {
+        self.day.in_year(year) * SECS_PER_DAY + self.time
+    }
+}
+
+/// A rule as found in the `TZ` environment variable.
+#[derive(Copy, Eq)]
+pub struct Rule {
+    std: State,
+    /// (summer time, start of summer time, end of summer time)
+    dst: Option<(State, Change, Change)>,
+}
+
+impl TryFrom for Rule {
+    fn try_from(r: &Rule) -> Result<Rule> {
+        Ok(*r)
+    }
+}
+
+impl Rule {
+    /// Returns the standard and summer time states of the rule.
+    pub fn states(&self) -> (State, Option<State>) {
+        (self.std, self.dst.map(|d| d.0))
+    }
+
+    /// Returns the state that is in effect at the given time.
+    pub fn state_at(&self, time: i64) -> State {
+        let (dst, start, end) = match self.dst {
+            Some(d) => d,
+            _ => return self.std,
+        };
+
+        // Split the time into days and seconds in standard time.
+        let (mut days, secs) = floor_div_rem(time, SECS_PER_DAY);
+        let (d, secs) = floor_div_rem(secs + self.std.offset, SECS_PER_DAY);
+        days += d;
+        let (year, day) = split_days(days);
+
+        // The number of seconds between the start of the year (in standard time) and
+        // the time.
+        let rel = day * SECS_PER_DAY + secs;
+        let prev_len = DAYS_PER_YEAR[is_leap(year - 1)] * SECS_PER_DAY;
+
+        // All changes are converted to standard time. The latest change that happened
+        // before the time determines the state.
+        let dst_offset = dst.offset - self.std.offset;
+        let changes = [
+            (start.in_year(year - 1) - prev_len, true),
+            (end.in_year(year - 1) - dst_offset - prev_len, false),
+            (start.in_year(year), true),
+            (end.in_year(year) - dst_offset, false),
+        ];
+        let mut latest = None;
+        for i in 0..changes.len() {
+            let (t, is_dst) = changes[i];
+            if t > rel {
+                continue;
+            }
+            match latest {
+                Some((l, _)) if l > t => { },
+                _ => latest = Some((t, is_dst)),
+            }
+        }
+        match latest {
+            Some((_, true)) => dst,
+            _ => self.std,
+        }
+    }
+}
+
+/// Parses a rule in the format of the `TZ` environment variable.
+///
+/// = Remarks
+///
+/// The format is `std offset [dst [offset] [,start[/time],end[/time]]]`. If a summer
+/// time but no rule is given, the rule of the United States is used.
+pub fn parse(mut tz: &[u8]) -> Result<Rule> {
+    let tz = &mut tz;
+
+    let std_abbr = try!(parse_abbr(tz));
+    let std = State {
+        offset:       -try!(parse_offset(tz, 24)),
+        summer_time:  false,
+        abbreviation: std_abbr,
+    };
+    if tz.len() == 0 {
+        return Ok(Rule { std: std, dst: None });
+    }
+
+    let dst_abbr = try!(parse_abbr(tz));
+    let dst_offset = match tz.len() > 0 && tz[0] != b',' {
+        true => -try!(parse_offset(tz, 24)),
+        _ => std.offset + SECS_PER_HOUR,
+    };
+    let dst = State {
+        offset:       dst_offset,
+        summer_time:  true,
+        abbreviation: dst_abbr,
+    };
+
+    let (start, end) = if tz.len() == 0 {
+        let start = Change { day: Day::Month(3, 2, 0), time: 2 * SECS_PER_HOUR };
+        let end = Change { day: Day::Month(11, 1, 0), time: 2 * SECS_PER_HOUR };
+        (start, end)
+    } else {
+        try!(expect(tz, b','));
+        let start = try!(parse_change(tz));
+        try!(expect(tz, b','));
+        let end = try!(parse_change(tz));
+        (start, end)
+    };
+
+    if tz.len() > 0 {
+        return Err(error::InvalidSequence);
+    }
+
+    Ok(Rule { std: std, dst: Some((dst, start, end)) })
+}
+
+fn expect(tz: &mut &[u8], c: u8) -> Result {
+    if tz.len() == 0 || tz[0] != c {
+        return Err(error::InvalidSequence);
+    }
+    *tz = &tz[1..];
+    Ok(())
+}
+
+fn parse_abbr(tz: &mut &[u8]) -> Result<Abbreviation> {
+    let s: &[u8] = *tz;
+    let (abbr, rest) = if s.len() > 0 && s[0] == b'<' {
+        let len = match s.find(|&c| c == b'>') {
+            Some(len) => len,
+            _ => return Err(error::InvalidSequence),
+        };
+        for &c in &s[1..len] {
+            match c {
+                b'a' ... b'z' | b'A' ... b'Z' | b'0' ... b'9' | b'+' | b'-' => { },
+                _ => return Err(error::InvalidSequence),
+            }
+        }
+        (&s[1..len], &s[len + 1..])
+    } else {
+        let len = match s.find(|&c| c < b'A' || (c > b'Z' && c < b'a') || c > b'z') {
+            Some(len) => len,
+            _ => s.len(),
+        };
+        (&s[..len], &s[len..])
+    };
+    if abbr.len() < 3 {
+        return Err(error::InvalidSequence);
+    }
+    *tz = rest;
+    Ok(Abbreviation::new(abbr))
+}
+
+fn parse_num(tz: &mut &[u8], max: i64) -> Result<i64> {
+    let mut num = 0;
+    let mut len = 0;
+    while len < tz.len() && tz[len] >= b'0' && tz[len] <= b'9' {
+        num = num * 10 + (tz[len] - b'0') as i64;
+        if num > max {
+            return Err(error::InvalidSequence);
+        }
+        len += 1;
+    }
+    if len == 0 {
+        return Err(error::InvalidSequence);
+    }
+    *tz = &tz[len..];
+    Ok(num)
+}
+
+/// Parses `[+-]hh[:mm[:ss]]` and returns the number of seconds.
+fn parse_offset(tz: &mut &[u8], max_hours: i64) -> Result<i64> {
+    let mut sign = 1;
+    if tz.len() > 0 && (tz[0] == b'+' || tz[0] == b'-') {
+        if tz[0] == b'-' {
+            sign = -1;
+        }
+        *tz = &tz[1..];
+    }
+    let mut secs = try!(parse_num(tz, max_hours)) * SECS_PER_HOUR;
+    if tz.len() > 0 && tz[0] == b':' {
+        *tz = &tz[1..];
+        secs += try!(parse_num(tz, 59)) * SECS_PER_MIN;
+        if tz.len() > 0 && tz[0] == b':' {
+            *tz = &tz[1..];
+            secs += try!(parse_num(tz, 59));
+        }
+    }
+    Ok(sign * secs)
+}
+
+/// Parses `Jn`, `n`, or `Mm.w.d` followed by an optional `/time`.
+fn parse_change(tz: &mut &[u8]) -> Result<Change> {
+    let day = if tz.len() > 0 && tz[0] == b'J' {
+        *tz = &tz[1..];
+        let n = try!(parse_num(tz, 365));
+        if n == 0 {
+            return Err(error::InvalidSequence);
+        }
+        Day::Julian(n)
+    } else if tz.len() > 0 && tz[0] == b'M' {
+        *tz = &tz[1..];
+        let m = try!(parse_num(tz, 12));
+        try!(expect(tz, b'.'));
+        let w = try!(parse_num(tz, 5));
+        try!(expect(tz, b'.'));
+        let d = try!(parse_num(tz, 6));
+        if m == 0 || w == 0 {
+            return Err(error::InvalidSequence);
+        }
+        Day::Month(m, w, d)
+    } else {
+        Day::Zero(try!(parse_num(tz, 365)))
+    };
+    let time = match tz.len() > 0 && tz[0] == b'/' {
+        true => {
+            *tz = &tz[1..];
+            try!(parse_offset(tz, 167))
+        },
+        _ => 2 * SECS_PER_HOUR,
+    };
+    Ok(Change { day: day, time: time })
+}
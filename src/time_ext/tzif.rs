@@ -7,7 +7,9 @@ use io::{Read};
 use core::{mem, cmp};
 use base::{error};
 
-use super::{Zone};
+use super::{Zone, Abbreviation};
+use convert::{State};
+use posix::{self};
 
 macro_rules! rd {
     ($ip:expr, $t:ty) => {
@@ -61,12 +63,32 @@ pub fn parse(ip: &mut &[u8]) -> Result<Zone> {
     };
     if version > 1 {
         try!(discard::<T32Reader>(ip));
-        parse_::<T64Reader>(ip)
+        let mut zone = try!(parse_::<T64Reader>(ip));
+        zone.rule = parse_footer(ip);
+        Ok(zone)
     } else {
         parse_::<T32Reader>(ip)
     }
 }
 
+/// Parses the `\nTZ\n` footer of version 2+ files.
+///
+/// An invalid footer is ignored since the rest of the data is still usable.
+fn parse_footer(ip: &mut &[u8]) -> Option<posix::Rule> {
+    if ip.len() == 0 || ip[0] != b'\n' {
+        return None;
+    }
+    let footer = &ip[1..];
+    let len = match footer.find(|&c| c == b'\n') {
+        Some(len) => len,
+        _ => return None,
+    };
+    match posix::parse(&footer[..len]) {
+        Ok(rule) => Some(rule),
+        _ => None,
+    }
+}
+
 fn consume(buf: &mut &[u8], n: usize) {
     let min = cmp::min(buf.len(), n);
     *buf = &buf[min..];
@@ -102,11 +124,29 @@ fn parse_<T: TReader>(ip: &mut &[u8]) -> Result<Zone> {
         transitions[i].1 = state;
     }
 
+    let mut abbr_idx = vec!();
     for _ in 0..num_states {
-        states.push((try!(read_i32(ip)) as i64, try!(read_u8(ip)) != 0));
-        consume(ip, 1);
+        states.push(State {
+            offset:       try!(read_i32(ip)) as i64,
+            summer_time:  try!(read_u8(ip)) != 0,
+            abbreviation: Abbreviation::new(&[]),
+        });
+        abbr_idx.push(try!(read_u8(ip)) as usize);
     }
 
+    if ip.len() < abbr_bytes {
+        return Err(error::InvalidSequence);
+    }
+    for i in 0..num_states {
+        if abbr_idx[i] < abbr_bytes {
+            let abbr = &ip[abbr_idx[i]..abbr_bytes];
+            let len = match abbr.find(|&c| c == 0) {
+                Some(len) => len,
+                _ => abbr.len(),
+            };
+            states[i].abbreviation = Abbreviation::new(&abbr[..len]);
+        }
+    }
     consume(ip, abbr_bytes);
 
     for _ in 0..num_leap_seconds {
@@ -120,6 +160,7 @@ fn parse_<T: TReader>(ip: &mut &[u8]) -> Result<Zone> {
         transitions:  transitions,
         states:       states,
         leap_seconds: leap_seconds,
+        rule:         None,
     })
 }
 
@@ -6,3 +6,4 @@ mod no;
 mod bda;
 mod align;
 mod ta;
+mod sort;
@@ -0,0 +1,44 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::alloc::{self, TaPool, Dummy};
+
+fn check_sort_by(pool_size: usize) {
+    let mut x = [0u32; 100];
+    for i in 0..100 {
+        x[i] = ((i * 37) % 100) as u32;
+    }
+
+    let mut buf = &mut [d8::new(0); 256][..pool_size];
+    let mut pool = TaPool::new(&mut buf);
+    alloc::sort_by(&mut x, &mut pool, |a, b| (a % 10).cmp(&(b % 10)));
+    for i in 1..100 {
+        test!(x[i - 1] % 10 <= x[i] % 10);
+        if x[i - 1] % 10 == x[i] % 10 {
+            // 73 is the inverse of 37 modulo 100 and yields the original index.
+            test!((x[i - 1] * 73) % 100 < (x[i] * 73) % 100);
+        }
+    }
+}
+
+#[test]
+fn sort_by() {
+    check_sort_by(256);
+}
+
+#[test]
+fn sort_by_small_pool() {
+    // The pool cannot hold the scratch buffer of 50 elements.
+    check_sort_by(16);
+}
+
+#[test]
+fn sort_fallback() {
+    let mut x = [5, 4, 3, 2, 1, 0, 9, 8, 7, 6, 11, 10, 13, 12, 15, 14, 17, 16, 19, 18,
+                 21, 20, 23, 22];
+    alloc::sort(&mut x, &mut Dummy::out_of(()));
+    for i in 0..x.len() {
+        test!(x[i] == i);
+    }
+}
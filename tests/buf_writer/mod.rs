@@ -0,0 +1,57 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::io::{BufWriter, Write, BufWrite};
+use std::alloc::{OncePool};
+
+#[test]
+fn buffers() {
+    let mut buf = [0; 32];
+    let mut vec = Vec::with_pool(OncePool::new(buf.as_mut()));
+    {
+        let mut w: BufWriter<_> = BufWriter::new(&mut vec, 8).unwrap();
+        test!(w.write(b"abc").unwrap() == 3);
+        test!(w.write(b"de").unwrap() == 2);
+        test!(w.available() == 5);
+        test!(w.get_ref().len() == 0);
+        w.flush().unwrap();
+        test!(w.available() == 0);
+        test!(&**w.get_ref() == "abcde");
+    }
+}
+
+#[test]
+fn writes_through() {
+    let mut buf = [0; 32];
+    let mut vec = Vec::with_pool(OncePool::new(buf.as_mut()));
+    {
+        let mut w: BufWriter<_> = BufWriter::new(&mut vec, 4).unwrap();
+        test!(w.write(b"ab").unwrap() == 2);
+        test!(w.write(b"cdefghij").unwrap() == 8);
+        test!(w.available() == 0);
+        test!(&**w.get_ref() == "abcdefghij");
+    }
+}
+
+#[test]
+fn flushes_on_drop() {
+    let mut buf = [0; 32];
+    let mut vec = Vec::with_pool(OncePool::new(buf.as_mut()));
+    {
+        let mut w: BufWriter<_> = BufWriter::new(&mut vec, 16).unwrap();
+        write!(w, "{}-{}", 1, 2);
+    }
+    test!(&*vec == "1-2");
+}
+
+#[test]
+fn read_to_eof() {
+    let mut buf = [0; 32];
+    let mut vec = Vec::with_pool(OncePool::new(buf.as_mut()));
+    {
+        let mut w: BufWriter<_> = BufWriter::new(&mut vec, 4).unwrap();
+        test!(w.read_to_eof(&b"hello world"[..]).unwrap() == 11);
+    }
+    test!(&*vec == "hello world");
+}
@@ -0,0 +1,110 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::{channel, sync_channel, Disconnected, SendError};
+use std::time::{self, Time};
+use std::thread::{self};
+use std::poll::{Epoll, EMPTY_EVENT, POLL_READ};
+use std::error::{self, Errno};
+
+fn err<T>(res: Result<T>) -> Errno {
+    match res {
+        Err(e) => e,
+        _ => abort!(),
+    }
+}
+
+fn send_err<T>(res: Result<(), SendError<T>>) -> Errno {
+    match res {
+        Err(e) => e.error,
+        _ => abort!(),
+    }
+}
+
+#[test]
+fn send_recv() {
+    let (send, recv) = channel().unwrap();
+    test!(err(recv.try_recv()) == error::WouldBlock);
+    send.send(1).unwrap();
+    send.send(2).unwrap();
+    test!(recv.recv().unwrap() == 1);
+    test!(recv.try_recv().unwrap() == 2);
+}
+
+#[test]
+fn sync_full() {
+    let (send, recv) = sync_channel(1).unwrap();
+    send.send(1).unwrap();
+    test!(send_err(send.try_send(2)) == error::WouldBlock);
+    test!(recv.recv().unwrap() == 1);
+    send.try_send(2).unwrap();
+}
+
+#[test]
+fn disconnected() {
+    let (send, recv) = channel().unwrap();
+    let send2 = send.clone();
+    send.send(1).unwrap();
+    drop(send);
+    drop(send2);
+    test!(recv.recv().unwrap() == 1);
+    test!(err(recv.recv()) == Disconnected);
+
+    let (send, recv) = channel::<i32>().unwrap();
+    drop(recv);
+    test!(send_err(send.send(1)) == Disconnected);
+}
+
+#[test]
+fn timeout() {
+    let (_send, recv) = channel::<i32>().unwrap();
+    test!(err(recv.recv_timeout(Time::milliseconds(1))) == error::TimedOut);
+}
+
+#[test]
+fn blocking_recv() {
+    let (send, recv) = channel().unwrap();
+    let _t = thread::scoped(move || {
+        time::MONO.sleep_for(Time::milliseconds(50)).unwrap();
+        send.send(1).unwrap();
+    }).unwrap();
+    test!(recv.recv().unwrap() == 1);
+    test!(err(recv.recv()) == Disconnected);
+}
+
+#[test]
+fn blocking_recv_timeout() {
+    let (send, recv) = channel().unwrap();
+    {
+        let _t = thread::scoped(|| {
+            time::MONO.sleep_for(Time::milliseconds(50)).unwrap();
+            send.send(1).unwrap();
+        }).unwrap();
+        test!(err(recv.recv_timeout(Time::milliseconds(1))) == error::TimedOut);
+        test!(recv.recv_timeout(Time::seconds(60)).unwrap() == 1);
+    }
+}
+
+#[test]
+fn eventfd() {
+    let (send, recv) = channel().unwrap();
+    let epoll = Epoll::new().unwrap();
+    epoll.add(&recv.eventfd().unwrap(), POLL_READ).unwrap();
+    let mut events = [EMPTY_EVENT; 1];
+    let zero = Time::nanoseconds(0);
+
+    test!(epoll.wait_timeout(&mut events, zero).unwrap().len() == 0);
+    send.send(1).unwrap();
+    send.send(2).unwrap();
+    test!(epoll.wait_timeout(&mut events, zero).unwrap().len() == 1);
+    test!(recv.try_recv().unwrap() == 1);
+    test!(epoll.wait_timeout(&mut events, zero).unwrap().len() == 1);
+    test!(recv.try_recv().unwrap() == 2);
+    test!(epoll.wait_timeout(&mut events, zero).unwrap().len() == 0);
+
+    // The eventfd also becomes readable once all senders are gone.
+    drop(send);
+    test!(epoll.wait_timeout(&mut events, zero).unwrap().len() == 1);
+    test!(err(recv.try_recv()) == Disconnected);
+}
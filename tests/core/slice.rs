@@ -119,6 +119,85 @@ fn sort_by() {
     test!(x == [3, 2, 1, 0]);
 }
 
+fn is_sorted(x: &[usize]) -> bool {
+    for i in 1..x.len() {
+        if x[i - 1] > x[i] {
+            return false;
+        }
+    }
+    true
+}
+
+#[test]
+fn sort_stable() {
+    let mut x = [0; 200];
+    for i in 0..200 {
+        x[i] = (i * 73) % 200;
+    }
+    x.sort_by_key(|v| v % 7);
+    for i in 1..200 {
+        test!(x[i - 1] % 7 <= x[i] % 7);
+        if x[i - 1] % 7 == x[i] % 7 {
+            // 137 is the inverse of 73 modulo 200 and yields the original index.
+            test!((x[i - 1] * 137) % 200 < (x[i] * 137) % 200);
+        }
+    }
+}
+
+#[test]
+fn sort_unstable() {
+    let mut x = [0; 1000];
+    for i in 0..1000 {
+        x[i] = i;
+    }
+    x.sort_unstable();
+    test!(is_sorted(&x));
+
+    x.reverse();
+    x.sort_unstable();
+    test!(is_sorted(&x));
+
+    for i in 0..1000 {
+        x[i] = (i * 7919) % 1009 % 10;
+    }
+    x.sort_unstable_by(|a, b| a.cmp(b));
+    test!(is_sorted(&x));
+
+    let mut seed = 1u32;
+    for i in 0..1000 {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        x[i] = (seed >> 16) as usize;
+    }
+    x.sort_unstable();
+    test!(is_sorted(&x));
+}
+
+#[test]
+fn select_nth_unstable() {
+    let mut x = [0; 101];
+    for i in 0..101 {
+        x[i] = (i * 31) % 101;
+    }
+    x.select_nth_unstable(50);
+    test!(x[50] == 50);
+    for i in 0..50 {
+        test!(x[i] < 50);
+    }
+
+    let mut x = [1, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 3, 0];
+    x.select_nth_unstable_by(1, |a, b| b.cmp(a));
+    test!(x[1] == 3);
+}
+
+#[test]
+fn binary_search_by() {
+    let x = [0, 1, 1, 1, 2, 4];
+    test!(x.binary_search(&1) == (Some(1), 1));
+    test!(x.binary_search(&3) == (None, 5));
+    test!(x.binary_search_by(|v| v.cmp(&5)) == (None, 6));
+    test!(x.binary_search_by_key(&4, |v| v * 2) == (Some(4), 4));
+}
+
 #[test]
 fn unchecked_slice() {
     let mut x = [0, 1, 2, 3];
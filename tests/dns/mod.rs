@@ -0,0 +1,315 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::io::{Read, Write};
+use std::error::{self, Errno};
+use std::thread::{self, JoinHandle};
+use std::dns::{Config, Resolver};
+use std::dns::msg::{self, rr_type, rcode, Message};
+use std::dns::hosts::{lookup_name, lookup_addr};
+use std::socket::{UdpSocket, TcpListener};
+use std::socket::ip::{IpAddr, Ipv4Addr, Ipv6Addr, IpSockAddrBuf, IPV6_SOCK_ADDR_SIZE};
+
+fn err<T>(res: Result<T>) -> Errno {
+    match res {
+        Err(e) => e,
+        _ => abort!(),
+    }
+}
+
+#[test]
+fn config() {
+    let conf = Config::parse(b"# comment\n\
+                               nameserver 10.0.0.1\n\
+                               nameserver ::1 ; comment\n\
+                               nameserver invalid\n\
+                               domain ignored.example\n\
+                               search a.example b.example\n\
+                               options ndots:2 timeout:100 attempts:3 rotate\n").unwrap();
+    test!(conf.nameservers.len() == 2);
+    test!(conf.nameservers[0].addr() == IpAddr::V4(Ipv4Addr(10, 0, 0, 1)));
+    test!(conf.nameservers[0].port() == 53);
+    test!(conf.nameservers[1].addr() == IpAddr::V6(Ipv6Addr::loopback()));
+    test!(conf.search.len() == 2);
+    test!(&conf.search[1][..] == &b"b.example"[..]);
+    test!(conf.ndots == 2);
+    test!(conf.timeout == 30);
+    test!(conf.attempts == 3);
+
+    // Values that do not fit into the fields are clamped as well.
+    let conf = Config::parse(b"options ndots:1000 timeout:300 attempts:70000
+").unwrap();
+    test!(conf.ndots == 15);
+    test!(conf.timeout == 30);
+    test!(conf.attempts == 5);
+    let conf = Config::parse(b"options timeout:0 attempts:0
+").unwrap();
+    test!(conf.timeout == 1);
+    test!(conf.attempts == 1);
+}
+
+#[test]
+fn hosts() {
+    let hosts = b"127.0.0.1 localhost\n\
+                  ::1 localhost ip6-localhost # comment\n\
+                  10.0.0.2\tServer server.example\n\
+                  10.0.0.3 semi;colon\n";
+    let mut addrs = Vec::new();
+    lookup_name(hosts, b"LOCALHOST", &mut addrs).unwrap();
+    test!(addrs.len() == 2);
+    test!(addrs[0] == IpAddr::V4(Ipv4Addr(127, 0, 0, 1)));
+    test!(addrs[1] == IpAddr::V6(Ipv6Addr::loopback()));
+
+    let mut names = Vec::new();
+    lookup_addr(hosts, IpAddr::V4(Ipv4Addr(10, 0, 0, 2)), &mut names).unwrap();
+    test!(names.len() == 2);
+    test!(&names[0][..] == &b"Server"[..]);
+    test!(&names[1][..] == &b"server.example"[..]);
+
+    // `;` only starts a comment in resolv.conf.
+    let mut addrs = Vec::new();
+    lookup_name(hosts, b"semi;colon", &mut addrs).unwrap();
+    test!(addrs.len() == 1);
+    test!(addrs[0] == IpAddr::V4(Ipv4Addr(10, 0, 0, 3)));
+}
+
+#[test]
+fn query() {
+    let mut buf = Vec::new();
+    msg::build_query(&mut buf, 0x1234, b"www.example.com.", rr_type::Aaaa).unwrap();
+    test!(&buf[..] == &b"\x12\x34\x01\x00\x00\x01\x00\x00\x00\x00\x00\x00\
+                        \x03www\x07example\x03com\x00\x00\x1c\x00\x01"[..]);
+    let msg = Message::parse(&buf).unwrap();
+    test!(msg.header().id == 0x1234);
+    test!(!msg.header().is_response());
+    let q = msg.question().unwrap();
+    test!(&q.name[..] == &b"www.example.com"[..]);
+    test!(q.ty == rr_type::Aaaa);
+
+    let mut buf = Vec::new();
+    test!(msg::build_query(&mut buf, 0, b"a..b", rr_type::A).is_err());
+}
+
+/// Turns a query into a response with an rcode and answer records.
+///
+/// The answer records refer to the queried name with a compression pointer.
+fn respond(query: &[u8], code: u8, answers: &[(u16, &[u8])], resp: &mut Vec<u8>) {
+    resp.push_all(query).unwrap();
+    resp[2] |= 0x80;
+    resp[3] = 0x80 | code;
+    resp[7] = answers.len() as u8;
+    for &(ty, data) in answers {
+        resp.push_all(&[0xc0, 0x0c, (ty >> 8) as u8, ty as u8, 0, 1, 0, 0, 0, 0x3c,
+                        (data.len() >> 8) as u8, data.len() as u8]).unwrap();
+        resp.push_all(data).unwrap();
+    }
+}
+
+/// Turns a query into a response with one `A` record.
+fn answer(query: &[u8], resp: &mut Vec<u8>) {
+    respond(query, rcode::NoError, &[(rr_type::A, &b"\x0a\x01\x02\x03"[..])], resp);
+}
+
+fn query_name(query: &[u8]) -> Vec<u8> {
+    Message::parse(query).unwrap().question().unwrap().name
+}
+
+fn localhost(port: u16) -> IpSockAddrBuf {
+    IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port)
+}
+
+/// Answers `n` queries over UDP. Returns the port of the server and a handle that
+/// returns the queried names.
+fn udp_server<F>(n: usize, f: F) -> (u16, JoinHandle<Vec<Vec<u8>>>)
+    where F: Fn(&[u8], &mut Vec<u8>) + Send + 'static,
+{
+    let server = UdpSocket::bind(&localhost(0)).unwrap();
+    let mut addr = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = server.local_addr(&mut addr).unwrap().as_ipv4().unwrap().port();
+
+    let handle = thread::spawn_joinable(move || {
+        let mut names = Vec::new();
+        for _ in 0..n {
+            let mut query = [0; 512];
+            let mut peer = [0; IPV6_SOCK_ADDR_SIZE];
+            let (len, peer) = server.recv_from(&mut query, &mut peer).unwrap();
+            names.push(query_name(&query[..len])).unwrap();
+            let mut resp = Vec::new();
+            f(&query[..len], &mut resp);
+            server.send_to(&resp, peer).unwrap();
+        }
+        names
+    }).unwrap();
+    (port, handle)
+}
+
+fn new_resolver(port: u16, conf: &[u8]) -> Resolver {
+    let mut conf = Config::parse(conf).unwrap();
+    conf.nameservers.push(localhost(port)).unwrap();
+    conf.attempts = 1;
+    conf.timeout = 1;
+    Resolver::with_config(conf, Vec::new())
+}
+
+#[test]
+fn resolver() {
+    let (port, handle) = udp_server(1, answer);
+    let resolver = new_resolver(port, b"");
+
+    let mut res = Vec::new();
+    resolver.query(b"host.example.", rr_type::A, |r| res.push(r.ip().unwrap())).unwrap();
+    handle.join().unwrap();
+    test!(res.len() == 1);
+    test!(res[0] == IpAddr::V4(Ipv4Addr(10, 1, 2, 3)));
+
+    let addrs = resolver.lookup_host(b"10.0.0.1", 80).unwrap();
+    test!(addrs.len() == 1);
+    test!(addrs[0].port() == 80);
+}
+
+#[test]
+fn resolver_tcp_fallback() {
+    let udp = UdpSocket::bind(&localhost(0)).unwrap();
+    let mut addr = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = udp.local_addr(&mut addr).unwrap().as_ipv4().unwrap().port();
+    let tcp = TcpListener::bind(&localhost(port)).unwrap();
+
+    let handle = thread::spawn_joinable(move || {
+        // Truncated response without answers over UDP.
+        let mut query = [0; 512];
+        let mut peer = [0; IPV6_SOCK_ADDR_SIZE];
+        let (len, peer) = udp.recv_from(&mut query, &mut peer).unwrap();
+        let mut resp = Vec::new();
+        respond(&query[..len], rcode::NoError, &[], &mut resp);
+        resp[2] |= 0x02;
+        udp.send_to(&resp, peer).unwrap();
+
+        // Full response over TCP.
+        let mut stream = tcp.accept().unwrap();
+        let mut len = [0; 2];
+        test!(stream.read_all(len.as_mut()).unwrap() == 2);
+        let len = (len[0] as usize) << 8 | len[1] as usize;
+        test!(stream.read_all(&mut query[..len]).unwrap() == len);
+        let mut resp = Vec::new();
+        answer(&query[..len], &mut resp);
+        stream.write_all(&[(resp.len() >> 8) as u8, resp.len() as u8]).unwrap();
+        stream.write_all(&resp).unwrap();
+    }).unwrap();
+
+    let resolver = new_resolver(port, b"");
+    let mut res = Vec::new();
+    resolver.query(b"host.example.", rr_type::A, |r| res.push(r.ip().unwrap())).unwrap();
+    handle.join().unwrap();
+    test!(res.len() == 1);
+    test!(res[0] == IpAddr::V4(Ipv4Addr(10, 1, 2, 3)));
+}
+
+#[test]
+fn resolver_errors() {
+    let (port, handle) = udp_server(3, |query, resp| {
+        let name = query_name(query);
+        let code = if &name[..] == &b"missing.example"[..] {
+            rcode::NxDomain
+        } else if &name[..] == &b"fail.example"[..] {
+            rcode::ServFail
+        } else {
+            rcode::Refused
+        };
+        respond(query, code, &[], resp);
+    });
+    let resolver = new_resolver(port, b"");
+    test!(err(resolver.lookup_ip(b"missing.example.")) == error::DoesNotExist);
+    test!(err(resolver.lookup_ip(b"fail.example.")) == error::ConnectionRefused);
+    test!(err(resolver.lookup_ip(b"refused.example.")) == error::ConnectionRefused);
+    handle.join().unwrap();
+}
+
+#[test]
+fn resolver_garbage() {
+    // The first server sends a response that cannot be parsed.
+    let (bad, bad_handle) = udp_server(1, |_, resp| resp.push_all(b"\x12").unwrap());
+    let (good, good_handle) = udp_server(1, answer);
+    let mut resolver = new_resolver(bad, b"");
+    resolver.config_mut().nameservers.push(localhost(good)).unwrap();
+
+    let mut res = Vec::new();
+    resolver.query(b"host.example.", rr_type::A, |r| res.push(r.ip().unwrap())).unwrap();
+    bad_handle.join().unwrap();
+    good_handle.join().unwrap();
+    test!(res.len() == 1);
+    test!(res[0] == IpAddr::V4(Ipv4Addr(10, 1, 2, 3)));
+}
+
+#[test]
+fn resolver_search() {
+    let (port, handle) = udp_server(5, |query, resp| {
+        let name = query_name(query);
+        if &name[..] == &b"host"[..] || &name[..] == &b"www.host.a.example"[..] {
+            answer(query, resp);
+        } else {
+            respond(query, rcode::NxDomain, &[], resp);
+        }
+    });
+    let resolver = new_resolver(port, b"search a.example b.example\noptions ndots:1\n");
+
+    // Fewer than `ndots` dots: The search list is tried first.
+    resolver.query(b"host", rr_type::A, |_| Ok(())).unwrap();
+    // At least `ndots` dots: The name is tried as-is first.
+    resolver.query(b"www.host", rr_type::A, |_| Ok(())).unwrap();
+
+    let names = handle.join().unwrap();
+    let expected: [&[u8]; 5] = [
+        b"host.a.example", b"host.b.example", b"host", b"www.host",
+        b"www.host.a.example",
+    ];
+    test!(names.len() == expected.len());
+    for i in 0..expected.len() {
+        test!(&names[i][..] == expected[i]);
+    }
+}
+
+#[test]
+fn resolver_srv() {
+    let (port, handle) = udp_server(1, |query, resp| {
+        respond(query, rcode::NoError, &[
+            (rr_type::Srv, &b"\x00\x0a\x00\x05\x00\x50\x01b\x07example\x00"[..]),
+            (rr_type::Srv, &b"\x00\x05\x00\x01\x00\x51\x01a\x07example\x00"[..]),
+            (rr_type::Srv, &b"\x00\x0a\x00\x14\x00\x52\x01c\x07example\x00"[..]),
+        ], resp);
+    });
+    let resolver = new_resolver(port, b"");
+    let srv = resolver.lookup_srv(b"_http._tcp.example.").unwrap();
+    let names = handle.join().unwrap();
+    test!(&names[0][..] == &b"_http._tcp.example"[..]);
+
+    test!(srv.len() == 3);
+    test!(&srv[0].target[..] == &b"a.example"[..]);
+    test!(srv[0].priority == 5);
+    test!(srv[0].port == 81);
+    test!(&srv[1].target[..] == &b"c.example"[..]);
+    test!(srv[1].weight == 20);
+    test!(&srv[2].target[..] == &b"b.example"[..]);
+    test!(srv[2].port == 80);
+}
+
+#[test]
+fn resolver_ptr() {
+    let (port, handle) = udp_server(2, |query, resp| {
+        respond(query, rcode::NoError, &[(rr_type::Ptr, &b"\x04host\x07example\x00"[..])],
+                resp);
+    });
+    let resolver = new_resolver(port, b"");
+    let v4 = resolver.lookup_addr(IpAddr::V4(Ipv4Addr(10, 1, 2, 3))).unwrap();
+    let v6 = IpAddr::V6(Ipv6Addr(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+    let v6 = resolver.lookup_addr(v6).unwrap();
+    let names = handle.join().unwrap();
+
+    test!(&names[0][..] == &b"3.2.1.10.in-addr.arpa"[..]);
+    test!(&names[1][..] == &b"1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.\
+                           0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"[..]);
+    test!(v4.len() == 1);
+    test!(&v4[0][..] == &b"host.example"[..]);
+    test!(v6.len() == 1);
+    test!(&v6[0][..] == &b"host.example"[..]);
+}
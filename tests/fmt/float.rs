@@ -0,0 +1,68 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::alloc::{OncePool};
+
+macro_rules! tt {
+    ($fmt:expr, $name:ident, $val:expr, $res:expr) => {
+        #[test] fn $name() {
+            let mut buf = [0; 64];
+            let mut buf = Vec::with_pool(OncePool::new(buf.as_mut()));
+            write!(&mut buf, $fmt, $val);
+            test!(&*buf == $res);
+        }
+    }
+}
+
+tt!("{}", display_f64, 0.1f64, "0.1");
+tt!("{:?}", debug_f64, 0.1f64, "0.1");
+tt!("{}", display_f64_int, 100.0f64, "100");
+tt!("{:?}", debug_f64_int, 100.0f64, "100.0");
+tt!("{}", display_f64_zero, 0.0f64, "0");
+tt!("{:?}", debug_f64_neg_zero, -0.0f64, "-0.0");
+tt!("{}", display_f64_small, 0.0001f64, "0.0001");
+tt!("{}", display_f64_tiny, 1e-5f64, "1e-5");
+tt!("{}", display_f64_large, 1e16f64, "1e16");
+tt!("{}", display_f64_max, 1.7976931348623157e308f64, "1.7976931348623157e308");
+tt!("{}", display_f64_subnormal, 5e-324f64, "5e-324");
+tt!("{}", display_f64_round_trip, 0.30000000000000004f64, "0.30000000000000004");
+tt!("{}", display_f64_nan, f64::nan(), "NaN");
+tt!("{}", display_f64_inf, f64::inf(), "inf");
+tt!("{}", display_f64_neg_inf, -f64::inf(), "-inf");
+
+tt!("{}", display_f32, 0.1f32, "0.1");
+tt!("{:?}", debug_f32, 16777216.0f32, "16777216.0");
+tt!("{}", display_f32_max, 3.4028235e38f32, "3.4028235e38");
+tt!("{}", display_f32_subnormal, 1e-45f32, "1e-45");
+
+tt!("{:e}", lowerexp_f64, 1234.5f64, "1.2345e3");
+tt!("{:E}", upperexp_f64, 0.00012f64, "1.2E-4");
+tt!("{:e}", lowerexp_f64_zero, 0.0f64, "0e0");
+tt!("{:e}", lowerexp_f32, 1.5f32, "1.5e0");
+
+tt!("{:.3}", precision_f64, 3.14159f64, "3.142");
+tt!("{:.0}", precision_f64_zero, 2.5f64, "2");
+tt!("{:.2}", precision_f64_tie, 0.125f64, "0.12");
+tt!("{:.2}", precision_f64_carry, 9.999f64, "10.00");
+tt!("{:.3}", precision_f64_small, 1e-10f64, "0.000");
+tt!("{:.1}", precision_f64_int, 1e21f64, "1000000000000000000000.0");
+tt!("{:.3?}", precision_debug_f64, 1.0f64, "1.000");
+tt!("{:.2}", precision_f32, 0.1f32, "0.10");
+tt!("{:.2e}", precision_lowerexp_f64, 123456.0f64, "1.23e5");
+tt!("{:.3E}", precision_upperexp_f64, 1.0f64, "1.000E0");
+
+#[test]
+fn precision_param() {
+    let mut buf = [0; 64];
+    let mut buf = Vec::with_pool(OncePool::new(buf.as_mut()));
+    write!(&mut buf, "{:.*} {:.0$}", 2, 1.0f64, 0.5f64);
+    test!(&*buf == "1.00 0.50");
+}
+
+tt!("{:8.2}", width_f64, -1.5f64, "   -1.50");
+tt!("{:<8}", align_left_f64, 1.5f64, "1.5     ");
+tt!("{:+}", plus_f64, 1.5f64, "+1.5");
+tt!("{:08.3}", zero_pad_f64, -3.14159f64, "-003.142");
+tt!("{:08}", zero_pad_f64_inf, f64::inf(), "     inf");
+tt!("{:>10e}", width_lowerexp_f64, 1500.0f64, "     1.5e3");
@@ -3,6 +3,7 @@
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
 mod num;
+mod float;
 mod str;
 mod option;
 mod boolean;
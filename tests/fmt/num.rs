@@ -50,3 +50,31 @@ tt!("{:X}", upperhex_i32, 127u32, "7F");
 
 tt!("{:x}", lowerhex_i64, u64::max(), "ffffffffffffffff");
 tt!("{:X}", upperhex_i64, u64::max(), "FFFFFFFFFFFFFFFF");
+
+tt!("{:o}", octal_u8, 8u8, "10");
+tt!("{:o}", octal_u64, u64::max(), "1777777777777777777777");
+tt!("{:b}", binary_u8, 5u8, "101");
+tt!("{:b}", binary_u32, 0u32, "0");
+
+tt!("{:>8}", align_right_i32, -12i32, "     -12");
+tt!("{:<8}", align_left_i32, -12i32, "-12     ");
+tt!("{:^8}", align_center_i32, -12i32, "  -12   ");
+tt!("{:*^9}", fill_center_u32, 12u32, "***12****");
+tt!("{:8}", width_u32, 12u32, "      12");
+tt!("{:2}", width_too_small_u32, 1234u32, "1234");
+tt!("{:+}", plus_i32, 12i32, "+12");
+tt!("{:08}", zero_pad_i32, -12i32, "-0000012");
+tt!("{:08x}", zero_pad_hex, 0xbeefu32, "0000beef");
+tt!("{:#x}", alternate_hex, 255u8, "0xff");
+tt!("{:#010X}", alternate_zero_pad_hex, 255u8, "0x000000FF");
+tt!("{:#b}", alternate_binary, 5u8, "0b101");
+tt!("{:#o}", alternate_octal, 8u8, "0o10");
+tt!("{:>21}", width_i64_min, i64::min(), " -9223372036854775808");
+
+#[test]
+fn width_param() {
+    let mut buf = [0; 30];
+    let mut buf = Vec::with_pool(OncePool::new(buf.as_mut()));
+    write!(&mut buf, "{:1$}|", 7u8, 3usize);
+    test!(&*buf == "  7|");
+}
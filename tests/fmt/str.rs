@@ -39,3 +39,19 @@ fn display_str() {
     write!(&mut buf, "{}", "aä日");
     test!(&*buf == "aä日");
 }
+
+#[test]
+fn pad_str() {
+    let mut buf = [0; 40];
+    let mut buf = Vec::with_pool(OncePool::new(buf.as_mut()));
+    write!(&mut buf, "[{:6}][{:>6}][{:-^7}][{:1}]", "aä日", "ab", "ab", "abc");
+    test!(&*buf == "[aä日   ][    ab][--ab---][abc]");
+}
+
+#[test]
+fn pad_debug_str() {
+    let mut buf = [0; 30];
+    let mut buf = Vec::with_pool(OncePool::new(buf.as_mut()));
+    write!(&mut buf, "{:>6?}", "ab");
+    test!(&*buf == "  \"ab\"");
+}
@@ -6,6 +6,8 @@
 #![crate_name = "tests"]
 #![feature(custom_derive, type_ascription)]
 
+extern crate test as lrs_test;
+
 macro_rules! test {
     ($e:expr) => {
         // if !$e { ::std::process::exit(1); }
@@ -45,9 +47,11 @@ mod signal;
 mod pipe;
 mod alloc;
 mod queue;
+mod channel;
 // mod box;
 mod c_ptr_ptr;
 mod buf_reader;
+mod buf_writer;
 mod rc;
 mod vec;
 mod ringbuf;
@@ -62,14 +66,18 @@ mod env;
 mod fs;
 mod socket;
 mod netlink;
+mod dns;
 mod sys;
 mod poll;
+mod reactor;
 mod dev;
 mod file;
 mod mqueue;
 mod tty;
 mod thread;
+mod thread_pool;
 mod process;
 mod time_ext;
 mod dir;
 mod user_group;
+mod test;
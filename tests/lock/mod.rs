@@ -1,3 +1,126 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::sync::{RwLock, Barrier};
+use std::atomic::{Atomic};
+use std::thread::{self};
+use std::time::{Time};
+use std::error::{self, Errno};
+
+fn err<T>(res: Result<T>) -> Errno {
+    match res {
+        Err(e) => e,
+        _ => abort!(),
+    }
+}
+
+#[test]
+fn rwlock_read() {
+    let lock = RwLock::new(1);
+    let r1 = lock.read();
+    let r2 = lock.try_read().unwrap();
+    test!(*r1 == 1);
+    test!(*r2 == 1);
+    test!(lock.try_write().is_err());
+}
+
+#[test]
+fn rwlock_write() {
+    let lock = RwLock::new(1);
+    {
+        let mut w = lock.write();
+        *w = 2;
+        test!(lock.try_read().is_err());
+        test!(lock.try_write().is_err());
+    }
+    test!(*lock.try_write().unwrap() == 2);
+    test!(*lock.read() == 2);
+}
+
+#[test]
+fn rwlock_contention() {
+    let lock = RwLock::new(0);
+    {
+        let _t = thread::scoped(|| {
+            for _ in 0..1000 {
+                *lock.write() += 1;
+            }
+        }).unwrap();
+        for _ in 0..1000 {
+            *lock.write() += 1;
+            test!(*lock.read() <= 2000);
+        }
+    }
+    test!(*lock.read() == 2000);
+}
+
+#[test]
+fn rwlock_timeout() {
+    let lock = RwLock::new(1);
+    {
+        let _w = lock.write();
+        test!(err(lock.try_read_until(Time::milliseconds(10))) == error::TimedOut);
+        test!(err(lock.try_write_until(Time::milliseconds(10))) == error::TimedOut);
+    }
+    {
+        let _r = lock.read();
+        test!(err(lock.try_write_until(Time::milliseconds(10))) == error::TimedOut);
+        test!(*lock.try_read_until(Time::milliseconds(10)).unwrap() == 1);
+    }
+    test!(*lock.try_write_until(Time::milliseconds(10)).unwrap() == 1);
+}
+
+#[test]
+fn barrier() {
+    let barrier = Barrier::new(1);
+    test!(barrier.wait());
+    test!(barrier.wait());
+}
+
+#[test]
+fn barrier_contention() {
+    let barrier = Barrier::new(2);
+    let leaders = Atomic::new(0usize);
+    let rounds = Atomic::new(0usize);
+    {
+        let _t = thread::scoped(|| {
+            for _ in 0..100 {
+                if barrier.wait() {
+                    leaders.add(1);
+                }
+                rounds.add(1);
+            }
+        }).unwrap();
+        for i in 0..100 {
+            if barrier.wait() {
+                leaders.add(1);
+            }
+            // Both threads have reached round `i` before either is released.
+            test!(rounds.load() >= 2 * i);
+            rounds.add(1);
+        }
+    }
+    test!(leaders.load() == 100);
+    test!(rounds.load() == 200);
+}
+
+#[test]
+fn barrier_timeout() {
+    let barrier = Barrier::new(2);
+    test!(err(barrier.try_wait_until(Time::milliseconds(10))) == error::TimedOut);
+
+    // The thread that timed out no longer counts towards the waiting threads.
+    let leaders = Atomic::new(0usize);
+    {
+        let _t = thread::scoped(|| {
+            if barrier.wait() {
+                leaders.add(1);
+            }
+        }).unwrap();
+        if barrier.try_wait_until(Time::seconds(60)).unwrap() {
+            leaders.add(1);
+        }
+    }
+    test!(leaders.load() == 1);
+}
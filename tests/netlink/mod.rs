@@ -1,3 +1,172 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::netlink::{RouteSocket, Route, Event, GenlSocket};
+use std::netlink::route::{group, route_scope};
+use std::socket::ip::{IpAddr, Ipv4Addr};
+use std::process::clone::{CLONE_NEWNET};
+use std::thread::{unshare};
+use std::time::{Time};
+
+// The tests that modify the network configuration need CAP_SYS_ADMIN to create a
+// network namespace. They are ignored by default. The dumps below only read the
+// configuration of the current namespace.
+
+/// Moves the test into a fresh network namespace.
+fn new_netns() {
+    unshare(CLONE_NEWNET).unwrap();
+}
+
+fn loopback(rs: &mut RouteSocket) -> i32 {
+    rs.link_by_name(b"lo").unwrap().index
+}
+
+#[test]
+fn link_dump() {
+    let mut rs = RouteSocket::new().unwrap();
+    let links = rs.links().unwrap();
+    let lo = match links.iter().find(|l| &l.name[..] == &b"lo"[..]) {
+        Some(lo) => lo,
+        None => abort!(),
+    };
+    test!(lo.is_loopback());
+    test!(loopback(&mut rs) == lo.index);
+    test!(rs.link(lo.index).unwrap().is_loopback());
+    test!(rs.link_by_name(b"does-not-exist").is_err());
+}
+
+#[test]
+fn address_dump() {
+    let mut rs = RouteSocket::new().unwrap();
+    let links = rs.links().unwrap();
+    let addrs = rs.addresses().unwrap();
+    for a in &addrs[..] {
+        test!(links.iter().any(|l| l.index as u32 == a.index));
+    }
+
+    let lo = rs.link_by_name(b"lo").unwrap();
+    if lo.is_up() {
+        let addr = IpAddr::V4(Ipv4Addr(127, 0, 0, 1));
+        test!(addrs.iter().any(|a| {
+            a.index == lo.index as u32 && a.local == Some(addr) && a.prefix_len == 8
+        }));
+    }
+}
+
+#[test]
+#[ignore]
+fn links() {
+    new_netns();
+    let mut rs = RouteSocket::new().unwrap();
+    let links = rs.links().unwrap();
+    test!(links.len() == 1);
+    test!(&links[0].name[..] == &b"lo"[..]);
+    test!(links[0].is_loopback());
+    test!(!links[0].is_up());
+
+    let index = loopback(&mut rs);
+    test!(index == links[0].index);
+    test!(rs.link_by_name(b"does-not-exist").is_err());
+
+    rs.set_link_up(index, true).unwrap();
+    test!(rs.link(index).unwrap().is_up());
+    rs.set_link_mtu(index, 1500).unwrap();
+    test!(rs.link(index).unwrap().mtu == Some(1500));
+    rs.set_link_up(index, false).unwrap();
+    test!(!rs.link(index).unwrap().is_up());
+}
+
+#[test]
+#[ignore]
+fn addresses() {
+    new_netns();
+    let mut rs = RouteSocket::new().unwrap();
+    let index = loopback(&mut rs);
+    rs.set_link_up(index, true).unwrap();
+
+    let addr = IpAddr::V4(Ipv4Addr(10, 1, 2, 3));
+    let has_addr = |rs: &mut RouteSocket| {
+        rs.addresses().unwrap().iter().any(|a| {
+            a.index == index as u32 && a.local == Some(addr) && a.prefix_len == 8
+        })
+    };
+
+    test!(!has_addr(&mut rs));
+    rs.add_address(index as u32, addr, 8).unwrap();
+    test!(has_addr(&mut rs));
+    test!(rs.add_address(index as u32, addr, 8).is_err());
+    rs.del_address(index as u32, addr, 8).unwrap();
+    test!(!has_addr(&mut rs));
+}
+
+#[test]
+#[ignore]
+fn routes() {
+    new_netns();
+    let mut rs = RouteSocket::new().unwrap();
+    let index = loopback(&mut rs);
+    rs.set_link_up(index, true).unwrap();
+
+    let dst = IpAddr::V4(Ipv4Addr(192, 168, 7, 0));
+    let mut route = Route::new(dst, 24);
+    route.oif = Some(index as u32);
+    route.scope = route_scope::Link;
+
+    let has_route = |rs: &mut RouteSocket| {
+        rs.routes().unwrap().iter().any(|r| {
+            r.dst == Some(dst) && r.dst_len == 24 && r.oif == Some(index as u32)
+        })
+    };
+
+    test!(!has_route(&mut rs));
+    rs.add_route(&route).unwrap();
+    test!(has_route(&mut rs));
+    rs.del_route(&route).unwrap();
+    test!(!has_route(&mut rs));
+}
+
+#[test]
+#[ignore]
+fn events() {
+    new_netns();
+    let mut watch = RouteSocket::new().unwrap();
+    watch.join_group(group::Link).unwrap();
+    watch.socket().set_recv_timeout(Some(Time::seconds(5))).unwrap();
+
+    let mut rs = RouteSocket::new().unwrap();
+    let index = loopback(&mut rs);
+    rs.set_link_up(index, true).unwrap();
+
+    let mut seen = false;
+    let mut tries = 0;
+    while !seen {
+        test!(tries < 100);
+        tries += 1;
+        watch.recv_events(|event| {
+            if let Event::NewLink(link) = event {
+                if link.index == index && link.is_up() {
+                    seen = true;
+                }
+            }
+        }).unwrap();
+    }
+}
+
+#[test]
+fn genl_family() {
+    let mut gs = GenlSocket::new().unwrap();
+    let ctrl = gs.family(b"nlctrl").unwrap();
+    test!(ctrl.id == 0x10);
+    test!(&ctrl.name[..] == &b"nlctrl"[..]);
+    test!(ctrl.group(b"notify").is_some());
+    test!(gs.family_id(b"nlctrl").unwrap() == 0x10);
+    test!(gs.family(b"does-not-exist").is_err());
+}
+
+#[test]
+fn genl_families() {
+    let mut gs = GenlSocket::new().unwrap();
+    let families = gs.families().unwrap();
+    test!(families.iter().any(|f| &f.name[..] == &b"nlctrl"[..]));
+}
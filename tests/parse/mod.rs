@@ -67,3 +67,46 @@ fn hex_init() {
     test!(u64::parse_bytes_init(b"0x101010 ").unwrap() == (0x101010, 8));
     test!(i64::parse_bytes_init(b"-0x101010 ").unwrap() == (-0x101010, 9));
 }
+
+#[test]
+fn float() {
+    test!(f64::parse_bytes(b"1.5").unwrap() == 1.5);
+    test!(f64::parse_bytes(b"-1.5e3").unwrap() == -1500.0);
+    test!(f64::parse_bytes(b"+.25").unwrap() == 0.25);
+    test!(f64::parse_bytes(b"1E-2").unwrap() == 0.01);
+    test!(f64::parse_bytes(b"9007199254740993").unwrap() == 9007199254740992.0);
+    test!(f64::parse_bytes(b"1.7976931348623157e308").unwrap() == 1.7976931348623157e308);
+    test!(f64::parse_bytes(b"4.9e-324").unwrap().to_bits() == 1);
+    test!(f64::parse_bytes(b"2.4703282292062327e-324").unwrap() == 0.0);
+    test!(f64::parse_bytes(b"1e400").unwrap() == f64::inf());
+    test!(f32::parse_bytes(b"3.4028235e38").unwrap() == 3.4028235e38);
+    test!(f32::parse_bytes(b"0.1").unwrap() == 0.1);
+    test!(f64::parse_bytes(b".").is_err());
+    test!(f64::parse_bytes(b"-").is_err());
+}
+
+#[test]
+fn float_special() {
+    test!(f64::parse_bytes(b"inf").unwrap() == f64::inf());
+    test!(f64::parse_bytes(b"-Infinity").unwrap() == -f64::inf());
+    test!(f64::parse_bytes(b"NaN").unwrap().is_nan());
+    test!(f32::parse_bytes(b"-inf").unwrap() == -f32::inf());
+}
+
+#[test]
+fn float_hex() {
+    test!(f64::parse_bytes(b"0x1.8p1").unwrap() == 3.0);
+    test!(f64::parse_bytes(b"-0x10").unwrap() == -16.0);
+    test!(f64::parse_bytes(b"0x1p-1074").unwrap().to_bits() == 1);
+    test!(f64::parse_bytes(b"0x1.00000000000008p0").unwrap() == 1.0);
+    test!(f32::parse_bytes(b"0x.8").unwrap() == 0.5);
+}
+
+#[test]
+fn float_init() {
+    test!(f64::parse_bytes_init(b"1.5 ").unwrap() == (1.5, 3));
+    test!(f64::parse_bytes_init(b"1.5e ").unwrap() == (1.5, 3));
+    test!(f64::parse_bytes_init(b"-2e+1x").unwrap() == (-20.0, 5));
+    test!(f64::parse_bytes_init(b"0x").unwrap() == (0.0, 1));
+    test!(f64::parse_bytes_init(b"infinite").unwrap() == (f64::inf(), 3));
+}
@@ -1,3 +1,151 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::process::{self, Command, Stdio, ChildStatus, ProcessId};
+use std::process::resource::{FileDescriptors};
+use std::file::{Mode};
+use std::signal::{self, signals};
+use std::pipe::{Pipe};
+use std::pipe::flags::{PIPE_CLOSE_ON_EXEC};
+use std::fd::{FdContainer};
+use std::io::{Read};
+use std::syscall::{dup3};
+use std::{error};
+
+#[test]
+fn command_output() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.args(&["-c", "echo out; echo err >&2"]).unwrap();
+    let output = cmd.output().unwrap();
+    test!(output.status == ChildStatus::Exited(0));
+    test!(&output.stdout[..] == "out\n");
+    test!(&output.stderr[..] == "err\n");
+}
+
+#[test]
+fn command_env() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.args(&["-c", "echo -n $LRS_TEST"]).unwrap();
+    cmd.env_clear().env("LRS_TEST", "value").unwrap();
+    let output = cmd.output().unwrap();
+    test!(&output.stdout[..] == "value");
+}
+
+#[test]
+fn command_exit_status() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.args(&["-c", "exit 3"]).unwrap();
+    cmd.stdout(Stdio::Null);
+    let mut child = cmd.spawn().unwrap();
+    test!(child.wait().unwrap() == ChildStatus::Exited(3));
+    test!(child.try_wait().unwrap() == Some(ChildStatus::Exited(3)));
+}
+
+#[test]
+fn command_not_found() {
+    let cmd: Command = Command::new("/lrs/does/not/exist").unwrap();
+    match cmd.spawn() {
+        Err(e) => test!(e == error::DoesNotExist),
+        _ => test!(false),
+    }
+}
+
+/// Runs a shell command and returns its trimmed output.
+fn sh(cmd: &mut Command, script: &str) -> Vec<u8> {
+    cmd.args(&["-c", script]).unwrap();
+    let output = cmd.output().unwrap();
+    test!(output.status == ChildStatus::Exited(0));
+    let mut stdout = output.stdout;
+    while stdout.last() == Some(&b'\n') {
+        stdout.pop();
+    }
+    stdout
+}
+
+/// Returns the process id, process group, and session of the shell.
+fn ids(cmd: &mut Command) -> (ProcessId, ProcessId, ProcessId) {
+    let out = sh(cmd, "read -r pid comm state ppid pgrp sid rest < /proc/$$/stat; \
+                       echo $pid $pgrp $sid");
+    let mut ids = [0; 3];
+    for (i, id) in out.split(|&b| b == b' ').enumerate() {
+        ids[i] = id.parse().unwrap();
+    }
+    (ids[0], ids[1], ids[2])
+}
+
+#[test]
+fn command_current_dir() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.current_dir("/proc").unwrap();
+    test!(&sh(&mut cmd, "pwd")[..] == "/proc");
+}
+
+#[test]
+fn command_file_mask() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.file_mask(Mode(0o027));
+    test!(&sh(&mut cmd, "umask")[..] == "0027");
+}
+
+#[test]
+fn command_process_group() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.process_group(0);
+    let (pid, pgrp, _) = ids(&mut cmd);
+    test!(pgrp == pid);
+    test!(pgrp != process::process_group(None).unwrap());
+}
+
+#[test]
+fn command_new_session() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.new_session(true);
+    let (pid, pgrp, sid) = ids(&mut cmd);
+    test!(sid == pid);
+    test!(pgrp == pid);
+    test!(sid != process::session(None).unwrap());
+}
+
+#[test]
+fn command_resource_limit() {
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.resource_limit(FileDescriptors, 64, 64).unwrap();
+    test!(&sh(&mut cmd, "ulimit -n")[..] == "64");
+}
+
+#[test]
+fn command_reset_signal_mask() {
+    const SCRIPT: &'static str = "grep SigBlk /proc/self/status";
+    const EMPTY: &'static str = "SigBlk:\t0000000000000000";
+
+    // SIGUSR1 is signal 10.
+    signal::block_signal(signals::User1).unwrap();
+
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    test!(&sh(&mut cmd, SCRIPT)[..] == "SigBlk:\t0000000000000200");
+
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.reset_signal_mask(true);
+    test!(&sh(&mut cmd, SCRIPT)[..] == EMPTY);
+}
+
+#[test]
+fn command_swapped_stdio() {
+    let (out_write, mut out_read) = Pipe::new(PIPE_CLOSE_ON_EXEC).unwrap();
+    let (err_write, mut err_read) = Pipe::new(PIPE_CLOSE_ON_EXEC).unwrap();
+    test!(dup3(out_write.borrow(), 1, 0) == 1);
+    test!(dup3(err_write.borrow(), 2, 0) == 2);
+
+    // Standard output of the child goes to our standard error and vice versa.
+    let mut cmd: Command = Command::new("/bin/sh").unwrap();
+    cmd.args(&["-c", "echo out; echo err >&2"]).unwrap();
+    cmd.stdout(Stdio::Fd(2)).stderr(Stdio::Fd(1));
+    test!(cmd.spawn().unwrap().wait().unwrap() == ChildStatus::Exited(0));
+
+    let mut buf = [0; 16];
+    let n = out_read.read(&mut buf).unwrap();
+    test!(&buf[..n] == "err\n");
+    let n = err_read.read(&mut buf).unwrap();
+    test!(&buf[..n] == "out\n");
+}
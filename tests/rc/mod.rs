@@ -1,3 +1,240 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::alloc::{MemPool, Heap};
+use std::atomic::{Atomic};
+use std::share::{Cell, RefCell};
+use std::rc::{Rc, RcWeak, Arc};
+use std::{thread};
+
+/// A pool that counts how often memory is returned to it.
+struct Counting<'a> {
+    heap: Heap,
+    frees: &'a Cell<usize>,
+}
+
+impl<'a> Counting<'a> {
+    fn new(frees: &'a Cell<usize>) -> Counting<'a> {
+        Counting { heap: Heap::out_of(()), frees: frees }
+    }
+}
+
+impl<'a> MemPool for Counting<'a> {
+    unsafe fn alloc(&mut self, size: usize, alignment: usize) -> Result<*mut d8> {
+        self.heap.alloc(size, alignment)
+    }
+
+    unsafe fn free(&mut self, ptr: *mut d8, size: usize, alignment: usize) {
+        self.frees.set(self.frees.get() + 1);
+        self.heap.free(ptr, size, alignment)
+    }
+
+    unsafe fn realloc(&mut self, ptr: *mut d8, old_size: usize, new_size: usize,
+                      alignment: usize) -> Result<*mut d8> {
+        self.heap.realloc(ptr, old_size, new_size, alignment)
+    }
+}
+
+/// A value that counts how often it is dropped.
+struct Dropped<'a>(&'a Cell<usize>);
+
+impl<'a> Drop for Dropped<'a> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+/// A value that atomically counts how often it is dropped.
+struct AtomicDropped<'a>(&'a Atomic<usize>, usize);
+
+impl<'a> Drop for AtomicDropped<'a> {
+    fn drop(&mut self) {
+        self.0.add(1);
+    }
+}
+
+/// A node in a tree whose children point back to their parent.
+struct Node<'a> {
+    parent: Option<RcWeak<Node<'a>>>,
+    children: RefCell<Vec<Rc<Node<'a>>>>,
+    _dropped: Dropped<'a>,
+}
+
+#[test]
+fn rc_weak() {
+    let frees = Cell::new(0);
+    let drops = Cell::new(0);
+    let rc = Rc::with_pool(Counting::new(&frees)).unwrap().set(Dropped(&drops));
+    let weak = rc.downgrade();
+    test!(rc.strong_count() == 1);
+    test!(rc.weak_count() == 1);
+
+    {
+        let rc2 = weak.upgrade().unwrap();
+        test!(weak.strong_count() == 2);
+        test!(rc2.weak_count() == 1);
+    }
+
+    let weak2 = weak.to();
+    test!(rc.weak_count() == 2);
+
+    drop(rc);
+    test!(drops.get() == 1);
+    test!(frees.get() == 0);
+    test!(weak.upgrade().is_none());
+    test!(weak.strong_count() == 0);
+    test!(weak.weak_count() == 2);
+
+    drop(weak);
+    test!(frees.get() == 0);
+    drop(weak2);
+    test!(frees.get() == 1);
+    test!(drops.get() == 1);
+}
+
+#[test]
+fn rc_cycle() {
+    let drops = Cell::new(0);
+    {
+        let parent: Rc<Node> = Rc::new().unwrap().set(Node {
+            parent: None,
+            children: RefCell::new(Vec::new()),
+            _dropped: Dropped(&drops),
+        });
+        for _ in 0..2 {
+            let child = Rc::new().unwrap().set(Node {
+                parent: Some(parent.downgrade()),
+                children: RefCell::new(Vec::new()),
+                _dropped: Dropped(&drops),
+            });
+            parent.children.borrow_mut().push(child).unwrap();
+        }
+        test!(parent.strong_count() == 1);
+        test!(parent.weak_count() == 2);
+        for child in &parent.children.borrow()[..] {
+            let p = child.parent.as_ref().unwrap().upgrade().unwrap();
+            test!(&*p as *const Node == &*parent as *const Node);
+        }
+    }
+    // The children only hold weak references to the parent, so the tree is freed.
+    test!(drops.get() == 3);
+}
+
+#[test]
+fn rc_as_mut() {
+    let mut rc: Rc<u32> = Rc::new().unwrap().set(1);
+    test!(rc.as_mut().is_some());
+    let weak = rc.downgrade();
+    test!(rc.as_mut().is_none());
+    drop(weak);
+    *rc.as_mut().unwrap() = 2;
+    test!(*rc == 2);
+}
+
+#[test]
+fn arc_weak() {
+    let frees = Cell::new(0);
+    let drops = Cell::new(0);
+    let arc = Arc::with_pool(Counting::new(&frees)).unwrap().set(Dropped(&drops));
+    let weak = arc.downgrade();
+    test!(arc.strong_count() == 1);
+    test!(arc.weak_count() == 1);
+
+    {
+        let arc2 = weak.upgrade().unwrap();
+        test!(arc2.ptr_eq(&arc));
+        test!(weak.strong_count() == 2);
+    }
+
+    drop(arc);
+    test!(drops.get() == 1);
+    test!(frees.get() == 0);
+    test!(weak.upgrade().is_none());
+    test!(weak.weak_count() == 1);
+
+    drop(weak);
+    test!(frees.get() == 1);
+}
+
+#[test]
+fn arc_try_unwrap() {
+    let arc: Arc<u32> = Arc::new().unwrap().set(1);
+    let arc2 = arc.add_ref();
+    let arc = match arc.try_unwrap() {
+        Err(arc) => arc,
+        _ => abort!(),
+    };
+    drop(arc2);
+
+    let weak = arc.downgrade();
+    match arc.try_unwrap() {
+        Ok(val) => test!(val == 1),
+        _ => abort!(),
+    }
+    test!(weak.upgrade().is_none());
+}
+
+#[test]
+fn arc_make_mut() {
+    let mut arc: Arc<u32> = Arc::new().unwrap().set(1);
+    let arc2 = arc.add_ref();
+    *arc.make_mut().unwrap() = 2;
+    test!(!arc.ptr_eq(&arc2));
+    test!((*arc, *arc2) == (2, 1));
+
+    let weak = arc.downgrade();
+    *arc.make_mut().unwrap() = 3;
+    test!(weak.upgrade().is_none());
+    test!(*arc == 3);
+
+    let ptr: *const u32 = &*arc;
+    *arc.make_mut().unwrap() = 4;
+    test!(ptr == &*arc as *const u32);
+    test!(arc.as_mut().is_some());
+}
+
+#[test]
+fn arc_upgrade_race() {
+    let drops = Atomic::new(0);
+    for i in 0..200 {
+        let arc = Arc::new().unwrap().set(AtomicDropped(&drops, i));
+        let weak = arc.downgrade();
+        {
+            let _t = thread::scoped(|| {
+                if let Some(arc) = weak.upgrade() {
+                    // The data is alive as long as the upgraded `Arc` exists.
+                    test!(arc.1 == i);
+                    test!(drops.load() == i);
+                }
+            }).unwrap();
+            drop(arc);
+        }
+        test!(drops.load() == i + 1);
+        test!(weak.upgrade().is_none());
+        test!(weak.strong_count() == 0);
+    }
+}
+
+#[test]
+fn arc_downgrade_race() {
+    let mut arc: Arc<u32> = Arc::new().unwrap().set(1);
+    let arc2 = arc.add_ref();
+    let dropping = Atomic::new(0usize);
+    {
+        let dropping = &dropping;
+        let _t = thread::scoped(move || {
+            for _ in 0..10000 {
+                let weak = arc2.downgrade();
+                test!(weak.upgrade().is_some());
+            }
+            dropping.store(1);
+            drop(arc2);
+        }).unwrap();
+        while arc.as_mut().is_none() { }
+        // `as_mut` only succeeds once the other `Arc` is gone.
+        test!(dropping.load() == 1);
+    }
+    test!(arc.strong_count() == 1);
+    test!(arc.weak_count() == 0);
+}
@@ -0,0 +1,119 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::poll::{Reactor, Token, POLL_READ, POLL_EDGE_TRIGGERED};
+use std::pipe::{Pipe};
+use std::pipe::flags::{PIPE_NONE};
+use std::time::{self, Time};
+use std::process::{self};
+use std::signal::{self, signals};
+
+#[test]
+fn token() {
+    let mut reactor = Reactor::new().unwrap();
+    let (write, read) = Pipe::new(PIPE_NONE).unwrap();
+    reactor.register(&read, POLL_READ, Token(7)).unwrap();
+    write.write(b"x").unwrap();
+    reactor.run_once(None).unwrap();
+    match reactor.next_event() {
+        Some((token, event)) => {
+            test!(token == Token(7));
+            test!(event.is_read());
+        },
+        None => abort!(),
+    }
+    test!(reactor.next_event().is_none());
+}
+
+#[test]
+fn callback() {
+    let mut reactor = Reactor::new().unwrap();
+    let (write, read) = Pipe::new(PIPE_NONE).unwrap();
+    reactor.register_fn(&read, POLL_READ, |r, event| {
+        if event.is_read() {
+            r.stop();
+        }
+    }).unwrap();
+    write.write(b"x").unwrap();
+    reactor.run().unwrap();
+}
+
+#[test]
+fn timers() {
+    let mut reactor = Reactor::new().unwrap();
+    let late = reactor.add_timer(Time::seconds(60), |_| abort!()).unwrap();
+    let mut ticks = 0;
+    let periodic = reactor.add_periodic(Time::milliseconds(1), move |r| {
+        ticks += 1;
+        if ticks == 3 {
+            r.stop();
+        }
+    }).unwrap();
+    reactor.run().unwrap();
+    test!(reactor.cancel_timer(periodic));
+    test!(!reactor.cancel_timer(periodic));
+    test!(reactor.cancel_timer(late));
+}
+
+#[test]
+fn cancel_timers() {
+    let mut reactor = Reactor::new().unwrap();
+    let mut ids = [None; 8];
+    for i in 0..8 {
+        let delay = Time::milliseconds(1 + i as i64);
+        ids[i] = Some(reactor.add_timer(delay, |_| abort!()).unwrap());
+    }
+    for i in 0..8 {
+        test!(reactor.cancel_timer(ids[i].unwrap()));
+    }
+    test!(!reactor.cancel_timer(ids[0].unwrap()));
+
+    // The cancelled timers no longer wake up the reactor.
+    reactor.add_timer(Time::milliseconds(20), |_| { }).unwrap();
+    let start = time::MONO.get_time().unwrap();
+    reactor.run_once(None).unwrap();
+    test!(time::MONO.get_time().unwrap() - start >= Time::milliseconds(20));
+}
+
+#[test]
+fn waker() {
+    let mut reactor = Reactor::new().unwrap();
+    reactor.waker().wake().unwrap();
+    reactor.run_once(None).unwrap();
+    test!(reactor.next_event().is_none());
+}
+
+#[test]
+fn edge_triggered() {
+    let mut reactor = Reactor::new().unwrap();
+    let (write, read) = Pipe::new(PIPE_NONE).unwrap();
+    let zero = Time::nanoseconds(0);
+    reactor.register(&read, POLL_READ | POLL_EDGE_TRIGGERED, Token(1)).unwrap();
+    write.write(b"x").unwrap();
+    reactor.run_once(Some(zero)).unwrap();
+    test!(reactor.next_event().map(|e| e.0) == Some(Token(1)));
+
+    // The data has not been consumed but no new data has arrived.
+    reactor.run_once(Some(zero)).unwrap();
+    test!(reactor.next_event().is_none());
+
+    write.write(b"x").unwrap();
+    reactor.run_once(Some(zero)).unwrap();
+    test!(reactor.next_event().map(|e| e.0) == Some(Token(1)));
+}
+
+#[test]
+fn signals() {
+    let mut reactor = Reactor::new().unwrap();
+    reactor.add_signal(signals::User1, |r, info| {
+        test!(info.signal() == signals::User1);
+        r.stop();
+    }).unwrap();
+    signal::send(process::process_id(), signals::User1).unwrap();
+    reactor.run().unwrap();
+
+    // The signal is unblocked again after the handler has been removed.
+    reactor.remove_signal(signals::User1).unwrap();
+    test!(!signal::blocked_signals().unwrap().is_set(signals::User1).unwrap());
+}
@@ -1,3 +1,411 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::{mem};
+use std::alloc::{OncePool};
+use std::parse::{Parsable};
+use std::io::{Read, Write};
+use std::time::{Time};
+use std::error::{self, Errno};
+use std::socket::{
+    TcpListener, TcpStream, KeepAlive, UdpSocket, UnixListener, UnixStream, SendMsg,
+    RecvMsg, BATCH_SIZE,
+};
+use std::socket::ip::{
+    IpAddr, Ipv4Addr, Ipv6Addr, Ipv4SockAddr, Ipv6SockAddr, IpNet, IPV6_SOCK_ADDR_SIZE,
+    IpSockAddrBuf,
+};
+use std::socket::unix::{UnixSockAddr};
+use std::socket::cmsg::{CMsg};
+use std::socket::msg::{MSG_NONE, MSG_WAIT_FOR_ONE, MSG_DONT_BLOCK};
+
+fn err<T>(res: Result<T>) -> Errno {
+    match res {
+        Err(e) => e,
+        _ => abort!(),
+    }
+}
+
+macro_rules! fmt {
+    ($buf:ident, $val:expr) => {{
+        let mut $buf = Vec::with_pool(OncePool::new($buf.as_mut()));
+        write!(&mut $buf, "{}", $val);
+        $buf
+    }}
+}
+
+#[test]
+fn ipv4_parse() {
+    test!(Ipv4Addr::parse_bytes(b"10.0.0.1").unwrap() == Ipv4Addr(10, 0, 0, 1));
+    test!(Ipv4Addr::parse_bytes(b"255.255.255.255").unwrap() == Ipv4Addr::broadcast());
+    test!(Ipv4Addr::parse_bytes(b"256.0.0.1").is_err());
+    test!(Ipv4Addr::parse_bytes(b"01.0.0.1").is_err());
+    test!(Ipv4Addr::parse_bytes(b"1.2.3").is_err());
+    test!(Ipv4Addr::parse_bytes(b"1.2.3.4.5").is_err());
+}
+
+#[test]
+fn ipv6_parse() {
+    let p = |s: &[u8]| Ipv6Addr::parse_bytes(s);
+    test!(p(b"::").unwrap() == Ipv6Addr::unspecified());
+    test!(p(b"::1").unwrap() == Ipv6Addr::loopback());
+    test!(p(b"fe80::1").unwrap() == Ipv6Addr(0xfe80, 0, 0, 0, 0, 0, 0, 1));
+    test!(p(b"1:2:3:4:5:6:7:8").unwrap() == Ipv6Addr(1, 2, 3, 4, 5, 6, 7, 8));
+    test!(p(b"1:2:3:4:5:6:7::").unwrap() == Ipv6Addr(1, 2, 3, 4, 5, 6, 7, 0));
+    test!(p(b"2001:DB8::").unwrap() == Ipv6Addr(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0));
+    test!(p(b"::ffff:10.0.0.1").unwrap() == Ipv6Addr(0, 0, 0, 0, 0, 0xffff, 0xa00, 1));
+    test!(p(b"1::2::3").is_err());
+    test!(p(b"1:2:3:4:5:6:7:8:9").is_err());
+    test!(p(b"1::2:3:4:5:6:7:8").is_err());
+    test!(p(b"1:2").is_err());
+    test!(p(b":1::").is_err());
+    test!(p(b"12345::").is_err());
+}
+
+#[test]
+fn ipv6_display() {
+    let t = |s: &[u8], res: &str| {
+        let mut buf = [0; 64];
+        let buf = fmt!(buf, Ipv6Addr::parse_bytes(s).unwrap());
+        test!(&*buf == res);
+    };
+    t(b"::", "::");
+    t(b"::1", "::1");
+    t(b"2001:0DB8:0:0:0:0:0:1", "2001:db8::1");
+    t(b"2001:db8:0:1:1:1:1:1", "2001:db8:0:1:1:1:1:1");
+    t(b"2001:0:0:1:0:0:0:1", "2001:0:0:1::1");
+    t(b"2001:db8:0:0:1:0:0:1", "2001:db8::1:0:0:1");
+    t(b"::ffff:192.0.2.1", "::ffff:192.0.2.1");
+}
+
+#[test]
+fn ip_addr() {
+    test!(IpAddr::parse_bytes(b"1.2.3.4").unwrap() == IpAddr::V4(Ipv4Addr(1, 2, 3, 4)));
+    test!(IpAddr::parse_bytes(b"::1").unwrap() == IpAddr::V6(Ipv6Addr::loopback()));
+}
+
+#[test]
+fn sock_addr() {
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    {
+        let addr = Ipv4SockAddr::from_text(&mut buf, b"10.0.0.1:8080").unwrap();
+        test!(addr.addr() == Ipv4Addr(10, 0, 0, 1));
+        test!(addr.port() == 8080);
+        let mut out = [0; 64];
+        test!(&*fmt!(out, addr) == "10.0.0.1:8080");
+    }
+    test!(Ipv4SockAddr::from_text(&mut buf, b"10.0.0.1:65536").is_err());
+    test!(Ipv4SockAddr::from_text(&mut buf, b"10.0.0.1").is_err());
+    {
+        let addr = Ipv6SockAddr::from_text(&mut buf, b"[fe80::1%7]:53").unwrap();
+        test!(addr.addr() == Ipv6Addr(0xfe80, 0, 0, 0, 0, 0, 0, 1));
+        test!(addr.port() == 53);
+        test!(addr.scope_id() == 7);
+        let mut out = [0; 64];
+        test!(&*fmt!(out, addr) == "[fe80::1%7]:53");
+    }
+    {
+        let addr = Ipv6SockAddr::from_text(&mut buf, b"[fe80::1%lo]:53").unwrap();
+        test!(addr.scope_id() == 1);
+    }
+    test!(Ipv6SockAddr::from_text(&mut buf, b"[::1]:").is_err());
+    test!(Ipv6SockAddr::from_text(&mut buf, b"::1:53").is_err());
+}
+
+#[test]
+fn ipv6_sock_addr_byte_order() {
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let addr = Ipv6SockAddr::from_addr_port(&mut buf, Ipv6Addr::loopback(),
+                                            0x1234).unwrap();
+    addr.set_scope_id(0x01020304);
+    {
+        // sin6_port is in network byte order, sin6_scope_id in host byte order.
+        let bytes: &[u8] = (*addr).as_ref();
+        test!(&bytes[2..4] == &[0x12, 0x34][..]);
+        test!(&bytes[24..28] == 0x01020304u32.as_ref());
+    }
+    test!(addr.port() == 0x1234);
+    test!(addr.scope_id() == 0x01020304);
+
+    addr.set_port(0x5678);
+    let bytes: &[u8] = (*addr).as_ref();
+    test!(&bytes[2..4] == &[0x56, 0x78][..]);
+}
+
+#[test]
+fn ip_net() {
+    let net = IpNet::parse_bytes(b"10.1.2.3/8").unwrap();
+    test!(net.prefix_len() == 8);
+    test!(net.network() == IpAddr::V4(Ipv4Addr(10, 0, 0, 0)));
+    test!(net.netmask() == IpAddr::V4(Ipv4Addr(255, 0, 0, 0)));
+    test!(net.broadcast() == IpAddr::V4(Ipv4Addr(10, 255, 255, 255)));
+    test!(net.contains(IpAddr::V4(Ipv4Addr(10, 9, 9, 9))));
+    test!(!net.contains(IpAddr::V4(Ipv4Addr(11, 0, 0, 0))));
+    test!(!net.contains(IpAddr::V6(Ipv6Addr::loopback())));
+    test!(IpNet::parse_bytes(b"10.0.0.0/33").is_err());
+
+    let net = IpNet::parse_bytes(b"192.168.0.1/30").unwrap();
+    let mut iter = net.iter();
+    for i in 0..4 {
+        test!(iter.next() == Some(IpAddr::V4(Ipv4Addr(192, 168, 0, i))));
+    }
+    test!(iter.next() == None);
+
+    let net = IpNet::parse_bytes(b"fe80::/10").unwrap();
+    test!(net.netmask() == IpAddr::V6(Ipv6Addr(0xffc0, 0, 0, 0, 0, 0, 0, 0)));
+    test!(net.contains(IpAddr::V6(Ipv6Addr(0xfebf, 0, 0, 0, 0, 0, 0, 1))));
+    let mut out = [0; 64];
+    test!(&*fmt!(out, net) == "fe80::/10");
+
+    let net = IpNet::parse_bytes(b"::ffff:ffff:ffff:fffe/127").unwrap();
+    let mut iter = net.iter();
+    test!(iter.next() == Some(IpAddr::V6(Ipv6Addr(0, 0, 0, 0, 0xffff, 0xffff, 0xffff,
+                                                  0xfffe))));
+    test!(iter.next() == Some(IpAddr::V6(Ipv6Addr(0, 0, 0, 0, 0xffff, 0xffff, 0xffff,
+                                                  0xffff))));
+    test!(iter.next() == None);
+}
+
+#[test]
+fn tcp_stream() {
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let listener = TcpListener::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = listener.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    let addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+
+    let mut client = TcpStream::connect_timeout(&addr, Time::seconds(1)).unwrap();
+    client.set_no_delay(true).unwrap();
+    test!(client.socket().tcp_is_no_delay().unwrap());
+    let ka = KeepAlive { idle: 30, interval: 5, count: 3 };
+    client.set_keep_alive(Some(ka)).unwrap();
+    test!(client.socket().tcp_keep_alive_interval().unwrap() == 5);
+    client.socket().tcp_set_user_timeout(Some(Time::milliseconds(1500))).unwrap();
+    test!(client.socket().tcp_user_timeout().unwrap() == Some(Time::milliseconds(1500)));
+    let mut name = [0; 16];
+    test!(client.socket().tcp_congestion(&mut name).unwrap().len() > 0);
+    test!(client.info().unwrap().state == 1);
+
+    let (mut server, peer) = listener.accept_addr(&mut buf).unwrap();
+    test!(peer.as_ipv4().unwrap().addr() == Ipv4Addr(127, 0, 0, 1));
+    client.write_all(b"hello").unwrap();
+    client.shutdown_outgoing().unwrap();
+    let mut data = [0; 8];
+    test!(server.read_all(data.as_mut()).unwrap() == 5);
+    test!(&data[..5] == b"hello");
+}
+
+#[test]
+fn tcp_connect_timeout() {
+    // Packets to this address are silently dropped.
+    let addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(10, 255, 255, 1)), 80);
+    let e = err(TcpStream::connect_timeout(&addr, Time::milliseconds(100)));
+    // There is no route at all in an isolated network namespace.
+    test!(e == error::TimedOut || e == error::NetworkUnreachable);
+}
+
+#[test]
+fn tcp_connect_refused() {
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let listener = TcpListener::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = listener.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    drop(listener);
+
+    let addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+    let res = TcpStream::connect_timeout(&addr, Time::seconds(1));
+    test!(err(res) == error::ConnectionRefused);
+}
+
+#[test]
+fn udp_socket() {
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let a = UdpSocket::bind(&any).unwrap();
+    let b = UdpSocket::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = b.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    let b_addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+
+    a.send_to(b"ping", &b_addr).unwrap();
+    let mut data = [0; 8];
+    let (len, from) = b.recv_from(&mut data, &mut buf).unwrap();
+    test!(&data[..len] == b"ping");
+    b.connect(from).unwrap();
+    b.send(b"pong").unwrap();
+    test!(a.recv(&mut data).unwrap() == 4);
+    test!(&data[..4] == b"pong");
+}
+
+#[test]
+fn unix_stream() {
+    let mut buf = [0; 32];
+    let addr = UnixSockAddr::from_abstract(&mut buf, "lrs-test-unix-stream").unwrap();
+    let listener = UnixListener::bind(addr).unwrap();
+    let mut client = UnixStream::connect(addr).unwrap();
+    let mut server = listener.accept().unwrap();
+    client.write_all(b"hello").unwrap();
+    let mut data = [0; 5];
+    test!(server.read_all(data.as_mut()).unwrap() == 5);
+    test!(&data[..] == b"hello");
+}
+
+#[test]
+fn ip_options() {
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let a = UdpSocket::bind(&any).unwrap();
+    let b = UdpSocket::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = b.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    let b_addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+
+    a.socket().ipv4_set_type_of_service(0x10).unwrap();
+    test!(a.socket().ipv4_type_of_service().unwrap() == 0x10);
+    a.socket().ipv4_set_hop_limit(42).unwrap();
+    b.socket().ipv4_set_receive_hop_limit(true).unwrap();
+    b.socket().ipv4_set_receive_packet_info(true).unwrap();
+    test!(b.socket().ipv4_is_receive_packet_info().unwrap());
+
+    a.send_to(b"ping", &b_addr).unwrap();
+    let mut data = [0; 8];
+    let mut ctrl = [0u64; 16];
+    let (len, _, cmsgs, _) = b.socket().recv_msg(&mut [&mut data[..]], &mut [],
+                                                 ctrl[..].as_mut(), MSG_NONE).unwrap();
+    test!(len == 4);
+    let mut hop_limit = 0;
+    let mut dest = Ipv4Addr::any();
+    for msg in cmsgs {
+        match msg {
+            CMsg::HopLimit(h) => hop_limit = h,
+            CMsg::Ipv4PacketInfo(info) => dest = info.dest_addr(),
+            _ => { },
+        }
+    }
+    test!(hop_limit == 42);
+    test!(dest == Ipv4Addr(127, 0, 0, 1));
+}
+
+#[test]
+fn send_recv_many() {
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let a = UdpSocket::bind(&any).unwrap();
+    let b = UdpSocket::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = b.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    let b_addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+
+    let one: &[&[u8]] = &[b"one"];
+    let two: &[&[u8]] = &[b"tw", b"o!"];
+    let mut out = [SendMsg::new(one), SendMsg::new(two)];
+    for msg in &mut out {
+        msg.set_addr(&b_addr);
+    }
+    test!(a.socket().send_many(&mut out, MSG_NONE).unwrap() == 2);
+    test!(out[1].len() == 4);
+
+    let (mut d1, mut d2, mut d3) = ([0; 8], [0; 8], [0; 8]);
+    let (mut b1, mut b2, mut b3) = ([&mut d1[..]], [&mut d2[..]], [&mut d3[..]]);
+    let mut addr = [0; IPV6_SOCK_ADDR_SIZE];
+    let mut inc = [RecvMsg::new(&mut b1), RecvMsg::new(&mut b2), RecvMsg::new(&mut b3)];
+    inc[0].set_addr(&mut addr);
+    let num = b.socket().recv_many(&mut inc, MSG_WAIT_FOR_ONE,
+                                   Some(Time::seconds(1))).unwrap();
+    test!(num == 2);
+    test!(inc[0].len() == 3);
+    test!(inc[1].len() == 4);
+    test!(inc[0].addr().unwrap().as_ipv4().unwrap().addr() == Ipv4Addr(127, 0, 0, 1));
+    test!(inc[1].addr().is_none());
+}
+
+#[test]
+fn send_recv_many_batches() {
+    // More messages than fit in one batch.
+    const NUM: usize = BATCH_SIZE + 5;
+
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let a = UdpSocket::bind(&any).unwrap();
+    let b = UdpSocket::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = b.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    let b_addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+
+    let mut payload = [[0u8; 1]; NUM];
+    for i in 0..NUM {
+        payload[i][0] = i as u8;
+    }
+    let empty: &[u8] = &[];
+    let mut parts = [[empty]; NUM];
+    for i in 0..NUM {
+        parts[i] = [&payload[i][..]];
+    }
+    let mut out = Vec::new();
+    for i in 0..NUM {
+        let mut msg = SendMsg::new(&parts[i]);
+        msg.set_addr(&b_addr);
+        out.push(msg).unwrap();
+    }
+    test!(a.socket().send_many(&mut out, MSG_NONE).unwrap() == NUM);
+
+    let mut data = [[0u8; 4]; NUM];
+    {
+        // The slices are all overwritten before they are used.
+        let mut bufs: [[&mut [u8]; 1]; NUM] = unsafe { mem::uninit() };
+        let mut data = data.iter_mut();
+        for b in bufs.iter_mut() {
+            *b = [&mut data.next().unwrap()[..]];
+        }
+        let mut inc = Vec::new();
+        for b in bufs.iter_mut() {
+            inc.push(RecvMsg::new(b)).unwrap();
+        }
+        let num = b.socket().recv_many(&mut inc, MSG_WAIT_FOR_ONE,
+                                       Some(Time::seconds(1))).unwrap();
+        test!(num == NUM);
+        for msg in &inc[..] {
+            test!(msg.len() == 1);
+        }
+    }
+    for i in 0..NUM {
+        test!(data[i][0] == i as u8);
+    }
+}
+
+#[test]
+fn recv_many_partial() {
+    // More slots than messages and more than fit in one batch.
+    const SLOTS: usize = BATCH_SIZE + 5;
+
+    let any = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), 0);
+    let a = UdpSocket::bind(&any).unwrap();
+    let b = UdpSocket::bind(&any).unwrap();
+    let mut buf = [0; IPV6_SOCK_ADDR_SIZE];
+    let port = b.local_addr(&mut buf).unwrap().as_ipv4().unwrap().port();
+    let b_addr = IpSockAddrBuf::new(IpAddr::V4(Ipv4Addr(127, 0, 0, 1)), port);
+
+    a.send_to(b"one", &b_addr).unwrap();
+    a.send_to(b"two", &b_addr).unwrap();
+    a.send_to(b"three", &b_addr).unwrap();
+
+    let mut data = [[0u8; 8]; SLOTS];
+    // The slices are all overwritten before they are used.
+    let mut bufs: [[&mut [u8]; 1]; SLOTS] = unsafe { mem::uninit() };
+    let mut iter = data.iter_mut();
+    for b in bufs.iter_mut() {
+        *b = [&mut iter.next().unwrap()[..]];
+    }
+    let mut inc = Vec::new();
+    for b in bufs.iter_mut() {
+        inc.push(RecvMsg::new(b)).unwrap();
+    }
+
+    let num = b.socket().recv_many(&mut inc, MSG_WAIT_FOR_ONE,
+                                   Some(Time::seconds(1))).unwrap();
+    test!(num == 3);
+    test!(inc[0].len() == 3);
+    test!(inc[1].len() == 3);
+    test!(inc[2].len() == 5);
+    test!(inc[3].len() == 0);
+
+    // Nothing is queued anymore.
+    test!(b.socket().recv_many(&mut inc, MSG_DONT_BLOCK, None).is_err());
+}
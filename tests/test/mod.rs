@@ -0,0 +1,237 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::string::{CStr};
+use std::time::{Time};
+use std::process::{ChildStatus};
+use std::vec::{Vec};
+use std::file::{self};
+use lrs_test::options::{Options};
+use lrs_test::report::{self, Record, Outcome};
+use lrs_test::bench::{self, Stats};
+
+macro_rules! cs {
+    ($t:expr) => {
+        concat!($t, "\0").try_as_ref().unwrap():&CStr
+    }
+}
+
+#[test]
+fn options_default() {
+    let opts = Options::parse(&[]).unwrap();
+    test!(opts.jobs > 0);
+    test!(opts.timeout == Some(Time::seconds(60)));
+    test!(opts.capture);
+    test!(opts.tap.is_none());
+    test!(opts.junit.is_none());
+    test!(!opts.bench);
+    test!(opts.baseline.is_none());
+    test!(opts.save_baseline.is_none());
+    test!(opts.filters.len() == 0);
+}
+
+#[test]
+fn options() {
+    let args = [
+        cs!("-j3"),
+        cs!("--timeout"), cs!("5"),
+        cs!("--nocapture"),
+        cs!("--tap=out.tap"),
+        cs!("--junit"), cs!("out.xml"),
+        cs!("net"),
+        cs!("-j4"),
+    ];
+    let opts = Options::parse(&args).unwrap();
+    test!(opts.jobs == 3);
+    test!(opts.timeout == Some(Time::seconds(5)));
+    test!(!opts.capture);
+    test!(opts.tap.unwrap() == "out.tap");
+    test!(opts.junit.unwrap() == "out.xml");
+    test!(opts.filters.len() == 2);
+    test!(opts.filters[0] == "net");
+    test!(opts.filters[1] == "-j4");
+
+    let opts = Options::parse(&[cs!("-t0"), cs!("--"), cs!("-j")]).unwrap();
+    test!(opts.timeout.is_none());
+    test!(opts.filters.len() == 1);
+    test!(opts.filters[0] == "-j");
+
+    let args = [
+        cs!("--bench"),
+        cs!("--baseline"), cs!("old"),
+        cs!("--save-baseline=new"),
+    ];
+    let opts = Options::parse(&args).unwrap();
+    test!(opts.bench);
+    test!(opts.baseline.unwrap() == "old");
+    test!(opts.save_baseline.unwrap() == "new");
+}
+
+#[test]
+fn options_invalid() {
+    test!(Options::parse(&[cs!("-j0")]).is_err());
+    test!(Options::parse(&[cs!("-jx")]).is_err());
+    test!(Options::parse(&[cs!("-t"), cs!("-1")]).is_err());
+    test!(Options::parse(&[cs!("--tap")]).is_err());
+    test!(Options::parse(&[cs!("--unknown")]).is_err());
+    test!(Options::parse(&[cs!("-x")]).is_err());
+}
+
+#[test]
+fn filters() {
+    let opts = Options::parse(&[]).unwrap();
+    test!(opts.selected("net::links"));
+
+    let opts = Options::parse(&[cs!("net::"), cs!("dns::resolver")]).unwrap();
+    test!(opts.selected("net::links"));
+    test!(opts.selected("dns::resolver"));
+    test!(opts.selected("dns::resolver_srv"));
+    test!(!opts.selected("dns::hosts"));
+    test!(!opts.selected("net"));
+    test!(!opts.selected("socket::net::links"));
+}
+
+fn record(name: &'static str, outcome: Outcome, ms: i64, status: Option<ChildStatus>,
+          output: &[u8]) -> Record {
+    let mut buf = Vec::new();
+    buf.push_all(output).unwrap();
+    Record {
+        name: name,
+        outcome: outcome,
+        duration: Time::milliseconds(ms),
+        status: status,
+        output: buf,
+    }
+}
+
+fn records() -> [Record; 4] {
+    [
+        record("a::pass", Outcome::Passed, 1500, Some(ChildStatus::Exited(0)),
+               b"hello\x01\n"),
+        record("a::fail<&>\"", Outcome::Failed, 2, Some(ChildStatus::Exited(101)),
+               b"line1\n<x>&\"y\""),
+        record("a::ignored", Outcome::Ignored, 0, None, b""),
+        record("a::slow", Outcome::TimedOut, 60_000, Some(ChildStatus::Killed(9)), b""),
+    ]
+}
+
+#[test]
+fn tap_report() {
+    let expected = "\
+TAP version 13
+1..4
+ok 1 - a::pass
+  ---
+  duration_ms: 1500
+  status: \"exited with status 0\"
+  output: |
+    hello?
+  ...
+not ok 2 - a::fail<&>\"
+  ---
+  duration_ms: 2
+  status: \"exited with status 101\"
+  output: |
+    line1
+    <x>&\"y\"
+  ...
+ok 3 - a::ignored # SKIP ignored
+not ok 4 - a::slow
+  ---
+  duration_ms: 60000
+  timeout: true
+  status: \"terminated by signal Kill\"
+  ...
+";
+    test!(&report::tap(&records()).unwrap()[..] == expected);
+}
+
+#[test]
+fn junit_report() {
+    let expected = "\
+<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuite name=\"lrs\" tests=\"4\" failures=\"2\" skipped=\"1\" time=\"61.502\">
+  <testcase name=\"a::pass\" time=\"1.500\">
+    <system-out>hello?
+</system-out>
+  </testcase>
+  <testcase name=\"a::fail&lt;&amp;&gt;&quot;\" time=\"0.002\">
+    <failure type=\"failure\" message=\"exited with status 101\"/>
+    <system-out>line1
+&lt;x&gt;&amp;&quot;y&quot;</system-out>
+  </testcase>
+  <testcase name=\"a::ignored\" time=\"0.000\">
+    <skipped/>
+  </testcase>
+  <testcase name=\"a::slow\" time=\"60.000\">
+    <failure type=\"timeout\" message=\"terminated by signal Kill\"/>
+  </testcase>
+</testsuite>
+";
+    test!(&report::junit(&records()).unwrap()[..] == expected);
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[test]
+fn stats() {
+    let stats = Stats::new(&mut [9.0, 2.0, 4.0, 5.0, 4.0, 7.0, 4.0, 5.0]);
+    test!(close(stats.mean, 5.0));
+    test!(close(stats.median, 4.5));
+    test!(close(stats.stddev, (32.0f64 / 7.0).sqrt()));
+    test!(close(stats.mad, 1.4826 * 0.5));
+
+    let stats = Stats::new(&mut [100.0, 3.0, 1.0, 4.0, 2.0]);
+    test!(close(stats.mean, 22.0));
+    test!(close(stats.median, 3.0));
+    test!(close(stats.stddev, (7610.0f64 / 4.0).sqrt()));
+    test!(close(stats.mad, 1.4826));
+
+    let stats = Stats::new(&mut [7.0]);
+    test!(close(stats.mean, 7.0));
+    test!(close(stats.median, 7.0));
+    test!(close(stats.stddev, 0.0));
+    test!(close(stats.mad, 0.0));
+}
+
+fn stats_with(median: f64, mad: f64) -> Stats {
+    Stats { mean: median, median: median, stddev: mad, mad: mad }
+}
+
+#[test]
+fn regressed() {
+    let base = stats_with(100.0, 1.0);
+    test!(stats_with(110.0, 1.0).regressed(&base));
+    test!(!stats_with(102.0, 1.0).regressed(&base));
+    test!(!stats_with(90.0, 1.0).regressed(&base));
+    // Differences below one percent are ignored even without noise.
+    test!(!stats_with(100.5, 0.0).regressed(&stats_with(100.0, 0.0)));
+    test!(stats_with(101.5, 0.0).regressed(&stats_with(100.0, 0.0)));
+}
+
+#[test]
+fn baseline() {
+    let path = cs!("/tmp/lrs_test_baseline");
+    let results = [
+        ("a::alloc", stats_with(12.3456, 0.5)),
+        ("a::hash", stats_with(1000.0, 20.0)),
+    ];
+    bench::save_baseline(path, &results).unwrap();
+    let baseline = bench::load_baseline(path);
+    file::remove(path).unwrap();
+    let baseline = baseline.unwrap();
+
+    test!(baseline.len() == 2);
+    test!(&baseline[0].name[..] == "a::alloc");
+    test!(close(baseline[0].stats.median, 12.345));
+    test!(close(baseline[0].stats.mad, 0.5));
+    test!(&baseline[1].name[..] == "a::hash");
+    test!(close(baseline[1].stats.median, 1000.0));
+    test!(close(baseline[1].stats.mad, 20.0));
+
+    test!(!results[1].1.regressed(&baseline[1].stats));
+    test!(stats_with(1100.0, 20.0).regressed(&baseline[1].stats));
+}
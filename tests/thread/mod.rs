@@ -1,3 +1,43 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::thread::{self, Builder, CpuMask};
+
+#[test]
+fn join() {
+    let handle = thread::spawn_joinable(|| 1 + 2).unwrap();
+    test!(handle.join().unwrap() == 3);
+}
+
+#[test]
+fn named() {
+    let mut builder = Builder::new().unwrap();
+    test!(builder.set_name(b"0123456789abcdef").is_err());
+    builder.set_name(b"worker").unwrap();
+    let handle = builder.spawn_joinable(|| {
+        let mut buf = [0; 16];
+        thread::name(&mut buf).unwrap() == &b"worker"[..]
+    }).unwrap();
+    test!(handle.join().unwrap());
+}
+
+#[test]
+fn pinned() {
+    let mut buf = [0; 128];
+    if !thread::cpus(0, &mut buf).unwrap()[0] {
+        // CPU 0 is not available to this process.
+        return;
+    }
+
+    let mut mask = [0; 8];
+    CpuMask::new_mut(&mut mask).set(0);
+    let mut builder = Builder::new().unwrap();
+    builder.set_cpus(CpuMask::new(&mask)).unwrap();
+    let handle = builder.spawn_joinable(|| {
+        let mut buf = [0; 128];
+        let cpus = thread::cpus(0, &mut buf).unwrap();
+        cpus.count() == 1 && cpus[0]
+    }).unwrap();
+    test!(handle.join().unwrap());
+}
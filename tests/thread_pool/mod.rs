@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::thread::{self, ThreadPool};
+use std::sync::{channel};
+use std::atomic::{Atomic};
+use std::time::{self, Time};
+
+#[test]
+fn execute() {
+    let pool = ThreadPool::with_threads(2, false).unwrap();
+    let (send, recv) = channel().unwrap();
+    for i in 0..4 {
+        let send = send.to();
+        pool.execute(move || send.send(i).unwrap()).unwrap();
+    }
+    let mut sum = 0;
+    for _ in 0..4 {
+        sum += recv.recv().unwrap();
+    }
+    test!(sum == 6);
+}
+
+#[test]
+fn scope() {
+    let pool = ThreadPool::with_threads(2, false).unwrap();
+    let mut a = 0;
+    let mut b = 0;
+    pool.scope(|s| {
+        s.spawn(|| a = 1).unwrap();
+        s.spawn(|| b = 2).unwrap();
+    });
+    test!(a == 1);
+    test!(b == 2);
+}
+
+#[test]
+fn for_each() {
+    let pool = ThreadPool::with_threads(3, false).unwrap();
+    let mut array = [0; 100];
+    for i in 0..100 {
+        array[i] = i;
+    }
+    pool.for_each(&mut array, |x| *x *= 2);
+    for i in 0..100 {
+        test!(array[i] == 2 * i);
+    }
+}
+
+#[test]
+fn scope_waits() {
+    let pool = ThreadPool::with_threads(2, false).unwrap();
+    let mut array = [0; 8];
+    pool.scope(|s| {
+        for (i, x) in array.iter_mut().enumerate() {
+            s.spawn(move || {
+                time::MONO.sleep_for(Time::milliseconds(10)).unwrap();
+                *x = i + 1;
+            }).unwrap();
+        }
+    });
+    for i in 0..8 {
+        test!(array[i] == i + 1);
+    }
+}
+
+#[test]
+fn steal() {
+    const N: usize = 16;
+    let owner = Atomic::new(0);
+    let done = Atomic::new(0usize);
+    let on_owner = Atomic::new(0usize);
+    let pool = ThreadPool::with_threads(2, false).unwrap();
+    pool.scope(|s| {
+        // Block one worker until all other jobs have completed.
+        s.spawn(|| {
+            owner.store(thread::thread_id());
+            while done.load() < N {
+                thread::deschedule();
+            }
+        }).unwrap();
+        while owner.load() == 0 {
+            thread::deschedule();
+        }
+        // Half of these jobs are pushed to the deque of the blocked worker and have to
+        // be stolen by the other threads.
+        for _ in 0..N {
+            s.spawn(|| {
+                if thread::thread_id() == owner.load() {
+                    on_owner.add(1);
+                }
+                done.add(1);
+            }).unwrap();
+        }
+    });
+    test!(done.load() == N);
+    test!(on_owner.load() == 0);
+}
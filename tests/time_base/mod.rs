@@ -1,3 +1,65 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::{Duration, Time};
+use std::vec::{Vec};
+
+fn display(d: Duration) -> Vec<u8> {
+    let mut buf = Vec::new();
+    write!(&mut buf, "{}", d);
+    buf
+}
+
+#[test]
+fn duration_display() {
+    test!(display(Duration::seconds(0)) == "0s");
+    test!(display(Duration::nanoseconds(12)) == "12ns");
+    test!(display(Duration::nanoseconds(1500)) == "1.5us");
+    test!(display(Duration::milliseconds(1)) == "1ms");
+    test!(display(Duration::microseconds(1250)) == "1.25ms");
+    test!(display(Duration::minutes(1)) == "1m0s");
+    test!(display(-Duration::seconds(90)) == "-1m30s");
+    let d = Duration::hours(1) + Duration::minutes(2) + Duration::milliseconds(3500);
+    test!(display(d) == "1h2m3.5s");
+    test!(display(Duration::days(2)) == "48h0m0s");
+}
+
+#[test]
+fn duration_parse() {
+    let d = Duration::hours(1) + Duration::minutes(2) + Duration::milliseconds(3500);
+    test!("1h2m3.5s".parse::<Duration>().unwrap() == d);
+    test!("-1.5h".parse::<Duration>().unwrap() == -Duration::minutes(90));
+    test!("+300ms".parse::<Duration>().unwrap() == Duration::milliseconds(300));
+    test!("1us2ns".parse::<Duration>().unwrap() == Duration::nanoseconds(1002));
+    test!(".5s".parse::<Duration>().unwrap() == Duration::milliseconds(500));
+    test!("0".parse::<Duration>().unwrap() == Duration::seconds(0));
+
+    test!("".parse::<Duration>().is_err());
+    test!("h".parse::<Duration>().is_err());
+    test!("1".parse::<Duration>().is_err());
+    test!("1x".parse::<Duration>().is_err());
+    test!("1h2".parse::<Duration>().is_err());
+    test!("9999999999999999999h".parse::<Duration>().is_err());
+    test!("9223372036854775807ns".parse::<Duration>().is_ok());
+    test!("9223372036854775808ns".parse::<Duration>().is_err());
+
+    let d = Duration::hours(25) + Duration::nanoseconds(1);
+    test!(display(d).parse::<Duration>().unwrap() == d);
+}
+
+#[test]
+fn duration_neg() {
+    let d = Duration(Time { seconds: -5, nanoseconds: 250_000_000 });
+    test!(-d == Duration(Time { seconds: 4, nanoseconds: 750_000_000 }));
+    test!(d.abs() == -d);
+    test!(-(-d) == d);
+
+    let max = Duration(Time { seconds: i64::max(), nanoseconds: 999_999_999 });
+    let min = Duration(Time { seconds: i64::min(), nanoseconds: 0 });
+    test!(-max == Duration(Time { seconds: i64::min(), nanoseconds: 1 }));
+    test!(-(-max) == max);
+    test!(-min == Duration(Time { seconds: i64::max(), nanoseconds: 0 }));
+    test!(min.abs() == -min);
+    test!(display(min).starts_with(b"-"));
+}
@@ -1,3 +1,339 @@
 // This Source Code Form is subject to the terms of the Mozilla Public
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use std::time::{self, Zone, Time, DateTime, Weekday};
+use std::vec::{Vec};
+use std::error::{self, Errno};
+
+fn expand(zone: &Zone, seconds: i64) -> DateTime {
+    zone.expand(Time::seconds(seconds))
+}
+
+fn abbr(date: &DateTime) -> &[u8] {
+    &date.abbreviation
+}
+
+fn err<T>(res: Result<T>) -> Errno {
+    match res {
+        Err(e) => e,
+        _ => abort!(),
+    }
+}
+
+#[test]
+fn posix_tz() {
+    let zone = Zone::from_posix_tz("EST5EDT,M3.2.0,M11.1.0").unwrap();
+
+    let date = expand(&zone, 1705320000);
+    test!(date.hour == 7);
+    test!(date.offset == -5 * 3600);
+    test!(!date.summer_time);
+    test!(abbr(&date) == "EST");
+
+    let date = expand(&zone, 1719835200);
+    test!(date.hour == 8);
+    test!(date.offset == -4 * 3600);
+    test!(date.summer_time);
+    test!(abbr(&date) == "EDT");
+
+    let date = expand(&zone, 1710054000 - 1);
+    test!((date.hour, date.minute, date.second) == (1, 59, 59));
+    test!(!date.summer_time);
+    let date = expand(&zone, 1710054000);
+    test!((date.hour, date.minute, date.second) == (3, 0, 0));
+    test!(date.summer_time);
+
+    let date = expand(&zone, 1730613600 - 1);
+    test!((date.hour, date.minute, date.second) == (1, 59, 59));
+    test!(date.summer_time);
+    let date = expand(&zone, 1730613600);
+    test!((date.hour, date.minute, date.second) == (1, 0, 0));
+    test!(!date.summer_time);
+}
+
+#[test]
+fn posix_tz_southern() {
+    let zone = Zone::from_posix_tz("<+10>-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+
+    let date = expand(&zone, 1704067200);
+    test!((date.year, date.month, date.day) == (2024, 1, 1));
+    test!(date.hour == 11);
+    test!(abbr(&date) == "AEDT");
+
+    let date = expand(&zone, 1712419200 - 1);
+    test!((date.hour, date.minute, date.second) == (2, 59, 59));
+    test!(date.summer_time);
+    let date = expand(&zone, 1712419200);
+    test!((date.hour, date.minute, date.second) == (2, 0, 0));
+    test!(abbr(&date) == "+10");
+}
+
+#[test]
+fn posix_tz_invalid() {
+    test!(Zone::from_posix_tz("").is_err());
+    test!(Zone::from_posix_tz("EST").is_err());
+    test!(Zone::from_posix_tz("ES5").is_err());
+    test!(Zone::from_posix_tz("EST5EDT,M13.1.0,M11.1.0").is_err());
+    test!(Zone::from_posix_tz("EST5EDT,M3.2.0").is_err());
+}
+
+#[test]
+fn tzif_footer() {
+    let mut data = Vec::new();
+    for _ in 0..2 {
+        data.push_all(b"TZif2");
+        data.push_all(&[0; 15]);
+        // isutcnt, isstdcnt, leapcnt, timecnt, typecnt, charcnt
+        data.push_all(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+                        0, 0, 0, 4]);
+        data.push_all(&[0, 0, 0x0e, 0x10, 0, 0]);
+        data.push_all(b"CET\0");
+    }
+    data.push_all(b"\nCET-1CEST,M3.5.0,M10.5.0/3\n");
+    let zone = Zone::load_bytes(&data).unwrap();
+
+    let date = expand(&zone, 1705320000);
+    test!(date.hour == 13);
+    test!(abbr(&date) == "CET");
+
+    let date = expand(&zone, 4875508800);
+    test!((date.year, date.hour) == (2124, 14));
+    test!(abbr(&date) == "CEST");
+}
+
+#[test]
+fn from_tz() {
+    test!(expand(&Zone::from_tz("").unwrap(), 0).offset == 0);
+    test!(expand(&Zone::from_tz(":").unwrap(), 0).offset == 0);
+
+    // There is no zone file with this name.
+    let date = expand(&Zone::from_tz("XST3").unwrap(), 0);
+    test!(date.offset == -3 * 3600);
+    test!(abbr(&date) == "XST");
+
+    // With a leading `:`, the value is never a rule.
+    test!(err(Zone::from_tz(":XST3")) == error::DoesNotExist);
+
+    // Only missing files fall back to rules.
+    test!(err(Zone::from_tz("/")) == error::IsADirectory);
+}
+
+fn format(date: &DateTime, pattern: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    date.format(pattern, &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn format_date() {
+    let utc = Zone::from_posix_tz("UTC0").unwrap();
+    let date = expand(&utc, 784111777);
+    test!(format(&date, time::IMF_FIXDATE) == "Sun, 06 Nov 1994 08:49:37 GMT");
+    test!(format(&date, time::RFC3339) == "1994-11-06T08:49:37+00:00");
+    test!(format(&date, "%a %j %u %w %I %p %e|%c|%D") ==
+          "Sun 310 7 0 08 AM  6|Sun Nov  6 08:49:37 1994|11/06/94");
+    test!(format(&date, "%Z %% %B %A %y") == "UTC % November Sunday 94");
+
+    let zone = Zone::from_posix_tz("<+0530>-5:30").unwrap();
+    let date = zone.expand(Time { seconds: 1435708799, nanoseconds: 5 });
+    test!(format(&date, "%F %T.%N %z %:z") ==
+          "2015-07-01 05:29:59.000000005 +0530 +05:30");
+
+    let zone = Zone::from_posix_tz("EST5").unwrap();
+    let date = expand(&zone, 1435708799);
+    test!(format(&date, time::RFC2822) == "Tue, 30 Jun 2015 18:59:59 -0500");
+
+    let mut date: DateTime = "0987-03-01".parse().unwrap();
+    test!(format(&date, time::RFC3339) == "0987-03-01T00:00:00+00:00");
+    let parsed: DateTime = format(&date, time::RFC3339).parse().unwrap();
+    test!(parsed.year == 987);
+    date.year = 5;
+    test!(format(&date, "%Y %F") == "0005 0005-03-01");
+    date.year = 12345;
+    test!(format(&date, "%Y") == "12345");
+
+    let mut buf = Vec::new();
+    test!(date.format("%Q", &mut buf).is_err());
+    test!(date.format("%", &mut buf).is_err());
+}
+
+#[test]
+fn parse_rfc3339() {
+    let date: DateTime = "2015-06-30T23:59:60.5+02:00".parse().unwrap();
+    test!((date.year, date.month, date.day) == (2015, 6, 30));
+    test!((date.hour, date.minute, date.second) == (23, 59, 60));
+    test!(date.nanosecond == 500_000_000);
+    test!(date.offset == 7200);
+    test!(date.weekday == Weekday::Tuesday);
+    test!(date.day_in_year == 181);
+
+    let date: DateTime = "1985-04-12t23:20:50.52Z".parse().unwrap();
+    test!(date.nanosecond == 520_000_000);
+    test!(date.offset == 0);
+    test!(abbr(&date) == "UTC");
+
+    let date: DateTime = "1937-01-01 12:00:27-00:20".parse().unwrap();
+    test!(date.offset == -1200);
+
+    let date: DateTime = "2024-02-29".parse().unwrap();
+    test!((date.year, date.month, date.day, date.hour) == (2024, 2, 29, 0));
+
+    test!("2023-02-29".parse::<DateTime>().is_err());
+    test!("2023-01-01T24:00:00Z".parse::<DateTime>().is_err());
+    test!("2023-01-01T12:00Z".parse::<DateTime>().is_err());
+    test!("2023-01-01T12:00:00".parse::<DateTime>().is_err());
+    test!("2023-01-01 12:00:00.5".parse::<DateTime>().is_err());
+    test!("2023-01-01T12:00:00+2400".parse::<DateTime>().is_err());
+}
+
+#[test]
+fn parse_rfc2822() {
+    let date: DateTime = "Tue, 1 Jul 2003 10:52:37 +0200".parse().unwrap();
+    test!((date.year, date.month, date.day) == (2003, 7, 1));
+    test!((date.hour, date.minute, date.second) == (10, 52, 37));
+    test!(date.offset == 7200);
+    test!(date.weekday == Weekday::Tuesday);
+
+    let date: DateTime = "Sun, 06 Nov 1994 08:49:37 GMT".parse().unwrap();
+    test!((date.year, date.month, date.day) == (1994, 11, 6));
+    test!(date.offset == 0);
+    test!(abbr(&date) == "GMT");
+
+    let date: DateTime = "21 nov 97 09:55 PDT".parse().unwrap();
+    test!((date.year, date.month, date.day) == (1997, 11, 21));
+    test!((date.hour, date.minute, date.second) == (9, 55, 0));
+    test!(date.offset == -7 * 3600);
+    test!(date.summer_time);
+
+    test!("Mon, 32 Jan 2000 00:00 GMT".parse::<DateTime>().is_err());
+    test!("Mon, 1 Foo 2000 00:00 GMT".parse::<DateTime>().is_err());
+    test!("Mon, 1 Jan 2000 00:00 XYZ".parse::<DateTime>().is_err());
+}
+
+#[test]
+fn round_trip() {
+    let zone = Zone::from_posix_tz("EST5EDT,M3.2.0,M11.1.0").unwrap();
+    let starts = [1710054000 - 7200, 1730613600 - 7200];
+    for &start in &starts[..] {
+        for i in 0..20 {
+            let secs = start + i * 900 + 1;
+            let date = expand(&zone, secs);
+
+            for &pattern in &[time::RFC3339, time::RFC2822][..] {
+                let parsed: DateTime = format(&date, pattern).parse().unwrap();
+                test!((parsed.year, parsed.month, parsed.day) ==
+                      (date.year, date.month, date.day));
+                test!((parsed.hour, parsed.minute, parsed.second) ==
+                      (date.hour, date.minute, date.second));
+                test!(parsed.offset == date.offset);
+                test!(zone.compact(parsed) == (date, Time::seconds(secs)));
+            }
+        }
+    }
+}
+
+fn date(s: &str) -> DateTime {
+    s.parse().unwrap()
+}
+
+fn ymd(date: DateTime) -> (i64, i8, i8) {
+    (date.year, date.month, date.day)
+}
+
+#[test]
+fn add_days() {
+    let d = date("2024-12-31T10:20:30+01:00").checked_add_days(1).unwrap();
+    test!(ymd(d) == (2025, 1, 1));
+    test!((d.hour, d.minute, d.second, d.offset) == (10, 20, 30, 3600));
+    test!(d.day_in_year == 1);
+    test!(d.weekday == Weekday::Wednesday);
+
+    let d = date("2024-03-01").checked_sub_days(1).unwrap();
+    test!(ymd(d) == (2024, 2, 29));
+    test!(d.day_in_year == 60);
+    test!(ymd(date("2000-01-01").checked_add_days(-730485).unwrap()) == (0, 1, 1));
+
+    test!(date("2024-01-01").checked_add_days(i64::max()).is_none());
+    test!(date("2024-01-01").checked_sub_days(i64::min()).is_none());
+    let mut invalid = date("2024-01-01");
+    invalid.month = 13;
+    test!(invalid.checked_add_days(1).is_none());
+}
+
+#[test]
+fn add_months() {
+    test!(ymd(date("2024-01-31").checked_add_months(1).unwrap()) == (2024, 2, 29));
+    test!(ymd(date("2023-01-31").checked_add_months(1).unwrap()) == (2023, 2, 28));
+    test!(ymd(date("2024-03-31").checked_sub_months(1).unwrap()) == (2024, 2, 29));
+    test!(ymd(date("2024-11-30").checked_add_months(3).unwrap()) == (2025, 2, 28));
+    test!(ymd(date("2024-01-15").checked_sub_months(13).unwrap()) == (2022, 12, 15));
+    let d = date("2024-05-31").checked_add_months(1).unwrap();
+    test!(ymd(d) == (2024, 6, 30));
+    test!(d.weekday == Weekday::Sunday);
+    test!(d.day_in_year == 182);
+
+    test!(ymd(date("2024-02-29").checked_add_years(1).unwrap()) == (2025, 2, 28));
+    test!(ymd(date("2024-02-29").checked_add_years(4).unwrap()) == (2028, 2, 29));
+    test!(ymd(date("2024-02-29").checked_sub_years(100).unwrap()) == (1924, 2, 29));
+
+    test!(date("2024-01-01").checked_add_months(i64::max()).is_none());
+    test!(date("2024-01-01").checked_add_years(i64::max() / 12).is_none());
+    test!(date("2024-01-01").checked_sub_years(i64::min()).is_none());
+}
+
+#[test]
+fn days_since() {
+    test!(date("1970-01-01").days_since_epoch() == Some(0));
+    test!(date("1969-12-31").days_since_epoch() == Some(-1));
+    test!(date("2000-03-01").days_since_epoch() == Some(11017));
+    test!(date("2024-03-01").days_since(&date("2023-12-25")) == Some(67));
+    test!(date("2023-12-25").days_since(&date("2024-03-01")) == Some(-67));
+
+    let mut invalid = date("2024-01-01");
+    invalid.month = 13;
+    test!(invalid.days_since_epoch().is_none());
+    test!(date("2024-01-01").days_since(&invalid).is_none());
+    invalid.month = 0;
+    test!(invalid.days_since_epoch().is_none());
+    invalid.month = 2;
+    invalid.day = 30;
+    test!(invalid.days_since_epoch().is_none());
+}
+
+#[test]
+fn iso_week() {
+    test!(date("2021-01-01").iso_week() == Some((2020, 53)));
+    test!(date("2020-12-31").iso_week() == Some((2020, 53)));
+    test!(date("2024-12-30").iso_week() == Some((2025, 1)));
+    test!(date("2026-01-01").iso_week() == Some((2026, 1)));
+    test!(date("2024-06-15").iso_week() == Some((2024, 24)));
+    test!(date("2027-01-03").iso_week() == Some((2026, 53)));
+
+    let mut invalid = date("2024-01-01");
+    invalid.month = 13;
+    test!(invalid.iso_week().is_none());
+}
+
+#[test]
+fn calendar() {
+    test!(time::is_leap_year(2000));
+    test!(!time::is_leap_year(1900));
+    test!(time::is_leap_year(2024));
+    test!(time::days_in_year(2023) == 365);
+    test!(time::days_in_year(2024) == 366);
+    test!(time::days_in_month(2023, 2) == 28);
+    test!(time::days_in_month(2024, 2) == 29);
+    test!(time::days_in_month(2024, 12) == 31);
+
+    test!(time::day_in_year(2024, 3, 1) == Some(61));
+    test!(time::day_in_year(2023, 12, 31) == Some(365));
+    test!(time::day_in_year(2023, 2, 29) == None);
+    test!(time::day_in_year(2023, 0, 1) == None);
+
+    test!(time::month_and_day(2024, 60) == Some((2, 29)));
+    test!(time::month_and_day(2023, 60) == Some((3, 1)));
+    test!(time::month_and_day(2024, 366) == Some((12, 31)));
+    test!(time::month_and_day(2023, 366) == None);
+    test!(time::month_and_day(2023, 0) == None);
+}